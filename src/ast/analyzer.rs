@@ -2,13 +2,17 @@
 
 use crate::Result;
 use crate::ast::{AnalysisResult, SourceFile};
+use crate::patterns::TestMarkers;
 
 /// Analyzer for Rust AST
 ///
 /// This struct provides the main interface for analyzing Rust source code
 /// and extracting information about functions, complexity metrics, and
 /// instrumentable code elements.
-pub struct AstAnalyzer;
+pub struct AstAnalyzer {
+    /// Attribute patterns that mark test code, compiled once for all files
+    test_markers: TestMarkers,
+}
 
 impl AstAnalyzer {
     /// Create a new AST analyzer
@@ -23,7 +27,9 @@ impl AstAnalyzer {
     /// let result = analyzer.analyze(source_file).unwrap();
     /// ```
     pub fn new() -> Self {
-        Self
+        Self {
+            test_markers: TestMarkers::default(),
+        }
     }
 
     /// Analyze a source file and extract comprehensive information
@@ -65,7 +71,7 @@ impl AstAnalyzer {
     /// ```
     pub fn analyze(&self, source_file: SourceFile) -> Result<AnalysisResult> {
         // Use the visitor to perform the analysis
-        let result = crate::ast::visitor::analyze_ast(source_file);
+        let result = crate::ast::visitor::analyze_ast(source_file, &self.test_markers);
         Ok(result)
     }
 
@@ -1,13 +1,15 @@
 //! AST visitor implementation for traversing Rust syntax trees
 
 use proc_macro2::Span;
-use quote::{ToTokens, quote};
+use quote::quote;
 use syn::{
     Attribute, BinOp, Block, Expr, File, ImplItem, Item, ItemFn, ReturnType, Stmt, Type,
     spanned::Spanned,
     visit::{self, Visit},
 };
 
+use crate::patterns::TestMarkers;
+
 use crate::ast::{
     AnalysisResult, CallInfo, ComplexityMetrics, ElementKind, ErrorHandlingInfo, FunctionInfo,
     InstrumentableElement, Location, ModuleInfo, SourceFile,
@@ -58,11 +60,19 @@ pub struct AstVisitor {
 
     /// Source lines for location mapping
     source_lines: Vec<String>,
+
+    /// Attribute patterns that mark test functions and modules
+    test_markers: TestMarkers,
 }
 
 impl AstVisitor {
     /// Create a new AST visitor for the given source file
-    pub fn new(source_file: SourceFile) -> Self {
+    ///
+    /// # Arguments
+    ///
+    /// * `source_file` - The file to analyze
+    /// * `test_markers` - Attribute patterns that mark test code
+    pub fn new(source_file: SourceFile, test_markers: &TestMarkers) -> Self {
         let source_lines: Vec<String> = source_file.source.lines().map(String::from).collect();
 
         Self {
@@ -81,6 +91,7 @@ impl AstVisitor {
                 id_counter: 0,
             },
             source_lines,
+            test_markers: test_markers.clone(),
         }
     }
 
@@ -110,27 +121,12 @@ impl AstVisitor {
         Location::new(start.line, start.column, end.line, end.column)
     }
 
-    /// Check if an item has a test attribute
+    /// Check if an item has a test attribute such as `#[test]`, `#[tokio::test]`
+    /// or `#[cfg(test)]`
     fn is_test_item(&self, attrs: &[Attribute]) -> bool {
-        attrs.iter().any(|attr| {
-            attr.path()
-                .get_ident()
-                .is_some_and(|ident| ident == "test" || ident == "tokio_test")
-        })
-    }
-
-    /// Check if an item has a cfg(test) attribute
-    fn is_cfg_test(&self, attrs: &[Attribute]) -> bool {
-        attrs.iter().any(|attr| {
-            if attr.path().get_ident().is_some_and(|i| i == "cfg") {
-                // Simple check for cfg(test)
-                // Note: This is a simplified check. For production use,
-                // we should properly parse the attribute tokens.
-                attr.to_token_stream().to_string().contains("test")
-            } else {
-                false
-            }
-        })
+        attrs
+            .iter()
+            .any(|attr| self.test_markers.is_test_attribute(attr))
     }
 
     /// Extract return type as a string
@@ -181,7 +177,8 @@ impl AstVisitor {
     /// Process a function and extract its information
     fn process_function(&mut self, item_fn: &ItemFn, is_method: bool) {
         let name = item_fn.sig.ident.to_string();
-        let is_test = self.is_test_item(&item_fn.attrs);
+        // Functions in a test module are test code too
+        let is_test = self.context.in_test_context || self.is_test_item(&item_fn.attrs);
         let was_in_test = self.context.in_test_context;
 
         if is_test {
@@ -303,7 +300,7 @@ impl<'ast> Visit<'ast> for AstVisitor {
                 self.process_function(item_fn, false);
             }
             Item::Mod(item_mod) => {
-                let is_test = self.is_cfg_test(&item_mod.attrs);
+                let is_test = self.is_test_item(&item_mod.attrs);
                 let was_in_test = self.context.in_test_context;
 
                 if is_test {
@@ -558,8 +555,8 @@ impl<'ast> Visit<'ast> for AstVisitor {
 }
 
 /// Public interface for performing AST analysis
-pub fn analyze_ast(source_file: SourceFile) -> AnalysisResult {
-    let visitor = AstVisitor::new(source_file);
+pub fn analyze_ast(source_file: SourceFile, test_markers: &TestMarkers) -> AnalysisResult {
+    let visitor = AstVisitor::new(source_file, test_markers);
     visitor.analyze()
 }
 
@@ -1,13 +1,53 @@
 //! Call graph builder that traces function calls from endpoints
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use syn::{Expr, ExprCall, ExprMethodCall, Item, ItemFn, visit::Visit};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{
+    Expr, ExprAsync, ExprCall, ExprClosure, ExprMethodCall, FnArg, ImplItem, ImplItemFn, Item,
+    ItemFn, ItemImpl, ItemMod, ItemTrait, Local, Macro, Pat, Signature, Token, TraitItem,
+    TraitItemFn, visit::Visit,
+};
 use thiserror::Error;
 use walkdir::WalkDir;
 
 use super::edge::CallContext;
-use super::{CallEdge, CallGraph, CallKind, FunctionNode, SymbolResolver};
+use super::module_tree::ModuleTree;
+use super::resolver::{SymbolKind, generic_bounds, type_name};
+use super::{
+    CallEdge, CallGraph, CallKind, FunctionNode, MacroCall, NodeKind, ResolvedSymbol,
+    SymbolResolver,
+};
+use crate::files::is_test_file;
+use crate::patterns::TestMarkers;
+
+/// Confidence for calls whose target is named explicitly (`Type::assoc()`, `self.method()`)
+const EXACT_CONFIDENCE: f64 = 1.0;
+
+/// Confidence for receivers typed by a declaration (field types, parameters, `let x: T`)
+const DECLARED_CONFIDENCE: f64 = 0.9;
+
+/// Confidence for receivers typed by an inferred return type (`let x = T::new()`)
+const INFERRED_CONFIDENCE: f64 = 0.75;
+
+/// Methods that return (a reference to) their receiver's type
+const PASSTHROUGH_METHODS: &[&str] = &[
+    "clone",
+    "to_owned",
+    "as_ref",
+    "as_mut",
+    "borrow",
+    "borrow_mut",
+    "unwrap",
+    "expect",
+];
+
+/// Functions and methods that run their argument as a separate task
+pub(crate) const SPAWN_FUNCTIONS: &[&str] = &["spawn", "spawn_blocking", "spawn_local"];
+
+/// Macros whose arguments are futures polled concurrently
+const JOIN_MACROS: &[&str] = &["join", "try_join"];
 
 /// Errors that can occur during graph building
 #[derive(Error, Debug)]
@@ -46,6 +86,14 @@ pub struct GraphBuilder {
     context_stack: Vec<FunctionContext>,
     /// Set of processed files
     processed_files: HashSet<PathBuf>,
+    /// Pre-parsed files awaiting call tracing in `build`
+    parsed_files: Vec<(PathBuf, syn::File)>,
+    /// Module paths of the files in each crate's module tree
+    module_tree: ModuleTree,
+    /// Crate of the file being processed, if known
+    current_crate: Option<String>,
+    /// Attribute patterns that mark functions and modules as tests
+    test_markers: TestMarkers,
 }
 
 /// Context for the current function being analyzed
@@ -59,6 +107,39 @@ struct FunctionContext {
     in_loop: bool,
     /// Current block depth
     block_depth: usize,
+    /// Types known for local bindings (parameters and `let` bindings)
+    locals: HashMap<String, TypeHint>,
+    /// Trait bounds of the generic type parameters in scope
+    type_params: HashMap<String, String>,
+    /// Number of closures and async blocks found so far in this body
+    nested_count: usize,
+}
+
+/// An `impl` or `trait` block whose items are being traced
+#[derive(Debug, Clone)]
+struct ImplScope {
+    /// The implementing type, or the trait for default methods
+    self_type: String,
+    /// Trait bounds of the block's generic type parameters
+    bounds: HashMap<String, String>,
+}
+
+/// A type inferred for a binding or expression
+#[derive(Debug, Clone)]
+struct TypeHint {
+    /// Name of the type
+    type_name: String,
+    /// Confidence that the binding has this type
+    confidence: f64,
+}
+
+impl TypeHint {
+    fn new(type_name: impl Into<String>, confidence: f64) -> Self {
+        Self {
+            type_name: type_name.into(),
+            confidence,
+        }
+    }
 }
 
 impl GraphBuilder {
@@ -70,9 +151,44 @@ impl GraphBuilder {
             current_file: None,
             context_stack: Vec::new(),
             processed_files: HashSet::new(),
+            parsed_files: Vec::new(),
+            module_tree: ModuleTree::new(),
+            current_crate: None,
+            test_markers: TestMarkers::default(),
         }
     }
 
+    /// Uses the given module tree to compute module paths
+    ///
+    /// Files outside the tree fall back to a module path derived from their
+    /// location under `src/`.
+    ///
+    /// # Arguments
+    ///
+    /// * `module_tree` - Module tree of the crates being analyzed
+    ///
+    /// # Returns
+    ///
+    /// The builder, for chaining
+    pub fn with_module_tree(mut self, module_tree: ModuleTree) -> Self {
+        self.module_tree = module_tree;
+        self
+    }
+
+    /// Uses the given markers to tell test functions and modules apart
+    ///
+    /// # Arguments
+    ///
+    /// * `test_markers` - Test markers shared with the rest of the analysis
+    ///
+    /// # Returns
+    ///
+    /// The builder, for chaining
+    pub fn with_test_markers(mut self, test_markers: TestMarkers) -> Self {
+        self.test_markers = test_markers;
+        self
+    }
+
     /// Builds a call graph from a directory of Rust source files
     ///
     /// # Arguments
@@ -87,6 +203,10 @@ impl GraphBuilder {
     ///
     /// Returns an error if file reading or parsing fails
     pub fn build_from_directory(&mut self, root_path: &Path) -> Result<CallGraph, GraphBuildError> {
+        if self.module_tree.is_empty() {
+            self.module_tree = ModuleTree::discover(root_path);
+        }
+
         // First pass: collect all function definitions
         self.collect_definitions(root_path)?;
 
@@ -127,14 +247,12 @@ impl GraphBuilder {
             error: e.to_string(),
         })?;
 
-        self.current_file = Some(file_path.to_path_buf());
-
-        // Extract module path from file path (simplified)
-        let module_path = self.extract_module_path(file_path);
+        let module_path = self.enter_file(file_path);
+        let in_test = is_test_file(file_path);
 
         // Visit all items to collect definitions
         for item in &file.items {
-            self.process_item_definitions(item, module_path.clone(), file_path);
+            self.process_item_definitions(item, module_path.clone(), file_path, in_test);
         }
 
         self.processed_files.insert(file_path.to_path_buf());
@@ -142,20 +260,27 @@ impl GraphBuilder {
     }
 
     /// Processes an item to extract function definitions
+    ///
+    /// Functions in test code (`in_test`, or marked by a test attribute
+    /// themselves) are added as [`NodeKind::Test`] nodes.
     fn process_item_definitions(
         &mut self,
         item: &Item,
         module_path: Vec<String>,
         file_path: &Path,
+        in_test: bool,
     ) {
         match item {
             Item::Fn(item_fn) => {
-                let node = FunctionNode::from_item_fn(
+                let mut node = FunctionNode::from_item_fn(
                     item_fn,
                     module_path,
                     Some(file_path.display().to_string()),
                 );
-                self.graph.add_node(node);
+                if in_test || self.is_test(&item_fn.attrs) {
+                    node.kind = NodeKind::Test;
+                }
+                self.add_node(node);
                 self.resolver.process_item(item, &file_path.to_path_buf());
             }
             Item::Mod(item_mod) => {
@@ -163,18 +288,73 @@ impl GraphBuilder {
                 new_module_path.push(item_mod.ident.to_string());
 
                 if let Some(content) = &item_mod.content {
+                    let in_test = in_test || self.is_test(&item_mod.attrs);
+                    self.resolver.enter_module(&item_mod.ident.to_string());
                     for item in &content.1 {
-                        self.process_item_definitions(item, new_module_path.clone(), file_path);
+                        self.process_item_definitions(
+                            item,
+                            new_module_path.clone(),
+                            file_path,
+                            in_test,
+                        );
+                    }
+                    self.resolver.exit_module();
+                }
+            }
+            Item::Impl(item_impl) => {
+                if let Some(self_type) = type_name(&item_impl.self_ty) {
+                    let in_test = in_test || self.is_test(&item_impl.attrs);
+                    for impl_item in &item_impl.items {
+                        if let ImplItem::Fn(method) = impl_item {
+                            let mut node = FunctionNode::from_impl_fn(
+                                method,
+                                &self_type,
+                                module_path.clone(),
+                                Some(file_path.display().to_string()),
+                            );
+                            if in_test || self.is_test(&method.attrs) {
+                                node.kind = NodeKind::Test;
+                            }
+                            self.add_node(node);
+                        }
                     }
                 }
+                self.resolver.process_item(item, &file_path.to_path_buf());
+            }
+            Item::Trait(item_trait) => {
+                let trait_name = item_trait.ident.to_string();
+                for trait_item in &item_trait.items {
+                    if let TraitItem::Fn(method) = trait_item {
+                        if method.default.is_some() {
+                            let mut node = FunctionNode::from_trait_fn(
+                                method,
+                                &trait_name,
+                                module_path.clone(),
+                                Some(file_path.display().to_string()),
+                            );
+                            if in_test {
+                                node.kind = NodeKind::Test;
+                            }
+                            self.add_node(node);
+                        }
+                    }
+                }
+                self.resolver.process_item(item, &file_path.to_path_buf());
             }
-            Item::Use(_) => {
+            Item::Use(_) | Item::Struct(_) => {
                 self.resolver.process_item(item, &file_path.to_path_buf());
             }
             _ => {}
         }
     }
 
+    /// Checks whether an item's attributes mark it as test code
+    fn is_test(&self, attrs: &[syn::Attribute]) -> bool {
+        attrs
+            .iter()
+            .any(|attr| self.test_markers.is_test_attribute(attr))
+    }
+
     /// Traces function calls in the codebase
     fn trace_calls(&mut self, root_path: &Path) -> Result<(), GraphBuildError> {
         // Reset processed files for second pass
@@ -206,33 +386,35 @@ impl GraphBuilder {
             error: e.to_string(),
         })?;
 
-        self.current_file = Some(file_path.to_path_buf());
-
         // Create a call tracer visitor
-        let mut tracer = CallTracer::new(self);
+        let mut tracer = CallTracer::new(self, file_path);
         tracer.visit_file(&file);
 
         self.processed_files.insert(file_path.to_path_buf());
         Ok(())
     }
 
-    /// Extracts module path from file path
-    fn extract_module_path(&self, file_path: &Path) -> Vec<String> {
-        // Simplified: extract from src/... path
-        let path_str = file_path.display().to_string();
-        if let Some(src_idx) = path_str.find("/src/") {
-            let module_part = &path_str[src_idx + 5..];
-            let module_part = module_part.trim_end_matches(".rs");
-            let module_part = module_part.replace("/", "::");
+    /// Makes a file current, syncing the resolver with its module path
+    ///
+    /// # Returns
+    ///
+    /// The module path of the file's top-level items
+    fn enter_file(&mut self, file_path: &Path) -> Vec<String> {
+        self.current_file = Some(file_path.to_path_buf());
 
-            if module_part == "main" || module_part == "lib" {
-                vec![]
-            } else {
-                module_part.split("::").map(String::from).collect()
-            }
-        } else {
-            vec![]
-        }
+        let location = self.module_tree.module_location(file_path);
+        self.resolver.set_crate_root(location.crate_root());
+        self.current_crate = location.crate_name.clone();
+
+        let module_path = location.path();
+        self.resolver.set_module_path(module_path.clone());
+        module_path
+    }
+
+    /// Adds a node defined in the current file to the graph
+    fn add_node(&mut self, mut node: FunctionNode) {
+        node.crate_name = self.current_crate.clone();
+        self.graph.add_node(node);
     }
 
     /// Post-processes the graph to identify external boundaries and other properties
@@ -282,16 +464,18 @@ impl GraphBuilder {
             return Ok(());
         }
 
-        self.current_file = Some(file_path.to_path_buf());
-
-        // Extract module path from file path
-        let module_path = self.extract_module_path(file_path);
+        let module_path = self.enter_file(file_path);
+        let in_test = is_test_file(file_path);
 
         // Visit all items to collect definitions
         for item in &source.syntax_tree.items {
-            self.process_item_definitions(item, module_path.clone(), file_path);
+            self.process_item_definitions(item, module_path.clone(), file_path, in_test);
         }
 
+        // Calls are traced in `build`, once every file's definitions are known
+        self.parsed_files
+            .push((file_path.to_path_buf(), source.syntax_tree.clone()));
+
         self.processed_files.insert(file_path.to_path_buf());
         Ok(())
     }
@@ -315,6 +499,12 @@ impl GraphBuilder {
     ///
     /// The constructed call graph
     pub fn build(mut self) -> Result<CallGraph, GraphBuildError> {
+        // Trace calls now that definitions from all files are registered
+        for (file_path, file) in std::mem::take(&mut self.parsed_files) {
+            let mut tracer = CallTracer::new(&mut self, &file_path);
+            tracer.visit_file(&file);
+        }
+
         // Post-process to identify external nodes
         self.post_process();
         Ok(self.graph)
@@ -324,11 +514,24 @@ impl GraphBuilder {
 /// Visitor for tracing function calls
 struct CallTracer<'a> {
     builder: &'a mut GraphBuilder,
+    /// Module path of the items being visited
+    module_path: Vec<String>,
+    /// Stack of the `impl` and `trait` blocks being visited
+    impl_scopes: Vec<ImplScope>,
+    /// Context for the next call recorded, overriding the default
+    context_override: Option<CallContext>,
 }
 
 impl<'a> CallTracer<'a> {
-    fn new(builder: &'a mut GraphBuilder) -> Self {
-        Self { builder }
+    fn new(builder: &'a mut GraphBuilder, file_path: &Path) -> Self {
+        let module_path = builder.enter_file(file_path);
+
+        Self {
+            builder,
+            module_path,
+            impl_scopes: Vec::new(),
+            context_override: None,
+        }
     }
 
     /// Gets the current function context
@@ -336,103 +539,678 @@ impl<'a> CallTracer<'a> {
         self.builder.context_stack.last()
     }
 
+    /// Prefixes a name with the current module path
+    fn qualify(&self, name: &str) -> String {
+        if self.module_path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}::{}", self.module_path.join("::"), name)
+        }
+    }
+
+    /// Gets the self type of the innermost `impl` block
+    fn current_impl_type(&self) -> Option<&str> {
+        self.impl_scopes
+            .last()
+            .map(|scope| scope.self_type.as_str())
+    }
+
+    /// Extracts a type name from a syntactic type
+    fn hint_type(&self, ty: &syn::Type) -> Option<String> {
+        self.resolve_type_name(type_name(ty)?)
+    }
+
+    /// Gets the module-qualified path of the innermost `impl` block's self type
+    fn current_impl_path(&self) -> Option<String> {
+        self.current_impl_type()
+            .map(|self_type| self.qualify(self_type))
+    }
+
+    /// Substitutes `Self` and generic parameters with the types they stand
+    /// for, and qualifies the result with the module defining it
+    ///
+    /// A generic parameter resolves to its trait bound, so calls on it
+    /// dispatch to every implementation of that trait. Types defined outside
+    /// the analyzed code keep their name.
+    fn resolve_type_name(&self, name: String) -> Option<String> {
+        if name == "Self" {
+            return self.current_impl_path();
+        }
+
+        let name = self
+            .current_function()
+            .and_then(|ctx| ctx.type_params.get(&name))
+            .cloned()
+            .unwrap_or(name);
+        Some(self.builder.resolver.qualified_type(&name).unwrap_or(name))
+    }
+
+    /// Traces the body of a function or method with the given ID
+    fn trace_function(&mut self, function_id: String, sig: &Signature, block: &syn::Block) {
+        let mut type_params = self
+            .impl_scopes
+            .last()
+            .map(|scope| scope.bounds.clone())
+            .unwrap_or_default();
+        type_params.extend(generic_bounds(&sig.generics));
+
+        self.builder.context_stack.push(FunctionContext {
+            function_id,
+            in_conditional: false,
+            in_loop: false,
+            block_depth: 0,
+            locals: HashMap::new(),
+            type_params,
+            nested_count: 0,
+        });
+
+        let mut locals = HashMap::new();
+        for input in &sig.inputs {
+            match input {
+                FnArg::Receiver(_) => {
+                    if let Some(self_type) = self.current_impl_path() {
+                        locals.insert(
+                            "self".to_string(),
+                            TypeHint::new(self_type, EXACT_CONFIDENCE),
+                        );
+                    }
+                }
+                FnArg::Typed(pat_type) => {
+                    if let (Pat::Ident(pat_ident), Some(ty)) =
+                        (&*pat_type.pat, self.hint_type(&pat_type.ty))
+                    {
+                        locals.insert(
+                            pat_ident.ident.to_string(),
+                            TypeHint::new(ty, DECLARED_CONFIDENCE),
+                        );
+                    }
+                }
+            }
+        }
+
+        if let Some(ctx) = self.builder.context_stack.last_mut() {
+            ctx.locals = locals;
+        }
+
+        self.visit_block(block);
+
+        self.builder.context_stack.pop();
+    }
+
+    /// Traces a closure or async block as a child node of the current function
+    ///
+    /// The child inherits the parent's local type hints (captures), and calls
+    /// in its body are attributed to the child. The parent gets a
+    /// `CallKind::Closure` edge to the child with the given context.
+    fn trace_nested<F>(
+        &mut self,
+        label: &str,
+        is_async: bool,
+        context: CallContext,
+        span: proc_macro2::Span,
+        visit_body: F,
+    ) where
+        F: FnOnce(&mut Self),
+    {
+        // An override applies to the call that introduced it, never to calls
+        // in the nested body
+        self.context_override = None;
+
+        let Some(parent) = self.builder.context_stack.last_mut() else {
+            visit_body(self);
+            return;
+        };
+
+        parent.nested_count += 1;
+        let name = format!("{{{}#{}}}", label, parent.nested_count);
+        let parent = parent.clone();
+
+        let start = span.start();
+        let child = self
+            .builder
+            .graph
+            .get_node(&parent.function_id)
+            .map(|parent_node| {
+                FunctionNode::nested(parent_node, name.clone(), is_async, Some(start.line))
+            });
+        let child_id = format!("{}::{}", parent.function_id, name);
+
+        if let Some(child) = child {
+            self.builder.graph.add_node(child);
+
+            let mut edge = CallEdge::new(
+                parent.function_id.clone(),
+                child_id.clone(),
+                CallKind::Closure,
+            )
+            .with_conditional(parent.in_conditional)
+            .with_in_loop(parent.in_loop)
+            .with_context(context);
+            if let Some(file) = &self.builder.current_file {
+                edge = edge.with_location(file.display().to_string(), start.line, start.column);
+            }
+            self.builder.graph.add_edge(edge);
+        }
+
+        self.builder.context_stack.push(FunctionContext {
+            function_id: child_id,
+            in_conditional: false,
+            in_loop: false,
+            block_depth: 0,
+            locals: parent.locals,
+            type_params: parent.type_params,
+            nested_count: 0,
+        });
+
+        visit_body(self);
+
+        self.builder.context_stack.pop();
+    }
+
+    /// Traces a closure, recording its typed parameters as locals
+    fn trace_closure(&mut self, closure: &ExprClosure, context: CallContext) {
+        self.trace_nested(
+            "closure",
+            closure.asyncness.is_some(),
+            context,
+            closure.span(),
+            |this| {
+                for input in &closure.inputs {
+                    if let Pat::Type(pat_type) = input {
+                        if let (Pat::Ident(pat_ident), Some(ty)) =
+                            (&*pat_type.pat, this.hint_type(&pat_type.ty))
+                        {
+                            if let Some(ctx) = this.builder.context_stack.last_mut() {
+                                ctx.locals.insert(
+                                    pat_ident.ident.to_string(),
+                                    TypeHint::new(ty, DECLARED_CONFIDENCE),
+                                );
+                            }
+                        }
+                    }
+                }
+                this.visit_expr(&closure.body);
+            },
+        );
+    }
+
+    /// Traces an async block
+    fn trace_async(&mut self, block: &ExprAsync, context: CallContext) {
+        self.trace_nested("async", true, context, block.span(), |this| {
+            this.visit_block(&block.block);
+        });
+    }
+
+    /// Traces the future or closure handed to a task spawner
+    ///
+    /// Async blocks and closures become child nodes reached through a
+    /// `CallContext::Spawn` edge; a spawned call such as `worker(rx)` gets a
+    /// `CallContext::Spawn` edge to the called function. Wrappers like
+    /// `.instrument(span)` are looked through.
+    fn trace_spawned(&mut self, task: &Expr) {
+        match task {
+            Expr::Async(block) => self.trace_async(block, CallContext::Spawn),
+            Expr::Closure(closure) => self.trace_closure(closure, CallContext::Spawn),
+            Expr::Paren(inner) => self.trace_spawned(&inner.expr),
+            Expr::MethodCall(call)
+                if matches!(
+                    call.method.to_string().as_str(),
+                    "instrument" | "in_current_span" | "with_current_subscriber" | "boxed"
+                ) =>
+            {
+                self.trace_spawned(&call.receiver);
+                for arg in &call.args {
+                    self.visit_expr(arg);
+                }
+            }
+            Expr::Call(_) | Expr::MethodCall(_) => {
+                self.context_override = Some(CallContext::Spawn);
+                self.visit_expr(task);
+                self.context_override = None;
+            }
+            _ => self.visit_expr(task),
+        }
+    }
+
+    /// Resolves the target of a call through a path (`foo()`, `Type::assoc()`, `Self::new()`)
+    fn resolve_call_target(&self, path: &syn::Path) -> Option<ResolvedSymbol> {
+        let segments: Vec<_> = path.segments.iter().collect();
+        if let [.., type_segment, method_segment] = segments.as_slice() {
+            let type_name = if type_segment.ident == "Self" {
+                self.current_impl_path()?
+            } else {
+                type_segment.ident.to_string()
+            };
+            let method = method_segment.ident.to_string();
+            if let Some(symbol) = self.builder.resolver.resolve_method(&type_name, &method) {
+                return Some(symbol);
+            }
+            if type_segment.ident == "Self" {
+                return None;
+            }
+        }
+
+        self.builder.resolver.resolve_path(path)
+    }
+
+    /// Infers the type of an expression from the local type hints
+    fn infer_expr_type(&self, expr: &Expr) -> Option<TypeHint> {
+        match expr {
+            Expr::Path(expr_path) => {
+                let ident = expr_path.path.get_ident()?.to_string();
+                self.current_function()?.locals.get(&ident).cloned()
+            }
+            Expr::Field(field) => {
+                let base = self.infer_expr_type(&field.base)?;
+                let syn::Member::Named(name) = &field.member else {
+                    return None;
+                };
+                let field_type = self
+                    .builder
+                    .resolver
+                    .field_type(&base.type_name, &name.to_string())?;
+                Some(TypeHint::new(
+                    self.resolve_type_name(field_type)?,
+                    base.confidence * DECLARED_CONFIDENCE,
+                ))
+            }
+            Expr::Call(call) => {
+                let Expr::Path(func) = &*call.func else {
+                    return None;
+                };
+                let target = self.resolve_call_target(&func.path)?;
+                let returned = self.builder.resolver.return_type(&target.full_path)?;
+                Some(TypeHint::new(returned, INFERRED_CONFIDENCE))
+            }
+            Expr::MethodCall(call) => {
+                let receiver = self.infer_expr_type(&call.receiver)?;
+                let method = call.method.to_string();
+                if PASSTHROUGH_METHODS.contains(&method.as_str()) {
+                    return Some(receiver);
+                }
+                let target = self
+                    .builder
+                    .resolver
+                    .resolve_method(&receiver.type_name, &method)?;
+                let returned = self.builder.resolver.return_type(&target.full_path)?;
+                Some(TypeHint::new(
+                    returned,
+                    receiver.confidence * INFERRED_CONFIDENCE,
+                ))
+            }
+            Expr::Struct(expr_struct) => {
+                let ident = &expr_struct.path.segments.last()?.ident;
+                let type_name = self.resolve_type_name(ident.to_string())?;
+                Some(TypeHint::new(type_name, EXACT_CONFIDENCE))
+            }
+            Expr::Cast(cast) => Some(TypeHint::new(
+                self.hint_type(&cast.ty)?,
+                DECLARED_CONFIDENCE,
+            )),
+            Expr::Await(inner) => self.infer_expr_type(&inner.base),
+            Expr::Try(inner) => self.infer_expr_type(&inner.expr),
+            Expr::Paren(inner) => self.infer_expr_type(&inner.expr),
+            Expr::Group(inner) => self.infer_expr_type(&inner.expr),
+            Expr::Reference(inner) => self.infer_expr_type(&inner.expr),
+            Expr::Unary(unary) if matches!(unary.op, syn::UnOp::Deref(_)) => {
+                self.infer_expr_type(&unary.expr)
+            }
+            _ => None,
+        }
+    }
+
+    /// Records a call from the current function to a resolved target
+    fn record_call(
+        &mut self,
+        target: ResolvedSymbol,
+        external_path: Option<&syn::Path>,
+        kind: CallKind,
+        context: CallContext,
+        confidence: f64,
+        span: proc_macro2::Span,
+    ) {
+        let Some(current_fn) = self.current_function() else {
+            return;
+        };
+
+        let kind = if kind == CallKind::Direct
+            && !target.is_external
+            && current_fn.function_id == target.full_path
+        {
+            CallKind::Recursive
+        } else {
+            kind
+        };
+
+        let mut edge = CallEdge::new(current_fn.function_id.clone(), target.full_path, kind)
+            .with_conditional(current_fn.in_conditional)
+            .with_in_loop(current_fn.in_loop)
+            .with_context(context)
+            .with_confidence(confidence);
+
+        if let Some(file) = &self.builder.current_file {
+            let start = span.start();
+            edge = edge.with_location(file.display().to_string(), start.line, start.column);
+        }
+
+        // Add node if it doesn't exist (for external functions)
+        if let Some(path) = external_path {
+            if target.is_external && self.builder.graph.get_node(&edge.to).is_none() {
+                self.builder.graph.add_node(FunctionNode::external(path));
+            }
+        }
+
+        // Add edge if both nodes exist
+        if self.builder.graph.get_node(&edge.from).is_some()
+            && self.builder.graph.get_node(&edge.to).is_some()
+        {
+            self.builder.graph.add_edge(edge);
+        }
+    }
+
+    /// Records a macro invocation on the current function's node
+    fn record_macro(&mut self, mac: &Macro) {
+        let Some(current_fn) = self.current_function() else {
+            return;
+        };
+        let function_id = current_fn.function_id.clone();
+
+        let path: Vec<String> = mac
+            .path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect();
+        let start = mac.path.span().start();
+        if let Some(node) = self.builder.graph.get_node_mut(&function_id) {
+            node.macro_calls.push(MacroCall {
+                path: format!("{}!", path.join("::")),
+                line: start.line,
+                column: start.column,
+            });
+        }
+    }
+
+    /// Records a call dispatched through a trait to each of its implementations
+    ///
+    /// # Returns
+    ///
+    /// Whether the trait had any implementation of the method
+    fn record_trait_calls(
+        &mut self,
+        trait_name: &str,
+        method: &str,
+        context: CallContext,
+        confidence: f64,
+        span: proc_macro2::Span,
+    ) -> bool {
+        let implementations = self
+            .builder
+            .resolver
+            .trait_implementations(trait_name, method);
+        let dispatched = !implementations.is_empty();
+
+        for target in implementations {
+            self.record_call(target, None, CallKind::Trait, context, confidence, span);
+        }
+
+        dispatched
+    }
+
     /// Processes a function call
     fn process_call(&mut self, expr: &ExprCall) {
-        if let Some(current_fn) = self.current_function() {
+        let context_override = self.context_override.take();
+        let mut is_spawn = false;
+
+        if self.current_function().is_some() {
             // Try to resolve the called function
             if let Expr::Path(expr_path) = &*expr.func {
-                if let Some(resolved) = self.builder.resolver.resolve_path(&expr_path.path) {
-                    let kind = if resolved.is_external {
-                        CallKind::Direct
-                    } else if current_fn.function_id == resolved.full_path {
-                        CallKind::Recursive
-                    } else {
-                        CallKind::Direct
-                    };
-
-                    let edge =
-                        CallEdge::new(current_fn.function_id.clone(), resolved.full_path, kind)
-                            .with_conditional(current_fn.in_conditional)
-                            .with_in_loop(current_fn.in_loop)
-                            .with_context(CallContext::Direct);
-
-                    // Add node if it doesn't exist (for external functions)
-                    if self.builder.graph.get_node(&edge.to).is_none() && resolved.is_external {
-                        let external_node = FunctionNode::external(&expr_path.path);
-                        self.builder.graph.add_node(external_node);
-                    }
-
-                    // Add edge if both nodes exist
-                    if self.builder.graph.get_node(&edge.from).is_some()
-                        && self.builder.graph.get_node(&edge.to).is_some()
-                    {
-                        self.builder.graph.add_edge(edge);
-                    }
+                // `R::build()` with `R: Trait`, or `Trait::method(&x)`
+                let segments: Vec<_> = expr_path.path.segments.iter().collect();
+                let dispatched = match segments.as_slice() {
+                    [type_segment, method_segment] => self
+                        .resolve_type_name(type_segment.ident.to_string())
+                        .is_some_and(|trait_name| {
+                            self.record_trait_calls(
+                                &trait_name,
+                                &method_segment.ident.to_string(),
+                                context_override.unwrap_or(CallContext::Associated),
+                                EXACT_CONFIDENCE,
+                                expr.func.span(),
+                            )
+                        }),
+                    _ => false,
+                };
+
+                let resolved = if dispatched {
+                    None
+                } else {
+                    self.resolve_call_target(&expr_path.path)
+                };
+                if let Some(resolved) = resolved {
+                    // `tokio::spawn(..)`, `task::spawn_blocking(..)`, `thread::spawn(..)`
+                    is_spawn = resolved.is_external
+                        && expr_path.path.segments.last().is_some_and(|segment| {
+                            SPAWN_FUNCTIONS.contains(&segment.ident.to_string().as_str())
+                        });
+
+                    let context =
+                        context_override.unwrap_or(if resolved.kind == SymbolKind::Method {
+                            CallContext::Associated
+                        } else {
+                            CallContext::Direct
+                        });
+                    self.record_call(
+                        resolved,
+                        Some(&expr_path.path),
+                        CallKind::Direct,
+                        context,
+                        EXACT_CONFIDENCE,
+                        expr.func.span(),
+                    );
                 }
             }
         }
 
         // Continue visiting arguments
         self.visit_expr(&expr.func);
-        for arg in &expr.args {
-            self.visit_expr(arg);
+        for (index, arg) in expr.args.iter().enumerate() {
+            if is_spawn && index == 0 {
+                self.trace_spawned(arg);
+            } else {
+                self.visit_expr(arg);
+            }
         }
     }
 
     /// Processes a method call
+    ///
+    /// The receiver's type is inferred from `self`, field types, parameter
+    /// and `let` annotations, or constructor return types; the edge carries
+    /// the confidence of that inference. Receivers typed by a trait (`dyn
+    /// Trait`, `impl Trait` or a bounded generic) fan out to every
+    /// implementation with `CallKind::Trait` edges.
     fn process_method_call(&mut self, expr: &ExprMethodCall) {
-        if let Some(current_fn) = self.current_function() {
-            // For method calls, we need more sophisticated resolution
-            // For now, create a placeholder
-            let method_name = expr.method.to_string();
-            let edge = CallEdge::new(current_fn.function_id.clone(), method_name, CallKind::Trait)
-                .with_conditional(current_fn.in_conditional)
-                .with_in_loop(current_fn.in_loop)
-                .with_context(CallContext::Method);
-
-            // We might not be able to resolve the exact method without type information
-            // This is a limitation that could be improved with more sophisticated analysis
+        let context_override = self.context_override.take();
+        let method = expr.method.to_string();
+        let mut is_internal = false;
+
+        if let Some(receiver) = self.infer_expr_type(&expr.receiver) {
+            let dispatched = self.record_trait_calls(
+                &receiver.type_name,
+                &method,
+                context_override.unwrap_or(CallContext::Method),
+                receiver.confidence,
+                expr.method.span(),
+            );
+
+            let resolved = if dispatched {
+                None
+            } else {
+                self.builder
+                    .resolver
+                    .resolve_method(&receiver.type_name, &method)
+            };
+            is_internal = dispatched || resolved.is_some();
+            if let Some(resolved) = resolved {
+                self.record_call(
+                    resolved,
+                    None,
+                    CallKind::Direct,
+                    context_override.unwrap_or(CallContext::Method),
+                    receiver.confidence,
+                    expr.method.span(),
+                );
+            }
         }
 
+        // `join_set.spawn(..)`, `handle.spawn_blocking(..)`
+        let is_spawn = !is_internal && SPAWN_FUNCTIONS.contains(&method.as_str());
+
         // Continue visiting
         self.visit_expr(&expr.receiver);
-        for arg in &expr.args {
-            self.visit_expr(arg);
+        for (index, arg) in expr.args.iter().enumerate() {
+            if is_spawn && index == 0 {
+                self.trace_spawned(arg);
+            } else {
+                self.visit_expr(arg);
+            }
         }
     }
 }
 
 impl<'a> Visit<'_> for CallTracer<'a> {
     fn visit_item_fn(&mut self, item: &ItemFn) {
-        // Extract function ID
-        let module_path = self
-            .builder
-            .extract_module_path(self.builder.current_file.as_ref().unwrap());
-        let function_id = if module_path.is_empty() {
-            item.sig.ident.to_string()
-        } else {
-            format!("{}::{}", module_path.join("::"), item.sig.ident)
+        let function_id = self.qualify(&item.sig.ident.to_string());
+        self.trace_function(function_id, &item.sig, &item.block);
+    }
+
+    fn visit_item_mod(&mut self, item: &ItemMod) {
+        self.module_path.push(item.ident.to_string());
+        self.builder
+            .resolver
+            .set_module_path(self.module_path.clone());
+
+        syn::visit::visit_item_mod(self, item);
+
+        self.module_path.pop();
+        self.builder
+            .resolver
+            .set_module_path(self.module_path.clone());
+    }
+
+    fn visit_item_impl(&mut self, item: &ItemImpl) {
+        let Some(self_type) = type_name(&item.self_ty) else {
+            return;
         };
 
-        // Push function context
-        self.builder.context_stack.push(FunctionContext {
-            function_id,
-            in_conditional: false,
-            in_loop: false,
-            block_depth: 0,
+        self.impl_scopes.push(ImplScope {
+            self_type,
+            bounds: generic_bounds(&item.generics),
         });
+        syn::visit::visit_item_impl(self, item);
+        self.impl_scopes.pop();
+    }
 
-        // Visit function body
-        self.visit_block(&item.block);
+    fn visit_item_trait(&mut self, item: &ItemTrait) {
+        self.impl_scopes.push(ImplScope {
+            self_type: item.ident.to_string(),
+            bounds: generic_bounds(&item.generics),
+        });
+        syn::visit::visit_item_trait(self, item);
+        self.impl_scopes.pop();
+    }
 
-        // Pop function context
-        self.builder.context_stack.pop();
+    fn visit_trait_item_fn(&mut self, item: &TraitItemFn) {
+        let (Some(self_type), Some(block)) = (self.current_impl_type(), &item.default) else {
+            return;
+        };
+        let function_id = self.qualify(&format!("{}::{}", self_type, item.sig.ident));
+        self.trace_function(function_id, &item.sig, block);
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &ImplItemFn) {
+        let Some(self_type) = self.current_impl_type() else {
+            return;
+        };
+        let function_id = self.qualify(&format!("{}::{}", self_type, item.sig.ident));
+        self.trace_function(function_id, &item.sig, &item.block);
+    }
+
+    fn visit_local(&mut self, local: &Local) {
+        syn::visit::visit_local(self, local);
+
+        // Record the binding's type after visiting the initializer so that
+        // `let repo = repo.clone()` infers from the shadowed binding
+        let binding = match &local.pat {
+            Pat::Type(pat_type) => match &*pat_type.pat {
+                Pat::Ident(pat_ident) => self.hint_type(&pat_type.ty).map(|ty| {
+                    (
+                        pat_ident.ident.to_string(),
+                        TypeHint::new(ty, DECLARED_CONFIDENCE),
+                    )
+                }),
+                _ => None,
+            },
+            Pat::Ident(pat_ident) => local
+                .init
+                .as_ref()
+                .and_then(|init| self.infer_expr_type(&init.expr))
+                .map(|hint| (pat_ident.ident.to_string(), hint)),
+            _ => None,
+        };
+
+        if let Some((name, hint)) = binding {
+            if let Some(ctx) = self.builder.context_stack.last_mut() {
+                ctx.locals.insert(name, hint);
+            }
+        }
     }
 
     fn visit_expr_call(&mut self, expr: &ExprCall) {
         self.process_call(expr);
     }
 
+    fn visit_expr_closure(&mut self, expr: &ExprClosure) {
+        self.trace_closure(expr, CallContext::Closure);
+    }
+
+    fn visit_expr_async(&mut self, expr: &ExprAsync) {
+        self.trace_async(expr, CallContext::Async);
+    }
+
+    fn visit_macro(&mut self, mac: &Macro) {
+        self.record_macro(mac);
+
+        // Macro arguments are opaque tokens to syn; trace the ones that parse
+        // as expressions (`join!(a(), b())`, `println!("{}", f())`)
+        let Ok(args) = mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated) else {
+            return;
+        };
+
+        let is_join = mac
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| JOIN_MACROS.contains(&segment.ident.to_string().as_str()));
+        let context = if is_join {
+            CallContext::Async
+        } else {
+            CallContext::Macro
+        };
+
+        for arg in &args {
+            match arg {
+                Expr::Async(block) => self.trace_async(block, context),
+                _ => {
+                    self.context_override = Some(context);
+                    self.visit_expr(arg);
+                    self.context_override = None;
+                }
+            }
+        }
+    }
+
     fn visit_expr_method_call(&mut self, expr: &ExprMethodCall) {
         self.process_method_call(expr);
     }
@@ -580,4 +1358,345 @@ mod tests {
         let external_nodes = graph.nodes_by_kind(NodeKind::External);
         assert!(!external_nodes.is_empty());
     }
+
+    #[test]
+    fn test_method_call_resolution() {
+        let temp_dir = TempDir::new().unwrap();
+        let src_dir = temp_dir.path().join("src");
+        fs::create_dir(&src_dir).unwrap();
+
+        let code = r#"
+            use std::sync::Arc;
+
+            pub struct UserRepo;
+
+            impl UserRepo {
+                pub fn new() -> Self {
+                    UserRepo
+                }
+
+                pub async fn find_user(&self, id: u64) -> Option<u64> {
+                    self.query(id)
+                }
+
+                fn query(&self, id: u64) -> Option<u64> {
+                    Some(id)
+                }
+            }
+
+            pub struct UserService {
+                repo: Arc<UserRepo>,
+            }
+
+            impl UserService {
+                pub fn new() -> Self {
+                    Self { repo: Arc::new(UserRepo::new()) }
+                }
+
+                pub async fn get_user(&self, id: u64) -> Option<u64> {
+                    self.repo.find_user(id).await
+                }
+            }
+
+            async fn handler() {
+                let service = UserService::new();
+                service.get_user(1).await;
+
+                let repo: UserRepo = UserRepo::new();
+                repo.find_user(2).await;
+            }
+        "#;
+
+        fs::write(src_dir.join("lib.rs"), code).unwrap();
+
+        let mut builder = GraphBuilder::new();
+        let graph = builder.build_from_directory(&src_dir).unwrap();
+
+        assert!(graph.get_node("UserRepo::find_user").is_some());
+        assert!(graph.get_node("UserService::get_user").is_some());
+
+        let edge = |from: &str, to: &str| {
+            graph
+                .edges()
+                .iter()
+                .find(|e| e.from == from && e.to == to)
+                .unwrap_or_else(|| panic!("missing edge {from} -> {to}"))
+                .clone()
+        };
+
+        // self.method()
+        let self_call = edge("UserRepo::find_user", "UserRepo::query");
+        assert_eq!(self_call.context, CallContext::Method);
+        assert!((self_call.confidence - EXACT_CONFIDENCE).abs() < f64::EPSILON);
+
+        // Type::assoc() and Self { .. } construction
+        let assoc = edge("handler", "UserService::new");
+        assert_eq!(assoc.context, CallContext::Associated);
+        assert!(assoc.line_number.is_some());
+        edge("UserService::new", "UserRepo::new");
+
+        // Field type hint: self.repo: Arc<UserRepo>
+        let field_call = edge("UserService::get_user", "UserRepo::find_user");
+        assert!((field_call.confidence - DECLARED_CONFIDENCE).abs() < f64::EPSILON);
+
+        // Constructor return type hint
+        let inferred = edge("handler", "UserService::get_user");
+        assert!((inferred.confidence - INFERRED_CONFIDENCE).abs() < f64::EPSILON);
+
+        // `let x: T` annotation
+        let annotated = edge("handler", "UserRepo::find_user");
+        assert!((annotated.confidence - DECLARED_CONFIDENCE).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_parsed_files_are_traced_on_build() {
+        let code = r#"
+            mod api {
+                pub fn handler() {
+                    Service::run();
+                }
+
+                pub struct Service;
+
+                impl Service {
+                    pub fn run() {}
+                }
+            }
+        "#;
+
+        let source = crate::ast::SourceFile {
+            path: PathBuf::from("src/lib.rs"),
+            syntax_tree: syn::parse_file(code).unwrap(),
+            source: code.to_string(),
+            content_hash: String::new(),
+        };
+
+        let mut builder = GraphBuilder::new();
+        builder.add_parsed_file(&source).unwrap();
+        let graph = builder.build().unwrap();
+
+        assert!(
+            graph
+                .edges()
+                .iter()
+                .any(|e| e.from == "api::handler" && e.to == "api::Service::run")
+        );
+    }
+
+    #[test]
+    fn test_trait_dispatch_expansion() {
+        let temp_dir = TempDir::new().unwrap();
+        let src_dir = temp_dir.path().join("src");
+        fs::create_dir(&src_dir).unwrap();
+
+        let code = r#"
+            use std::sync::Arc;
+
+            pub trait UserRepository {
+                fn save(&self, id: u64);
+
+                fn save_all(&self, ids: Vec<u64>) {
+                    for id in ids {
+                        self.save(id);
+                    }
+                }
+            }
+
+            pub struct PostgresRepo;
+            impl UserRepository for PostgresRepo {
+                fn save(&self, id: u64) {}
+            }
+
+            pub struct RedisRepo;
+            impl UserRepository for RedisRepo {
+                fn save(&self, id: u64) {}
+            }
+
+            pub struct AppState {
+                repo: Arc<dyn UserRepository + Send + Sync>,
+            }
+
+            async fn create_user(state: AppState) {
+                state.repo.save(1);
+            }
+
+            fn import_users<R>(repo: &R)
+            where
+                R: UserRepository,
+            {
+                repo.save_all(vec![1, 2]);
+            }
+        "#;
+
+        fs::write(src_dir.join("lib.rs"), code).unwrap();
+
+        let mut builder = GraphBuilder::new();
+        let graph = builder.build_from_directory(&src_dir).unwrap();
+
+        let targets = |from: &str| {
+            let mut targets: Vec<_> = graph
+                .edges()
+                .iter()
+                .filter(|e| e.from == from)
+                .map(|e| (e.to.clone(), e.kind))
+                .collect();
+            targets.sort_by(|a, b| a.0.cmp(&b.0));
+            targets
+        };
+
+        // Arc<dyn Trait> field fans out to every implementation
+        assert_eq!(
+            targets("create_user"),
+            vec![
+                ("PostgresRepo::save".to_string(), CallKind::Trait),
+                ("RedisRepo::save".to_string(), CallKind::Trait),
+            ]
+        );
+
+        // Generic `R: Trait` reaches the default method body, which in turn
+        // dispatches `self.save()` to every implementation
+        assert_eq!(
+            targets("import_users"),
+            vec![("UserRepository::save_all".to_string(), CallKind::Trait)]
+        );
+        assert_eq!(targets("UserRepository::save_all").len(), 2);
+
+        let path = graph.find_path("create_user", "RedisRepo::save");
+        assert!(path.is_some());
+    }
+
+    #[test]
+    fn test_closures_async_blocks_and_spawned_tasks() {
+        let temp_dir = TempDir::new().unwrap();
+        let src_dir = temp_dir.path().join("src");
+        fs::create_dir(&src_dir).unwrap();
+
+        let code = r#"
+            async fn handler(ids: Vec<u64>) {
+                let users: Vec<u64> = ids.iter().map(|id| load(*id)).collect();
+
+                tokio::spawn(async move {
+                    notify();
+                });
+
+                tokio::task::spawn_blocking(move || compress());
+                tokio::spawn(worker());
+
+                tokio::join!(fetch_a(), async { fetch_b().await });
+            }
+
+            fn load(id: u64) -> u64 { id }
+            fn notify() {}
+            fn compress() {}
+            async fn worker() {}
+            async fn fetch_a() {}
+            async fn fetch_b() {}
+        "#;
+
+        fs::write(src_dir.join("lib.rs"), code).unwrap();
+
+        let mut builder = GraphBuilder::new();
+        let graph = builder.build_from_directory(&src_dir).unwrap();
+
+        let edge = |from: &str, to: &str| {
+            graph
+                .edges()
+                .iter()
+                .find(|e| e.from == from && e.to == to)
+                .unwrap_or_else(|| panic!("missing edge {from} -> {to}"))
+                .clone()
+        };
+
+        // `.map(|id| load(*id))`: the closure is a child node making the call
+        let closure = edge("handler", "handler::{closure#1}");
+        assert_eq!(closure.kind, CallKind::Closure);
+        assert_eq!(closure.context, CallContext::Closure);
+        assert_eq!(
+            graph.get_node("handler::{closure#1}").unwrap().kind,
+            NodeKind::Closure
+        );
+        edge("handler::{closure#1}", "load");
+        assert!(
+            !graph
+                .edges()
+                .iter()
+                .any(|e| e.from == "handler" && e.to == "load")
+        );
+
+        // Spawned async block, spawned closure and spawned call
+        assert_eq!(
+            edge("handler", "handler::{async#2}").context,
+            CallContext::Spawn
+        );
+        edge("handler::{async#2}", "notify");
+        assert_eq!(
+            edge("handler", "handler::{closure#3}").context,
+            CallContext::Spawn
+        );
+        edge("handler::{closure#3}", "compress");
+        assert_eq!(edge("handler", "worker").context, CallContext::Spawn);
+
+        // join! arguments are async calls
+        assert_eq!(edge("handler", "fetch_a").context, CallContext::Async);
+        assert_eq!(
+            edge("handler", "handler::{async#4}").context,
+            CallContext::Async
+        );
+        edge("handler::{async#4}", "fetch_b");
+    }
+
+    #[test]
+    fn test_same_named_types_in_different_modules() {
+        let temp_dir = TempDir::new().unwrap();
+        let src_dir = temp_dir.path().join("src");
+        fs::create_dir(&src_dir).unwrap();
+
+        let code = r#"
+            pub struct Smtp;
+            impl Smtp {
+                pub fn open(&self) {}
+            }
+
+            pub struct Http;
+            impl Http {
+                pub fn open(&self) {}
+            }
+
+            mod mail {
+                pub struct Client {
+                    transport: Smtp,
+                }
+
+                pub struct Mailer {
+                    client: Client,
+                }
+
+                impl Mailer {
+                    pub fn deliver(&self) {
+                        self.client.transport.open();
+                    }
+                }
+            }
+
+            mod billing {
+                pub struct Client {
+                    transport: Http,
+                }
+            }
+        "#;
+
+        fs::write(src_dir.join("lib.rs"), code).unwrap();
+
+        let mut builder = GraphBuilder::new();
+        let graph = builder.build_from_directory(&src_dir).unwrap();
+
+        // `billing::Client` is registered last but doesn't shadow `mail::Client`
+        let targets: Vec<_> = graph
+            .edges()
+            .iter()
+            .filter(|e| e.from == "mail::Mailer::deliver")
+            .map(|e| e.to.as_str())
+            .collect();
+        assert_eq!(targets, vec!["Smtp::open"]);
+    }
 }
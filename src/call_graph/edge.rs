@@ -24,6 +24,11 @@ pub struct CallEdge {
     pub is_in_loop: bool,
     /// Call context (e.g., method call, function call, closure)
     pub context: CallContext,
+    /// Confidence that the call resolves to `to` (1.0 = certain)
+    ///
+    /// Method calls whose receiver type is inferred from local hints such as
+    /// field types or constructor return types carry a lower confidence.
+    pub confidence: f64,
 }
 
 impl CallEdge {
@@ -49,6 +54,7 @@ impl CallEdge {
             is_conditional: false,
             is_in_loop: false,
             context: CallContext::Direct,
+            confidence: 1.0,
         }
     }
 
@@ -78,6 +84,12 @@ impl CallEdge {
         self
     }
 
+    /// Sets the resolution confidence, clamped to `0.0..=1.0`
+    pub fn with_confidence(mut self, confidence: f64) -> Self {
+        self.confidence = confidence.clamp(0.0, 1.0);
+        self
+    }
+
     /// Returns a unique identifier for this edge
     pub fn id(&self) -> String {
         format!("{} -> {}", self.from, self.to)
@@ -97,7 +109,7 @@ impl CallEdge {
         let conditional_factor = if self.is_conditional { 0.8 } else { 1.0 };
         let loop_factor = if self.is_in_loop { 1.5 } else { 1.0 };
 
-        base_weight * conditional_factor * loop_factor
+        base_weight * conditional_factor * loop_factor * self.confidence
     }
 }
 
@@ -167,6 +179,8 @@ pub enum CallContext {
     Macro,
     /// Async await point
     Async,
+    /// Future or closure handed to a task spawner (e.g. `tokio::spawn`)
+    Spawn,
 }
 
 impl fmt::Display for CallContext {
@@ -178,6 +192,7 @@ impl fmt::Display for CallContext {
             CallContext::Closure => write!(f, "closure"),
             CallContext::Macro => write!(f, "macro"),
             CallContext::Async => write!(f, "async"),
+            CallContext::Spawn => write!(f, "spawn"),
         }
     }
 }
@@ -227,6 +242,18 @@ mod tests {
         assert!((complex_edge.weight() - 1.2).abs() < 0.0001);
     }
 
+    #[test]
+    fn test_edge_confidence() {
+        let edge = CallEdge::new("a".to_string(), "b".to_string(), CallKind::Direct);
+        assert!((edge.confidence - 1.0).abs() < f64::EPSILON);
+
+        let inferred = edge.clone().with_confidence(0.75);
+        assert!((inferred.weight() - 0.75).abs() < f64::EPSILON);
+
+        let clamped = edge.with_confidence(1.7);
+        assert!((clamped.confidence - 1.0).abs() < f64::EPSILON);
+    }
+
     #[test]
     fn test_edge_equality() {
         let edge1 = CallEdge::new("a".to_string(), "b".to_string(), CallKind::Direct);
@@ -258,6 +258,59 @@ impl CallGraph {
         None
     }
 
+    /// Finds the shortest path from a node to every node within a depth limit
+    ///
+    /// Callees are visited in ID order, so ties between equally short paths
+    /// are broken deterministically.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_id` - The ID of the starting node
+    /// * `max_depth` - Maximum number of calls to follow
+    ///
+    /// # Returns
+    ///
+    /// A map from each reachable node ID to the path leading to it, starting
+    /// with `start_id` itself
+    pub fn shortest_paths_from(
+        &self,
+        start_id: &str,
+        max_depth: usize,
+    ) -> HashMap<String, Vec<String>> {
+        let mut paths: HashMap<String, Vec<String>> = HashMap::new();
+        let mut queue = VecDeque::new();
+
+        if self.nodes.contains_key(start_id) {
+            paths.insert(start_id.to_string(), vec![start_id.to_string()]);
+            queue.push_back(start_id.to_string());
+        }
+
+        while let Some(current) = queue.pop_front() {
+            let path = paths[&current].clone();
+            if path.len() > max_depth {
+                continue;
+            }
+
+            let Some(neighbors) = self.adjacency.get(&current) else {
+                continue;
+            };
+            let mut neighbors: Vec<&String> = neighbors.iter().collect();
+            neighbors.sort();
+
+            for neighbor in neighbors {
+                if paths.contains_key(neighbor) {
+                    continue;
+                }
+                let mut neighbor_path = path.clone();
+                neighbor_path.push(neighbor.clone());
+                paths.insert(neighbor.clone(), neighbor_path);
+                queue.push_back(neighbor.clone());
+            }
+        }
+
+        paths
+    }
+
     /// Detects cycles in the graph using DFS
     ///
     /// # Returns
@@ -417,6 +470,7 @@ mod tests {
             id: "main".to_string(),
             name: "main".to_string(),
             module_path: vec![],
+            crate_name: None,
             kind: NodeKind::Endpoint,
             file_path: None,
             line_number: None,
@@ -427,6 +481,7 @@ mod tests {
             attributes: vec![],
             calls: HashSet::new(),
             called_by: HashSet::new(),
+            macro_calls: Vec::new(),
         };
 
         let mut foo = main.clone();
@@ -522,6 +577,19 @@ mod tests {
         assert!(no_path.is_none());
     }
 
+    #[test]
+    fn test_shortest_paths_within_depth() {
+        let graph = create_test_graph();
+
+        let paths = graph.shortest_paths_from("main", 2);
+        assert_eq!(paths["main"], vec!["main"]);
+        assert_eq!(paths["bar"], vec!["main", "foo", "bar"]);
+        assert!(!paths.contains_key("baz"));
+
+        let paths = graph.shortest_paths_from("main", 3);
+        assert_eq!(paths["baz"], vec!["main", "foo", "bar", "baz"]);
+    }
+
     #[test]
     fn test_cycle_detection() {
         let mut graph = create_test_graph();
@@ -41,17 +41,21 @@
 //! - `FunctionNode`: Represents functions in the graph
 //! - `CallEdge`: Represents function calls with metadata
 //! - `SymbolResolver`: Resolves function references across modules
+//! - `ModuleTree`: Maps source files to module paths by walking `mod` declarations
 
 mod builder;
 mod edge;
 mod graph;
+mod module_tree;
 mod node;
 mod resolver;
 
 pub use builder::{GraphBuildError, GraphBuilder};
-pub use edge::{CallEdge, CallKind};
+pub use edge::{CallContext, CallEdge, CallKind};
 pub use graph::{CallGraph, GraphStats};
-pub use node::{FunctionNode, NodeKind};
+pub use module_tree::{ModuleLocation, ModuleTree};
+pub use node::{FunctionNode, MacroCall, NodeKind};
+pub(crate) use resolver::type_name;
 pub use resolver::{ResolvedSymbol, SymbolResolver};
 
 /// Result type for call graph operations
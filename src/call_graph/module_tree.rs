@@ -0,0 +1,471 @@
+//! Module paths computed from a crate's real module tree
+//!
+//! Module paths are found by walking `mod` declarations from each crate root
+//! the same way rustc does, so `mod.rs` files, `#[path]` attributes, inline
+//! `mod` blocks and binary targets all map to the module they define.
+
+use cargo_metadata::{Metadata, MetadataCommand};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use syn::{Item, ItemMod};
+
+use super::GraphBuildError;
+use crate::dependencies::ProjectDependencies;
+
+/// Where a source file sits in its crate's module tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleLocation {
+    /// Name of the crate the file belongs to, if known from cargo metadata
+    pub crate_name: Option<String>,
+    /// Modules from the crate root down to the file (empty for the root)
+    pub modules: Vec<String>,
+}
+
+impl ModuleLocation {
+    /// Returns the full module path, starting with the crate name when known
+    pub fn path(&self) -> Vec<String> {
+        self.crate_name
+            .iter()
+            .cloned()
+            .chain(self.modules.iter().cloned())
+            .collect()
+    }
+
+    /// Returns the path `crate::` expands to for this file
+    pub fn crate_root(&self) -> Vec<String> {
+        self.crate_name.iter().cloned().collect()
+    }
+}
+
+/// Maps source files to their module paths
+#[derive(Debug, Clone, Default)]
+pub struct ModuleTree {
+    /// Canonicalized file path to its location in the module tree
+    files: HashMap<PathBuf, ModuleLocation>,
+    /// Root directory of the discovered project or workspace
+    root: Option<PathBuf>,
+}
+
+impl ModuleTree {
+    /// Creates an empty module tree
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Discovers the module tree for a project
+    ///
+    /// Uses cargo metadata for the nearest `Cargo.toml` at or above `root`.
+    /// Without a manifest, `lib.rs`, `main.rs` and `bin/*.rs` under `root`
+    /// (or `root/src`) are used as anonymous crate roots.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - A project directory, source directory or file inside it
+    ///
+    /// # Returns
+    ///
+    /// The discovered module tree; empty if no crate root was found
+    pub fn discover(root: &Path) -> Self {
+        let metadata = ProjectDependencies::workspace_metadata(root).ok().flatten();
+        Self::discover_with_metadata(root, metadata.as_ref())
+    }
+
+    /// Discovers the module tree for a project from already loaded metadata
+    ///
+    /// Like [`discover`](Self::discover), for callers that also need the cargo
+    /// metadata of the project and shouldn't run `cargo metadata` twice.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - A project directory, source directory or file inside it
+    /// * `metadata` - Cargo metadata of the workspace containing `root`, if any
+    ///
+    /// # Returns
+    ///
+    /// The discovered module tree; empty if no crate root was found
+    pub fn discover_with_metadata(root: &Path, metadata: Option<&Metadata>) -> Self {
+        if let Some(tree) = metadata.map(Self::from_metadata) {
+            if !tree.files.is_empty() {
+                return tree;
+            }
+        }
+
+        let mut tree = Self::new();
+        tree.root = Some(if root.is_dir() {
+            root.to_path_buf()
+        } else {
+            root.parent().unwrap_or(root).to_path_buf()
+        });
+        let src_dir = if root.join("src").is_dir() {
+            root.join("src")
+        } else {
+            root.to_path_buf()
+        };
+        for name in ["lib.rs", "main.rs"] {
+            let crate_root = src_dir.join(name);
+            if crate_root.is_file() {
+                tree.add_crate_root(None, &crate_root);
+            }
+        }
+        if let Ok(entries) = std::fs::read_dir(src_dir.join("bin")) {
+            for entry in entries.filter_map(|e| e.ok()) {
+                let path = entry.path();
+                let (name, crate_root) = if path.is_dir() {
+                    (path.file_name(), path.join("main.rs"))
+                } else {
+                    (path.file_stem(), path.clone())
+                };
+                let name = name.map(|n| n.to_string_lossy().replace('-', "_"));
+                if crate_root.extension().is_some_and(|ext| ext == "rs") && crate_root.is_file() {
+                    tree.add_crate_root(name, &crate_root);
+                }
+            }
+        }
+
+        tree
+    }
+
+    /// Builds the module tree for every target of every workspace member
+    ///
+    /// # Arguments
+    ///
+    /// * `manifest_path` - Path to a `Cargo.toml`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if cargo metadata fails
+    pub fn from_manifest(manifest_path: &Path) -> Result<Self, GraphBuildError> {
+        let metadata = MetadataCommand::new()
+            .manifest_path(manifest_path)
+            .no_deps()
+            .exec()
+            .map_err(|e| GraphBuildError::InvalidPath(format!("cargo metadata failed: {e}")))?;
+
+        Ok(Self::from_metadata(&metadata))
+    }
+
+    /// Builds the module tree for every target of every workspace member
+    ///
+    /// # Arguments
+    ///
+    /// * `metadata` - Cargo metadata of a workspace
+    pub fn from_metadata(metadata: &Metadata) -> Self {
+        let mut tree = Self::new();
+        tree.root = Some(metadata.workspace_root.clone().into_std_path_buf());
+        for package in metadata.workspace_packages() {
+            for target in &package.targets {
+                if target.kind.iter().any(|kind| kind == "custom-build") {
+                    continue;
+                }
+                let crate_name = target.name.replace('-', "_");
+                tree.add_crate_root(Some(crate_name), target.src_path.as_std_path());
+            }
+        }
+
+        tree
+    }
+
+    /// Adds a crate root and every module file reachable from it
+    ///
+    /// Files already mapped by an earlier crate root keep their location, so a
+    /// `main.rs` that declares `mod api;` doesn't steal `api.rs` from `lib.rs`.
+    ///
+    /// # Arguments
+    ///
+    /// * `crate_name` - Name of the crate, or None to leave paths unprefixed
+    /// * `root` - The crate root file (`lib.rs`, `main.rs`, ...)
+    pub fn add_crate_root(&mut self, crate_name: Option<String>, root: &Path) {
+        let location = ModuleLocation {
+            crate_name,
+            modules: Vec::new(),
+        };
+        self.add_file(root, location, true);
+    }
+
+    /// Looks up where a file sits in the module tree
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - Path to a source file
+    ///
+    /// # Returns
+    ///
+    /// The file's location, or None if no crate root declares it
+    pub fn location(&self, file: &Path) -> Option<&ModuleLocation> {
+        let file = file.canonicalize().ok()?;
+        self.files.get(&file)
+    }
+
+    /// Looks up where a file sits in the module tree
+    ///
+    /// Files no crate root declares are placed by their path under `src/`:
+    /// `src/api/users.rs` and `src/api/users/mod.rs` both map to
+    /// `["api", "users"]`, crate roots (`lib.rs`, `main.rs`) and files outside
+    /// a `src` directory to the root module.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - Path to a source file
+    ///
+    /// # Returns
+    ///
+    /// The file's location, without a crate name if it was guessed
+    pub fn module_location(&self, file: &Path) -> ModuleLocation {
+        self.location(file)
+            .cloned()
+            .unwrap_or_else(|| ModuleLocation {
+                crate_name: None,
+                modules: modules_from_path(file),
+            })
+    }
+
+    /// Returns the root directory of the project the tree was discovered for
+    ///
+    /// This is the cargo workspace root when a manifest was found, and None
+    /// for trees built by hand.
+    pub fn root(&self) -> Option<&Path> {
+        self.root.as_deref()
+    }
+
+    /// Returns the number of files in the tree
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Checks whether the tree has no files
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Records a module file and walks the `mod` declarations in it
+    ///
+    /// `mod_rs` is true for crate roots, `mod.rs` files and files loaded via
+    /// `#[path]`, whose child modules live next to them rather than in a
+    /// directory named after the module.
+    fn add_file(&mut self, file: &Path, location: ModuleLocation, mod_rs: bool) {
+        let Ok(file) = file.canonicalize() else {
+            return;
+        };
+        if self.files.contains_key(&file) {
+            return;
+        }
+        let Ok(content) = std::fs::read_to_string(&file) else {
+            return;
+        };
+        let Ok(syntax) = syn::parse_file(&content) else {
+            return;
+        };
+
+        let file_dir = file.parent().map(Path::to_path_buf).unwrap_or_default();
+        let module_dir = if mod_rs {
+            file_dir.clone()
+        } else {
+            let stem = file.file_stem().unwrap_or_default();
+            file_dir.join(stem)
+        };
+
+        self.files.insert(file, location.clone());
+        self.walk_items(&syntax.items, &location, &file_dir, &module_dir, false);
+    }
+
+    /// Walks items looking for `mod` declarations
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - Items of a file or inline module
+    /// * `location` - Location of the module containing the items
+    /// * `file_dir` - Directory containing the current file
+    /// * `module_dir` - Directory child module files are looked up in
+    /// * `inline` - Whether the items are inside an inline `mod { ... }`
+    fn walk_items(
+        &mut self,
+        items: &[Item],
+        location: &ModuleLocation,
+        file_dir: &Path,
+        module_dir: &Path,
+        inline: bool,
+    ) {
+        for item in items {
+            let Item::Mod(item_mod) = item else {
+                continue;
+            };
+
+            let name = item_mod.ident.to_string();
+            let mut child = location.clone();
+            child.modules.push(name.clone());
+
+            if let Some((_, items)) = &item_mod.content {
+                let child_dir = match path_attribute(item_mod) {
+                    Some(path) => module_dir.join(path),
+                    None => module_dir.join(&name),
+                };
+                self.walk_items(items, &child, file_dir, &child_dir, true);
+                continue;
+            }
+
+            // `#[path]` is relative to the current file's directory, or to the
+            // inline module's directory when nested in one
+            if let Some(path) = path_attribute(item_mod) {
+                let base = if inline { module_dir } else { file_dir };
+                self.add_file(&base.join(path), child, true);
+                continue;
+            }
+
+            let flat = module_dir.join(format!("{}.rs", name));
+            let nested = module_dir.join(&name).join("mod.rs");
+            if flat.is_file() {
+                self.add_file(&flat, child, false);
+            } else if nested.is_file() {
+                self.add_file(&nested, child, true);
+            }
+        }
+    }
+}
+
+/// Derives the modules of a file from its location under `src/`
+fn modules_from_path(file: &Path) -> Vec<String> {
+    let components: Vec<String> = file
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+
+    let Some(src_index) = components.iter().rposition(|c| c == "src") else {
+        return Vec::new();
+    };
+
+    let mut modules: Vec<String> = components[src_index + 1..]
+        .iter()
+        .map(|c| c.trim_end_matches(".rs").to_string())
+        .collect();
+
+    if matches!(modules.last().map(String::as_str), Some("mod")) {
+        modules.pop();
+    }
+    if matches!(modules.as_slice(), [root] if root == "lib" || root == "main") {
+        modules.clear();
+    }
+
+    modules
+}
+
+/// Extracts the value of a `#[path = "..."]` attribute on a module
+fn path_attribute(item_mod: &ItemMod) -> Option<String> {
+    item_mod.attrs.iter().find_map(|attr| {
+        if !attr.path().is_ident("path") {
+            return None;
+        }
+        let syn::Meta::NameValue(meta) = &attr.meta else {
+            return None;
+        };
+        match &meta.value {
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(path),
+                ..
+            }) => Some(path.value()),
+            _ => None,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn modules(tree: &ModuleTree, root: &Path, path: &str) -> Option<Vec<String>> {
+        tree.location(&root.join(path))
+            .map(|location| location.path())
+    }
+
+    #[test]
+    fn test_module_tree_follows_mod_declarations() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "src/lib.rs",
+            "pub mod api;\nmod db;\nmod util { pub mod fmt; }\n",
+        );
+        write(root, "src/api/mod.rs", "pub mod users;\n");
+        write(root, "src/api/users.rs", "pub fn create() {}\n");
+        write(
+            root,
+            "src/db.rs",
+            "#[path = \"pg.rs\"]\nmod postgres;\nmod pool;\n",
+        );
+        write(root, "src/pg.rs", "mod conn;\n");
+        write(root, "src/conn.rs", "");
+        write(root, "src/db/pool.rs", "");
+        write(root, "src/util/fmt.rs", "");
+        write(root, "src/orphan.rs", "");
+
+        let mut tree = ModuleTree::new();
+        tree.add_crate_root(Some("shop".to_string()), &root.join("src/lib.rs"));
+
+        assert_eq!(modules(&tree, root, "src/lib.rs").unwrap(), vec!["shop"]);
+        assert_eq!(
+            modules(&tree, root, "src/api/users.rs").unwrap(),
+            vec!["shop", "api", "users"]
+        );
+        assert_eq!(
+            modules(&tree, root, "src/pg.rs").unwrap(),
+            vec!["shop", "db", "postgres"]
+        );
+        assert_eq!(
+            modules(&tree, root, "src/conn.rs").unwrap(),
+            vec!["shop", "db", "postgres", "conn"]
+        );
+        assert_eq!(
+            modules(&tree, root, "src/db/pool.rs").unwrap(),
+            vec!["shop", "db", "pool"]
+        );
+        assert_eq!(
+            modules(&tree, root, "src/util/fmt.rs").unwrap(),
+            vec!["shop", "util", "fmt"]
+        );
+        assert!(modules(&tree, root, "src/orphan.rs").is_none());
+
+        // Files outside the tree are placed by their path
+        let orphan = tree.module_location(&root.join("src/orphan.rs"));
+        assert_eq!(orphan.crate_name, None);
+        assert_eq!(orphan.modules, vec!["orphan"]);
+        assert_eq!(
+            tree.module_location(Path::new("src/api/mod.rs")).modules,
+            vec!["api"]
+        );
+        assert!(
+            tree.module_location(Path::new("src/main.rs"))
+                .modules
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_discover_without_manifest() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        write(root, "src/main.rs", "mod handlers;\n");
+        write(root, "src/handlers.rs", "");
+        write(root, "src/bin/worker/main.rs", "mod jobs;\n");
+        write(root, "src/bin/worker/jobs.rs", "");
+
+        let tree = ModuleTree::discover(root);
+
+        assert_eq!(
+            modules(&tree, root, "src/handlers.rs").unwrap(),
+            vec!["handlers"]
+        );
+        assert_eq!(
+            modules(&tree, root, "src/bin/worker/jobs.rs").unwrap(),
+            vec!["worker", "jobs"]
+        );
+        assert_eq!(tree.root(), Some(root));
+    }
+}
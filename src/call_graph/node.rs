@@ -3,7 +3,7 @@
 use std::collections::HashSet;
 use std::fmt;
 use std::hash::{Hash, Hasher};
-use syn::{ItemFn, Path};
+use syn::{ImplItemFn, ItemFn, Path, Signature, TraitItemFn};
 
 /// Represents a function node in the call graph
 #[derive(Debug, Clone)]
@@ -14,6 +14,10 @@ pub struct FunctionNode {
     pub name: String,
     /// Full module path to the function
     pub module_path: Vec<String>,
+    /// Crate the function belongs to, when known from cargo metadata
+    ///
+    /// When set, it is also the first segment of `module_path`.
+    pub crate_name: Option<String>,
     /// Kind of function node
     pub kind: NodeKind,
     /// File path where the function is defined
@@ -34,6 +38,19 @@ pub struct FunctionNode {
     pub calls: HashSet<String>,
     /// Set of functions that call this node
     pub called_by: HashSet<String>,
+    /// Macros invoked in the function's body, in source order
+    pub macro_calls: Vec<MacroCall>,
+}
+
+/// A macro invocation in a function's body
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MacroCall {
+    /// Path of the macro as written, with the `!` (e.g. `sqlx::query_as!`)
+    pub path: String,
+    /// Line of the invocation
+    pub line: usize,
+    /// Column of the invocation
+    pub column: usize,
 }
 
 impl FunctionNode {
@@ -81,9 +98,10 @@ impl FunctionNode {
             id,
             name,
             module_path,
+            crate_name: None,
             kind,
             file_path,
-            line_number: None, // To be set later with span information
+            line_number: Some(item.sig.ident.span().start().line),
             signature,
             is_async,
             is_unsafe,
@@ -91,6 +109,134 @@ impl FunctionNode {
             attributes,
             calls: HashSet::new(),
             called_by: HashSet::new(),
+            macro_calls: Vec::new(),
+        }
+    }
+
+    /// Creates a new function node from a method in an `impl` block
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - The parsed method
+    /// * `type_name` - The name of the implementing type
+    /// * `module_path` - The module path to the implementing type
+    /// * `file_path` - Optional file path where the method is defined
+    ///
+    /// # Returns
+    ///
+    /// A new FunctionNode with a `module::Type::method` ID
+    pub fn from_impl_fn(
+        item: &ImplItemFn,
+        type_name: &str,
+        module_path: Vec<String>,
+        file_path: Option<String>,
+    ) -> Self {
+        Self::from_method(&item.sig, &item.attrs, type_name, module_path, file_path)
+    }
+
+    /// Creates a new function node from a trait method with a default body
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - The parsed trait method
+    /// * `trait_name` - The name of the trait
+    /// * `module_path` - The module path to the trait
+    /// * `file_path` - Optional file path where the trait is defined
+    ///
+    /// # Returns
+    ///
+    /// A new FunctionNode with a `module::Trait::method` ID
+    pub fn from_trait_fn(
+        item: &TraitItemFn,
+        trait_name: &str,
+        module_path: Vec<String>,
+        file_path: Option<String>,
+    ) -> Self {
+        Self::from_method(&item.sig, &item.attrs, trait_name, module_path, file_path)
+    }
+
+    /// Creates a node for a method owned by a type or trait
+    fn from_method(
+        sig: &Signature,
+        attrs: &[syn::Attribute],
+        owner: &str,
+        module_path: Vec<String>,
+        file_path: Option<String>,
+    ) -> Self {
+        let name = sig.ident.to_string();
+        let mut owner_path = module_path.clone();
+        owner_path.push(owner.to_string());
+        let id = Self::generate_id(&owner_path, &name);
+
+        let signature = quote::quote!(#sig).to_string();
+
+        let generics = sig
+            .generics
+            .params
+            .iter()
+            .map(|param| quote::quote!(#param).to_string())
+            .collect();
+
+        let attributes = attrs
+            .iter()
+            .map(|attr| quote::quote!(#attr).to_string())
+            .collect();
+
+        let kind = Self::determine_kind(attrs, &name);
+
+        Self {
+            id,
+            name,
+            module_path,
+            crate_name: None,
+            kind,
+            file_path,
+            line_number: Some(sig.ident.span().start().line),
+            signature,
+            is_async: sig.asyncness.is_some(),
+            is_unsafe: sig.unsafety.is_some(),
+            generics,
+            attributes,
+            calls: HashSet::new(),
+            called_by: HashSet::new(),
+            macro_calls: Vec::new(),
+        }
+    }
+
+    /// Creates a node for a closure or async block nested in a function
+    ///
+    /// # Arguments
+    ///
+    /// * `parent` - The enclosing function's node
+    /// * `name` - Name of the nested body within its parent (e.g. `{closure#1}`)
+    /// * `is_async` - Whether the body is an async block or async closure
+    /// * `line_number` - Line where the body starts
+    ///
+    /// # Returns
+    ///
+    /// A new FunctionNode with a `parent::name` ID
+    pub fn nested(
+        parent: &FunctionNode,
+        name: String,
+        is_async: bool,
+        line_number: Option<usize>,
+    ) -> Self {
+        Self {
+            id: format!("{}::{}", parent.id, name),
+            signature: name.clone(),
+            name,
+            module_path: parent.module_path.clone(),
+            crate_name: parent.crate_name.clone(),
+            kind: NodeKind::Closure,
+            file_path: parent.file_path.clone(),
+            line_number,
+            is_async,
+            is_unsafe: false,
+            generics: vec![],
+            attributes: vec![],
+            calls: HashSet::new(),
+            called_by: HashSet::new(),
+            macro_calls: Vec::new(),
         }
     }
 
@@ -125,6 +271,7 @@ impl FunctionNode {
             id: path_str.clone(),
             name,
             module_path,
+            crate_name: None,
             kind: NodeKind::External,
             file_path: None,
             line_number: None,
@@ -135,6 +282,7 @@ impl FunctionNode {
             attributes: vec![],
             calls: HashSet::new(),
             called_by: HashSet::new(),
+            macro_calls: Vec::new(),
         }
     }
 
@@ -190,6 +338,30 @@ impl FunctionNode {
         self.id.clone()
     }
 
+    /// Returns the function's path within its crate
+    ///
+    /// This is the ID without the leading crate name, e.g. `api::users::create`
+    /// for `shop::api::users::create`.
+    pub fn crate_relative_id(&self) -> &str {
+        self.crate_name
+            .as_ref()
+            .and_then(|name| self.id.strip_prefix(name.as_str()))
+            .and_then(|rest| rest.strip_prefix("::"))
+            .unwrap_or(&self.id)
+    }
+
+    /// Returns the function's name within its module
+    ///
+    /// This is the ID without the module path, e.g. `PgRepo::find_all` for
+    /// `shop::db::PgRepo::find_all`.
+    pub fn local_name(&self) -> &str {
+        let module = self.module_path.join("::");
+        self.id
+            .strip_prefix(module.as_str())
+            .and_then(|rest| rest.strip_prefix("::"))
+            .unwrap_or(&self.id)
+    }
+
     /// Checks if this node is reachable from any endpoint
     pub fn is_reachable(&self) -> bool {
         !self.called_by.is_empty() || matches!(self.kind, NodeKind::Endpoint | NodeKind::Test)
@@ -247,6 +419,8 @@ pub enum NodeKind {
     Internal,
     /// An external function from a dependency
     External,
+    /// A closure or async block nested in another function
+    Closure,
 }
 
 impl fmt::Display for NodeKind {
@@ -256,6 +430,7 @@ impl fmt::Display for NodeKind {
             NodeKind::Test => write!(f, "test"),
             NodeKind::Internal => write!(f, "internal"),
             NodeKind::External => write!(f, "external"),
+            NodeKind::Closure => write!(f, "closure"),
         }
     }
 }
@@ -282,11 +457,35 @@ mod tests {
 
         assert_eq!(node.name, "get_users");
         assert_eq!(node.id, "api::get_users");
+        assert_eq!(node.local_name(), "get_users");
         assert!(node.is_async);
         assert!(!node.is_unsafe);
         assert_eq!(node.kind, NodeKind::Endpoint);
     }
 
+    #[test]
+    fn test_function_node_from_impl_fn() {
+        let item: ImplItemFn = parse_quote! {
+            pub async fn find_user(&self, id: u64) -> Option<User> {
+                None
+            }
+        };
+
+        let node = FunctionNode::from_impl_fn(
+            &item,
+            "UserRepo",
+            vec!["repo".to_string()],
+            Some("src/repo.rs".to_string()),
+        );
+
+        assert_eq!(node.name, "find_user");
+        assert_eq!(node.id, "repo::UserRepo::find_user");
+        assert_eq!(node.module_path, vec!["repo"]);
+        assert_eq!(node.local_name(), "UserRepo::find_user");
+        assert!(node.is_async);
+        assert_eq!(node.kind, NodeKind::Internal);
+    }
+
     #[test]
     fn test_external_node() {
         let path: Path = parse_quote!(std::collections::HashMap::new);
@@ -1,8 +1,8 @@
 //! Symbol resolution for function references
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
-use syn::{Item, ItemFn, ItemMod, Path};
+use syn::{ImplItem, Item, ItemFn, ItemImpl, ItemMod, ItemStruct, ItemTrait, Path, TraitItem};
 
 /// Resolves symbols and function references in the codebase
 #[derive(Debug)]
@@ -11,8 +11,39 @@ pub struct SymbolResolver {
     symbols: HashMap<String, ResolvedSymbol>,
     /// Current module path during resolution
     current_module_path: Vec<String>,
+    /// Module path `crate::` expands to in the current file
+    crate_root: Vec<String>,
     /// Map of use statements for import resolution
     imports: HashMap<String, String>,
+    /// Module-qualified paths of the structs, traits and `impl` self types
+    types: HashSet<String>,
+    /// Map from qualified struct path to its named field types
+    field_types: HashMap<String, ScopedTypes>,
+    /// Map from function path to the type it returns, as named in the
+    /// function's module
+    return_types: HashMap<String, (Vec<String>, String)>,
+    /// `impl Trait for Type` blocks
+    trait_impls: Vec<TraitImpl>,
+}
+
+/// Type names written in a module, resolved against that module on lookup
+#[derive(Debug, Clone)]
+struct ScopedTypes {
+    /// Module the names are written in
+    module_path: Vec<String>,
+    /// Map from field name to the field's type name
+    types: HashMap<String, String>,
+}
+
+/// An `impl Trait for Type` block
+#[derive(Debug, Clone)]
+struct TraitImpl {
+    /// Trait name as written in the block
+    trait_name: String,
+    /// Module the block is in
+    module_path: Vec<String>,
+    /// Qualified path of the implementing type
+    type_path: String,
 }
 
 impl SymbolResolver {
@@ -21,7 +52,12 @@ impl SymbolResolver {
         Self {
             symbols: HashMap::new(),
             current_module_path: Vec::new(),
+            crate_root: Vec::new(),
             imports: HashMap::new(),
+            types: HashSet::new(),
+            field_types: HashMap::new(),
+            return_types: HashMap::new(),
+            trait_impls: Vec::new(),
         }
     }
 
@@ -64,6 +100,381 @@ impl SymbolResolver {
         }
     }
 
+    /// Registers a method defined in an `impl` block
+    ///
+    /// Methods are identified as `module::Type::method` and are also
+    /// reachable through the shorter `Type::method` key if it is unique.
+    ///
+    /// # Arguments
+    ///
+    /// * `type_name` - The name of the implementing type
+    /// * `name` - The method name
+    /// * `module_path` - The module path to the implementing type
+    /// * `file_path` - The file where the method is defined
+    /// * `is_public` - Whether the method is public
+    pub fn register_method(
+        &mut self,
+        type_name: &str,
+        name: &str,
+        module_path: Vec<String>,
+        file_path: PathBuf,
+        is_public: bool,
+    ) {
+        let short_path = format!("{}::{}", type_name, name);
+        let full_path = if module_path.is_empty() {
+            short_path.clone()
+        } else {
+            format!("{}::{}", module_path.join("::"), short_path)
+        };
+
+        let symbol = ResolvedSymbol {
+            name: name.to_string(),
+            full_path: full_path.clone(),
+            module_path,
+            file_path,
+            kind: SymbolKind::Method,
+            is_public,
+            is_external: false,
+        };
+
+        self.symbols.insert(full_path, symbol.clone());
+        self.symbols.entry(short_path).or_insert(symbol);
+    }
+
+    /// Registers a struct and the types of its named fields
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The struct name
+    /// * `module_path` - The module path to the struct
+    /// * `file_path` - The file where the struct is defined
+    /// * `fields` - Map from field name to the field's type name
+    pub fn register_struct(
+        &mut self,
+        name: &str,
+        module_path: Vec<String>,
+        file_path: PathBuf,
+        fields: HashMap<String, String>,
+    ) {
+        let full_path = if module_path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}::{}", module_path.join("::"), name)
+        };
+
+        let symbol = ResolvedSymbol {
+            name: name.to_string(),
+            full_path: full_path.clone(),
+            module_path,
+            file_path,
+            kind: SymbolKind::Struct,
+            is_public: true,
+            is_external: false,
+        };
+
+        self.symbols.insert(full_path.clone(), symbol.clone());
+        self.symbols
+            .entry(name.to_string())
+            .or_insert(symbol.clone());
+        self.types.insert(full_path.clone());
+        self.field_types.insert(
+            full_path,
+            ScopedTypes {
+                module_path: symbol.module_path,
+                types: fields,
+            },
+        );
+    }
+
+    /// Records the type returned by a function or method
+    ///
+    /// # Arguments
+    ///
+    /// * `function_path` - The full path of the function
+    /// * `type_name` - The name of the returned type, as written in the
+    ///   current module
+    pub fn register_return_type(&mut self, function_path: &str, type_name: &str) {
+        self.return_types.insert(
+            function_path.to_string(),
+            (self.current_module_path.clone(), type_name.to_string()),
+        );
+    }
+
+    /// Resolves a type or trait name to the module-qualified path it was
+    /// registered under, as seen from the current module
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A type name as written, or an already qualified path
+    ///
+    /// # Returns
+    ///
+    /// The qualified path, or None if no such type is known
+    pub fn qualified_type(&self, name: &str) -> Option<String> {
+        self.qualify_type_in(&self.current_module_path, name)
+    }
+
+    /// Resolves a type name written in `module_path`: an imported type, or
+    /// one defined in the module or one of its ancestors
+    fn qualify_type_in(&self, module_path: &[String], name: &str) -> Option<String> {
+        if name.contains("::") && self.types.contains(name) {
+            return Some(name.to_string());
+        }
+        if let Some(imported) = self.imports.get(name) {
+            if self.types.contains(imported) {
+                return Some(imported.clone());
+            }
+        }
+        (0..=module_path.len()).rev().find_map(|len| {
+            let candidate = if len == 0 {
+                name.to_string()
+            } else {
+                format!("{}::{}", module_path[..len].join("::"), name)
+            };
+            self.types.contains(&candidate).then_some(candidate)
+        })
+    }
+
+    /// Resolves a type name written in `module_path`, keeping names of
+    /// unknown types as they are
+    fn scoped_type(&self, module_path: &[String], name: &str) -> String {
+        self.qualify_type_in(module_path, name)
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    /// Looks up the type of a named field on a struct
+    ///
+    /// # Arguments
+    ///
+    /// * `type_name` - The struct's qualified path, or its name as seen from
+    ///   the current module
+    /// * `field` - The field name
+    ///
+    /// # Returns
+    ///
+    /// The qualified path of the field's type (its name if the type is not
+    /// known), or None if unknown
+    pub fn field_type(&self, type_name: &str, field: &str) -> Option<String> {
+        let type_path = self
+            .qualified_type(type_name)
+            .unwrap_or_else(|| type_name.to_string());
+        let fields = self.field_types.get(&type_path)?;
+        let field_type = fields.types.get(field)?;
+        Some(self.scoped_type(&fields.module_path, field_type))
+    }
+
+    /// Looks up the type returned by a function or method
+    ///
+    /// # Arguments
+    ///
+    /// * `function_path` - The full path of the function
+    ///
+    /// # Returns
+    ///
+    /// The qualified path of the returned type (its name if the type is not
+    /// known), or None if unknown
+    pub fn return_type(&self, function_path: &str) -> Option<String> {
+        let (module_path, returned) = self.return_types.get(function_path)?;
+        Some(self.scoped_type(module_path, returned))
+    }
+
+    /// Resolves a method on a type to its full qualified name
+    ///
+    /// A known type resolves to its own methods and the default methods of
+    /// its traits only, so same-named types in other modules are never
+    /// confused with it. Unknown types are looked up by name.
+    ///
+    /// # Arguments
+    ///
+    /// * `type_name` - The receiver type's qualified path, or its name as
+    ///   seen from the current module
+    /// * `method` - The method name
+    ///
+    /// # Returns
+    ///
+    /// The resolved method symbol, or None if the type has no such method
+    pub fn resolve_method(&self, type_name: &str, method: &str) -> Option<ResolvedSymbol> {
+        if let Some(type_path) = self.qualified_type(type_name) {
+            return self
+                .symbols
+                .get(&format!("{}::{}", type_path, method))
+                .filter(|symbol| symbol.kind == SymbolKind::Method)
+                .cloned()
+                .or_else(|| self.resolve_default_method(&type_path, method));
+        }
+
+        let short_path = format!("{}::{}", type_name, method);
+
+        let mut candidates = Vec::new();
+        let mut search_path = self.current_module_path.clone();
+        loop {
+            if search_path.is_empty() {
+                candidates.push(short_path.clone());
+                break;
+            }
+            candidates.push(format!("{}::{}", search_path.join("::"), short_path));
+            search_path.pop();
+        }
+        if let Some(imported_path) = self.imports.get(type_name) {
+            candidates.insert(0, format!("{}::{}", imported_path, method));
+        }
+
+        candidates
+            .iter()
+            .filter_map(|candidate| self.symbols.get(candidate))
+            .find(|symbol| symbol.kind == SymbolKind::Method)
+            .cloned()
+    }
+
+    /// Resolves a method a type inherits from a default body in one of its traits
+    fn resolve_default_method(&self, type_path: &str, method: &str) -> Option<ResolvedSymbol> {
+        self.trait_impls
+            .iter()
+            .filter(|trait_impl| trait_impl.type_path == type_path)
+            .find_map(|trait_impl| {
+                let trait_path = self.scoped_type(&trait_impl.module_path, &trait_impl.trait_name);
+                self.symbols
+                    .get(&format!("{}::{}", trait_path, method))
+                    .filter(|symbol| symbol.kind == SymbolKind::Method)
+                    .cloned()
+            })
+    }
+
+    /// Registers a trait definition
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The trait name
+    /// * `module_path` - The module path to the trait
+    /// * `file_path` - The file where the trait is defined
+    /// * `is_public` - Whether the trait is public
+    pub fn register_trait(
+        &mut self,
+        name: &str,
+        module_path: Vec<String>,
+        file_path: PathBuf,
+        is_public: bool,
+    ) {
+        let full_path = if module_path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}::{}", module_path.join("::"), name)
+        };
+
+        let symbol = ResolvedSymbol {
+            name: name.to_string(),
+            full_path: full_path.clone(),
+            module_path,
+            file_path,
+            kind: SymbolKind::Trait,
+            is_public,
+            is_external: false,
+        };
+
+        self.symbols.insert(full_path.clone(), symbol.clone());
+        self.symbols.entry(name.to_string()).or_insert(symbol);
+        self.types.insert(full_path);
+    }
+
+    /// Registers an `impl Trait for Type` block in the current module
+    ///
+    /// # Arguments
+    ///
+    /// * `trait_name` - The name of the implemented trait, as written
+    /// * `type_name` - The name of the implementing type, defined in the
+    ///   current module
+    pub fn register_trait_impl(&mut self, trait_name: &str, type_name: &str) {
+        let type_path = self.qualify(type_name);
+        let registered = self.trait_impls.iter().any(|trait_impl| {
+            trait_impl.type_path == type_path
+                && trait_impl.trait_name == trait_name
+                && trait_impl.module_path == self.current_module_path
+        });
+        if !registered {
+            self.trait_impls.push(TraitImpl {
+                trait_name: trait_name.to_string(),
+                module_path: self.current_module_path.clone(),
+                type_path,
+            });
+        }
+    }
+
+    /// Resolves a trait method call to every implementation in the codebase
+    ///
+    /// Implementations that don't override the method resolve to the trait's
+    /// default body when it has one.
+    ///
+    /// # Arguments
+    ///
+    /// * `trait_name` - The qualified path of the trait the receiver is known
+    ///   by, or its name as seen from the current module
+    /// * `method` - The method name
+    ///
+    /// # Returns
+    ///
+    /// The method symbol for each implementing type, without duplicates; empty
+    /// if `trait_name` has no known implementations
+    pub fn trait_implementations(&self, trait_name: &str, method: &str) -> Vec<ResolvedSymbol> {
+        let trait_path = self
+            .qualified_type(trait_name)
+            .unwrap_or_else(|| trait_name.to_string());
+        let mut resolved: Vec<ResolvedSymbol> = Vec::new();
+
+        let implementors = self.trait_impls.iter().filter(|trait_impl| {
+            self.scoped_type(&trait_impl.module_path, &trait_impl.trait_name) == trait_path
+        });
+        for trait_impl in implementors {
+            if let Some(symbol) = self.resolve_method(&trait_impl.type_path, method) {
+                if !resolved.iter().any(|s| s.full_path == symbol.full_path) {
+                    resolved.push(symbol);
+                }
+            }
+        }
+
+        resolved
+    }
+
+    /// Sets the module path used for registration and relative resolution
+    pub fn set_module_path(&mut self, module_path: Vec<String>) {
+        self.current_module_path = module_path;
+    }
+
+    /// Sets the module path `crate::` expands to in the current file
+    ///
+    /// This is the crate name for crates known from cargo metadata, or empty
+    /// when module paths are not prefixed with a crate name.
+    pub fn set_crate_root(&mut self, crate_root: Vec<String>) {
+        self.crate_root = crate_root;
+    }
+
+    /// Expands a leading `crate`, `self` or `super` into an absolute path
+    fn absolute_segments(&self, segments: Vec<String>) -> Vec<String> {
+        match segments.first().map(String::as_str) {
+            Some("crate") => self
+                .crate_root
+                .iter()
+                .cloned()
+                .chain(segments.into_iter().skip(1))
+                .collect(),
+            Some("self") => self
+                .current_module_path
+                .iter()
+                .cloned()
+                .chain(segments.into_iter().skip(1))
+                .collect(),
+            Some("super") => {
+                let supers = segments.iter().take_while(|s| *s == "super").count();
+                let parent_len = self.current_module_path.len().saturating_sub(supers);
+                self.current_module_path[..parent_len]
+                    .iter()
+                    .cloned()
+                    .chain(segments.into_iter().skip(supers))
+                    .collect()
+            }
+            _ => segments,
+        }
+    }
+
     /// Registers a use statement
     ///
     /// # Arguments
@@ -95,7 +506,14 @@ impl SymbolResolver {
     ///
     /// The resolved symbol information, or None if not found
     pub fn resolve_path(&self, path: &Path) -> Option<ResolvedSymbol> {
-        let path_str = quote::quote!(#path).to_string();
+        let path_str = self
+            .absolute_segments(
+                path.segments
+                    .iter()
+                    .map(|seg| seg.ident.to_string())
+                    .collect(),
+            )
+            .join("::");
 
         // Try direct lookup
         if let Some(symbol) = self.symbols.get(&path_str) {
@@ -210,6 +628,15 @@ impl SymbolResolver {
             Item::Use(item_use) => {
                 self.process_use(&item_use.tree);
             }
+            Item::Impl(item_impl) => {
+                self.process_impl(item_impl, file_path);
+            }
+            Item::Struct(item_struct) => {
+                self.process_struct(item_struct, file_path);
+            }
+            Item::Trait(item_trait) => {
+                self.process_trait(item_trait, file_path);
+            }
             _ => {}
         }
     }
@@ -217,13 +644,125 @@ impl SymbolResolver {
     /// Processes a function item
     fn process_function(&mut self, item_fn: &ItemFn, file_path: &PathBuf) {
         let is_public = matches!(item_fn.vis, syn::Visibility::Public(_));
+        let name = item_fn.sig.ident.to_string();
 
         self.register_function(
-            &item_fn.sig.ident.to_string(),
+            &name,
             self.current_module_path.clone(),
             file_path.clone(),
             is_public,
         );
+
+        if let syn::ReturnType::Type(_, ty) = &item_fn.sig.output {
+            if let Some(type_name) = type_name(ty) {
+                let full_path = self.qualify(&name);
+                self.register_return_type(&full_path, &type_name);
+            }
+        }
+    }
+
+    /// Processes an impl block, registering its methods and their return types
+    fn process_impl(&mut self, item_impl: &ItemImpl, file_path: &PathBuf) {
+        let Some(self_type) = type_name(&item_impl.self_ty) else {
+            return;
+        };
+
+        self.types.insert(self.qualify(&self_type));
+        if let Some((_, trait_path, _)) = &item_impl.trait_ {
+            if let Some(trait_segment) = trait_path.segments.last() {
+                self.register_trait_impl(&trait_segment.ident.to_string(), &self_type);
+            }
+        }
+
+        for impl_item in &item_impl.items {
+            let ImplItem::Fn(method) = impl_item else {
+                continue;
+            };
+
+            let name = method.sig.ident.to_string();
+            let is_public =
+                matches!(method.vis, syn::Visibility::Public(_)) || item_impl.trait_.is_some();
+            self.register_method(
+                &self_type,
+                &name,
+                self.current_module_path.clone(),
+                file_path.clone(),
+                is_public,
+            );
+
+            if let syn::ReturnType::Type(_, ty) = &method.sig.output {
+                if let Some(returned) = type_name(ty) {
+                    let returned = if returned == "Self" {
+                        self_type.clone()
+                    } else {
+                        returned
+                    };
+                    let full_path = self.qualify(&format!("{}::{}", self_type, name));
+                    self.register_return_type(&full_path, &returned);
+                }
+            }
+        }
+    }
+
+    /// Processes a trait item, registering the trait and its default methods
+    fn process_trait(&mut self, item_trait: &ItemTrait, file_path: &PathBuf) {
+        let trait_name = item_trait.ident.to_string();
+        let is_public = matches!(item_trait.vis, syn::Visibility::Public(_));
+        self.register_trait(
+            &trait_name,
+            self.current_module_path.clone(),
+            file_path.clone(),
+            is_public,
+        );
+
+        for trait_item in &item_trait.items {
+            let TraitItem::Fn(method) = trait_item else {
+                continue;
+            };
+            if method.default.is_some() {
+                self.register_method(
+                    &trait_name,
+                    &method.sig.ident.to_string(),
+                    self.current_module_path.clone(),
+                    file_path.clone(),
+                    is_public,
+                );
+            }
+        }
+    }
+
+    /// Processes a struct item, recording the types of its named fields
+    ///
+    /// Fields typed by a generic parameter record the parameter's trait bound
+    /// instead, so `repo: R` with `R: UserRepository` dispatches through the
+    /// trait.
+    fn process_struct(&mut self, item_struct: &ItemStruct, file_path: &PathBuf) {
+        let bounds = generic_bounds(&item_struct.generics);
+        let fields = item_struct
+            .fields
+            .iter()
+            .filter_map(|field| {
+                let ty = type_name(&field.ty)?;
+                let ty = bounds.get(&ty).cloned().unwrap_or(ty);
+                Some((field.ident.as_ref()?.to_string(), ty))
+            })
+            .collect();
+
+        self.register_struct(
+            &item_struct.ident.to_string(),
+            self.current_module_path.clone(),
+            file_path.clone(),
+            fields,
+        );
+    }
+
+    /// Prefixes a name with the current module path
+    fn qualify(&self, name: &str) -> String {
+        if self.current_module_path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}::{}", self.current_module_path.join("::"), name)
+        }
     }
 
     /// Processes a module item
@@ -274,16 +813,21 @@ impl SymbolResolver {
             }
             syn::UseTree::Name(use_name) => {
                 let local_name = use_name.ident.to_string();
-                let full_path = path.join("::");
+                let full_path = self.absolute_segments(path.clone()).join("::");
                 self.imports
                     .insert(local_name, format!("{}::{}", full_path, use_name.ident));
             }
             syn::UseTree::Rename(use_rename) => {
                 let renamed = use_rename.rename.to_string();
-                let full_path = path.join("::");
+                let full_path = self.absolute_segments(path.clone()).join("::");
                 self.imports
                     .insert(renamed, format!("{}::{}", full_path, use_rename.ident));
             }
+            syn::UseTree::Group(use_group) => {
+                for tree in &use_group.items {
+                    self.collect_use_path(tree, &mut path.clone());
+                }
+            }
             _ => {}
         }
     }
@@ -295,6 +839,96 @@ impl Default for SymbolResolver {
     }
 }
 
+/// Extracts the name of the type a value of `ty` dispatches methods on
+///
+/// References, smart pointers (`Box`, `Arc`, `Rc`) and the `Option` /
+/// `Result` wrappers are peeled so that `Arc<UserRepo>` and
+/// `Result<Self, Error>` yield `UserRepo` and `Self`. Trait objects yield the
+/// name of their first trait bound.
+///
+/// # Arguments
+///
+/// * `ty` - The type to inspect
+///
+/// # Returns
+///
+/// The last path segment of the underlying type, or None for types without a
+/// nameable path (tuples, `impl Trait`, function pointers, ...)
+pub fn type_name(ty: &syn::Type) -> Option<String> {
+    match ty {
+        syn::Type::Reference(reference) => type_name(&reference.elem),
+        syn::Type::Paren(paren) => type_name(&paren.elem),
+        syn::Type::Group(group) => type_name(&group.elem),
+        syn::Type::Path(type_path) => {
+            let segment = type_path.path.segments.last()?;
+            let ident = segment.ident.to_string();
+            if matches!(ident.as_str(), "Box" | "Arc" | "Rc" | "Option" | "Result") {
+                if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                    return args.args.iter().find_map(|arg| match arg {
+                        syn::GenericArgument::Type(inner) => type_name(inner),
+                        _ => None,
+                    });
+                }
+            }
+            Some(ident)
+        }
+        syn::Type::TraitObject(object) => first_trait_bound(&object.bounds),
+        syn::Type::ImplTrait(impl_trait) => first_trait_bound(&impl_trait.bounds),
+        _ => None,
+    }
+}
+
+/// Maps each generic type parameter to the name of its first trait bound
+///
+/// Bounds are collected from both the parameter list and the `where` clause,
+/// so `<R: UserRepository>` and `where R: UserRepository` both map `R` to
+/// `UserRepository`.
+///
+/// # Arguments
+///
+/// * `generics` - The generics of an item
+///
+/// # Returns
+///
+/// Map from type parameter name to trait name
+pub fn generic_bounds(generics: &syn::Generics) -> HashMap<String, String> {
+    let mut bounds = HashMap::new();
+
+    for param in generics.type_params() {
+        if let Some(bound) = first_trait_bound(&param.bounds) {
+            bounds.insert(param.ident.to_string(), bound);
+        }
+    }
+
+    for predicate in generics.where_clause.iter().flat_map(|w| &w.predicates) {
+        if let syn::WherePredicate::Type(predicate) = predicate {
+            if let (Some(param), Some(bound)) = (
+                type_name(&predicate.bounded_ty),
+                first_trait_bound(&predicate.bounds),
+            ) {
+                bounds.entry(param).or_insert(bound);
+            }
+        }
+    }
+
+    bounds
+}
+
+/// Gets the name of the first trait in a bound list, skipping marker traits
+fn first_trait_bound<'a>(
+    bounds: impl IntoIterator<Item = &'a syn::TypeParamBound>,
+) -> Option<String> {
+    bounds.into_iter().find_map(|bound| match bound {
+        syn::TypeParamBound::Trait(bound) => bound
+            .path
+            .segments
+            .last()
+            .map(|seg| seg.ident.to_string())
+            .filter(|name| !matches!(name.as_str(), "Send" | "Sync" | "Sized" | "Unpin")),
+        _ => None,
+    })
+}
+
 /// Represents a resolved symbol
 #[derive(Debug, Clone)]
 pub struct ResolvedSymbol {
@@ -319,6 +953,8 @@ pub struct ResolvedSymbol {
 pub enum SymbolKind {
     /// Function symbol
     Function,
+    /// Method or associated function defined in an `impl` block
+    Method,
     /// Struct symbol
     Struct,
     /// Trait symbol
@@ -390,6 +1026,178 @@ mod tests {
         assert!(resolver.is_external_path(&tokio_path));
     }
 
+    #[test]
+    fn test_method_resolution() {
+        let mut resolver = SymbolResolver::new();
+        resolver.set_module_path(vec!["repo".to_string()]);
+
+        let file: syn::File = syn::parse_quote! {
+            pub struct UserService {
+                repo: std::sync::Arc<UserRepo>,
+            }
+
+            impl UserRepo {
+                pub fn new() -> Result<Self, Error> { todo!() }
+                pub fn find_user(&self) {}
+            }
+        };
+        for item in &file.items {
+            resolver.process_item(item, &PathBuf::from("src/repo.rs"));
+        }
+
+        let method = resolver.resolve_method("UserRepo", "find_user").unwrap();
+        assert_eq!(method.full_path, "repo::UserRepo::find_user");
+        assert_eq!(method.kind, SymbolKind::Method);
+        assert!(resolver.resolve_method("UserRepo", "missing").is_none());
+
+        assert_eq!(
+            resolver.field_type("UserService", "repo").as_deref(),
+            Some("repo::UserRepo")
+        );
+        assert_eq!(
+            resolver.return_type("repo::UserRepo::new").as_deref(),
+            Some("repo::UserRepo")
+        );
+
+        let path: Path = syn::parse_quote!(UserRepo::new);
+        assert_eq!(
+            resolver.resolve_path(&path).unwrap().full_path,
+            "repo::UserRepo::new"
+        );
+    }
+
+    #[test]
+    fn test_trait_implementations() {
+        let mut resolver = SymbolResolver::new();
+
+        let file: syn::File = syn::parse_quote! {
+            pub trait UserRepository {
+                fn save(&self);
+                fn describe(&self) -> String { String::new() }
+            }
+
+            pub struct PgRepo;
+            impl UserRepository for PgRepo {
+                fn save(&self) {}
+            }
+
+            pub struct RedisRepo;
+            impl UserRepository for RedisRepo {
+                fn save(&self) {}
+                fn describe(&self) -> String { "redis".into() }
+            }
+
+            pub struct Service<R: UserRepository> {
+                repo: R,
+            }
+        };
+        for item in &file.items {
+            resolver.process_item(item, &PathBuf::from("src/lib.rs"));
+        }
+
+        let saves: Vec<_> = resolver
+            .trait_implementations("UserRepository", "save")
+            .into_iter()
+            .map(|s| s.full_path)
+            .collect();
+        assert_eq!(saves, vec!["PgRepo::save", "RedisRepo::save"]);
+
+        let describes: Vec<_> = resolver
+            .trait_implementations("UserRepository", "describe")
+            .into_iter()
+            .map(|s| s.full_path)
+            .collect();
+        assert_eq!(
+            describes,
+            vec!["UserRepository::describe", "RedisRepo::describe"]
+        );
+
+        assert!(resolver.trait_implementations("Unknown", "save").is_empty());
+        assert_eq!(
+            resolver.field_type("Service", "repo").as_deref(),
+            Some("UserRepository")
+        );
+    }
+
+    #[test]
+    fn test_same_named_types_in_different_modules() {
+        let mut resolver = SymbolResolver::new();
+        let file = PathBuf::from("src/lib.rs");
+
+        let modules: [(&str, syn::File); 2] = [
+            (
+                "billing",
+                syn::parse_quote! {
+                    pub struct Client { http: Http }
+                    impl Client { pub fn send(&self) {} }
+                    impl Notifier for Client { fn notify(&self) {} }
+                },
+            ),
+            (
+                "mail",
+                syn::parse_quote! {
+                    pub struct Client { smtp: Smtp }
+                    impl Client { pub fn send(&self) {} }
+                    impl Notifier for Client { fn notify(&self) {} }
+                },
+            ),
+        ];
+        for (module, items) in &modules {
+            resolver.set_module_path(vec![module.to_string()]);
+            for item in &items.items {
+                resolver.process_item(item, &file);
+            }
+        }
+
+        resolver.set_module_path(vec!["mail".to_string()]);
+        assert_eq!(
+            resolver.resolve_method("Client", "send").unwrap().full_path,
+            "mail::Client::send"
+        );
+        assert_eq!(
+            resolver.field_type("Client", "smtp").as_deref(),
+            Some("Smtp")
+        );
+        assert!(resolver.field_type("Client", "http").is_none());
+        assert_eq!(
+            resolver.field_type("billing::Client", "http").as_deref(),
+            Some("Http")
+        );
+
+        let notifies: Vec<_> = resolver
+            .trait_implementations("Notifier", "notify")
+            .into_iter()
+            .map(|s| s.full_path)
+            .collect();
+        assert_eq!(
+            notifies,
+            vec!["billing::Client::notify", "mail::Client::notify"]
+        );
+    }
+
+    #[test]
+    fn test_generic_bounds() {
+        let item: syn::ItemFn = syn::parse_quote! {
+            fn handle<R: Send + UserRepository, C>(repo: R, cache: C) where C: Cache {}
+        };
+        let bounds = generic_bounds(&item.sig.generics);
+
+        assert_eq!(bounds.get("R").map(String::as_str), Some("UserRepository"));
+        assert_eq!(bounds.get("C").map(String::as_str), Some("Cache"));
+    }
+
+    #[test]
+    fn test_type_name_peels_wrappers() {
+        let ty: syn::Type = syn::parse_quote!(&Arc<Box<UserRepo>>);
+        assert_eq!(type_name(&ty).as_deref(), Some("UserRepo"));
+
+        let ty: syn::Type = syn::parse_quote!(Arc<dyn Repository + Send>);
+        assert_eq!(type_name(&ty).as_deref(), Some("Repository"));
+
+        let ty: syn::Type = syn::parse_quote!((u8, u8));
+        assert!(type_name(&ty).is_none());
+    }
+
     #[test]
     fn test_external_symbol_creation() {
         let resolver = SymbolResolver::new();
@@ -3,9 +3,30 @@
 //! This module defines configuration options for analyzing Rust code
 //! and detecting optimal instrumentation points for observability.
 
+use crate::framework::DetectedFramework;
 use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Name of the configuration file the CLI looks for
+pub const CONFIG_FILE_NAME: &str = "instrument-rs.toml";
+
+/// Top-level settings of [`Config`], in declaration order
+const SETTINGS: &[&str] = &[
+    "threshold",
+    "max_depth",
+    "trace_from_endpoints",
+    "critical_paths",
+    "include_tests",
+    "framework",
+    "patterns_file",
+    "include_patterns",
+    "exclude_patterns",
+    "respect_ignore_files",
+    "source_dirs",
+    "naming_rules",
+];
 
 /// The main configuration structure for instrument-rs
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,23 +39,48 @@ pub struct Config {
     #[serde(default = "default_max_depth")]
     pub max_depth: usize,
 
+    /// Only suggest instrumentation for functions reachable from an endpoint
+    #[serde(default)]
+    pub trace_from_endpoints: bool,
+
+    /// Maximum number of critical paths to report per endpoint
+    #[serde(default = "default_critical_paths")]
+    pub critical_paths: usize,
+
     /// Include test functions in analysis
     #[serde(default)]
     pub include_tests: bool,
 
-    /// Web framework to use for endpoint detection
-    #[serde(default)]
-    pub framework: FrameworkType,
+    /// Web frameworks to use for endpoint detection
+    ///
+    /// Accepts a single framework or a list. Named frameworks are always
+    /// analyzed; `auto` adds every framework detected in the project.
+    #[serde(
+        default = "default_frameworks",
+        deserialize_with = "deserialize_frameworks"
+    )]
+    pub framework: Vec<FrameworkType>,
 
     /// Custom patterns file path
     #[serde(default)]
     pub patterns_file: Option<PathBuf>,
 
-    /// Patterns to exclude from analysis
+    /// Glob patterns a file must match to be analyzed (empty = all files)
+    #[serde(default)]
+    pub include_patterns: Vec<String>,
+
+    /// Glob patterns of files and directories to exclude from analysis
     #[serde(default = "default_exclude_patterns")]
     pub exclude_patterns: Vec<String>,
 
-    /// Source directories to analyze (relative to root)
+    /// Skip files ignored by `.gitignore` and `.ignore`
+    #[serde(default = "default_respect_ignore_files")]
+    pub respect_ignore_files: bool,
+
+    /// Source directories to analyze
+    ///
+    /// Relative directories are looked up in each package, or in the
+    /// directory of the configuration file that sets them.
     #[serde(default = "default_source_dirs")]
     pub source_dirs: Vec<PathBuf>,
 
@@ -83,6 +129,10 @@ fn default_max_depth() -> usize {
     10
 }
 
+fn default_critical_paths() -> usize {
+    5
+}
+
 fn default_exclude_patterns() -> Vec<String> {
     vec![
         "target".to_string(),
@@ -91,10 +141,62 @@ fn default_exclude_patterns() -> Vec<String> {
     ]
 }
 
+fn default_respect_ignore_files() -> bool {
+    true
+}
+
+fn default_frameworks() -> Vec<FrameworkType> {
+    vec![FrameworkType::Auto]
+}
+
+/// Accept either `framework = "axum"` or `framework = ["axum", "tonic"]`
+fn deserialize_frameworks<'de, D>(deserializer: D) -> Result<Vec<FrameworkType>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(FrameworkType),
+        Many(Vec<FrameworkType>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(framework) => vec![framework],
+        OneOrMany::Many(frameworks) => frameworks,
+    })
+}
+
 fn default_source_dirs() -> Vec<PathBuf> {
     vec![PathBuf::from("src")]
 }
 
+/// Finds the root of the cargo workspace containing a path
+///
+/// This is the outermost ancestor whose `Cargo.toml` has a `[workspace]`
+/// table, or the nearest package root when no workspace is declared.
+fn workspace_root(path: &Path) -> Option<PathBuf> {
+    let path = path.canonicalize().ok()?;
+    let manifests: Vec<&Path> = path
+        .ancestors()
+        .filter(|dir| dir.join("Cargo.toml").is_file())
+        .collect();
+
+    let is_workspace = |dir: &Path| {
+        std::fs::read_to_string(dir.join("Cargo.toml"))
+            .ok()
+            .and_then(|content| content.parse::<toml::Table>().ok())
+            .is_some_and(|manifest| manifest.contains_key("workspace"))
+    };
+
+    manifests
+        .iter()
+        .rev()
+        .find(|dir| is_workspace(dir))
+        .or(manifests.first())
+        .map(|dir| dir.to_path_buf())
+}
+
 /// Web framework type for endpoint detection
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, ValueEnum)]
 #[serde(rename_all = "lowercase")]
@@ -110,6 +212,10 @@ pub enum FrameworkType {
     Rocket,
     /// Tonic gRPC framework
     Tonic,
+    /// Warp web framework
+    Warp,
+    /// Poem web framework
+    Poem,
 }
 
 /// Output format for analysis results
@@ -130,10 +236,14 @@ impl Default for Config {
         Self {
             threshold: default_threshold(),
             max_depth: default_max_depth(),
+            trace_from_endpoints: false,
+            critical_paths: default_critical_paths(),
             include_tests: false,
-            framework: FrameworkType::Auto,
+            framework: default_frameworks(),
             patterns_file: None,
+            include_patterns: Vec::new(),
             exclude_patterns: default_exclude_patterns(),
+            respect_ignore_files: default_respect_ignore_files(),
             source_dirs: default_source_dirs(),
             naming_rules: NamingRules::default(),
         }
@@ -141,6 +251,26 @@ impl Default for Config {
 }
 
 impl Config {
+    /// Frameworks selected by name, excluding `auto`
+    #[must_use]
+    pub fn selected_frameworks(&self) -> Vec<DetectedFramework> {
+        let mut frameworks = Vec::new();
+        for framework in self.framework.iter().filter_map(FrameworkType::detected) {
+            if !frameworks.contains(&framework) {
+                frameworks.push(framework);
+            }
+        }
+        frameworks
+    }
+
+    /// Whether frameworks should be detected from the project
+    ///
+    /// True when `auto` is selected or no framework is named at all.
+    #[must_use]
+    pub fn auto_detect_frameworks(&self) -> bool {
+        self.framework.is_empty() || self.framework.contains(&FrameworkType::Auto)
+    }
+
     /// Load configuration from a TOML file
     ///
     /// # Arguments
@@ -156,6 +286,26 @@ impl Config {
         Ok(config)
     }
 
+    /// Finds the configuration file for a project
+    ///
+    /// # Arguments
+    ///
+    /// * `cwd` - The current directory, searched first
+    /// * `project_root` - A path in the project; the root of its cargo
+    ///   workspace is searched next
+    ///
+    /// # Returns
+    ///
+    /// The path of the first `instrument-rs.toml` found, or None
+    pub fn discover(cwd: &Path, project_root: &Path) -> Option<PathBuf> {
+        let candidates = [Some(cwd.to_path_buf()), workspace_root(project_root)];
+        candidates
+            .into_iter()
+            .flatten()
+            .map(|dir| dir.join(CONFIG_FILE_NAME))
+            .find(|path| path.is_file())
+    }
+
     /// Save configuration to a TOML file
     ///
     /// # Arguments
@@ -173,6 +323,208 @@ impl Config {
     }
 }
 
+/// Where an effective setting came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// Built-in default
+    Default,
+    /// A configuration file
+    File(PathBuf),
+    /// An explicit command line flag
+    CommandLine,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::CommandLine => write!(f, "command line"),
+        }
+    }
+}
+
+/// Settings given explicitly on the command line
+///
+/// Each `Some` value overrides the configuration file.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOverrides {
+    /// `--threshold`
+    pub threshold: Option<f64>,
+    /// `--max-depth`
+    pub max_depth: Option<usize>,
+    /// `--trace-from-endpoints`
+    pub trace_from_endpoints: Option<bool>,
+    /// `--critical-paths`
+    pub critical_paths: Option<usize>,
+    /// `--include-tests`
+    pub include_tests: Option<bool>,
+    /// `--framework`
+    pub framework: Option<Vec<FrameworkType>>,
+    /// `--patterns`
+    pub patterns_file: Option<PathBuf>,
+    /// `--no-ignore`
+    pub respect_ignore_files: Option<bool>,
+}
+
+/// A configuration merged from defaults, a configuration file and the
+/// command line
+#[derive(Debug, Clone)]
+pub struct LoadedConfig {
+    /// The effective configuration
+    pub config: Config,
+    /// The configuration file that was loaded, if any
+    pub file: Option<PathBuf>,
+    /// Keys in the configuration file that aren't settings
+    pub ignored_keys: Vec<String>,
+    /// Source of each top-level setting, in declaration order
+    sources: Vec<(&'static str, ConfigSource)>,
+}
+
+impl LoadedConfig {
+    /// Loads the configuration and applies command line overrides
+    ///
+    /// The file is `config_path` when given; otherwise `instrument-rs.toml`
+    /// is looked up in `cwd`, then in the workspace root of `project_root`.
+    /// Relative `patterns_file` and `source_dirs` in the file are relative to
+    /// the file's directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `config_path` - Path passed with `--config`, if any
+    /// * `cwd` - The current directory
+    /// * `project_root` - The first path being analyzed
+    /// * `overrides` - Settings given explicitly on the command line
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration file cannot be read or parsed
+    pub fn load(
+        config_path: Option<&Path>,
+        cwd: &Path,
+        project_root: &Path,
+        overrides: ConfigOverrides,
+    ) -> crate::Result<Self> {
+        let file = match config_path {
+            Some(path) => Some(path.to_path_buf()),
+            None => Config::discover(cwd, project_root),
+        };
+
+        let mut config = Config::default();
+        let mut sources: Vec<(&'static str, ConfigSource)> = SETTINGS
+            .iter()
+            .map(|key| (*key, ConfigSource::Default))
+            .collect();
+        let mut ignored_keys = Vec::new();
+
+        if let Some(path) = &file {
+            let content = std::fs::read_to_string(path).map_err(|e| {
+                crate::Error::Config(format!("cannot read {}: {e}", path.display()))
+            })?;
+            let table: toml::Table = toml::from_str(&content)?;
+            config = toml::from_str(&content)?;
+
+            for key in table.keys() {
+                match sources.iter_mut().find(|(setting, _)| setting == key) {
+                    Some((_, source)) => *source = ConfigSource::File(path.clone()),
+                    None => ignored_keys.push(key.clone()),
+                }
+            }
+
+            // Paths in the file are relative to the file, wherever it was
+            // found or however `--config` spelled it
+            let dir = path
+                .canonicalize()
+                .ok()
+                .and_then(|path| path.parent().map(Path::to_path_buf))
+                .unwrap_or_default();
+            if let Some(patterns) = &mut config.patterns_file {
+                if patterns.is_relative() {
+                    *patterns = dir.join(&patterns);
+                }
+            }
+            if table.contains_key("source_dirs") {
+                for source_dir in &mut config.source_dirs {
+                    if source_dir.is_relative() {
+                        *source_dir = dir.join(&source_dir);
+                    }
+                }
+            }
+        }
+
+        let mut set = |key: &str| {
+            if let Some((_, source)) = sources.iter_mut().find(|(setting, _)| *setting == key) {
+                *source = ConfigSource::CommandLine;
+            }
+        };
+        if let Some(threshold) = overrides.threshold {
+            config.threshold = threshold;
+            set("threshold");
+        }
+        if let Some(max_depth) = overrides.max_depth {
+            config.max_depth = max_depth;
+            set("max_depth");
+        }
+        if let Some(trace_from_endpoints) = overrides.trace_from_endpoints {
+            config.trace_from_endpoints = trace_from_endpoints;
+            set("trace_from_endpoints");
+        }
+        if let Some(critical_paths) = overrides.critical_paths {
+            config.critical_paths = critical_paths;
+            set("critical_paths");
+        }
+        if let Some(include_tests) = overrides.include_tests {
+            config.include_tests = include_tests;
+            set("include_tests");
+        }
+        if let Some(framework) = overrides.framework {
+            config.framework = framework;
+            set("framework");
+        }
+        if let Some(patterns_file) = overrides.patterns_file {
+            config.patterns_file = Some(patterns_file);
+            set("patterns_file");
+        }
+        if let Some(respect_ignore_files) = overrides.respect_ignore_files {
+            config.respect_ignore_files = respect_ignore_files;
+            set("respect_ignore_files");
+        }
+
+        Ok(Self {
+            config,
+            file,
+            ignored_keys,
+            sources,
+        })
+    }
+
+    /// Returns where a top-level setting came from
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Name of the setting, as in the configuration file
+    pub fn source(&self, key: &str) -> Option<&ConfigSource> {
+        self.sources
+            .iter()
+            .find(|(setting, _)| *setting == key)
+            .map(|(_, source)| source)
+    }
+
+    /// Lists every top-level setting with its effective value and source
+    ///
+    /// # Returns
+    ///
+    /// `(key, value, source)` tuples, with values rendered as TOML; unset
+    /// optional settings have no value
+    pub fn settings(&self) -> Vec<(&'static str, Option<String>, &ConfigSource)> {
+        let values = toml::Table::try_from(&self.config).unwrap_or_default();
+        self.sources
+            .iter()
+            .map(|(key, source)| (*key, values.get(*key).map(|v| v.to_string()), source))
+            .collect()
+    }
+}
+
 impl FrameworkType {
     /// Get the display name of the framework
     #[must_use]
@@ -183,6 +535,26 @@ impl FrameworkType {
             Self::Actix => "actix-web",
             Self::Rocket => "rocket",
             Self::Tonic => "tonic",
+            Self::Warp => "warp",
+            Self::Poem => "poem",
+        }
+    }
+
+    /// Get the framework this selection forces, if any
+    ///
+    /// # Returns
+    ///
+    /// `None` for [`FrameworkType::Auto`], which leaves detection to the analyzer
+    #[must_use]
+    pub fn detected(&self) -> Option<DetectedFramework> {
+        match self {
+            Self::Auto => None,
+            Self::Axum => Some(DetectedFramework::Axum),
+            Self::Actix => Some(DetectedFramework::Actix),
+            Self::Rocket => Some(DetectedFramework::Rocket),
+            Self::Tonic => Some(DetectedFramework::Tonic),
+            Self::Warp => Some(DetectedFramework::Warp),
+            Self::Poem => Some(DetectedFramework::Poem),
         }
     }
 }
@@ -3,10 +3,11 @@
 //! This module uses cargo_metadata to analyze project dependencies and
 //! provide context for smarter instrumentation detection.
 
+use crate::patterns::Category;
 use crate::{Error, Result};
 use cargo_metadata::{Metadata, MetadataCommand, Package};
 use std::collections::HashSet;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Known database crates
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -26,6 +27,16 @@ pub enum DatabaseCrate {
 }
 
 impl DatabaseCrate {
+    /// Every known database crate
+    pub const ALL: [Self; 6] = [
+        Self::Sqlx,
+        Self::Diesel,
+        Self::SeaOrm,
+        Self::TokioPostgres,
+        Self::Rusqlite,
+        Self::MongoDb,
+    ];
+
     /// Returns the crate name as it appears in Cargo.toml
     pub fn crate_name(&self) -> &'static str {
         match self {
@@ -65,6 +76,9 @@ pub enum HttpClientCrate {
 }
 
 impl HttpClientCrate {
+    /// Every known HTTP client crate
+    pub const ALL: [Self; 4] = [Self::Reqwest, Self::Hyper, Self::Ureq, Self::Surf];
+
     /// Returns the crate name as it appears in Cargo.toml
     pub fn crate_name(&self) -> &'static str {
         match self {
@@ -106,6 +120,9 @@ pub enum CacheCrate {
 }
 
 impl CacheCrate {
+    /// Every known cache crate
+    pub const ALL: [Self; 4] = [Self::Redis, Self::Memcache, Self::Moka, Self::Cached];
+
     /// Returns the crate name as it appears in Cargo.toml
     pub fn crate_name(&self) -> &'static str {
         match self {
@@ -199,7 +216,7 @@ impl ObservabilityCrate {
 }
 
 /// Analyzed project dependencies
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct ProjectDependencies {
     /// Detected database crates
     pub databases: HashSet<DatabaseCrate>,
@@ -245,6 +262,67 @@ impl ProjectDependencies {
         Ok(Self::from_metadata(&metadata))
     }
 
+    /// Load the cargo metadata of the workspace containing a path
+    ///
+    /// The nearest `Cargo.toml` at or above `path` is used, so both a
+    /// workspace root and any directory inside a member work.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A directory or file inside the workspace
+    ///
+    /// # Returns
+    ///
+    /// The workspace metadata, or None if `path` is not inside a cargo project
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if cargo metadata fails
+    pub fn workspace_metadata(path: &Path) -> Result<Option<Metadata>> {
+        let Some(cargo_toml) = path
+            .ancestors()
+            .map(|dir| dir.join("Cargo.toml"))
+            .find(|manifest| manifest.is_file())
+        else {
+            return Ok(None);
+        };
+
+        MetadataCommand::new()
+            .manifest_path(&cargo_toml)
+            .no_deps()
+            .exec()
+            .map(Some)
+            .map_err(|e| Error::Generic(format!("Failed to get cargo metadata: {e}")))
+    }
+
+    /// Enumerate the packages of a workspace
+    ///
+    /// # Arguments
+    ///
+    /// * `metadata` - Cargo metadata of the workspace
+    ///
+    /// # Returns
+    ///
+    /// Each workspace member with its own dependencies
+    pub fn workspace_members(metadata: &Metadata) -> Vec<WorkspaceMember> {
+        metadata
+            .workspace_packages()
+            .into_iter()
+            .map(WorkspaceMember::from_package)
+            .collect()
+    }
+
+    /// Merge another set of dependencies into this one
+    pub fn merge(&mut self, other: &Self) {
+        self.databases.extend(other.databases.iter().copied());
+        self.http_clients.extend(other.http_clients.iter().copied());
+        self.caches.extend(other.caches.iter().copied());
+        self.frameworks.extend(other.frameworks.iter().copied());
+        self.observability
+            .extend(other.observability.iter().copied());
+        self.all_deps.extend(other.all_deps.iter().cloned());
+    }
+
     /// Create from cargo metadata
     fn from_metadata(metadata: &Metadata) -> Self {
         let mut deps = Self::default();
@@ -434,6 +512,37 @@ impl ProjectDependencies {
     }
 }
 
+/// A package in a cargo workspace
+#[derive(Debug, Clone)]
+pub struct WorkspaceMember {
+    /// Package name as declared in its Cargo.toml
+    pub name: String,
+    /// Directory containing the package's Cargo.toml
+    pub root: PathBuf,
+    /// The package's own dependencies
+    pub dependencies: ProjectDependencies,
+}
+
+impl WorkspaceMember {
+    /// Create from a package in cargo metadata
+    fn from_package(package: &Package) -> Self {
+        let mut dependencies = ProjectDependencies::default();
+        dependencies.analyze_package(package);
+
+        let root = package
+            .manifest_path
+            .parent()
+            .map(|dir| dir.as_std_path().to_path_buf())
+            .unwrap_or_default();
+
+        Self {
+            name: package.name.clone(),
+            root,
+            dependencies,
+        }
+    }
+}
+
 /// Detection context based on project dependencies
 ///
 /// This provides hints for smarter pattern matching based on
@@ -530,6 +639,76 @@ impl DetectionContext {
         ];
         cache_patterns.iter().any(|p| lower.contains(p))
     }
+
+    /// Classify a called path by the usage patterns of the known database,
+    /// HTTP client and cache crates
+    ///
+    /// Patterns naming the crate (`sqlx::query`, `reqwest::`) match in any
+    /// project; bare names such as `PgPool` or `table!` only match when the
+    /// project depends on the crate. Source text patterns like `.get(` can't
+    /// be checked against a path and are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of a called function, or of a macro ending in `!`,
+    ///   without whitespace (e.g. `reqwest::Client::new`, `sqlx::query_as!`)
+    ///
+    /// # Returns
+    ///
+    /// The category of the call and the usage pattern that matched
+    pub fn classify_call(&self, path: &str) -> Option<(Category, &'static str)> {
+        let databases = DatabaseCrate::ALL.iter().map(|krate| {
+            (
+                Category::Database,
+                krate.crate_name(),
+                self.deps.databases.contains(krate),
+                krate.usage_patterns(),
+            )
+        });
+        let http_clients = HttpClientCrate::ALL.iter().map(|krate| {
+            (
+                Category::HttpClient,
+                krate.crate_name(),
+                self.deps.http_clients.contains(krate),
+                krate.usage_patterns(),
+            )
+        });
+        let caches = CacheCrate::ALL.iter().map(|krate| {
+            (
+                Category::Cache,
+                krate.crate_name(),
+                self.deps.caches.contains(krate),
+                krate.usage_patterns(),
+            )
+        });
+
+        databases.chain(http_clients).chain(caches).find_map(
+            |(category, crate_name, used, patterns)| {
+                let prefix = format!("{}::", crate_name.replace('-', "_"));
+                patterns
+                    .iter()
+                    .filter(|pattern| used || pattern.starts_with(&prefix))
+                    .find(|pattern| call_matches(path, pattern))
+                    .map(|pattern| (category, *pattern))
+            },
+        )
+    }
+}
+
+/// Checks whether a called path matches a crate usage pattern
+fn call_matches(path: &str, pattern: &str) -> bool {
+    if pattern.starts_with('.') || pattern.starts_with('#') {
+        return false;
+    }
+
+    if pattern.contains("::") {
+        // `sqlx::query` also matches `sqlx::query_as!`
+        path.starts_with(pattern) || path.contains(&format!("::{pattern}"))
+    } else if pattern.ends_with('!') {
+        path == pattern || path.ends_with(&format!("::{pattern}"))
+    } else {
+        path.split("::").any(|segment| segment == pattern)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,536 @@
+//! Critical path extraction
+//!
+//! Finds the call paths from each endpoint down to the external boundaries a
+//! request crosses (database, HTTP client, cache and message queue calls),
+//! ranks them, and records which hops are instrumented so it is clear where a
+//! request loses trace coverage.
+
+use super::{Endpoint, ExistingInstrumentation, Location};
+use crate::call_graph::{CallGraph, FunctionNode, NodeKind};
+use crate::dependencies::ProjectDependencies;
+use crate::patterns::{Category, MatchResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Maximum number of paths collected per endpoint before ranking
+///
+/// Bounds the walk on graphs where many branches reach the same boundaries.
+const MAX_CANDIDATE_PATHS: usize = 1000;
+
+/// A ranked path from an endpoint to an external boundary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CriticalPath {
+    /// Endpoint the path starts from, as "METHOD path"
+    pub endpoint: String,
+    /// Kind of boundary the path ends at
+    pub boundary: Category,
+    /// Functions on the path, from the handler to the boundary
+    pub hops: Vec<PathHop>,
+    /// Ranking score; higher is more critical
+    pub score: f64,
+}
+
+/// A function on a critical path
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathHop {
+    /// Call graph node ID of the function
+    pub function: String,
+    /// Location of the function, if it is defined in the project
+    pub location: Option<Location>,
+    /// Whether the function is instrumented
+    pub status: HopStatus,
+}
+
+/// Instrumentation status of a hop
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum HopStatus {
+    /// The function has a span or `#[instrument]`
+    Instrumented,
+    /// The function has no instrumentation
+    Uninstrumented,
+    /// Library function outside the project; its spans aren't visible
+    External,
+}
+
+impl CriticalPath {
+    /// Returns the first project function on the path without instrumentation
+    ///
+    /// This is where a request traced from the endpoint stops being covered.
+    pub fn coverage_lost_at(&self) -> Option<&PathHop> {
+        self.hops
+            .iter()
+            .find(|hop| hop.status == HopStatus::Uninstrumented)
+    }
+
+    /// Checks whether every project function on the path is instrumented
+    pub fn is_fully_instrumented(&self) -> bool {
+        self.coverage_lost_at().is_none()
+    }
+}
+
+/// Extracts critical paths from a call graph
+pub struct CriticalPathFinder<'a> {
+    /// The call graph to walk
+    graph: &'a CallGraph,
+    /// Boundary category of each boundary node, keyed by node ID
+    boundaries: HashMap<String, Category>,
+    /// Heaviest edge weight between each pair of nodes
+    weights: HashMap<(&'a str, &'a str), f64>,
+    /// Existing instrumentation used for hop status
+    existing: &'a [ExistingInstrumentation],
+    /// Maximum number of calls to follow from a handler
+    max_depth: usize,
+}
+
+impl<'a> CriticalPathFinder<'a> {
+    /// Creates a finder for a call graph
+    ///
+    /// Project functions are boundaries when pattern matching put them in a
+    /// boundary category. Library calls are boundaries when they are made
+    /// into a database, HTTP client or cache crate the project depends on.
+    ///
+    /// # Arguments
+    ///
+    /// * `graph` - The call graph to walk
+    /// * `patterns` - Pattern matches for the graph's functions
+    /// * `dependencies` - The project's dependencies
+    /// * `existing` - Existing instrumentation in the project
+    pub fn new(
+        graph: &'a CallGraph,
+        patterns: &[MatchResult],
+        dependencies: &ProjectDependencies,
+        existing: &'a [ExistingInstrumentation],
+    ) -> Self {
+        let mut boundaries: HashMap<String, Category> = patterns
+            .iter()
+            .filter(|pattern| boundary_weight(pattern.category).is_some())
+            .map(|pattern| (pattern.function_name.clone(), pattern.category))
+            .collect();
+
+        for node in graph.nodes() {
+            if node.kind != NodeKind::External {
+                continue;
+            }
+            if let Some(category) = external_boundary(node, dependencies) {
+                boundaries.insert(node.id.clone(), category);
+            }
+        }
+
+        let mut weights = HashMap::new();
+        for edge in graph.edges() {
+            let weight = weights
+                .entry((edge.from.as_str(), edge.to.as_str()))
+                .or_insert(0.0_f64);
+            *weight = weight.max(edge.weight());
+        }
+
+        Self {
+            graph,
+            boundaries,
+            weights,
+            existing,
+            max_depth: 10,
+        }
+    }
+
+    /// Sets the maximum number of calls to follow from a handler
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Finds the most critical paths of an endpoint
+    ///
+    /// Paths stop at the first boundary they reach. A path's score is the
+    /// product of its call weights, so calls in loops raise it and
+    /// conditional or uncertain calls lower it, scaled by how costly the
+    /// boundary kind usually is.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The endpoint to trace from
+    /// * `limit` - Maximum number of paths to return
+    ///
+    /// # Returns
+    ///
+    /// Up to `limit` paths, most critical first; empty if the handler isn't
+    /// in the graph
+    pub fn find(&self, endpoint: &Endpoint, limit: usize) -> Vec<CriticalPath> {
+        let Some(handler) = self.graph.get_node(&endpoint.location.function_name) else {
+            return Vec::new();
+        };
+
+        let mut found = Vec::new();
+        let mut path = vec![handler.id.as_str()];
+        let mut on_path = HashSet::from([handler.id.as_str()]);
+        let mut dead_ends = HashMap::new();
+        self.walk(&mut path, &mut on_path, &mut dead_ends, 1.0, &mut found);
+
+        let label = format!("{} {}", endpoint.method, endpoint.path);
+        let mut paths: Vec<CriticalPath> = found
+            .into_iter()
+            .map(|(ids, weight)| {
+                let boundary = self.boundaries[*ids.last().unwrap_or(&"")];
+                CriticalPath {
+                    endpoint: label.clone(),
+                    boundary,
+                    score: weight * boundary_weight(boundary).unwrap_or(0.0),
+                    hops: ids.iter().map(|id| self.hop(id)).collect(),
+                }
+            })
+            .collect();
+
+        paths.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then(a.hops.len().cmp(&b.hops.len()))
+                .then_with(|| {
+                    let a = a.hops.iter().map(|hop| &hop.function);
+                    let b = b.hops.iter().map(|hop| &hop.function);
+                    a.cmp(b)
+                })
+        });
+        paths.truncate(limit);
+        paths
+    }
+
+    /// Finds the most critical paths of every endpoint
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoints` - The endpoints to trace from
+    /// * `limit` - Maximum number of paths to return per endpoint
+    pub fn find_all(&self, endpoints: &[Endpoint], limit: usize) -> Vec<CriticalPath> {
+        endpoints
+            .iter()
+            .flat_map(|endpoint| self.find(endpoint, limit))
+            .collect()
+    }
+
+    /// Depth-first walk collecting simple paths that end at a boundary
+    ///
+    /// Functions from which no boundary was reached are remembered with the
+    /// shallowest depth they were walked at, and not walked again at that
+    /// depth or deeper. Otherwise every path through a densely connected part
+    /// of the graph without boundaries would be enumerated.
+    fn walk(
+        &self,
+        path: &mut Vec<&'a str>,
+        on_path: &mut HashSet<&'a str>,
+        dead_ends: &mut HashMap<&'a str, usize>,
+        weight: f64,
+        found: &mut Vec<(Vec<&'a str>, f64)>,
+    ) {
+        if found.len() >= MAX_CANDIDATE_PATHS {
+            return;
+        }
+
+        let current = *path.last().unwrap_or(&"");
+        if let Some(category) = self.boundaries.get(current) {
+            // A function classified by the library call it makes ends at
+            // that call
+            let library_calls = self.library_calls(current, *category);
+            if library_calls.is_empty() {
+                found.push((path.clone(), weight));
+            }
+            for callee in library_calls {
+                let call_weight = self.weights.get(&(current, callee)).copied().unwrap_or(1.0);
+                path.push(callee);
+                found.push((path.clone(), weight * call_weight));
+                path.pop();
+            }
+            return;
+        }
+        if path.len() > self.max_depth {
+            return;
+        }
+
+        let Some(callees) = self.graph.get_callees(current) else {
+            return;
+        };
+        let mut callees: Vec<&'a String> = callees.iter().collect();
+        callees.sort();
+
+        for callee in callees {
+            let Some(node) = self.graph.get_node(callee) else {
+                continue;
+            };
+            let callee = node.id.as_str();
+            if on_path.contains(callee)
+                || dead_ends
+                    .get(callee)
+                    .is_some_and(|&depth| depth <= path.len())
+            {
+                continue;
+            }
+            let call_weight = self.weights.get(&(current, callee)).copied().unwrap_or(1.0);
+
+            let found_before = found.len();
+            path.push(callee);
+            on_path.insert(callee);
+            self.walk(path, on_path, dead_ends, weight * call_weight, found);
+            on_path.remove(callee);
+            path.pop();
+            if found.len() == found_before {
+                dead_ends.insert(callee, path.len());
+            }
+        }
+    }
+
+    /// Returns the library boundaries of a category a project function calls
+    fn library_calls(&self, id: &str, category: Category) -> Vec<&'a str> {
+        let Some(callees) = self.graph.get_callees(id) else {
+            return Vec::new();
+        };
+
+        let mut calls: Vec<&'a str> = callees
+            .iter()
+            .filter_map(|callee| self.graph.get_node(callee))
+            .filter(|node| {
+                node.kind == NodeKind::External && self.boundaries.get(&node.id) == Some(&category)
+            })
+            .map(|node| node.id.as_str())
+            .collect();
+        calls.sort_unstable();
+        calls
+    }
+
+    /// Describes a function on a path
+    fn hop(&self, id: &str) -> PathHop {
+        let node = self.graph.get_node(id);
+        let location = node
+            .filter(|node| node.kind != NodeKind::External)
+            .and_then(|node| {
+                Some(Location {
+                    file: node.file()?,
+                    line: node.line().unwrap_or(0),
+                    column: 1,
+                    function_name: node.id.clone(),
+                })
+            });
+
+        let status = match &location {
+            None => HopStatus::External,
+            Some(location) => {
+                if self
+                    .existing
+                    .iter()
+                    .any(|e| e.instruments(&location.function_name))
+                {
+                    HopStatus::Instrumented
+                } else {
+                    HopStatus::Uninstrumented
+                }
+            }
+        };
+
+        PathHop {
+            function: id.to_string(),
+            location,
+            status,
+        }
+    }
+}
+
+/// Returns how costly a boundary kind usually is, or None if the category
+/// isn't a boundary
+fn boundary_weight(category: Category) -> Option<f64> {
+    match category {
+        Category::Database => Some(1.0),
+        Category::HttpClient | Category::ExternalService => Some(0.9),
+        Category::MessageQueue => Some(0.85),
+        Category::Cache => Some(0.7),
+        _ => None,
+    }
+}
+
+/// Classifies a library call by the crate it is made into
+fn external_boundary(node: &FunctionNode, dependencies: &ProjectDependencies) -> Option<Category> {
+    let krate = node.module_path.first()?;
+    if dependencies
+        .databases
+        .iter()
+        .any(|db| db.crate_name() == krate)
+    {
+        Some(Category::Database)
+    } else if dependencies
+        .http_clients
+        .iter()
+        .any(|client| client.crate_name() == krate)
+    {
+        Some(Category::HttpClient)
+    } else if dependencies
+        .caches
+        .iter()
+        .any(|cache| cache.crate_name() == krate)
+    {
+        Some(Category::Cache)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::SourceFile;
+    use crate::call_graph::GraphBuilder;
+    use crate::dependencies::DatabaseCrate;
+    use crate::detector::{ExistingKind, InstrumentationQuality};
+    use std::path::PathBuf;
+
+    fn graph(code: &str) -> CallGraph {
+        let source = SourceFile {
+            path: PathBuf::from("src/lib.rs"),
+            syntax_tree: syn::parse_file(code).unwrap(),
+            source: code.to_string(),
+            content_hash: String::new(),
+        };
+        let mut builder = GraphBuilder::new();
+        builder.add_parsed_file(&source).unwrap();
+        builder.build().unwrap()
+    }
+
+    fn endpoint(handler: &str) -> Endpoint {
+        Endpoint {
+            method: "GET".to_string(),
+            path: "/orders".to_string(),
+            handler: handler.to_string(),
+            location: Location {
+                file: PathBuf::from("src/lib.rs"),
+                line: 1,
+                column: 1,
+                function_name: handler.to_string(),
+            },
+            framework: "axum".to_string(),
+            parameters: vec![],
+            documentation: None,
+            streaming: None,
+        }
+    }
+
+    fn pattern(graph: &CallGraph, id: &str, category: Category) -> MatchResult {
+        let node = graph.get_node(id).unwrap();
+        let mut result = MatchResult::with_location(
+            node.file().unwrap_or_default(),
+            id.to_string(),
+            node.line().unwrap_or(0),
+        );
+        result.category = category;
+        result
+    }
+
+    #[test]
+    fn test_paths_ranked_by_weight() {
+        let graph = graph(
+            r#"
+            fn list_orders(ids: Vec<u64>) {
+                for id in ids {
+                    load_order(id);
+                }
+                if ids.is_empty() {
+                    cached_orders();
+                }
+            }
+            fn load_order(id: u64) { query_order(id); }
+            fn query_order(id: u64) { sqlx::query("SELECT 1"); }
+            fn cached_orders() {}
+            "#,
+        );
+        let patterns = vec![pattern(&graph, "cached_orders", Category::Cache)];
+        let mut deps = ProjectDependencies::default();
+        deps.databases.insert(DatabaseCrate::Sqlx);
+
+        let finder = CriticalPathFinder::new(&graph, &patterns, &deps, &[]);
+        let paths = finder.find(&endpoint("list_orders"), 5);
+
+        assert_eq!(paths.len(), 2);
+        // The query in a loop outranks the conditional cache lookup
+        assert_eq!(paths[0].boundary, Category::Database);
+        let functions: Vec<_> = paths[0].hops.iter().map(|h| h.function.as_str()).collect();
+        assert_eq!(
+            functions,
+            vec!["list_orders", "load_order", "query_order", "sqlx :: query"]
+        );
+        assert_eq!(paths[0].hops[3].status, HopStatus::External);
+        assert_eq!(paths[1].boundary, Category::Cache);
+        assert!(paths[0].score > paths[1].score);
+
+        // The limit keeps the most critical paths
+        assert_eq!(finder.find(&endpoint("list_orders"), 1).len(), 1);
+        // The depth limit stops before the database call
+        let shallow = CriticalPathFinder::new(&graph, &patterns, &deps, &[]).with_max_depth(2);
+        let paths = shallow.find(&endpoint("list_orders"), 5);
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].boundary, Category::Cache);
+    }
+
+    #[test]
+    fn test_walk_skips_dead_ends() {
+        // Every function of a layer calls every function of the next, so
+        // there are 6^8 paths through the layers, none reaching a boundary
+        let mut code = String::from("fn handler() { layer0_0(); query_user(); }\n");
+        for layer in 0..8 {
+            for index in 0..6 {
+                let calls: String = (0..6)
+                    .map(|next| format!("layer{}_{next}(); ", layer + 1))
+                    .collect();
+                code.push_str(&format!("fn layer{layer}_{index}() {{ {calls}}}\n"));
+            }
+        }
+        code.push_str("fn query_user() {}\n");
+        let graph = graph(&code);
+        let patterns = vec![pattern(&graph, "query_user", Category::Database)];
+
+        let finder =
+            CriticalPathFinder::new(&graph, &patterns, &ProjectDependencies::default(), &[]);
+        let paths = finder.find(&endpoint("handler"), 5);
+
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].hops.len(), 2);
+    }
+
+    #[test]
+    fn test_hop_instrumentation_status() {
+        let graph = graph(
+            "fn handler() {\n    load();\n}\nfn load() {\n    query_user();\n}\nfn query_user() {}\n",
+        );
+        let patterns = vec![pattern(&graph, "query_user", Category::Database)];
+        let handler_line = graph.get_node("handler").unwrap().line().unwrap();
+        let existing = vec![ExistingInstrumentation {
+            location: Location {
+                file: PathBuf::from("src/lib.rs"),
+                line: handler_line,
+                column: 1,
+                function_name: "handler".to_string(),
+            },
+            kind: ExistingKind::TracingInstrument,
+            span_name: None,
+            quality: InstrumentationQuality::default(),
+            args: None,
+            instrumented_futures: Vec::new(),
+            otel: None,
+        }];
+
+        let finder = CriticalPathFinder::new(
+            &graph,
+            &patterns,
+            &ProjectDependencies::default(),
+            &existing,
+        );
+        let paths = finder.find(&endpoint("handler"), 5);
+
+        assert_eq!(paths.len(), 1);
+        let statuses: Vec<_> = paths[0].hops.iter().map(|h| h.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                HopStatus::Instrumented,
+                HopStatus::Uninstrumented,
+                HopStatus::Uninstrumented
+            ]
+        );
+        assert_eq!(paths[0].coverage_lost_at().unwrap().function, "load");
+        assert!(!paths[0].is_fully_instrumented());
+    }
+}
@@ -1,270 +1,351 @@
 //! Endpoint detection from web frameworks
 //!
 //! Detects HTTP handlers and gRPC service methods from various frameworks.
+//! Each framework is analyzed on the syntax tree by a [`FrameworkDetector`]
+//! registered in a [`DetectorRegistry`].
 
-use super::{Endpoint, Location};
+use super::{Endpoint, EndpointParam, Location};
 use crate::ast::SourceFile;
+use crate::call_graph::{CallGraph, FunctionNode, ModuleTree, NodeKind};
 use crate::framework::DetectedFramework;
+use crate::framework::web::routing::resolve_item_path;
+use crate::framework::web::{DetectorRegistry, FrameworkDetector};
+use std::path::Path;
+use syn::{ImplItem, ImplItemFn, Item, ItemFn};
+
+/// Detect endpoints from parsed source files using the default detectors
+///
+/// Files are placed in modules by their path under `src/`.
+pub fn detect_endpoints(files: &[SourceFile], frameworks: &[DetectedFramework]) -> Vec<Endpoint> {
+    detect_endpoints_with_registry(
+        &DetectorRegistry::new(),
+        files,
+        &ModuleTree::default(),
+        frameworks,
+        None,
+    )
+}
 
-/// Detect endpoints from parsed source files
-pub fn detect_endpoints(files: &[SourceFile], framework: &DetectedFramework) -> Vec<Endpoint> {
+/// Detect endpoints from parsed source files using a detector registry
+///
+/// Every registered detector that handles one of the given frameworks runs,
+/// so a project serving both HTTP and gRPC gets the endpoints of both. When
+/// no framework is known every detector runs. With a `project_root`,
+/// detectors that report their framework as used by the project run as well.
+///
+/// # Arguments
+///
+/// * `registry` - Registry of syntax-tree based framework detectors
+/// * `files` - The parsed source files
+/// * `modules` - Module tree placing each file in its crate
+/// * `frameworks` - The frameworks used by the project
+/// * `project_root` - Root of the project, passed to [`FrameworkDetector::detect`]
+pub fn detect_endpoints_with_registry(
+    registry: &DetectorRegistry,
+    files: &[SourceFile],
+    modules: &ModuleTree,
+    frameworks: &[DetectedFramework],
+    project_root: Option<&Path>,
+) -> Vec<Endpoint> {
+    let known: Vec<&DetectedFramework> = frameworks.iter().filter(|f| f.is_known()).collect();
     let mut endpoints = Vec::new();
 
-    for file in files {
-        match framework {
-            DetectedFramework::Axum => {
-                endpoints.extend(detect_axum_endpoints(file));
-            }
-            DetectedFramework::Actix => {
-                endpoints.extend(detect_actix_endpoints(file));
-            }
-            DetectedFramework::Rocket => {
-                endpoints.extend(detect_rocket_endpoints(file));
-            }
-            DetectedFramework::Tonic => {
-                endpoints.extend(detect_tonic_endpoints(file));
-            }
-            DetectedFramework::Unknown => {
-                // Try all frameworks
-                endpoints.extend(detect_axum_endpoints(file));
-                endpoints.extend(detect_actix_endpoints(file));
-                endpoints.extend(detect_rocket_endpoints(file));
-                endpoints.extend(detect_tonic_endpoints(file));
-            }
+    for detector in registry.detectors() {
+        let handles_framework = known
+            .iter()
+            .any(|framework| framework_matches(detector.name(), framework));
+        let used_by_project =
+            project_root.is_some_and(|root| detector.detect(root).unwrap_or(false));
+
+        if handles_framework || known.is_empty() || used_by_project {
+            endpoints.extend(endpoints_from_detector(detector, files, modules));
         }
     }
 
     endpoints
 }
 
-fn detect_axum_endpoints(file: &SourceFile) -> Vec<Endpoint> {
-    let mut endpoints = Vec::new();
-    let source = file.source();
-
-    // Look for Router::new().route() patterns
-    // and handler functions with axum extractors
-    for (line_num, line) in source.lines().enumerate() {
-        let line = line.trim();
-
-        // Match .route("/path", method(handler))
-        if let Some(captures) = parse_axum_route(line) {
-            let handler = captures.handler;
-            // Find the actual function definition line, not the route registration line
-            let fn_line = find_function_definition_line(source, &handler).unwrap_or(line_num + 1);
-            endpoints.push(Endpoint {
-                method: captures.method,
-                path: captures.path,
-                handler: handler.clone(),
+/// Run a single framework detector over all files
+///
+/// Endpoints are converted into [`Endpoint`]s whose location points at the
+/// handler definition when it can be resolved (falling back to the route
+/// registration), carrying the handler's parameters and documentation.
+/// Handlers only resolve to definitions in the crate of the route.
+pub fn endpoints_from_detector(
+    detector: &dyn FrameworkDetector,
+    files: &[SourceFile],
+    modules: &ModuleTree,
+) -> Vec<Endpoint> {
+    let definitions = FunctionIndex::new(files, modules);
+    let framework = detector.name().to_lowercase();
+
+    detector
+        .extract_project_endpoints(files, modules)
+        .into_iter()
+        .map(|endpoint| {
+            let crate_name = endpoint
+                .file
+                .as_deref()
+                .and_then(|file| modules.location(file))
+                .and_then(|location| location.crate_name.as_deref());
+            let definition =
+                definitions.resolve(crate_name, &endpoint.module_path, &endpoint.handler);
+            let handler_info = definition
+                .and_then(|(_, _, function)| detector.detect_handler(&function.to_item_fn()));
+
+            let (file, line) = match definition {
+                Some((file, line, _)) => (file.to_path_buf(), line),
+                None => (endpoint.file.clone().unwrap_or_default(), endpoint.line),
+            };
+
+            let parameters = handler_info
+                .as_ref()
+                .map(|info| {
+                    info.parameters
+                        .iter()
+                        .map(|param| EndpointParam {
+                            name: param.name.clone(),
+                            ty: param.ty.clone(),
+                            is_extractor: param.is_extractor,
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            let documentation = endpoint
+                .documentation
+                .or_else(|| handler_info.and_then(|info| info.documentation));
+
+            let function_name = endpoint
+                .handler
+                .rsplit("::")
+                .next()
+                .unwrap_or(&endpoint.handler)
+                .to_string();
+
+            Endpoint {
+                method: endpoint.method.as_str().to_string(),
+                path: endpoint.path,
+                handler: endpoint.handler,
                 location: Location {
-                    file: file.path().to_path_buf(),
-                    line: fn_line,
+                    file,
+                    line,
                     column: 1,
-                    function_name: handler,
+                    function_name,
                 },
-                framework: "axum".to_string(),
-            });
-        }
-    }
-
-    endpoints
-}
-
-struct RouteCapture {
-    method: String,
-    path: String,
-    handler: String,
+                framework: framework.clone(),
+                parameters,
+                documentation,
+                streaming: endpoint.streaming,
+            }
+        })
+        .collect()
 }
 
-fn parse_axum_route(line: &str) -> Option<RouteCapture> {
-    // Simple pattern matching for .route("/path", get(handler))
-    if !line.contains(".route(") {
-        return None;
-    }
-
-    // Extract path
-    let path_start = line.find('"')? + 1;
-    let path_end = line[path_start..].find('"')? + path_start;
-    let path = line[path_start..path_end].to_string();
-
-    // Extract method and handler
-    let methods = ["get", "post", "put", "delete", "patch", "head", "options"];
-    for method in methods {
-        let pattern = format!("{method}(");
-        if let Some(pos) = line.find(&pattern) {
-            let handler_start = pos + pattern.len();
-            let handler_end = line[handler_start..].find(')')? + handler_start;
-            let handler = line[handler_start..handler_end].trim().to_string();
-            return Some(RouteCapture {
-                method: method.to_uppercase(),
-                path,
-                handler,
-            });
+/// Name each endpoint's location after the call graph node of its handler
+///
+/// Detectors only know the handler as written in the route, so locations
+/// start out with its last segment. Matching them against the graph lets
+/// endpoint points be compared with existing instrumentation and the other
+/// points by node ID. Handlers resolved to a definition are found by the line
+/// of their name; the rest by the node ID ending with the handler's name.
+///
+/// # Arguments
+///
+/// * `endpoints` - Endpoints from [`detect_endpoints_with_registry`]
+/// * `graph` - Call graph built from the same files
+pub fn attribute_to_graph(endpoints: &mut [Endpoint], graph: &CallGraph) {
+    for endpoint in endpoints {
+        let file = endpoint.location.file.display().to_string();
+        let node = graph
+            .nodes()
+            .filter(|node| !matches!(node.kind, NodeKind::External | NodeKind::Closure))
+            .find(|node| {
+                node.file_path.as_deref() == Some(file.as_str())
+                    && node.line_number == Some(endpoint.location.line)
+            })
+            .or_else(|| handler_node(graph, endpoint));
+
+        if let Some(node) = node {
+            endpoint.location.function_name = node.id.clone();
         }
     }
-
-    None
 }
 
-fn detect_actix_endpoints(file: &SourceFile) -> Vec<Endpoint> {
-    let mut endpoints = Vec::new();
-    let source = file.source();
-
-    // Look for #[get("/path")], #[post("/path")] etc.
-    let methods = ["get", "post", "put", "delete", "patch", "head"];
-
-    for (line_num, line) in source.lines().enumerate() {
-        let line = line.trim();
-
-        for method in methods {
-            let pattern = format!("#[{method}(");
-            if line.starts_with(&pattern) {
-                if let Some(path) = extract_path_from_attribute(line) {
-                    // Try to find the handler name on the next non-empty line
-                    let handler = find_next_function_name(source, line_num)
-                        .unwrap_or_else(|| "unknown".to_string());
-                    endpoints.push(Endpoint {
-                        method: method.to_uppercase(),
-                        path,
-                        handler: handler.clone(),
-                        location: Location {
-                            file: file.path().to_path_buf(),
-                            line: line_num + 1,
-                            column: 1,
-                            function_name: handler,
-                        },
-                        framework: "actix-web".to_string(),
-                    });
-                }
-            }
-        }
+/// Finds the call graph node of an endpoint's handler
+///
+/// Handlers are named as written in the route (`get_user`,
+/// `handlers::get_user`, `Greeter::say_hello`), so the node is the one whose
+/// ID ends with that name. A node in the endpoint's own file wins when the
+/// name is ambiguous.
+///
+/// # Arguments
+///
+/// * `graph` - The call graph
+/// * `endpoint` - The endpoint whose handler to find
+///
+/// # Returns
+///
+/// The handler's node, or None if it isn't in the graph or is ambiguous
+fn handler_node<'a>(graph: &'a CallGraph, endpoint: &Endpoint) -> Option<&'a FunctionNode> {
+    let handler = endpoint
+        .handler
+        .trim_start_matches("crate::")
+        .trim_start_matches("self::");
+    let suffix = format!("::{}", handler);
+
+    let mut candidates: Vec<&FunctionNode> = graph
+        .nodes()
+        .filter(|node| !matches!(node.kind, NodeKind::External | NodeKind::Closure))
+        .filter(|node| node.id == handler || node.id.ends_with(&suffix))
+        .collect();
+    candidates.sort_by(|a, b| a.id.cmp(&b.id));
+
+    if let Some(node) = candidates
+        .iter()
+        .find(|node| node.file().as_ref() == Some(&endpoint.location.file))
+    {
+        return Some(node);
     }
 
-    endpoints
-}
-
-fn detect_rocket_endpoints(file: &SourceFile) -> Vec<Endpoint> {
-    let mut endpoints = Vec::new();
-    let source = file.source();
-
-    // Look for #[get("/path")], #[post("/path")] etc.
-    let methods = ["get", "post", "put", "delete", "patch", "head"];
-
-    for (line_num, line) in source.lines().enumerate() {
-        let line = line.trim();
-
-        for method in methods {
-            // Rocket uses same attribute syntax as actix
-            let pattern = format!("#[{method}(");
-            if line.starts_with(&pattern) {
-                if let Some(path) = extract_path_from_attribute(line) {
-                    let handler = find_next_function_name(source, line_num)
-                        .unwrap_or_else(|| "unknown".to_string());
-                    endpoints.push(Endpoint {
-                        method: method.to_uppercase(),
-                        path,
-                        handler: handler.clone(),
-                        location: Location {
-                            file: file.path().to_path_buf(),
-                            line: line_num + 1,
-                            column: 1,
-                            function_name: handler,
-                        },
-                        framework: "rocket".to_string(),
-                    });
-                }
-            }
-        }
+    match candidates.as_slice() {
+        [node] => Some(node),
+        _ => None,
     }
-
-    endpoints
 }
 
-fn detect_tonic_endpoints(file: &SourceFile) -> Vec<Endpoint> {
-    let mut endpoints = Vec::new();
-    let source = file.source();
-
-    // Look for #[tonic::async_trait] impl blocks and gRPC methods
-    for (line_num, line) in source.lines().enumerate() {
-        let line = line.trim();
-
-        // Look for async fn inside impl blocks that look like gRPC services
-        if line.starts_with("async fn ") && line.contains("Request<") {
-            if let Some(fn_name) = extract_function_name(line) {
-                endpoints.push(Endpoint {
-                    method: "gRPC".to_string(),
-                    path: format!("/{fn_name}"),
-                    handler: fn_name.clone(),
-                    location: Location {
-                        file: file.path().to_path_buf(),
-                        line: line_num + 1,
-                        column: 1,
-                        function_name: fn_name,
-                    },
-                    framework: "tonic".to_string(),
-                });
-            }
-        }
-    }
+/// Compare a detector name (e.g. `Actix-web`) with a framework
+fn framework_matches(detector_name: &str, framework: &DetectedFramework) -> bool {
+    let normalize = |name: &str| -> String {
+        name.chars()
+            .filter(char::is_ascii_alphanumeric)
+            .collect::<String>()
+            .to_lowercase()
+    };
+    normalize(detector_name) == normalize(framework.name())
+}
 
-    endpoints
+/// Index of every function definition in the analyzed files
+///
+/// Used to map handler paths from route registrations back to the function
+/// that implements them.
+struct FunctionIndex<'a> {
+    /// Crate of each function, if the module tree knows it
+    crates: Vec<Option<String>>,
+    /// Full path segments of each function within its crate (methods include
+    /// their self type)
+    paths: Vec<Vec<String>>,
+    /// File, line and definition of each function
+    locations: Vec<(&'a Path, usize, FunctionRef<'a>)>,
 }
 
-fn extract_path_from_attribute(line: &str) -> Option<String> {
-    let start = line.find('"')? + 1;
-    let end = line[start..].find('"')? + start;
-    Some(line[start..end].to_string())
+/// A free function or a method in an `impl` block
+#[derive(Clone, Copy)]
+enum FunctionRef<'a> {
+    Item(&'a ItemFn),
+    Method(&'a ImplItemFn),
 }
 
-fn find_next_function_name(source: &str, current_line: usize) -> Option<String> {
-    for line in source.lines().skip(current_line + 1) {
-        let trimmed = line.trim();
-        if trimmed.starts_with("pub async fn ")
-            || trimmed.starts_with("async fn ")
-            || trimmed.starts_with("pub fn ")
-            || trimmed.starts_with("fn ")
-        {
-            return extract_function_name(trimmed);
-        }
-        // Skip attributes and comments
-        if !trimmed.starts_with('#') && !trimmed.starts_with("//") && !trimmed.is_empty() {
-            break;
+impl FunctionRef<'_> {
+    fn to_item_fn(self) -> ItemFn {
+        match self {
+            Self::Item(item_fn) => item_fn.clone(),
+            Self::Method(method) => ItemFn {
+                attrs: method.attrs.clone(),
+                vis: method.vis.clone(),
+                sig: method.sig.clone(),
+                block: Box::new(method.block.clone()),
+            },
         }
     }
-    None
 }
 
-fn extract_function_name(line: &str) -> Option<String> {
-    let line = line
-        .trim_start_matches("pub ")
-        .trim_start_matches("async ")
-        .trim_start_matches("fn ");
-
-    let end = line.find('(')?;
-    let name = line[..end].trim();
-    if name.is_empty() {
-        None
-    } else {
-        Some(name.to_string())
+impl<'a> FunctionIndex<'a> {
+    fn new(files: &'a [SourceFile], modules: &ModuleTree) -> Self {
+        let mut index = Self {
+            crates: Vec::new(),
+            paths: Vec::new(),
+            locations: Vec::new(),
+        };
+
+        for file in files {
+            let location = modules.module_location(file.path());
+            index.add_items(&file.syntax_tree.items, &location.modules, file.path());
+            index
+                .crates
+                .resize(index.paths.len(), location.crate_name.clone());
+        }
+
+        index
     }
-}
 
-/// Find the line number where a function is defined
-fn find_function_definition_line(source: &str, fn_name: &str) -> Option<usize> {
-    for (line_num, line) in source.lines().enumerate() {
-        let trimmed = line.trim();
-
-        // Look for function definitions
-        if trimmed.starts_with("pub async fn ")
-            || trimmed.starts_with("async fn ")
-            || trimmed.starts_with("pub fn ")
-            || trimmed.starts_with("fn ")
-        {
-            if let Some(name) = extract_function_name(trimmed) {
-                if name == fn_name {
-                    return Some(line_num + 1); // 1-based line number
+    fn add_items(&mut self, items: &'a [Item], module_path: &[String], file: &'a Path) {
+        for item in items {
+            match item {
+                Item::Fn(item_fn) => {
+                    self.add(module_path, None, FunctionRef::Item(item_fn), file);
+                }
+                Item::Impl(item_impl) => {
+                    let self_ty = &item_impl.self_ty;
+                    let self_ty = quote::quote!(#self_ty).to_string().replace(' ', "");
+                    for impl_item in &item_impl.items {
+                        if let ImplItem::Fn(method) = impl_item {
+                            self.add(
+                                module_path,
+                                Some(&self_ty),
+                                FunctionRef::Method(method),
+                                file,
+                            );
+                        }
+                    }
                 }
+                Item::Mod(item_mod) => {
+                    if let Some((_, items)) = &item_mod.content {
+                        let mut nested = module_path.to_vec();
+                        nested.push(item_mod.ident.to_string());
+                        self.add_items(items, &nested, file);
+                    }
+                }
+                _ => {}
             }
         }
     }
-    None
+
+    fn add(
+        &mut self,
+        module_path: &[String],
+        self_ty: Option<&str>,
+        function: FunctionRef<'a>,
+        file: &'a Path,
+    ) {
+        let ident = match function {
+            FunctionRef::Item(item_fn) => &item_fn.sig.ident,
+            FunctionRef::Method(method) => &method.sig.ident,
+        };
+
+        let mut path = module_path.to_vec();
+        path.extend(self_ty.map(str::to_string));
+        path.push(ident.to_string());
+
+        self.paths.push(path);
+        self.locations
+            .push((file, ident.span().start().line, function));
+    }
+
+    /// Resolve a handler path written in `from_module` of `crate_name` to its
+    /// definition in the same crate
+    fn resolve(
+        &self,
+        crate_name: Option<&str>,
+        from_module: &[String],
+        handler: &str,
+    ) -> Option<(&'a Path, usize, FunctionRef<'a>)> {
+        let candidates: Vec<usize> = (0..self.paths.len())
+            .filter(|&index| self.crates[index].as_deref() == crate_name)
+            .collect();
+        let paths = candidates.iter().map(|&index| self.paths[index].as_slice());
+
+        resolve_item_path(paths, from_module, handler)
+            .map(|found| self.locations[candidates[found]])
+    }
 }
@@ -1,250 +1,1185 @@
 //! Detection of existing instrumentation in code
 //!
-//! Finds existing tracing, logging, and metrics instrumentation.
+//! Finds existing tracing, logging, and metrics instrumentation by walking
+//! the syntax tree, so attributes spanning several lines are read whole and
+//! text in comments or strings is never mistaken for instrumentation. Each
+//! finding is attributed to the function it instruments or appears in, and
+//! futures wrapped with `.instrument(span)` to the functions they call.
+//!
+//! Spans started through the OpenTelemetry API (`tracer.start(..)`,
+//! `tracer.in_span(..)`) are found too, with the attributes and status their
+//! function records on them.
+//!
+//! Log events are assessed where they are emitted, so the visitor tracks the
+//! `Err` branches, error-handling closures and loops it is in. An error
+//! logged on its way out of a function and again by the caller it reaches
+//! is flagged at the caller.
 
+use super::quality::{LogContext, assess_instrument_quality, assess_log_quality, returns_result};
+use super::spawn::{SPAN_PROPAGATORS, mentions_span};
 use super::{
-    ExistingInstrumentation, ExistingKind, InstrumentationQuality, Location, QualityIssue,
-    QualityIssueKind,
+    ExistingInstrumentation, ExistingKind, InstrumentArgs, InstrumentationQuality, Location,
+    OtelSpanInfo, QualityIssue, QualityIssueKind, SpanField,
 };
 use crate::ast::SourceFile;
+use crate::call_graph::{CallGraph, NodeKind, type_name};
+use proc_macro2::{Delimiter, TokenStream, TokenTree};
+use quote::ToTokens;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{
+    Arm, Attribute, Block, Expr, ImplItemFn, ItemFn, ItemImpl, ItemTrait, Lit, Local, Macro, Meta,
+    Pat, Signature, Stmt, Token, TraitItemFn,
+};
+
+/// Macros that create a span, without `tracing::`
+const SPAN_MACROS: &[&str] = &[
+    "span",
+    "trace_span",
+    "debug_span",
+    "info_span",
+    "warn_span",
+    "error_span",
+];
+
+/// Macros that emit a log event, without `tracing::` or `log::`
+const LOG_MACROS: &[&str] = &["trace", "debug", "info", "warn", "error"];
+
+/// Macros that record a metric, without `metrics::`
+const METRICS_MACROS: &[&str] = &["counter", "gauge", "histogram"];
+
+/// Tracer methods that start an OpenTelemetry span, with their arity
+const OTEL_SPAN_STARTS: &[(&str, usize)] = &[
+    ("start", 1),
+    ("start_with_context", 2),
+    ("in_span", 2),
+    ("span_builder", 1),
+];
+
+/// Methods that set attributes on an OpenTelemetry span or span builder
+const OTEL_ATTRIBUTE_SETTERS: &[&str] = &["set_attribute", "set_attributes", "with_attributes"];
+
+/// Methods whose closure argument handles an error (`map_err(|e| ..)`)
+const ERROR_HANDLERS: &[&str] = &["map_err", "inspect_err", "unwrap_or_else", "or_else"];
+
+/// Error handlers that pass the error on
+const ERROR_PASSERS: &[&str] = &["map_err", "inspect_err"];
+
+/// Words in a condition that limit how often its branch runs
+const RATE_LIMIT_HINTS: &[&str] = &["limit", "throttle", "sample", "every", "once", "elapsed"];
+
+/// Span names that say nothing about the operation
+const GENERIC_SPAN_NAMES: &[&str] = &[
+    "span",
+    "operation",
+    "request",
+    "handler",
+    "function",
+    "process",
+    "run",
+];
 
 /// Detect existing instrumentation in source files
-pub fn detect_existing_instrumentation(files: &[SourceFile]) -> Vec<ExistingInstrumentation> {
+///
+/// # Arguments
+///
+/// * `files` - Parsed source files to scan
+/// * `graph` - Call graph of the files, whose node IDs name the functions
+///   each finding belongs to
+///
+/// # Returns
+///
+/// Every `#[instrument]` attribute, span, log event and metric, with
+/// `Location::function_name` set to the ID of the enclosing function
+pub fn detect_existing_instrumentation(
+    files: &[SourceFile],
+    graph: &CallGraph,
+) -> Vec<ExistingInstrumentation> {
+    // Functions are identified by the line of their name, as in the graph
+    let mut function_ids: HashMap<(String, usize), &str> = HashMap::new();
+    for node in graph.nodes() {
+        if matches!(node.kind, NodeKind::External | NodeKind::Closure) {
+            continue;
+        }
+        if let (Some(file), Some(line)) = (&node.file_path, node.line_number) {
+            function_ids.insert((file.clone(), line), node.id.as_str());
+        }
+    }
+
+    // Called futures are identified by the position of their call or async
+    // block, as recorded on the edge to them
+    let mut callees: HashMap<(String, usize, usize), Vec<&str>> = HashMap::new();
+    for edge in graph.edges() {
+        let internal = graph
+            .get_node(&edge.to)
+            .is_some_and(|node| node.kind != NodeKind::External);
+        if let (true, Some(file), Some(line), Some(column)) =
+            (internal, &edge.file_path, edge.line_number, edge.column)
+        {
+            callees
+                .entry((file.clone(), line, column))
+                .or_default()
+                .push(edge.to.as_str());
+        }
+    }
+
     let mut results = Vec::new();
+    // Indices of the log events recording an error that is passed on
+    let mut propagated_logs = Vec::new();
 
     for file in files {
-        results.extend(detect_in_file(file));
+        let mut visitor = ExistingVisitor::new(file.path().to_path_buf());
+        visitor.visit_file(&file.syntax_tree);
+
+        let file_key = file.path().display().to_string();
+        for found in visitor.found {
+            let mut instrumentation = found.instrumentation;
+            if let Some(function) = found.function {
+                instrumentation.location.function_name = function_ids
+                    .get(&(file_key.clone(), function.line))
+                    .map_or(function.name, |id| id.to_string());
+            }
+
+            let mut futures: Vec<String> = found
+                .futures
+                .iter()
+                .flat_map(|&(line, column)| {
+                    callees
+                        .get(&(file_key.clone(), line, column))
+                        .into_iter()
+                        .flatten()
+                })
+                .map(|id| id.to_string())
+                .collect();
+            if found.runs_function && !instrumentation.location.function_name.is_empty() {
+                futures.push(instrumentation.location.function_name.clone());
+            }
+            futures.sort();
+            futures.dedup();
+            instrumentation.instrumented_futures = futures;
+
+            if found.logs_propagated_error {
+                propagated_logs.push(results.len());
+            }
+            results.push(instrumentation);
+        }
     }
 
+    flag_duplicate_error_logs(&mut results, &propagated_logs, graph);
     results
 }
 
-fn detect_in_file(file: &SourceFile) -> Vec<ExistingInstrumentation> {
-    let mut results = Vec::new();
-    let source = file.source();
-    let path = file.path().to_path_buf();
+/// Flags the log events of errors already logged by the callee that returned
+/// them
+///
+/// Both functions log the error and pass it on, so it is logged once for
+/// every level it propagates through.
+fn flag_duplicate_error_logs(
+    results: &mut [ExistingInstrumentation],
+    propagated_logs: &[usize],
+    graph: &CallGraph,
+) {
+    let logging: HashSet<String> = propagated_logs
+        .iter()
+        .map(|&index| results[index].location.function_name.clone())
+        .collect();
+
+    for &index in propagated_logs {
+        let function = &results[index].location.function_name;
+        let nested = format!("{function}::{{");
+        let callee = graph
+            .edges()
+            .iter()
+            .filter(|edge| edge.from == *function || edge.from.starts_with(&nested))
+            .find(|edge| edge.to != *function && logging.contains(&edge.to))
+            .map(|edge| edge.to.clone());
+        let Some(callee) = callee else {
+            continue;
+        };
+
+        let quality = &mut results[index].quality;
+        quality.score = (quality.score - 0.2).max(0.0);
+        quality.issues.push(QualityIssue {
+            kind: QualityIssueKind::DuplicateErrorLog,
+            message: format!(
+                "Error is already logged by '{callee}' before it is returned; log it once, \
+                 where it is handled"
+            ),
+        });
+    }
+}
+
+/// A function enclosing the visitor's position
+#[derive(Debug, Clone)]
+struct EnclosingFunction {
+    /// Name, `Type::method` for methods
+    name: String,
+    /// Line of the function's name
+    line: usize,
+}
+
+/// Instrumentation found by the visitor, before function IDs are known
+struct Found {
+    instrumentation: ExistingInstrumentation,
+    /// Function the instrumentation is in
+    function: Option<EnclosingFunction>,
+    /// Positions of the calls and async blocks whose futures run in the span
+    futures: Vec<(usize, usize)>,
+    /// Whether the enclosing function itself runs in the span, as when it
+    /// awaits an instrumented async block
+    runs_function: bool,
+    /// Whether this is a `warn!` or `error!` of an error that is passed on
+    logs_propagated_error: bool,
+}
 
-    for (line_num, line) in source.lines().enumerate() {
-        let trimmed = line.trim();
+/// An `Err` branch or error-handling closure enclosing the visitor's position
+struct ErrorContext {
+    /// Names bound to the error
+    bindings: Vec<String>,
+    /// Whether the error is passed on: returned, or mapped with `map_err`
+    propagates: bool,
+}
 
-        // Detect #[instrument] macro
-        if let Some(inst) = detect_instrument_macro(trimmed, &path, line_num) {
-            results.push(inst);
+/// Collects the instrumentation in a file
+struct ExistingVisitor {
+    file: PathBuf,
+    /// Stack of enclosing functions
+    functions: Vec<EnclosingFunction>,
+    /// Stack of enclosing `impl` self types and trait names
+    impl_types: Vec<String>,
+    /// Positions of `.instrument()` calls whose future is awaited right away
+    awaited: HashSet<(usize, usize)>,
+    /// Positions of `.enter()` calls whose guard is held across an `.await`
+    guards_across_await: HashSet<(usize, usize)>,
+    /// What each enclosing function records on OpenTelemetry spans
+    otel_records: Vec<OtelSpanInfo>,
+    /// Stack of error contexts in the current function
+    error_contexts: Vec<ErrorContext>,
+    /// Number of loops around the visitor's position in the current function
+    loop_depth: usize,
+    /// Number of rate-limiting conditions around the visitor's position
+    rate_limited: usize,
+    found: Vec<Found>,
+}
+
+impl ExistingVisitor {
+    fn new(file: PathBuf) -> Self {
+        Self {
+            file,
+            functions: Vec::new(),
+            impl_types: Vec::new(),
+            awaited: HashSet::new(),
+            guards_across_await: HashSet::new(),
+            otel_records: Vec::new(),
+            error_contexts: Vec::new(),
+            loop_depth: 0,
+            rate_limited: 0,
+            found: Vec::new(),
         }
+    }
 
-        // Detect manual span creation
-        if let Some(inst) = detect_manual_span(trimmed, &path, line_num) {
-            results.push(inst);
+    /// Records instrumentation in the current function
+    ///
+    /// # Returns
+    ///
+    /// The new finding, without span name, issues or arguments
+    fn add(&mut self, kind: ExistingKind, span: proc_macro2::Span) -> &mut Found {
+        let start = span.start();
+        self.found.push(Found {
+            instrumentation: ExistingInstrumentation {
+                location: Location {
+                    file: self.file.clone(),
+                    line: start.line,
+                    column: start.column + 1,
+                    function_name: String::new(),
+                },
+                kind,
+                span_name: None,
+                quality: InstrumentationQuality::default(),
+                args: None,
+                instrumented_futures: Vec::new(),
+                otel: None,
+            },
+            function: self.functions.last().cloned(),
+            futures: Vec::new(),
+            runs_function: false,
+            logs_propagated_error: false,
+        });
+        self.found.last_mut().expect("just pushed")
+    }
+
+    /// Records a future wrapped with `.instrument(span)` or `.in_current_span()`
+    fn add_instrumented_future(&mut self, call: &syn::ExprMethodCall) {
+        let awaited = self.awaited.contains(&position(call.method.span()));
+        let future = strip_parens(&call.receiver);
+        let span_name = call.args.first().and_then(span_macro_name);
+
+        let found = self.add(ExistingKind::InstrumentedFuture, call.method.span());
+        found.instrumentation.span_name = span_name;
+        match future {
+            Expr::Async(block) => {
+                found.futures.push(position_of(block));
+                found.runs_function = awaited;
+            }
+            Expr::Closure(closure) => found.futures.push(position_of(closure)),
+            Expr::Call(inner) => found.futures.push(position_of(&inner.func)),
+            Expr::MethodCall(inner) => found.futures.push(position(inner.method.span())),
+            _ => {}
         }
+    }
+
+    /// Records a span entered with a guard or `in_scope`
+    fn add_span_guard(&mut self, call: &syn::ExprMethodCall) {
+        let across_await = self
+            .guards_across_await
+            .contains(&position(call.method.span()));
+        let span_name = span_macro_name(&call.receiver);
 
-        // Detect log macros
-        if let Some(inst) = detect_log_macro(trimmed, &path, line_num) {
-            results.push(inst);
+        let found = self.add(ExistingKind::SpanGuard, call.method.span());
+        found.instrumentation.span_name = span_name;
+        if across_await {
+            found.instrumentation.quality = InstrumentationQuality {
+                score: 0.5,
+                issues: vec![QualityIssue {
+                    kind: QualityIssueKind::GuardAcrossAwait,
+                    message: "Span guard is held across an .await, so the span stays \
+                              entered while the task is suspended; use .instrument(span) \
+                              instead"
+                        .to_string(),
+                }],
+            };
         }
+    }
+
+    /// Records a log event, assessed in its error and loop context
+    fn add_log_event(&mut self, mac: &Macro, level: &str) {
+        let error_context = self.error_contexts.last();
+        let context = LogContext {
+            error_bindings: error_context.map(|context| context.bindings.clone()),
+            in_loop: self.loop_depth > 0,
+            rate_limited: self.rate_limited > 0,
+        };
+        let propagated = matches!(level, "warn" | "error")
+            && error_context.is_some_and(|context| context.propagates);
+
+        let found = self.add(ExistingKind::LogMacro, mac.path.span());
+        found.instrumentation.quality = assess_log_quality(level, &mac.tokens, &context);
+        found.logs_propagated_error = propagated;
+    }
+
+    /// Visits code that handles an error
+    fn in_error_context(&mut self, context: ErrorContext, visit: impl FnOnce(&mut Self)) {
+        self.error_contexts.push(context);
+        visit(self);
+        self.error_contexts.pop();
+    }
+
+    /// Visits a loop
+    fn in_loop(&mut self, visit: impl FnOnce(&mut Self)) {
+        self.loop_depth += 1;
+        visit(self);
+        self.loop_depth -= 1;
+    }
 
-        // Detect metrics
-        if let Some(inst) = detect_metrics(trimmed, &path, line_num) {
-            results.push(inst);
+    /// Records an OpenTelemetry span started with a tracer
+    ///
+    /// Its attributes and status are filled in once the whole function has
+    /// been visited.
+    fn add_otel_span(&mut self, call: &syn::ExprMethodCall) {
+        let span_name = call.args.first().and_then(string_literal);
+        let found = self.add(ExistingKind::OpenTelemetrySpan, call.method.span());
+        found.instrumentation.span_name = span_name;
+        found.instrumentation.otel = Some(OtelSpanInfo::default());
+    }
+
+    /// Notes what a method call records on an OpenTelemetry span
+    fn record_otel_call(&mut self, call: &syn::ExprMethodCall) {
+        let Some(record) = self.otel_records.last_mut() else {
+            return;
+        };
+        let method = call.method.to_string();
+        if OTEL_ATTRIBUTE_SETTERS.contains(&method.as_str()) {
+            for arg in &call.args {
+                record.attributes.extend(attribute_keys(arg));
+            }
+        } else if matches!(method.as_str(), "set_status" | "with_status") {
+            record.sets_status = true;
+        } else if method == "record_error" {
+            record.records_errors = true;
         }
     }
 
-    results
+    /// Visits a function, recording its `#[instrument]` attributes
+    fn visit_function(
+        &mut self,
+        attrs: &[Attribute],
+        sig: &Signature,
+        visit: impl FnOnce(&mut Self),
+    ) {
+        let name = match self.impl_types.last() {
+            Some(self_type) if !self_type.is_empty() => format!("{}::{}", self_type, sig.ident),
+            _ => sig.ident.to_string(),
+        };
+        let line = sig.ident.span().start().line;
+        self.functions.push(EnclosingFunction { name, line });
+        self.otel_records.push(OtelSpanInfo::default());
+        let error_contexts = std::mem::take(&mut self.error_contexts);
+        let loop_depth = std::mem::take(&mut self.loop_depth);
+        let rate_limited = std::mem::take(&mut self.rate_limited);
+        let first = self.found.len();
+
+        for attr in attrs.iter().filter(|attr| is_instrument_attribute(attr)) {
+            let args = parse_instrument_args(attr);
+            let found = self.add(ExistingKind::TracingInstrument, attr.span());
+            found.instrumentation.span_name = args.name.clone();
+            found.instrumentation.quality = assess_instrument_quality(&args, sig);
+            found.instrumentation.args = Some(args);
+        }
+
+        visit(self);
+        self.error_contexts = error_contexts;
+        self.loop_depth = loop_depth;
+        self.rate_limited = rate_limited;
+
+        // Nested functions have recorded their own spans already
+        let record = self.otel_records.pop().unwrap_or_default();
+        let returns_result = returns_result(sig);
+        for found in &mut self.found[first..] {
+            let in_function = found.function.as_ref().is_some_and(|f| f.line == line);
+            let instrumentation = &mut found.instrumentation;
+            if in_function && instrumentation.otel.is_some() {
+                instrumentation.quality = assess_otel_quality(
+                    instrumentation.span_name.as_deref(),
+                    &record,
+                    returns_result,
+                );
+                instrumentation.otel = Some(record.clone());
+            }
+        }
+        self.functions.pop();
+    }
 }
 
-fn detect_instrument_macro(
-    line: &str,
-    path: &PathBuf,
-    line_num: usize,
-) -> Option<ExistingInstrumentation> {
-    if !line.starts_with("#[instrument") && !line.starts_with("#[tracing::instrument") {
+impl<'ast> Visit<'ast> for ExistingVisitor {
+    fn visit_item_fn(&mut self, item: &'ast ItemFn) {
+        self.visit_function(&item.attrs, &item.sig, |v| visit::visit_item_fn(v, item));
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast ItemImpl) {
+        self.impl_types
+            .push(type_name(&item.self_ty).unwrap_or_default());
+        visit::visit_item_impl(self, item);
+        self.impl_types.pop();
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &'ast ImplItemFn) {
+        self.visit_function(&item.attrs, &item.sig, |v| {
+            visit::visit_impl_item_fn(v, item)
+        });
+    }
+
+    fn visit_item_trait(&mut self, item: &'ast ItemTrait) {
+        self.impl_types.push(item.ident.to_string());
+        visit::visit_item_trait(self, item);
+        self.impl_types.pop();
+    }
+
+    fn visit_trait_item_fn(&mut self, item: &'ast TraitItemFn) {
+        self.visit_function(&item.attrs, &item.sig, |v| {
+            visit::visit_trait_item_fn(v, item)
+        });
+    }
+
+    fn visit_macro(&mut self, mac: &'ast Macro) {
+        let segments: Vec<String> = mac
+            .path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect();
+        let span = mac.path.span();
+
+        if is_macro_of(&segments, &["tracing"], SPAN_MACROS) {
+            self.add(ExistingKind::ManualSpan, span)
+                .instrumentation
+                .span_name = extract_span_name(&mac.tokens);
+        } else if is_macro_of(&segments, &["tracing", "log"], LOG_MACROS) {
+            let level = segments.last().cloned().unwrap_or_default();
+            self.add_log_event(mac, &level);
+        } else if is_macro_of(&segments, &["metrics"], METRICS_MACROS)
+            || segments.first().is_some_and(|first| first == "prometheus")
+        {
+            self.add(ExistingKind::Metrics, span);
+        }
+
+        visit::visit_macro(self, mac);
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        // `prometheus::IntCounter::new(...)`, `prometheus::register(...)`
+        if let Expr::Path(func) = &*call.func {
+            let segments = &func.path.segments;
+            if segments.len() > 1 && segments.first().is_some_and(|s| s.ident == "prometheus") {
+                self.add(ExistingKind::Metrics, func.path.span());
+            }
+            // `Context::current_with_span(span)`
+            if segments
+                .last()
+                .is_some_and(|s| s.ident == "current_with_span")
+            {
+                self.add(ExistingKind::OpenTelemetrySpan, func.path.span());
+            }
+        }
+
+        visit::visit_expr_call(self, call);
+    }
+
+    fn visit_expr_await(&mut self, expr: &'ast syn::ExprAwait) {
+        if let Expr::MethodCall(call) = strip_parens(&expr.base) {
+            if SPAN_PROPAGATORS.contains(&call.method.to_string().as_str()) {
+                self.awaited.insert(position(call.method.span()));
+            }
+        }
+
+        visit::visit_expr_await(self, expr);
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        let method = call.method.to_string();
+        if SPAN_PROPAGATORS.contains(&method.as_str()) {
+            self.add_instrumented_future(call);
+        } else if is_span_guard(call) {
+            self.add_span_guard(call);
+        } else if is_otel_span_start(call) {
+            self.add_otel_span(call);
+        } else if is_otel_context(call) {
+            self.add(ExistingKind::OpenTelemetrySpan, call.method.span());
+        }
+        self.record_otel_call(call);
+
+        if !ERROR_HANDLERS.contains(&method.as_str()) {
+            visit::visit_expr_method_call(self, call);
+            return;
+        }
+        self.visit_expr(&call.receiver);
+        for arg in &call.args {
+            match strip_parens(arg) {
+                Expr::Closure(closure) if !closure.inputs.is_empty() => {
+                    let context = ErrorContext {
+                        bindings: closure.inputs.iter().flat_map(pattern_names).collect(),
+                        propagates: ERROR_PASSERS.contains(&method.as_str())
+                            || passes_error_on(|r| r.visit_expr(&closure.body)),
+                    };
+                    self.in_error_context(context, |v| v.visit_expr_closure(closure));
+                }
+                arg => self.visit_expr(arg),
+            }
+        }
+    }
+
+    fn visit_arm(&mut self, arm: &'ast Arm) {
+        match error_pattern_bindings(&arm.pat) {
+            Some(bindings) => {
+                let context = ErrorContext {
+                    bindings,
+                    propagates: passes_error_on(|r| r.visit_expr(&arm.body)),
+                };
+                self.in_error_context(context, |v| visit::visit_arm(v, arm));
+            }
+            None => visit::visit_arm(self, arm),
+        }
+    }
+
+    fn visit_expr_if(&mut self, expr: &'ast syn::ExprIf) {
+        self.visit_expr(&expr.cond);
+
+        // `if let Err(e) = ..`, `if attempts % 100 == 0` in a loop
+        let error = match strip_parens(&expr.cond) {
+            Expr::Let(binding) => error_pattern_bindings(&binding.pat),
+            _ => None,
+        };
+        let limited = usize::from(self.loop_depth > 0 && limits_rate(expr.cond.to_token_stream()));
+        self.rate_limited += limited;
+        match error {
+            Some(bindings) => {
+                let context = ErrorContext {
+                    bindings,
+                    propagates: passes_error_on(|r| r.visit_block(&expr.then_branch)),
+                };
+                self.in_error_context(context, |v| v.visit_block(&expr.then_branch));
+            }
+            None => self.visit_block(&expr.then_branch),
+        }
+        self.rate_limited -= limited;
+
+        if let Some((_, else_branch)) = &expr.else_branch {
+            self.visit_expr(else_branch);
+        }
+    }
+
+    fn visit_local(&mut self, local: &'ast Local) {
+        // `let Ok(value) = .. else { .. }` discards the error
+        let diverge = local.init.as_ref().and_then(|init| {
+            let (_, diverge) = init.diverge.as_ref()?;
+            is_ok_pattern(&local.pat).then_some((&init.expr, diverge))
+        });
+        let Some((init, diverge)) = diverge else {
+            visit::visit_local(self, local);
+            return;
+        };
+
+        self.visit_pat(&local.pat);
+        self.visit_expr(init);
+        let context = ErrorContext {
+            bindings: Vec::new(),
+            propagates: passes_error_on(|r| r.visit_expr(diverge)),
+        };
+        self.in_error_context(context, |v| v.visit_expr(diverge));
+    }
+
+    fn visit_expr_for_loop(&mut self, expr: &'ast syn::ExprForLoop) {
+        self.in_loop(|v| visit::visit_expr_for_loop(v, expr));
+    }
+
+    fn visit_expr_while(&mut self, expr: &'ast syn::ExprWhile) {
+        self.in_loop(|v| visit::visit_expr_while(v, expr));
+    }
+
+    fn visit_expr_loop(&mut self, expr: &'ast syn::ExprLoop) {
+        self.in_loop(|v| visit::visit_expr_loop(v, expr));
+    }
+
+    fn visit_block(&mut self, block: &'ast Block) {
+        for (index, stmt) in block.stmts.iter().enumerate() {
+            let Some((guard, call)) = span_guard_binding(stmt) else {
+                continue;
+            };
+            if held_across_await(&guard, &block.stmts[index + 1..]) {
+                self.guards_across_await
+                    .insert(position(call.method.span()));
+            }
+        }
+
+        visit::visit_block(self, block);
+    }
+}
+
+/// Returns the 1-based line and 0-based column a span starts at, as recorded
+/// on call graph edges
+fn position(span: proc_macro2::Span) -> (usize, usize) {
+    let start = span.start();
+    (start.line, start.column)
+}
+
+/// Returns the position a syntax node starts at
+fn position_of(node: &impl Spanned) -> (usize, usize) {
+    position(node.span())
+}
+
+/// Looks through parentheses around an expression
+fn strip_parens(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Paren(inner) => strip_parens(&inner.expr),
+        _ => expr,
+    }
+}
+
+/// Returns the names an `Err(..)` pattern binds, if the pattern matches errors
+fn error_pattern_bindings(pat: &Pat) -> Option<Vec<String>> {
+    match pat {
+        Pat::TupleStruct(tuple) if tuple.path.segments.last()?.ident == "Err" => {
+            Some(tuple.elems.iter().flat_map(pattern_names).collect())
+        }
+        Pat::Or(or) => or.cases.iter().find_map(error_pattern_bindings),
+        _ => None,
+    }
+}
+
+/// Checks whether a pattern is `Ok(..)`
+fn is_ok_pattern(pat: &Pat) -> bool {
+    matches!(
+        pat,
+        Pat::TupleStruct(tuple) if tuple.path.segments.last().is_some_and(|s| s.ident == "Ok")
+    )
+}
+
+/// Returns the names a pattern binds
+fn pattern_names(pat: &Pat) -> Vec<String> {
+    struct Names(Vec<String>);
+
+    impl<'ast> Visit<'ast> for Names {
+        fn visit_pat_ident(&mut self, pat: &'ast syn::PatIdent) {
+            self.0.push(pat.ident.to_string());
+            visit::visit_pat_ident(self, pat);
+        }
+
+        fn visit_type(&mut self, _: &'ast syn::Type) {}
+    }
+
+    let mut names = Names(Vec::new());
+    names.visit_pat(pat);
+    names.0
+}
+
+/// Finds where code passes an error on
+struct ErrorReturns(bool);
+
+impl<'ast> Visit<'ast> for ErrorReturns {
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        if let Expr::Path(func) = &*call.func {
+            self.0 |= func.path.segments.last().is_some_and(|s| s.ident == "Err");
+        }
+        visit::visit_expr_call(self, call);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast Macro) {
+        self.0 |= mac.path.segments.last().is_some_and(|s| s.ident == "bail");
+    }
+
+    fn visit_expr_closure(&mut self, _: &'ast syn::ExprClosure) {}
+
+    fn visit_expr_async(&mut self, _: &'ast syn::ExprAsync) {}
+
+    fn visit_item(&mut self, _: &'ast syn::Item) {}
+}
+
+/// Checks whether code passes an error on: `return Err(e)`, `Err(e.into())`
+/// or `bail!(..)`
+///
+/// Closures and async blocks don't count: they return elsewhere.
+fn passes_error_on(visit: impl FnOnce(&mut ErrorReturns)) -> bool {
+    let mut returns = ErrorReturns(false);
+    visit(&mut returns);
+    returns.0
+}
+
+/// Checks whether a condition limits how often its branch runs: sampling
+/// with `%`, or a rate limiter or timer (`limiter.check()`,
+/// `last.elapsed() > ..`)
+fn limits_rate(cond: TokenStream) -> bool {
+    cond.into_iter().any(|token| match token {
+        TokenTree::Punct(punct) => punct.as_char() == '%',
+        TokenTree::Ident(ident) => {
+            let ident = ident.to_string().to_lowercase();
+            RATE_LIMIT_HINTS.iter().any(|hint| ident.contains(hint))
+        }
+        TokenTree::Group(group) => limits_rate(group.stream()),
+        TokenTree::Literal(_) => false,
+    })
+}
+
+/// Returns the name of a span created in place with `info_span!("name")`
+fn span_macro_name(expr: &Expr) -> Option<String> {
+    let Expr::Macro(expr) = strip_parens(expr) else {
         return None;
+    };
+    let segments: Vec<String> = expr
+        .mac
+        .path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    if is_macro_of(&segments, &["tracing"], SPAN_MACROS) {
+        extract_span_name(&expr.mac.tokens)
+    } else {
+        None
     }
+}
 
-    let span_name = extract_instrument_name(line);
-    let quality = assess_instrument_quality(line);
+/// Checks whether a method call enters a span: `span.enter()`,
+/// `span.entered()` or `span.in_scope(|| ..)`
+///
+/// The receiver must look like a span, so `runtime.enter()` doesn't count.
+fn is_span_guard(call: &syn::ExprMethodCall) -> bool {
+    let arity = match call.method.to_string().as_str() {
+        "enter" | "entered" => 0,
+        "in_scope" => 1,
+        _ => return false,
+    };
+    call.args.len() == arity && mentions_span(&call.receiver)
+}
 
-    Some(ExistingInstrumentation {
-        location: Location {
-            file: path.clone(),
-            line: line_num + 1,
-            column: 1,
-            function_name: String::new(), // Will be filled by caller if needed
+/// Checks whether a method call starts an OpenTelemetry span:
+/// `tracer.start("name")`, `tracer.in_span("name", |cx| ..)`,
+/// `global::tracer("svc").span_builder("name")`, ...
+fn is_otel_span_start(call: &syn::ExprMethodCall) -> bool {
+    let method = call.method.to_string();
+    OTEL_SPAN_STARTS
+        .iter()
+        .any(|&(name, arity)| name == method && call.args.len() == arity)
+        && is_tracer(&call.receiver)
+}
+
+/// Checks whether an expression is a tracer: `tracer`, `self.tracer`,
+/// `global::tracer("svc")` or `provider.tracer("svc")`
+///
+/// Only the end of a method chain counts, so the `start` finishing
+/// `tracer.span_builder("name").with_kind(..).start(&tracer)` is not a second
+/// span.
+fn is_tracer(expr: &Expr) -> bool {
+    let name = match strip_parens(expr) {
+        Expr::Path(path) => path.path.segments.last().map(|s| s.ident.to_string()),
+        Expr::Field(field) => match &field.member {
+            syn::Member::Named(ident) => Some(ident.to_string()),
+            syn::Member::Unnamed(_) => None,
         },
-        kind: ExistingKind::TracingInstrument,
-        span_name,
-        quality,
-    })
+        Expr::Call(call) => match &*call.func {
+            Expr::Path(func) => func.path.segments.last().map(|s| s.ident.to_string()),
+            _ => None,
+        },
+        Expr::MethodCall(call) => Some(call.method.to_string()),
+        Expr::Reference(reference) => return is_tracer(&reference.expr),
+        _ => None,
+    };
+    name.is_some_and(|name| name.to_lowercase().ends_with("tracer"))
 }
 
-fn detect_manual_span(
-    line: &str,
-    path: &PathBuf,
-    line_num: usize,
-) -> Option<ExistingInstrumentation> {
-    // Look for tracing::span! or info_span!, debug_span!, etc.
-    let span_patterns = [
-        "tracing::span!",
-        "info_span!",
-        "debug_span!",
-        "trace_span!",
-        "warn_span!",
-        "error_span!",
-    ];
-
-    for pattern in span_patterns {
-        if line.contains(pattern) {
-            let span_name = extract_span_name(line);
-            return Some(ExistingInstrumentation {
-                location: Location {
-                    file: path.clone(),
-                    line: line_num + 1,
-                    column: 1,
-                    function_name: String::new(),
-                },
-                kind: ExistingKind::ManualSpan,
-                span_name,
-                quality: InstrumentationQuality::default(),
-            });
+/// Checks whether a method call puts a span into an OpenTelemetry context:
+/// `Context::current().with_span(span)` or, with `tracing-opentelemetry`,
+/// `span.set_parent(cx)`
+fn is_otel_context(call: &syn::ExprMethodCall) -> bool {
+    if call.args.len() != 1 {
+        return false;
+    }
+    match call.method.to_string().as_str() {
+        "with_span" => call
+            .receiver
+            .to_token_stream()
+            .to_string()
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .any(|token| token == "cx" || token.to_lowercase().contains("context")),
+        "set_parent" => mentions_span(&call.receiver),
+        _ => false,
+    }
+}
+
+/// Collects the keys of the `KeyValue::new(key, ..)` attributes in an expression
+///
+/// Keys given as constants (`semconv::HTTP_METHOD`) are kept as written.
+fn attribute_keys(expr: &Expr) -> Vec<String> {
+    struct KeyValues(Vec<String>);
+
+    impl<'ast> Visit<'ast> for KeyValues {
+        fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+            if let Expr::Path(func) = &*call.func {
+                let segments: Vec<_> = func.path.segments.iter().collect();
+                if let [.., ty, method] = segments.as_slice() {
+                    if ty.ident == "KeyValue" && method.ident == "new" {
+                        if let Some(key) = call.args.first() {
+                            self.0
+                                .push(string_literal(key).unwrap_or_else(|| expr_text(key)));
+                        }
+                    }
+                }
+            }
+            visit::visit_expr_call(self, call);
+        }
+
+        // `vec![KeyValue::new(..), ..]`
+        fn visit_macro(&mut self, mac: &'ast Macro) {
+            if let Ok(elements) =
+                mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated)
+            {
+                for element in &elements {
+                    self.visit_expr(element);
+                }
+            }
         }
     }
 
-    None
-}
-
-fn detect_log_macro(
-    line: &str,
-    path: &PathBuf,
-    line_num: usize,
-) -> Option<ExistingInstrumentation> {
-    // Look for log macros
-    let log_patterns = [
-        "tracing::info!",
-        "tracing::debug!",
-        "tracing::warn!",
-        "tracing::error!",
-        "tracing::trace!",
-        "log::info!",
-        "log::debug!",
-        "log::warn!",
-        "log::error!",
-        "log::trace!",
-        "info!(",
-        "debug!(",
-        "warn!(",
-        "error!(",
-        "trace!(",
-    ];
-
-    for pattern in log_patterns {
-        if line.contains(pattern) {
-            return Some(ExistingInstrumentation {
-                location: Location {
-                    file: path.clone(),
-                    line: line_num + 1,
-                    column: 1,
-                    function_name: String::new(),
-                },
-                kind: ExistingKind::LogMacro,
-                span_name: None,
-                quality: InstrumentationQuality::default(),
-            });
+    let mut keys = KeyValues(Vec::new());
+    keys.visit_expr(expr);
+    keys.0
+}
+
+/// Finds a guard bound with `let guard = span.enter();`
+///
+/// `let _ = span.enter();` drops the guard at once and is not a binding.
+fn span_guard_binding(stmt: &Stmt) -> Option<(String, &syn::ExprMethodCall)> {
+    let Stmt::Local(local) = stmt else {
+        return None;
+    };
+    let pat = match &local.pat {
+        Pat::Type(typed) => &*typed.pat,
+        pat => pat,
+    };
+    let Pat::Ident(ident) = pat else {
+        return None;
+    };
+    let Expr::MethodCall(call) = strip_parens(&local.init.as_ref()?.expr) else {
+        return None;
+    };
+    let method = call.method.to_string();
+    (matches!(method.as_str(), "enter" | "entered") && is_span_guard(call))
+        .then(|| (ident.ident.to_string(), call))
+}
+
+/// Checks whether statements await before a guard is dropped
+///
+/// Awaits in closures and async blocks don't count: they run elsewhere.
+fn held_across_await(guard: &str, stmts: &[Stmt]) -> bool {
+    struct AwaitFinder(bool);
+
+    impl<'ast> Visit<'ast> for AwaitFinder {
+        fn visit_expr_await(&mut self, _: &'ast syn::ExprAwait) {
+            self.0 = true;
+        }
+
+        fn visit_expr_closure(&mut self, _: &'ast syn::ExprClosure) {}
+
+        fn visit_expr_async(&mut self, _: &'ast syn::ExprAsync) {}
+
+        fn visit_item(&mut self, _: &'ast syn::Item) {}
+    }
+
+    for stmt in stmts {
+        if is_drop_of(stmt, guard) {
+            return false;
+        }
+        let mut finder = AwaitFinder(false);
+        finder.visit_stmt(stmt);
+        if finder.0 {
+            return true;
         }
     }
+    false
+}
 
-    None
+/// Checks whether a statement is `drop(guard);`
+fn is_drop_of(stmt: &Stmt, guard: &str) -> bool {
+    let Stmt::Expr(Expr::Call(call), _) = stmt else {
+        return false;
+    };
+    let is_drop = matches!(&*call.func, Expr::Path(func) if func.path.is_ident("drop"));
+    let is_guard = matches!(
+        call.args.first(),
+        Some(Expr::Path(arg)) if arg.path.is_ident(guard)
+    );
+    is_drop && is_guard
 }
 
-fn detect_metrics(line: &str, path: &PathBuf, line_num: usize) -> Option<ExistingInstrumentation> {
-    // Look for common metrics patterns
-    let metrics_patterns = [
-        "counter!",
-        "gauge!",
-        "histogram!",
-        "metrics::counter",
-        "metrics::gauge",
-        "metrics::histogram",
-        "prometheus::",
-    ];
+/// Checks whether a macro path is one of `names`, bare or under one of `crates`
+fn is_macro_of(segments: &[String], crates: &[&str], names: &[&str]) -> bool {
+    match segments {
+        [name] => names.contains(&name.as_str()),
+        [krate, name] => crates.contains(&krate.as_str()) && names.contains(&name.as_str()),
+        _ => false,
+    }
+}
 
-    for pattern in metrics_patterns {
-        if line.contains(pattern) {
-            return Some(ExistingInstrumentation {
-                location: Location {
-                    file: path.clone(),
-                    line: line_num + 1,
-                    column: 1,
-                    function_name: String::new(),
-                },
-                kind: ExistingKind::Metrics,
-                span_name: None,
-                quality: InstrumentationQuality::default(),
-            });
+/// Checks whether an attribute is `#[instrument]` or `#[tracing::instrument]`
+fn is_instrument_attribute(attr: &Attribute) -> bool {
+    let segments: Vec<String> = attr
+        .path()
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    is_macro_of(&segments, &["tracing"], &["instrument"])
+}
+
+/// Parses the arguments of an `#[instrument]` attribute
+///
+/// Arguments that don't parse are left at their defaults.
+pub(crate) fn parse_instrument_args(attr: &Attribute) -> InstrumentArgs {
+    let mut args = InstrumentArgs::default();
+    let Meta::List(_) = &attr.meta else {
+        return args;
+    };
+    let Ok(metas) = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated) else {
+        return args;
+    };
+
+    for meta in metas {
+        let Some(key) = meta.path().get_ident().map(|ident| ident.to_string()) else {
+            continue;
+        };
+        match (key.as_str(), &meta) {
+            ("skip_all", Meta::Path(_)) => args.skip_all = true,
+            ("err", _) => args.err = true,
+            ("ret", _) => args.ret = true,
+            ("skip", Meta::List(list)) => {
+                args.skip = split_arguments(list.tokens.clone())
+                    .iter()
+                    .map(|tokens| tokens.to_string())
+                    .collect();
+            }
+            ("fields", Meta::List(list)) => {
+                args.fields = split_arguments(list.tokens.clone())
+                    .into_iter()
+                    .filter_map(parse_span_field)
+                    .collect();
+            }
+            (_, Meta::NameValue(name_value)) => {
+                let value = &name_value.value;
+                match key.as_str() {
+                    "name" => args.name = string_literal(value),
+                    "target" => args.target = string_literal(value),
+                    "level" => args.level = level_name(value),
+                    "parent" => args.parent = Some(expr_text(value)),
+                    "follows_from" => args.follows_from = Some(expr_text(value)),
+                    _ => {}
+                }
+            }
+            _ => {}
         }
     }
 
-    None
+    args
 }
 
-fn extract_instrument_name(line: &str) -> Option<String> {
-    // Look for name = "..." in #[instrument(name = "...")]
-    if let Some(pos) = line.find("name") {
-        let after_name = &line[pos..];
-        if let Some(start) = after_name.find('"') {
-            let rest = &after_name[start + 1..];
-            if let Some(end) = rest.find('"') {
-                return Some(rest[..end].to_string());
+/// Splits a token stream at its top-level commas
+pub(crate) fn split_arguments(tokens: TokenStream) -> Vec<TokenStream> {
+    let mut arguments = Vec::new();
+    let mut current = Vec::new();
+
+    for token in tokens {
+        match &token {
+            TokenTree::Punct(punct) if punct.as_char() == ',' => {
+                arguments.push(current.drain(..).collect());
             }
+            _ => current.push(token),
         }
     }
-    None
+    if !current.is_empty() {
+        arguments.push(current.into_iter().collect());
+    }
+
+    arguments
 }
 
-fn extract_span_name(line: &str) -> Option<String> {
-    // Extract the first string argument from span! macro
-    if let Some(start) = line.find('"') {
-        let rest = &line[start + 1..];
-        if let Some(end) = rest.find('"') {
-            return Some(rest[..end].to_string());
+/// Parses one entry of `fields(...)`
+///
+/// Entries are `name = value`, `%name`, `?name` or `name`, where the name may
+/// be dotted (`http.method`) or a string literal.
+fn parse_span_field(tokens: TokenStream) -> Option<SpanField> {
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    let equals = tokens
+        .iter()
+        .position(|token| matches!(token, TokenTree::Punct(punct) if punct.as_char() == '='));
+    let (name_tokens, value) = match equals {
+        Some(index) => {
+            let value: TokenStream = tokens[index + 1..].iter().cloned().collect();
+            (&tokens[..index], Some(value.to_string()))
+        }
+        // `%id` and `?id` record the parameter of that name, a bare `id`
+        // declares an empty field
+        None => {
+            let sigil = tokens.first().is_some_and(
+                |token| matches!(token, TokenTree::Punct(p) if matches!(p.as_char(), '%' | '?')),
+            );
+            let value: TokenStream = tokens.iter().cloned().collect();
+            (&tokens[..], sigil.then(|| value.to_string()))
         }
+    };
+
+    let name: String = name_tokens
+        .iter()
+        .filter(|token| !matches!(token, TokenTree::Punct(p) if matches!(p.as_char(), '%' | '?')))
+        .map(|token| match token {
+            TokenTree::Literal(literal) => literal.to_string().trim_matches('"').to_string(),
+            other => other.to_string(),
+        })
+        .collect();
+
+    (!name.is_empty()).then_some(SpanField { name, value })
+}
+
+/// Returns the value of a string literal expression
+fn string_literal(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Str(s) => Some(s.value()),
+            _ => None,
+        },
+        _ => None,
     }
-    None
 }
 
-fn assess_instrument_quality(line: &str) -> InstrumentationQuality {
+/// Returns the lowercased level of `"debug"`, `Level::DEBUG` or `tracing::Level::DEBUG`
+fn level_name(expr: &Expr) -> Option<String> {
+    let level = match expr {
+        Expr::Path(path) => path.path.segments.last()?.ident.to_string(),
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Str(s) => s.value(),
+            Lit::Int(i) => i.to_string(),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(level.to_lowercase())
+}
+
+/// Renders an expression without the spaces `quote` puts between tokens
+fn expr_text(expr: &Expr) -> String {
+    expr.to_token_stream()
+        .to_string()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect()
+}
+
+/// Extracts a span's name: the first argument that is a lone string literal
+///
+/// Skips the level of `span!` and `target:`/`parent:` prefixes.
+fn extract_span_name(tokens: &TokenStream) -> Option<String> {
+    split_arguments(tokens.clone())
+        .into_iter()
+        .find_map(|argument| {
+            let mut argument = argument.into_iter();
+            match (argument.next(), argument.next()) {
+                (Some(TokenTree::Literal(literal)), None) => match Lit::new(literal) {
+                    Lit::Str(s) => Some(s.value()),
+                    _ => None,
+                },
+                (Some(TokenTree::Group(group)), None) if group.delimiter() == Delimiter::None => {
+                    extract_span_name(&group.stream())
+                }
+                _ => None,
+            }
+        })
+}
+
+/// Assesses an OpenTelemetry span by what its function records on it
+fn assess_otel_quality(
+    span_name: Option<&str>,
+    record: &OtelSpanInfo,
+    returns_result: bool,
+) -> InstrumentationQuality {
     let mut issues = Vec::new();
     let mut score: f64 = 1.0;
 
-    // Check for skip directive on large fields
-    if line.contains("body") || line.contains("request") || line.contains("response") {
-        if !line.contains("skip") {
+    if let Some(name) = span_name {
+        if name.is_empty() || GENERIC_SPAN_NAMES.contains(&name.to_lowercase().as_str()) {
             issues.push(QualityIssue {
-                kind: QualityIssueKind::MissingSkip,
-                message: "Large fields should use skip or skip_all".to_string(),
+                kind: QualityIssueKind::PoorNaming,
+                message: format!("Span name '{name}' doesn't describe the operation"),
             });
             score -= 0.2;
         }
     }
 
-    // Check for sensitive fields without redaction
-    let sensitive_fields = ["password", "token", "secret", "key", "credential"];
-    for field in sensitive_fields {
-        if line.contains(field) && !line.contains("skip") {
-            issues.push(QualityIssue {
-                kind: QualityIssueKind::SensitiveData,
-                message: format!("Sensitive field '{field}' should be skipped or redacted"),
-            });
-            score -= 0.3;
-        }
+    if record.attributes.is_empty() {
+        issues.push(QualityIssue {
+            kind: QualityIssueKind::MissingFields,
+            message: "Span has no attributes; add some with set_attribute(KeyValue::new(..))"
+                .to_string(),
+        });
+        score -= 0.1;
     }
 
-    // Check for err handling
-    if line.contains("err") && !line.contains("err = true") && !line.contains("err(") {
+    if returns_result && !record.sets_status && !record.records_errors {
         issues.push(QualityIssue {
             kind: QualityIssueKind::NoErrorHandling,
-            message: "Consider adding err = true for error tracking".to_string(),
+            message: "Errors are returned but never recorded on the span; use set_status or \
+                      record_error"
+                .to_string(),
         });
-        score -= 0.1;
+        score -= 0.2;
     }
 
     InstrumentationQuality {
@@ -252,3 +1187,374 @@ fn assess_instrument_quality(line: &str) -> InstrumentationQuality {
         issues,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::call_graph::GraphBuilder;
+
+    fn detect(code: &str) -> Vec<ExistingInstrumentation> {
+        let file = SourceFile {
+            path: PathBuf::from("src/lib.rs"),
+            syntax_tree: syn::parse_file(code).unwrap(),
+            source: code.to_string(),
+            content_hash: String::new(),
+        };
+        let mut builder = GraphBuilder::new();
+        builder.add_parsed_file(&file).unwrap();
+        let graph = builder.build().unwrap();
+        detect_existing_instrumentation(std::slice::from_ref(&file), &graph)
+    }
+
+    #[test]
+    fn test_parses_multiline_instrument_arguments() {
+        let found = detect(
+            r#"
+use tracing::instrument;
+
+pub struct Orders;
+
+impl Orders {
+    #[tracing::instrument(
+        name = "orders.create",
+        skip(self, db),
+        fields(order.id = %id, http.method = "POST", ?customer),
+        err(Display),
+        ret,
+        level = Level::DEBUG,
+        target = "orders",
+        parent = None,
+        follows_from = [cause],
+    )]
+    pub async fn create(&self, db: &Pool, id: u64, customer: &str) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[instrument(skip_all, level = "info")]
+fn login(password: &str) {}
+"#,
+        );
+
+        assert_eq!(found.len(), 2);
+        let create = &found[0];
+        assert_eq!(create.kind, ExistingKind::TracingInstrument);
+        assert_eq!(create.location.line, 7);
+        assert_eq!(create.location.function_name, "Orders::create");
+        assert_eq!(create.span_name.as_deref(), Some("orders.create"));
+
+        let args = create.args.as_ref().unwrap();
+        assert_eq!(args.skip, vec!["self", "db"]);
+        assert!(!args.skip_all);
+        let fields: Vec<_> = args.fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(fields, vec!["order.id", "http.method", "customer"]);
+        assert_eq!(args.fields[0].value.as_deref(), Some("% id"));
+        assert_eq!(args.fields[2].value.as_deref(), Some("? customer"));
+        assert!(args.err && args.ret);
+        assert_eq!(args.level.as_deref(), Some("debug"));
+        assert_eq!(args.target.as_deref(), Some("orders"));
+        assert_eq!(args.parent.as_deref(), Some("None"));
+        assert_eq!(args.follows_from.as_deref(), Some("[cause]"));
+        assert!(create.quality.issues.is_empty());
+
+        let login = &found[1];
+        let args = login.args.as_ref().unwrap();
+        assert!(args.skip_all);
+        assert_eq!(args.level.as_deref(), Some("info"));
+        // Skipped parameters aren't recorded
+        assert!(login.quality.issues.is_empty());
+    }
+
+    #[test]
+    fn test_attributes_spans_events_and_metrics_to_functions() {
+        let found = detect(
+            r#"
+// info!("in a comment") and #[instrument] aren't instrumentation
+fn helper() {
+    let text = "info_span!(\"in a string\")";
+}
+
+fn process(order_id: u64) {
+    let span = tracing::info_span!(target: "jobs", "process_order", order_id);
+    let _enter = span.enter();
+    tokio::spawn(async move {
+        info!(order_id, "processing");
+    });
+    metrics::counter!("orders_processed").increment(1);
+    let requests = prometheus::IntCounter::new("requests", "help");
+}
+"#,
+        );
+
+        let kinds: Vec<_> = found
+            .iter()
+            .map(|e| (e.kind, e.location.function_name.as_str()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (ExistingKind::ManualSpan, "process"),
+                (ExistingKind::SpanGuard, "process"),
+                (ExistingKind::LogMacro, "process"),
+                (ExistingKind::Metrics, "process"),
+                (ExistingKind::Metrics, "process"),
+            ]
+        );
+        assert_eq!(found[0].span_name.as_deref(), Some("process_order"));
+        assert_eq!(found[0].location.line, 8);
+        assert!(found[0].instruments("process"));
+        assert!(!found[2].instruments("process"));
+        assert!(!found[0].instruments("helper"));
+    }
+
+    #[test]
+    fn test_quality_checks_recorded_parameters() {
+        let found = detect(
+            r#"
+#[instrument(
+    fields(user = %user_id)
+)]
+async fn upload(user_id: u64, body: Vec<u8>, api_token: String) -> Result<(), Error> {
+    Ok(())
+}
+
+#[instrument(skip(body, api_token), err)]
+async fn upload_skipped(user_id: u64, body: Vec<u8>, api_token: String) -> Result<(), Error> {
+    Ok(())
+}
+"#,
+        );
+
+        let kinds: Vec<_> = found[0].quality.issues.iter().map(|i| i.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                QualityIssueKind::MissingSkip,
+                QualityIssueKind::SensitiveData,
+                QualityIssueKind::NoErrorHandling,
+            ]
+        );
+        assert!((found[0].quality.score - 0.4).abs() < 1e-9);
+        assert!(found[1].quality.issues.is_empty());
+    }
+
+    #[test]
+    fn test_instrumented_futures_and_span_guards() {
+        let found = detect(
+            r#"
+async fn handler(id: u64) {
+    fetch(id)
+        .instrument(tracing::info_span!("fetch", id))
+        .await;
+    tokio::spawn(notify(id).in_current_span());
+}
+
+async fn worker() {
+    async move {
+        process().await;
+    }
+    .instrument(Span::current())
+    .await;
+}
+
+async fn fetch(id: u64) {}
+async fn notify(id: u64) {}
+async fn process() {}
+
+async fn guarded() {
+    let span = Span::current();
+    let _enter = span.enter();
+    process().await;
+}
+
+async fn dropped() {
+    let _guard = info_span!("sync_part").entered();
+    compute();
+    drop(_guard);
+    process().await;
+}
+
+fn blocking(rt: &Runtime) {
+    let _rt = rt.enter();
+    tracing::Span::current().in_scope(|| compute());
+}
+"#,
+        );
+
+        let future = |line: usize| {
+            found
+                .iter()
+                .find(|e| e.kind == ExistingKind::InstrumentedFuture && e.location.line == line)
+                .unwrap()
+        };
+        let fetch = future(4);
+        assert_eq!(fetch.span_name.as_deref(), Some("fetch"));
+        assert_eq!(fetch.location.function_name, "handler");
+        assert!(fetch.instruments("fetch"));
+        assert!(!fetch.instruments("handler"));
+        assert!(future(6).instruments("notify"));
+
+        // An awaited async block is the function's own work
+        let block = future(13);
+        assert!(block.instruments("worker"));
+        assert!(block.instruments("worker::{async#1}"));
+
+        let guards: Vec<_> = found
+            .iter()
+            .filter(|e| e.kind == ExistingKind::SpanGuard)
+            .map(|e| {
+                (
+                    e.location.function_name.as_str(),
+                    e.quality.issues.iter().map(|i| i.kind).collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            guards,
+            vec![
+                ("guarded", vec![QualityIssueKind::GuardAcrossAwait]),
+                ("dropped", vec![]),
+                ("blocking", vec![]),
+            ]
+        );
+        assert!(
+            found
+                .iter()
+                .any(|e| e.kind == ExistingKind::SpanGuard && e.instruments("guarded"))
+        );
+    }
+
+    #[test]
+    fn test_log_events_in_error_branches_and_loops() {
+        let found = detect(
+            r#"
+fn load(path: &str) -> Result<Config, Error> {
+    let text = read(path).map_err(|e| {
+        tracing::error!(error = %e, "read failed");
+        e
+    })?;
+    match parse(&text) {
+        Ok(config) => Ok(config),
+        Err(_) => {
+            info!("parse failed");
+            Err(Error::Parse)
+        }
+    }
+}
+
+fn start() -> Result<(), Error> {
+    let config = load("app.toml").inspect_err(|err| error!(%err, "load failed"))?;
+    for attempt in 0..3 {
+        if let Err(e) = connect(&config) {
+            error!(error = %e, attempt, "connect failed");
+        }
+        if attempt % 10 == 0 {
+            log::error!("still failing");
+        }
+    }
+    let Ok(socket) = bind() else {
+        warn!("bind failed");
+        return Ok(());
+    };
+    Ok(())
+}
+"#,
+        );
+
+        let issues = |line: usize| -> Vec<QualityIssueKind> {
+            let event = found
+                .iter()
+                .find(|e| e.kind == ExistingKind::LogMacro && e.location.line == line)
+                .unwrap();
+            event
+                .quality
+                .issues
+                .iter()
+                .map(|issue| issue.kind)
+                .collect()
+        };
+        use QualityIssueKind::*;
+
+        assert!(issues(4).is_empty());
+        assert_eq!(issues(10), vec![MissingErrorValue, LevelMismatch]);
+        // The error `load` logged is logged again as it comes out of `load`
+        assert_eq!(issues(17), vec![DuplicateErrorLog]);
+        let duplicate = found.iter().find(|e| e.location.line == 17).unwrap();
+        assert!(duplicate.quality.issues[0].message.contains("'load'"));
+        assert_eq!(issues(20), vec![LogInLoop]);
+        assert!(issues(23).is_empty());
+        assert_eq!(issues(27), vec![MissingErrorValue]);
+    }
+
+    #[test]
+    fn test_opentelemetry_spans() {
+        let found = detect(
+            r#"
+use opentelemetry::{global, trace::{Span, Status, Tracer}, Context, KeyValue};
+
+async fn charge(order_id: u64) -> Result<(), Error> {
+    let tracer = global::tracer("payments");
+    let mut span = tracer
+        .span_builder("payments.charge")
+        .with_attributes(vec![KeyValue::new("order.id", order_id as i64)])
+        .start(&tracer);
+    span.set_attribute(KeyValue::new(semconv::PEER_SERVICE, "stripe"));
+    if let Err(e) = send().await {
+        span.record_error(&e);
+        span.set_status(Status::error(e.to_string()));
+    }
+    Ok(())
+}
+
+fn refund(&self) -> Result<(), Error> {
+    self.tracer.in_span("operation", |cx| {
+        let cx = Context::current_with_span(cx.span());
+    });
+    Ok(())
+}
+
+fn link(cx: Context) {
+    tracing::Span::current().set_parent(cx);
+    let _rt = runtime.start(1);
+}
+"#,
+        );
+
+        let spans: Vec<_> = found
+            .iter()
+            .filter(|e| e.kind == ExistingKind::OpenTelemetrySpan)
+            .map(|e| (e.location.function_name.as_str(), e.span_name.as_deref()))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                ("charge", Some("payments.charge")),
+                ("refund", Some("operation")),
+                ("refund", None),
+                ("link", None),
+            ]
+        );
+
+        let charge = &found[0];
+        let otel = charge.otel.as_ref().unwrap();
+        assert_eq!(otel.attributes, vec!["order.id", "semconv::PEER_SERVICE"]);
+        assert!(otel.sets_status && otel.records_errors);
+        assert!(charge.quality.issues.is_empty());
+        assert!(charge.instruments("charge"));
+
+        let refund = &found[1];
+        let kinds: Vec<_> = refund.quality.issues.iter().map(|i| i.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                QualityIssueKind::PoorNaming,
+                QualityIssueKind::MissingFields,
+                QualityIssueKind::NoErrorHandling,
+            ]
+        );
+
+        // Context propagation isn't scored as a span of its own
+        assert!(found[2].otel.is_none());
+        assert!(found[2].quality.issues.is_empty());
+    }
+}
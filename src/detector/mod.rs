@@ -6,11 +6,18 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+pub use crate::framework::web::StreamingKind;
+
+pub mod critical_path;
 pub mod endpoint;
 pub mod existing;
 pub mod gaps;
 pub mod priority;
+pub mod quality;
 pub mod rules;
+pub mod spawn;
+pub mod test_code;
+pub mod trace;
 
 /// Location in source code
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +45,26 @@ pub struct Endpoint {
     pub location: Location,
     /// Framework that defines this endpoint
     pub framework: String,
+    /// Parameters of the handler, including framework extractors
+    #[serde(default)]
+    pub parameters: Vec<EndpointParam>,
+    /// Documentation of the handler
+    #[serde(default)]
+    pub documentation: Option<String>,
+    /// Streaming mode, for gRPC methods
+    #[serde(default)]
+    pub streaming: Option<StreamingKind>,
+}
+
+/// Parameter of an endpoint handler
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointParam {
+    /// Parameter name (bindings of destructuring patterns, e.g. `id`)
+    pub name: String,
+    /// Parameter type
+    pub ty: String,
+    /// Whether the framework extracts this parameter from the request
+    pub is_extractor: bool,
 }
 
 /// Suggested instrumentation point
@@ -55,6 +82,18 @@ pub struct InstrumentationPoint {
     pub suggested_span_name: String,
     /// Suggested fields to capture
     pub suggested_fields: Vec<Field>,
+    /// Endpoints reaching this point and the calls leading to it
+    #[serde(default)]
+    pub call_paths: Vec<CallPath>,
+}
+
+/// Chain of calls from an endpoint's handler to an instrumentation point
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallPath {
+    /// The endpoint, as `METHOD path` (e.g. `GET /api/users`)
+    pub endpoint: String,
+    /// Function IDs from the handler down to the point's function
+    pub functions: Vec<String>,
 }
 
 /// Field to capture in instrumentation
@@ -113,6 +152,59 @@ pub struct ExistingInstrumentation {
     pub span_name: Option<String>,
     /// Quality assessment
     pub quality: InstrumentationQuality,
+    /// Arguments of a `#[instrument]` attribute
+    pub args: Option<InstrumentArgs>,
+    /// IDs of the functions whose futures run in the span, for
+    /// `InstrumentedFuture`
+    pub instrumented_futures: Vec<String>,
+    /// What the function records on a span it starts, for `OpenTelemetrySpan`
+    pub otel: Option<OtelSpanInfo>,
+}
+
+/// Arguments of a `#[instrument]` attribute
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct InstrumentArgs {
+    /// Span name set with `name = "..."`
+    pub name: Option<String>,
+    /// Parameters left out with `skip(...)`
+    pub skip: Vec<String>,
+    /// Whether `skip_all` leaves out every parameter
+    pub skip_all: bool,
+    /// Fields declared with `fields(...)`
+    pub fields: Vec<SpanField>,
+    /// Whether `err` records returned errors
+    pub err: bool,
+    /// Whether `ret` records the return value
+    pub ret: bool,
+    /// Span level (`"debug"`, `Level::DEBUG`), lowercased
+    pub level: Option<String>,
+    /// Target set with `target = "..."`
+    pub target: Option<String>,
+    /// Expression given as `parent = ...`
+    pub parent: Option<String>,
+    /// Expression given as `follows_from = ...`
+    pub follows_from: Option<String>,
+}
+
+/// A field declared in `fields(...)` of a span
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SpanField {
+    /// Field name (`user.id` for `user.id = %id`)
+    pub name: String,
+    /// Recorded expression with its `%`/`?` sigil, `None` for an empty
+    /// field to be recorded later
+    pub value: Option<String>,
+}
+
+/// What a function records on an OpenTelemetry span it starts
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OtelSpanInfo {
+    /// Keys of the attributes set (`KeyValue::new("http.method", ..)`)
+    pub attributes: Vec<String>,
+    /// Whether the span's status is set with `set_status`
+    pub sets_status: bool,
+    /// Whether errors are recorded with `record_error`
+    pub records_errors: bool,
 }
 
 /// Kind of existing instrumentation
@@ -122,6 +214,13 @@ pub enum ExistingKind {
     TracingInstrument,
     /// Manual tracing span
     ManualSpan,
+    /// Future wrapped with `.instrument(span)` or `.in_current_span()`
+    InstrumentedFuture,
+    /// Span entered with `span.enter()`, `span.entered()` or `span.in_scope(..)`
+    SpanGuard,
+    /// OpenTelemetry API span (`tracer.start(..)`, `tracer.in_span(..)`) or
+    /// span context (`Context::current_with_span`, `span.set_parent(cx)`)
+    OpenTelemetrySpan,
     /// `log!` macro (`info!`, `warn!`, `error!`, etc.)
     LogMacro,
     /// Metrics recording
@@ -159,6 +258,22 @@ pub enum QualityIssueKind {
     SensitiveData,
     /// Missing skip directive for large data
     MissingSkip,
+    /// Recorded parameter whose type may not implement `Debug`
+    NonDebugArgument,
+    /// Declared field referencing a name that is not a parameter
+    UnknownField,
+    /// `Span::enter` guard held across an `.await`
+    GuardAcrossAwait,
+    /// Log message interpolating values instead of recording fields
+    UnstructuredLog,
+    /// Error logged without the error value
+    MissingErrorValue,
+    /// `error!` in a loop without rate limiting
+    LogInLoop,
+    /// Log level too low for the error being handled
+    LevelMismatch,
+    /// Error logged again by a caller it was propagated to
+    DuplicateErrorLog,
 }
 
 /// Gap in instrumentation coverage
@@ -226,6 +341,30 @@ impl Priority {
     }
 }
 
+impl ExistingInstrumentation {
+    /// Checks whether this instrumentation gives a function a span
+    ///
+    /// `#[instrument]` on the function and spans created or entered in its
+    /// body count, as does an instrumented future of the function; log
+    /// events and metrics don't start a span.
+    ///
+    /// # Arguments
+    ///
+    /// * `function_id` - ID of the function's call graph node
+    pub fn instruments(&self, function_id: &str) -> bool {
+        match self.kind {
+            ExistingKind::TracingInstrument
+            | ExistingKind::ManualSpan
+            | ExistingKind::SpanGuard
+            | ExistingKind::OpenTelemetrySpan => self.location.function_name == function_id,
+            ExistingKind::InstrumentedFuture => {
+                self.instrumented_futures.iter().any(|id| id == function_id)
+            }
+            ExistingKind::LogMacro | ExistingKind::Metrics => false,
+        }
+    }
+}
+
 impl Default for InstrumentationQuality {
     fn default() -> Self {
         Self {
@@ -3,7 +3,7 @@
 //! Determines which code locations should be instrumented first.
 
 use super::{Field, InstrumentationKind, InstrumentationPoint, Location, Priority};
-use crate::call_graph::CallGraph;
+use crate::call_graph::{CallContext, CallGraph, NodeKind};
 use crate::patterns::MatchResult;
 
 /// Calculate priority and create instrumentation points
@@ -42,11 +42,19 @@ pub fn prioritize_points(
                 sanitize_path(&endpoint.path)
             ),
             suggested_fields,
+            call_paths: Vec::new(),
         });
     }
 
-    // Add pattern matches
+    // Add pattern matches, unless the function is already an endpoint
     for pattern in patterns {
+        if points
+            .iter()
+            .any(|p| p.location.function_name == pattern.function_name)
+        {
+            continue;
+        }
+
         let kind = pattern_to_kind(&pattern.category);
         let priority = calculate_pattern_priority(
             &pattern.category,
@@ -58,6 +66,26 @@ pub fn prioritize_points(
         if priority.score() as f64 / 4.0 >= threshold {
             let suggested_fields = suggest_fields_for_kind(&kind, &pattern.function_name);
 
+            // Call-site matches name the call that classified the function
+            let call_site = pattern
+                .matches
+                .iter()
+                .find_map(|m| m.location.map(|(line, _)| (&m.matched_text, line)));
+            let reason = match call_site {
+                Some((call, line)) => format!(
+                    "Calls {} at line {} ({} pattern with {:.0}% confidence)",
+                    call,
+                    line,
+                    pattern.category.name(),
+                    pattern.confidence * 100.0
+                ),
+                None => format!(
+                    "Matched {} pattern with {:.0}% confidence",
+                    pattern.category.name(),
+                    pattern.confidence * 100.0
+                ),
+            };
+
             points.push(InstrumentationPoint {
                 location: Location {
                     file: pattern.file.clone(),
@@ -67,13 +95,50 @@ pub fn prioritize_points(
                 },
                 kind,
                 priority,
-                reason: format!(
-                    "Matched {} pattern with {:.0}% confidence",
-                    pattern.category.name(),
-                    pattern.confidence * 100.0
+                reason,
+                suggested_span_name: generate_span_name(
+                    &kind,
+                    local_name(graph, &pattern.function_name),
                 ),
-                suggested_span_name: generate_span_name(&kind, &pattern.function_name),
                 suggested_fields,
+                call_paths: Vec::new(),
+            });
+        }
+    }
+
+    // Add spawned functions, which run detached from the span that started
+    // them. Spawned async blocks and closures can't carry `#[instrument]`;
+    // losing the span there is reported as a context break instead
+    for edge in graph.edges() {
+        if edge.context != CallContext::Spawn {
+            continue;
+        }
+        let Some(task) = graph.get_node(&edge.to) else {
+            continue;
+        };
+        if matches!(task.kind, NodeKind::External | NodeKind::Closure)
+            || points.iter().any(|p| p.location.function_name == task.id)
+        {
+            continue;
+        }
+
+        let priority = Priority::High;
+        if priority.score() as f64 / 4.0 >= threshold {
+            let kind = InstrumentationKind::BackgroundJob;
+
+            points.push(InstrumentationPoint {
+                location: Location {
+                    file: task.file().unwrap_or_default(),
+                    line: task.line().unwrap_or(0),
+                    column: 1,
+                    function_name: task.id.clone(),
+                },
+                kind,
+                priority,
+                reason: format!("Task spawned by '{}'", edge.from),
+                suggested_span_name: generate_span_name(&kind, &task.name),
+                suggested_fields: Vec::new(),
+                call_paths: Vec::new(),
             });
         }
     }
@@ -90,6 +155,11 @@ pub fn prioritize_points(
                 continue;
             }
 
+            // Library functions and nested bodies aren't instrumented on their own
+            if matches!(node.kind, NodeKind::External | NodeKind::Closure) {
+                continue;
+            }
+
             // Add functions with high connectivity
             if caller_count > 5 || callee_count > 10 {
                 let priority = if caller_count > 10 {
@@ -112,8 +182,9 @@ pub fn prioritize_points(
                             "High connectivity: {} callers, {} callees",
                             caller_count, callee_count
                         ),
-                        suggested_span_name: node_name.clone(),
+                        suggested_span_name: node.local_name().to_string(),
                         suggested_fields: Vec::new(),
+                        call_paths: Vec::new(),
                     });
                 }
             }
@@ -220,6 +291,15 @@ fn suggest_fields_for_kind(kind: &InstrumentationKind, function_name: &str) -> V
     }
 }
 
+/// Name of a function within its module (`Type::method`), so span names
+/// don't carry the module path
+fn local_name<'a>(graph: &'a CallGraph, function_id: &'a str) -> &'a str {
+    match graph.get_node(function_id) {
+        Some(node) => node.local_name(),
+        None => function_id.rsplit("::").next().unwrap_or(function_id),
+    }
+}
+
 fn generate_span_name(kind: &InstrumentationKind, function_name: &str) -> String {
     let prefix = match kind {
         InstrumentationKind::DatabaseCall => "db",
@@ -0,0 +1,769 @@
+//! Quality assessment of `#[instrument]` attributes and log events
+//!
+//! An instrumented function records every parameter it doesn't skip, so an
+//! attribute is judged against the function's signature: which parameters
+//! end up on the span, what their types are, and which names the declared
+//! `fields(...)` refer to.
+//!
+//! A log event is judged by its arguments and by where it is emitted: in an
+//! `Err` branch, an error-handling closure or a loop.
+
+use super::existing::split_arguments;
+use super::{InstrumentArgs, InstrumentationQuality, QualityIssue, QualityIssueKind, SpanField};
+use proc_macro2::{TokenStream, TokenTree};
+use std::collections::HashSet;
+use syn::visit::{self, Visit};
+use syn::{
+    Expr, FnArg, GenericArgument, GenericParam, Pat, PathArguments, ReturnType, Signature, Type,
+    TypeParamBound, WherePredicate,
+};
+
+/// Types too large or noisy to record on every span
+const LARGE_TYPES: &[&str] = &[
+    "Bytes",
+    "BytesMut",
+    "Body",
+    "Request",
+    "Response",
+    "HttpRequest",
+    "HttpResponse",
+    "Multipart",
+    "Payload",
+    "Json",
+    "Form",
+];
+
+/// Suffixes of handle types whose `Debug` output says nothing useful
+const LARGE_TYPE_SUFFIXES: &[&str] = &["Pool", "Client", "Connection"];
+
+/// Wrapper types looked through to the type they hold
+const WRAPPER_TYPES: &[&str] = &["Arc", "Rc", "Box", "Option"];
+
+/// Parameter names that usually hold large payloads
+const LARGE_NAMES: &[&str] = &["body", "request", "response", "payload"];
+
+/// Last words of names that usually hold secrets
+const SENSITIVE_WORDS: &[&str] = &[
+    "password",
+    "passwd",
+    "pwd",
+    "secret",
+    "token",
+    "credential",
+    "credentials",
+    "ssn",
+];
+
+/// Words that make a `key` a secret (`api_key`, `private_key`)
+const KEY_QUALIFIERS: &[&str] = &[
+    "api",
+    "private",
+    "secret",
+    "access",
+    "signing",
+    "encryption",
+];
+
+/// Types that hold secrets
+const SENSITIVE_TYPES: &[&str] = &[
+    "Secret",
+    "SecretString",
+    "SecretBox",
+    "Password",
+    "Token",
+    "Credentials",
+];
+
+/// Log levels too low for an error being handled
+const LOW_LEVELS: &[&str] = &["trace", "debug", "info"];
+
+/// Arguments of a log macro that configure the event rather than record
+/// data (`target: "db"`)
+const EVENT_SETTINGS: &[&str] = &["target", "parent", "name"];
+
+/// Where a log event is emitted
+#[derive(Debug, Clone, Default)]
+pub struct LogContext {
+    /// Names bound to the error being handled, when the event is in an `Err`
+    /// branch or an error-handling closure such as `map_err(|e| ..)`
+    pub error_bindings: Option<Vec<String>>,
+    /// Whether the event is inside a loop
+    pub in_loop: bool,
+    /// Whether a condition inside the loop limits how often the event fires
+    pub rate_limited: bool,
+}
+
+/// Assesses an `#[instrument]` attribute against the function it's on
+///
+/// Each recorded parameter and declared field is checked on its own:
+///
+/// - `NonDebugArgument`: a recorded parameter is `impl Trait`, `dyn Trait` or
+///   a generic parameter without a `Debug` bound
+/// - `MissingSkip`: a recorded parameter is a payload, byte buffer, pool or
+///   client
+/// - `SensitiveData`: a recorded parameter or field looks like a secret
+/// - `UnknownField`: a field's value refers to a name that isn't a parameter
+/// - `NoErrorHandling`: the function returns a `Result` without `err`
+///
+/// # Arguments
+///
+/// * `args` - The parsed attribute
+/// * `sig` - Signature of the instrumented function
+///
+/// # Returns
+///
+/// A score lowered for every issue, and the issues
+pub fn assess_instrument_quality(args: &InstrumentArgs, sig: &Signature) -> InstrumentationQuality {
+    let mut issues = Vec::new();
+    let mut score: f64 = 1.0;
+    let mut report = |kind: QualityIssueKind, penalty: f64, message: String| {
+        issues.push(QualityIssue { kind, message });
+        score -= penalty;
+    };
+
+    let generics = generics_without_debug(sig);
+    let recorded = sig.inputs.iter().filter_map(|input| match input {
+        FnArg::Typed(typed) => match &*typed.pat {
+            Pat::Ident(ident) => Some((ident.ident.to_string(), &*typed.ty)),
+            _ => None,
+        },
+        FnArg::Receiver(_) => None,
+    });
+    let recorded = recorded.filter(|(name, _)| !args.skip_all && !args.skip.contains(name));
+
+    for (name, ty) in recorded {
+        if may_not_be_debug(ty, &generics) {
+            report(
+                QualityIssueKind::NonDebugArgument,
+                0.2,
+                format!("Parameter '{name}' may not implement Debug; skip it"),
+            );
+        } else if is_large(&name, ty) {
+            report(
+                QualityIssueKind::MissingSkip,
+                0.2,
+                format!("Parameter '{name}' is large or noisy to record; skip it"),
+            );
+        }
+        if is_sensitive_name(&name) || is_sensitive_type(ty) {
+            report(
+                QualityIssueKind::SensitiveData,
+                0.3,
+                format!("Sensitive parameter '{name}' is recorded; skip or redact it"),
+            );
+        }
+    }
+
+    let in_scope = bound_names(sig);
+    for field in &args.fields {
+        let references = field_references(field);
+        if is_sensitive_name(&field.name) || references.iter().any(|name| is_sensitive_name(name)) {
+            report(
+                QualityIssueKind::SensitiveData,
+                0.3,
+                format!("Field '{}' records sensitive data", field.name),
+            );
+        }
+        for name in references.iter().filter(|name| !in_scope.contains(*name)) {
+            report(
+                QualityIssueKind::UnknownField,
+                0.2,
+                format!(
+                    "Field '{}' refers to '{name}', which is not a parameter",
+                    field.name
+                ),
+            );
+        }
+    }
+
+    if returns_result(sig) && !args.err {
+        report(
+            QualityIssueKind::NoErrorHandling,
+            0.1,
+            "Consider adding err to record returned errors".to_string(),
+        );
+    }
+
+    InstrumentationQuality {
+        score: score.max(0.0),
+        issues,
+    }
+}
+
+/// Assesses a `log` or `tracing` event macro
+///
+/// - `UnstructuredLog`: values are interpolated into the message instead of
+///   recorded as fields
+/// - `MissingErrorValue`: an error being handled is logged without its value
+/// - `SensitiveData`: the event records a value that looks like a secret
+/// - `LogInLoop`: `error!` in a loop without a condition limiting its rate
+/// - `LevelMismatch`: an error being handled is logged below `warn`
+///
+/// # Arguments
+///
+/// * `level` - Name of the macro (`info`, `error`, ...)
+/// * `tokens` - Arguments of the macro
+/// * `context` - Where the event is emitted
+///
+/// # Returns
+///
+/// A score lowered for every issue, and the issues
+pub fn assess_log_quality(
+    level: &str,
+    tokens: &TokenStream,
+    context: &LogContext,
+) -> InstrumentationQuality {
+    let mut issues = Vec::new();
+    let mut score: f64 = 1.0;
+    let mut report = |kind: QualityIssueKind, penalty: f64, message: String| {
+        issues.push(QualityIssue { kind, message });
+        score -= penalty;
+    };
+
+    let event = LogEvent::parse(tokens);
+    let interpolated = event.interpolated();
+
+    if let Some(first) = interpolated.first() {
+        report(
+            QualityIssueKind::UnstructuredLog,
+            0.1,
+            format!("Message interpolates '{first}'; record values as fields instead"),
+        );
+    }
+
+    let referenced = event.references(&interpolated);
+    if let Some(bindings) = &context.error_bindings {
+        if !bindings.iter().any(|name| referenced.contains(name)) {
+            report(
+                QualityIssueKind::MissingErrorValue,
+                0.2,
+                "Error is logged without its value; record it as a field".to_string(),
+            );
+        }
+        if LOW_LEVELS.contains(&level) {
+            report(
+                QualityIssueKind::LevelMismatch,
+                0.2,
+                format!("Error is logged at '{level}' level; use warn or error"),
+            );
+        }
+    }
+
+    // Positional arguments are interpolated as written, their names are
+    // among the identifiers
+    let mut secrets: Vec<&String> = referenced
+        .iter()
+        .filter(|name| !name.contains(' ') && is_sensitive_name(name))
+        .collect();
+    secrets.sort();
+    for name in secrets {
+        report(
+            QualityIssueKind::SensitiveData,
+            0.3,
+            format!("Log event records '{name}', which looks like a secret; redact it"),
+        );
+    }
+
+    if level == "error" && context.in_loop && !context.rate_limited {
+        report(
+            QualityIssueKind::LogInLoop,
+            0.2,
+            "error! in a loop can flood the logs; rate-limit or aggregate it".to_string(),
+        );
+    }
+
+    InstrumentationQuality {
+        score: score.max(0.0),
+        issues,
+    }
+}
+
+/// Arguments of a log macro
+struct LogEvent {
+    /// Fields recorded before the message: `user_id = %id`, `?request`
+    fields: Vec<TokenStream>,
+    /// Format string of the message
+    format: Option<String>,
+    /// Arguments after the format string
+    format_args: Vec<TokenStream>,
+}
+
+impl LogEvent {
+    /// Splits a macro's arguments at the first string literal, the message
+    fn parse(tokens: &TokenStream) -> Self {
+        let mut arguments = split_arguments(tokens.clone());
+        arguments.retain(|argument| !is_event_setting(argument));
+
+        let message = arguments
+            .iter()
+            .position(|argument| syn::parse2::<syn::LitStr>(argument.clone()).is_ok());
+        let Some(message) = message else {
+            return Self {
+                fields: arguments,
+                format: None,
+                format_args: Vec::new(),
+            };
+        };
+
+        let format_args = arguments.split_off(message + 1);
+        let format = arguments
+            .pop()
+            .and_then(|literal| syn::parse2::<syn::LitStr>(literal).ok())
+            .map(|literal| literal.value());
+        Self {
+            fields: arguments,
+            format,
+            format_args,
+        }
+    }
+
+    /// Returns what each placeholder of the message interpolates: the names
+    /// of inline arguments (`{id}`) and the text of the others
+    fn interpolated(&self) -> Vec<String> {
+        let mut next = 0;
+        placeholders(self.format.as_deref().unwrap_or(""))
+            .into_iter()
+            .filter_map(|argument| {
+                let value = if argument.is_empty() {
+                    next += 1;
+                    self.format_args.get(next - 1)
+                } else if let Ok(index) = argument.parse::<usize>() {
+                    self.format_args.get(index)
+                } else {
+                    return Some(argument);
+                };
+                value.map(|tokens| tokens.to_string())
+            })
+            .collect()
+    }
+
+    /// Returns every name the event refers to
+    fn references(&self, interpolated: &[String]) -> HashSet<String> {
+        self.fields
+            .iter()
+            .chain(&self.format_args)
+            .flat_map(identifiers)
+            .chain(interpolated.iter().cloned())
+            .collect()
+    }
+}
+
+/// Checks whether a macro argument is `target: ..`, `parent: ..` or `name: ..`
+fn is_event_setting(argument: &TokenStream) -> bool {
+    let mut tokens = argument.clone().into_iter();
+    matches!(
+        (tokens.next(), tokens.next()),
+        (Some(TokenTree::Ident(ident)), Some(TokenTree::Punct(punct)))
+            if EVENT_SETTINGS.contains(&ident.to_string().as_str()) && punct.as_char() == ':'
+    )
+}
+
+/// Returns the argument of every `{..}` placeholder in a format string:
+/// empty for `{}`, an index for `{0}` or a name for `{id:?}`
+fn placeholders(format: &str) -> Vec<String> {
+    let mut arguments = Vec::new();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '{' {
+            continue;
+        }
+        if chars.peek() == Some(&'{') {
+            chars.next();
+            continue;
+        }
+        let inner: String = chars.by_ref().take_while(|&c| c != '}').collect();
+        let argument = inner.split(':').next().unwrap_or("").trim();
+        arguments.push(argument.to_string());
+    }
+    arguments
+}
+
+/// Returns the identifiers in a token stream, groups included
+fn identifiers(tokens: &TokenStream) -> Vec<String> {
+    tokens
+        .clone()
+        .into_iter()
+        .flat_map(|token| match token {
+            TokenTree::Ident(ident) => vec![ident.to_string()],
+            TokenTree::Group(group) => identifiers(&group.stream()),
+            _ => Vec::new(),
+        })
+        .collect()
+}
+
+/// Checks whether a function returns a `Result`
+///
+/// # Arguments
+///
+/// * `sig` - Signature of the function
+pub fn returns_result(sig: &Signature) -> bool {
+    match &sig.output {
+        ReturnType::Type(_, ty) => match &**ty {
+            Type::Path(path) => path
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident.to_string().ends_with("Result")),
+            _ => false,
+        },
+        ReturnType::Default => false,
+    }
+}
+
+/// Returns the generic type parameters that have no `Debug` bound, neither
+/// inline nor in the `where` clause
+fn generics_without_debug(sig: &Signature) -> HashSet<String> {
+    let mut with_debug = HashSet::new();
+    let mut all = HashSet::new();
+
+    for param in &sig.generics.params {
+        if let GenericParam::Type(param) = param {
+            all.insert(param.ident.to_string());
+            if has_debug_bound(param.bounds.iter()) {
+                with_debug.insert(param.ident.to_string());
+            }
+        }
+    }
+    for predicate in sig.generics.where_clause.iter().flat_map(|w| &w.predicates) {
+        if let WherePredicate::Type(predicate) = predicate {
+            if let (Type::Path(path), true) = (
+                &predicate.bounded_ty,
+                has_debug_bound(predicate.bounds.iter()),
+            ) {
+                if let Some(ident) = path.path.get_ident() {
+                    with_debug.insert(ident.to_string());
+                }
+            }
+        }
+    }
+
+    all.difference(&with_debug).cloned().collect()
+}
+
+/// Checks whether trait bounds include `Debug`
+fn has_debug_bound<'a>(mut bounds: impl Iterator<Item = &'a TypeParamBound>) -> bool {
+    bounds.any(|bound| match bound {
+        TypeParamBound::Trait(bound) => bound
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Debug"),
+        _ => false,
+    })
+}
+
+/// Checks whether a type may not implement `Debug`: `impl Trait`, `dyn Trait`
+/// or a generic parameter without a `Debug` bound, also inside generic
+/// arguments (`Box<dyn Fn()>`)
+fn may_not_be_debug(ty: &Type, generics: &HashSet<String>) -> bool {
+    match ty {
+        Type::Reference(reference) => may_not_be_debug(&reference.elem, generics),
+        Type::Paren(paren) => may_not_be_debug(&paren.elem, generics),
+        Type::Group(group) => may_not_be_debug(&group.elem, generics),
+        Type::ImplTrait(impl_trait) => !has_debug_bound(impl_trait.bounds.iter()),
+        Type::TraitObject(object) => !has_debug_bound(object.bounds.iter()),
+        Type::Path(path) => {
+            if path
+                .path
+                .get_ident()
+                .is_some_and(|ident| generics.contains(&ident.to_string()))
+            {
+                return true;
+            }
+            path.path.segments.iter().any(|segment| {
+                type_arguments(&segment.arguments).any(|arg| may_not_be_debug(arg, generics))
+            })
+        }
+        _ => false,
+    }
+}
+
+/// Checks whether a parameter holds a payload, byte buffer, pool or client
+fn is_large(name: &str, ty: &Type) -> bool {
+    words(name)
+        .iter()
+        .any(|word| LARGE_NAMES.contains(&word.as_str()))
+        || is_large_type(ty)
+}
+
+/// Checks whether a type is a payload, byte buffer, pool or client
+fn is_large_type(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(reference) => is_large_type(&reference.elem),
+        Type::Paren(paren) => is_large_type(&paren.elem),
+        Type::Group(group) => is_large_type(&group.elem),
+        Type::Slice(slice) => is_byte(&slice.elem),
+        Type::Path(path) => {
+            let Some(segment) = path.path.segments.last() else {
+                return false;
+            };
+            let ident = segment.ident.to_string();
+            let mut arguments = type_arguments(&segment.arguments);
+            if WRAPPER_TYPES.contains(&ident.as_str()) {
+                return arguments.any(is_large_type);
+            }
+            LARGE_TYPES.contains(&ident.as_str())
+                || LARGE_TYPE_SUFFIXES
+                    .iter()
+                    .any(|suffix| ident.ends_with(suffix))
+                || (ident == "Vec" && arguments.any(is_byte))
+        }
+        _ => false,
+    }
+}
+
+/// Checks whether a type is `u8`
+fn is_byte(ty: &Type) -> bool {
+    matches!(ty, Type::Path(path) if path.path.is_ident("u8"))
+}
+
+/// Checks whether a type holds a secret (`SecretString`, `Secret<String>`)
+fn is_sensitive_type(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(reference) => is_sensitive_type(&reference.elem),
+        Type::Path(path) => path.path.segments.last().is_some_and(|segment| {
+            SENSITIVE_TYPES.contains(&segment.ident.to_string().as_str())
+                || (WRAPPER_TYPES.contains(&segment.ident.to_string().as_str())
+                    && type_arguments(&segment.arguments).any(is_sensitive_type))
+        }),
+        _ => false,
+    }
+}
+
+/// Checks whether a parameter or field name looks like a secret
+///
+/// The last word decides, so `api_token` and `user.password` are secrets
+/// while `token_count` and `monkey` are not.
+fn is_sensitive_name(name: &str) -> bool {
+    let words = words(name);
+    match words.as_slice() {
+        [.., last] if SENSITIVE_WORDS.contains(&last.as_str()) => true,
+        [.., qualifier, last] if last == "key" => KEY_QUALIFIERS.contains(&qualifier.as_str()),
+        _ => false,
+    }
+}
+
+/// Splits a snake_case or dotted name into lowercase words
+fn words(name: &str) -> Vec<String> {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Returns the type arguments of a path segment (`T` in `Vec<T>`)
+fn type_arguments(arguments: &PathArguments) -> impl Iterator<Item = &Type> {
+    let args = match arguments {
+        PathArguments::AngleBracketed(args) => Some(&args.args),
+        _ => None,
+    };
+    args.into_iter().flatten().filter_map(|arg| match arg {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    })
+}
+
+/// Returns the names a function's parameters bind, `self` included
+fn bound_names(sig: &Signature) -> HashSet<String> {
+    struct Bindings(HashSet<String>);
+
+    impl<'ast> Visit<'ast> for Bindings {
+        fn visit_pat_ident(&mut self, pat: &'ast syn::PatIdent) {
+            self.0.insert(pat.ident.to_string());
+            visit::visit_pat_ident(self, pat);
+        }
+
+        fn visit_type(&mut self, _: &'ast Type) {}
+    }
+
+    let mut bindings = Bindings(HashSet::new());
+    for input in &sig.inputs {
+        match input {
+            FnArg::Typed(typed) => bindings.visit_pat(&typed.pat),
+            FnArg::Receiver(_) => {
+                bindings.0.insert("self".to_string());
+            }
+        }
+    }
+    bindings.0
+}
+
+/// Returns the local names a field's value refers to
+///
+/// `%user.id` refers to `user`; paths with several segments, constants and
+/// called functions are not local names.
+fn field_references(field: &SpanField) -> Vec<String> {
+    struct References(Vec<String>);
+
+    impl<'ast> Visit<'ast> for References {
+        fn visit_expr_path(&mut self, expr: &'ast syn::ExprPath) {
+            if let Some(ident) = expr.path.get_ident() {
+                let name = ident.to_string();
+                if name.starts_with(|c: char| c.is_lowercase() || c == '_')
+                    && !self.0.contains(&name)
+                {
+                    self.0.push(name);
+                }
+            }
+        }
+
+        fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+            for arg in &call.args {
+                self.visit_expr(arg);
+            }
+        }
+
+        fn visit_expr_closure(&mut self, _: &'ast syn::ExprClosure) {}
+    }
+
+    let Some(value) = &field.value else {
+        return Vec::new();
+    };
+    let Ok(expr) = syn::parse_str::<Expr>(value.trim_start_matches(['%', '?'])) else {
+        return Vec::new();
+    };
+    let mut references = References(Vec::new());
+    references.visit_expr(&expr);
+    references.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detector::QualityIssueKind::*;
+
+    fn issues(attribute: &str, function: &str) -> Vec<(QualityIssueKind, String)> {
+        let item: syn::ItemFn = syn::parse_str(&format!("{attribute}\n{function}")).unwrap();
+        let args = crate::detector::existing::parse_instrument_args(&item.attrs[0]);
+        assess_instrument_quality(&args, &item.sig)
+            .issues
+            .into_iter()
+            .map(|issue| {
+                let subject = issue.message.split('\'').nth(1).unwrap_or("").to_string();
+                (issue.kind, subject)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_recorded_parameters_are_checked_by_type_and_name() {
+        let found = issues(
+            "#[instrument]",
+            "async fn upload<S: Stream, T: Debug>(
+                pool: &PgPool,
+                raw: Vec<u8>,
+                stream: S,
+                meta: T,
+                callback: impl Fn(),
+                api_key: String,
+                token_count: usize,
+                monkey: &str,
+                secret: Arc<SecretString>,
+            ) {}",
+        );
+        assert_eq!(
+            found,
+            vec![
+                (MissingSkip, "pool".to_string()),
+                (MissingSkip, "raw".to_string()),
+                (NonDebugArgument, "stream".to_string()),
+                (NonDebugArgument, "callback".to_string()),
+                (SensitiveData, "api_key".to_string()),
+                (SensitiveData, "secret".to_string()),
+            ]
+        );
+
+        // Skipped parameters aren't recorded
+        let found = issues(
+            "#[instrument(skip(pool, raw, stream, callback, api_key, secret))]",
+            "async fn upload<S: Stream>(
+                pool: &PgPool, raw: Vec<u8>, stream: S, callback: impl Fn(),
+                api_key: String, secret: SecretString,
+            ) {}",
+        );
+        assert!(found.is_empty(), "{found:?}");
+    }
+
+    fn log_issues(level: &str, args: &str, context: &LogContext) -> Vec<QualityIssueKind> {
+        let tokens: TokenStream = args.parse().unwrap();
+        assess_log_quality(level, &tokens, context)
+            .issues
+            .into_iter()
+            .map(|issue| issue.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_log_events() {
+        let plain = LogContext::default();
+        assert!(log_issues("info", r#"user_id = %id, "logged in""#, &plain).is_empty());
+        assert!(log_issues("info", r#"target: "auth", "{{literal}} braces""#, &plain).is_empty());
+        assert_eq!(
+            log_issues("info", r#""user {} logged in", user.id"#, &plain),
+            vec![UnstructuredLog]
+        );
+        assert_eq!(
+            log_issues("warn", r#"?password, "login failed for {user:?}""#, &plain),
+            vec![UnstructuredLog, SensitiveData]
+        );
+
+        let handling = LogContext {
+            error_bindings: Some(vec!["e".to_string()]),
+            ..LogContext::default()
+        };
+        assert!(log_issues("error", r#"error = %e, "save failed""#, &handling).is_empty());
+        assert_eq!(
+            log_issues("error", r#""save failed: {e}""#, &handling),
+            vec![UnstructuredLog]
+        );
+        assert_eq!(
+            log_issues("info", r#""save failed""#, &handling),
+            vec![MissingErrorValue, LevelMismatch]
+        );
+
+        let in_loop = LogContext {
+            in_loop: true,
+            ..LogContext::default()
+        };
+        assert_eq!(
+            log_issues("error", r#""retrying""#, &in_loop),
+            vec![LogInLoop]
+        );
+        assert!(log_issues("warn", r#""retrying""#, &in_loop).is_empty());
+        let limited = LogContext {
+            rate_limited: true,
+            ..in_loop
+        };
+        assert!(log_issues("error", r#""retrying""#, &limited).is_empty());
+    }
+
+    #[test]
+    fn test_fields_and_errors() {
+        let found = issues(
+            "#[instrument(skip_all, fields(
+                user.id = %user.id,
+                order = ?order_id,
+                auth.token = %auth.bearer,
+                pid = std::process::id(),
+                empty_later,
+                version = VERSION,
+                len = body_len(request),
+            ))]",
+            "fn handle(user: User, auth: Auth, request: Req) -> anyhow::Result<()> {}",
+        );
+        assert_eq!(
+            found,
+            vec![
+                (UnknownField, "order".to_string()),
+                (SensitiveData, "auth.token".to_string()),
+                (NoErrorHandling, String::new()),
+            ]
+        );
+
+        let found = issues(
+            "#[instrument(skip(self), err)]",
+            "fn save(&self, id: u64) -> Result<(), Error> {}",
+        );
+        assert!(found.is_empty(), "{found:?}");
+    }
+}
@@ -132,7 +132,10 @@ impl<'a> RuleChecker<'a> {
         // In future, we could try to infer the kind from the function
 
         let expected_prefix = match inst.kind {
-            ExistingKind::TracingInstrument | ExistingKind::ManualSpan => {
+            ExistingKind::TracingInstrument
+            | ExistingKind::ManualSpan
+            | ExistingKind::InstrumentedFuture
+            | ExistingKind::OpenTelemetrySpan => {
                 // Try to detect from span name patterns
                 if span_name.contains("db")
                     || span_name.contains("sql")
@@ -152,7 +155,7 @@ impl<'a> RuleChecker<'a> {
                     None
                 }
             }
-            ExistingKind::LogMacro | ExistingKind::Metrics => None,
+            ExistingKind::SpanGuard | ExistingKind::LogMacro | ExistingKind::Metrics => None,
         };
 
         if let Some(prefix) = expected_prefix {
@@ -286,6 +289,9 @@ mod tests {
             kind: ExistingKind::TracingInstrument,
             span_name: Some("get_user_password".to_string()),
             quality: crate::detector::InstrumentationQuality::default(),
+            args: None,
+            instrumented_futures: Vec::new(),
+            otel: None,
         };
 
         let violations = checker.check_existing(&[inst]);
@@ -303,6 +309,9 @@ mod tests {
             kind: ExistingKind::TracingInstrument,
             span_name: Some("query_users".to_string()), // Contains "query" but doesn't start with "db."
             quality: crate::detector::InstrumentationQuality::default(),
+            args: None,
+            instrumented_futures: Vec::new(),
+            otel: None,
         };
 
         let violations = checker.check_existing(&[inst]);
@@ -320,6 +329,9 @@ mod tests {
             kind: ExistingKind::TracingInstrument,
             span_name: Some("db.query_users".to_string()),
             quality: crate::detector::InstrumentationQuality::default(),
+            args: None,
+            instrumented_futures: Vec::new(),
+            otel: None,
         };
 
         let violations = checker.check_existing(&[inst]);
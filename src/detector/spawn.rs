@@ -0,0 +1,965 @@
+//! Span propagation checks for spawned tasks and other context boundaries
+//!
+//! A future handed to `tokio::spawn` runs detached from the span that was
+//! current when it was created, so its events and child spans lose their
+//! parent unless the future is wrapped with `.instrument(span)` or
+//! `.in_current_span()`.
+//!
+//! The same happens to closures run by `spawn_blocking` or
+//! `std::thread::spawn`, to messages sent over a channel and to callbacks
+//! stored for later, unless they carry a span across explicitly. Where such a
+//! boundary sits between two instrumented functions, the callee's span starts
+//! a new trace instead of continuing the caller's.
+
+use super::{ExistingInstrumentation, Location};
+use crate::ast::SourceFile;
+use crate::call_graph::{CallGraph, NodeKind, type_name};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use syn::spanned::Spanned;
+use syn::visit::Visit;
+use syn::{Expr, ImplItemFn, ItemFn, ItemImpl};
+
+/// Spawners that run a future as a separate task
+const FUTURE_SPAWNERS: &[&str] = &["spawn", "spawn_local"];
+
+/// Methods that attach a span to a future
+pub(crate) const SPAN_PROPAGATORS: &[&str] = &["instrument", "in_current_span"];
+
+/// Methods that make a span current inside a closure
+const SPAN_ENTERS: &[&str] = &[
+    "enter",
+    "entered",
+    "in_scope",
+    "instrument",
+    "in_current_span",
+];
+
+/// Channel methods that send a single message
+const CHANNEL_SENDS: &[&str] = &[
+    "send",
+    "try_send",
+    "blocking_send",
+    "send_async",
+    "unbounded_send",
+    "send_timeout",
+];
+
+/// Channel methods that receive a message
+const CHANNEL_RECEIVES: &[&str] = &[
+    "recv",
+    "try_recv",
+    "blocking_recv",
+    "recv_async",
+    "recv_timeout",
+];
+
+/// Collection methods that store a callback in a field
+const CALLBACK_STORES: &[&str] = &["push", "push_back", "insert", "set"];
+
+/// Kind of call that runs code outside the current span
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum BoundaryKind {
+    /// Future handed to an async task spawner (`tokio::spawn`, `JoinSet::spawn`)
+    SpawnedTask,
+    /// Closure run on the blocking pool (`spawn_blocking`)
+    BlockingTask,
+    /// Closure run on a new OS thread (`std::thread::spawn`)
+    Thread,
+    /// Message sent over a channel to another task
+    Channel,
+    /// Closure stored in a struct to be called later
+    StoredCallback,
+}
+
+impl BoundaryKind {
+    /// Returns a human-readable name for the boundary kind
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::SpawnedTask => "Spawned Task",
+            Self::BlockingTask => "Blocking Task",
+            Self::Thread => "Thread",
+            Self::Channel => "Channel",
+            Self::StoredCallback => "Stored Callback",
+        }
+    }
+
+    /// Returns how to carry the span across this kind of boundary
+    fn suggested_fix(&self) -> &'static str {
+        match self {
+            Self::SpawnedTask => "Wrap the future with `.in_current_span()` or `.instrument(span)`",
+            Self::BlockingTask | Self::Thread | Self::StoredCallback => {
+                "Capture `Span::current()` and enter it inside the closure"
+            }
+            Self::Channel => "Send `Span::current()` with the message and enter it in the receiver",
+        }
+    }
+}
+
+/// A call site that hands work to code running outside the current span
+#[derive(Debug, Clone)]
+pub struct ContextBoundary {
+    /// Kind of boundary
+    pub kind: BoundaryKind,
+    /// Location of the call site, with the enclosing function's name
+    pub location: Location,
+    /// Last line of the task, closure or message handed over
+    pub end_line: usize,
+    /// Line of the enclosing function's name
+    pub function_line: usize,
+    /// The call that crosses the boundary (`tokio::spawn`, `tx.send`, ...)
+    pub call: String,
+    /// Whether a span is carried across the boundary
+    pub propagates_span: bool,
+    /// Name of the channel with its sender/receiver affix removed
+    pub channel: Option<String>,
+}
+
+/// A function that receives messages from a channel
+#[derive(Debug, Clone)]
+struct ChannelReceiver {
+    file: PathBuf,
+    /// Line of the receiving function's name
+    function_line: usize,
+    /// Name of the channel with its sender/receiver affix removed
+    channel: String,
+}
+
+/// An instrumented call chain broken by a context boundary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextBreak {
+    /// Kind of boundary that drops the span
+    pub kind: BoundaryKind,
+    /// The offending call site, in the caller's function
+    pub location: Location,
+    /// The call that crosses the boundary (`tokio::spawn`, `tx.send`, ...)
+    pub call: String,
+    /// Instrumented function whose span is current at the call site, or the
+    /// spawning function when a task is spawned outside any span
+    pub caller: String,
+    /// Instrumented functions reached only across the boundary
+    pub callees: Vec<String>,
+    /// Description of the break
+    pub description: String,
+    /// How to carry the span across the boundary
+    pub suggested_fix: String,
+}
+
+/// Find spawned futures that don't carry the current span
+///
+/// These are reported as context breaks rather than instrumentation gaps:
+/// the fix wraps the spawned expression, which `#[instrument]` insertion
+/// can't do.
+///
+/// # Arguments
+///
+/// * `graph` - The call graph of the files
+/// * `files` - Parsed source files to check
+/// * `existing` - Existing instrumentation in the files
+///
+/// # Returns
+///
+/// A break for each `spawn(fut)` / `join_set.spawn(fut)` whose future is not
+/// wrapped with `.instrument(span)` or `.in_current_span()`, located in the
+/// spawning function's call graph node
+pub fn detect_lost_spans(
+    graph: &CallGraph,
+    files: &[SourceFile],
+    existing: &[ExistingInstrumentation],
+) -> Vec<ContextBreak> {
+    let boundaries: Vec<ContextBoundary> = find_context_boundaries(files)
+        .into_iter()
+        .filter(|boundary| {
+            boundary.kind == BoundaryKind::SpawnedTask
+                && !boundary.propagates_span
+                && !boundary.call.ends_with("spawn_blocking")
+        })
+        .collect();
+
+    let chains = Chains::new(graph, existing, &boundaries);
+    let mut breaks = Vec::new();
+
+    for (boundary, crossing) in boundaries.iter().zip(&chains.crossings) {
+        let function = chains
+            .function_at(&boundary.location.file, boundary.function_line)
+            .unwrap_or(&boundary.location.function_name);
+        let callees: Vec<String> = chains
+            .instrumented_below(crossing.iter().map(|(_, to)| to.as_str()))
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+
+        breaks.push(ContextBreak {
+            kind: boundary.kind,
+            location: Location {
+                function_name: function.to_string(),
+                ..boundary.location.clone()
+            },
+            call: boundary.call.clone(),
+            caller: chains
+                .instrumented_above(function)
+                .unwrap_or(function)
+                .to_string(),
+            callees,
+            description: format!(
+                "Task spawned in '{}' does not carry the current span",
+                function
+            ),
+            suggested_fix: boundary.kind.suggested_fix().to_string(),
+        });
+    }
+
+    breaks
+}
+
+/// Find call sites that hand work to code running outside the current span
+///
+/// # Arguments
+///
+/// * `files` - Parsed source files to check
+///
+/// # Returns
+///
+/// Every spawn, blocking task, thread, channel send and stored callback,
+/// whether or not it carries a span
+pub fn find_context_boundaries(files: &[SourceFile]) -> Vec<ContextBoundary> {
+    files
+        .iter()
+        .flat_map(|file| scan(file).boundaries)
+        .collect()
+}
+
+/// Find instrumented call chains broken by a context boundary
+///
+/// A chain is broken when the span of an instrumented function is current at
+/// a boundary that doesn't carry it, and an instrumented function is reached
+/// across that boundary but not through ordinary calls. Channel senders are
+/// linked to the functions receiving from a channel of the same name
+/// (`jobs_tx` and `jobs_rx`, `sender` and `receiver`).
+///
+/// # Arguments
+///
+/// * `graph` - The call graph of the files
+/// * `files` - Parsed source files to check
+/// * `existing` - Existing instrumentation in the files
+///
+/// # Returns
+///
+/// A break for each boundary call site between instrumented functions
+pub fn detect_broken_chains(
+    graph: &CallGraph,
+    files: &[SourceFile],
+    existing: &[ExistingInstrumentation],
+) -> Vec<ContextBreak> {
+    let mut boundaries = Vec::new();
+    let mut receivers = Vec::new();
+    for file in files {
+        let visitor = scan(file);
+        boundaries.extend(visitor.boundaries);
+        receivers.extend(visitor.receivers);
+    }
+
+    let chains = Chains::new(graph, existing, &boundaries);
+    let mut breaks = Vec::new();
+
+    for (boundary, crossing) in boundaries.iter().zip(&chains.crossings) {
+        if boundary.propagates_span {
+            continue;
+        }
+        let Some(caller) = chains.function_at(&boundary.location.file, boundary.function_line)
+        else {
+            continue;
+        };
+
+        let entries: Vec<&str> = match &boundary.channel {
+            Some(channel) => receivers
+                .iter()
+                .filter(|receiver| &receiver.channel == channel)
+                .filter_map(|receiver| chains.function_at(&receiver.file, receiver.function_line))
+                .collect(),
+            None => crossing.iter().map(|(_, to)| to.as_str()).collect(),
+        };
+
+        let direct = chains.instrumented_below([caller]);
+        let callees: Vec<String> = chains
+            .instrumented_below(entries)
+            .into_iter()
+            .filter(|callee| !direct.contains(callee) && *callee != caller)
+            .map(str::to_string)
+            .collect();
+        if callees.is_empty() {
+            continue;
+        }
+        let Some(span_owner) = chains.instrumented_above(caller) else {
+            continue;
+        };
+
+        breaks.push(ContextBreak {
+            kind: boundary.kind,
+            location: Location {
+                function_name: caller.to_string(),
+                ..boundary.location.clone()
+            },
+            call: boundary.call.clone(),
+            caller: span_owner.to_string(),
+            description: format!(
+                "Span of '{}' is lost at {} ({}) before reaching '{}'",
+                span_owner,
+                boundary.call,
+                boundary.kind.name(),
+                callees.join("', '")
+            ),
+            callees,
+            suggested_fix: boundary.kind.suggested_fix().to_string(),
+        });
+    }
+
+    breaks
+}
+
+/// Scans a file for spawn sites, context boundaries and channel receivers
+fn scan(file: &SourceFile) -> SpawnVisitor {
+    let mut visitor = SpawnVisitor::new(file.path());
+    visitor.visit_file(&file.syntax_tree);
+    visitor
+}
+
+/// Call graph view used to follow spans through instrumented functions
+struct Chains<'a> {
+    graph: &'a CallGraph,
+    /// IDs of functions with existing instrumentation
+    instrumented: HashSet<&'a str>,
+    /// Calls made across each boundary, in the same order as the boundaries
+    crossings: Vec<Vec<(String, String)>>,
+    /// Calls across boundaries that drop the span
+    dropped: HashSet<(String, String)>,
+}
+
+impl<'a> Chains<'a> {
+    fn new(
+        graph: &'a CallGraph,
+        existing: &[ExistingInstrumentation],
+        boundaries: &[ContextBoundary],
+    ) -> Self {
+        let instrumented = graph
+            .nodes()
+            .filter(|node| !matches!(node.kind, NodeKind::External | NodeKind::Closure))
+            .filter(|node| existing.iter().any(|e| e.instruments(&node.id)))
+            .map(|node| node.id.as_str())
+            .collect();
+
+        // Calls whose site lies inside the task, closure or message handed
+        // over at a boundary
+        let crossings: Vec<Vec<(String, String)>> = boundaries
+            .iter()
+            .map(|boundary| {
+                let file = boundary.location.file.display().to_string();
+                graph
+                    .edges()
+                    .iter()
+                    .filter(|edge| edge.file_path.as_deref() == Some(file.as_str()))
+                    .filter(|edge| {
+                        edge.line_number.is_some_and(|line| {
+                            (boundary.location.line..=boundary.end_line).contains(&line)
+                        })
+                    })
+                    .map(|edge| (edge.from.clone(), edge.to.clone()))
+                    .collect()
+            })
+            .collect();
+
+        let dropped = boundaries
+            .iter()
+            .zip(&crossings)
+            .filter(|(boundary, _)| !boundary.propagates_span)
+            .flat_map(|(_, crossing)| crossing.iter().cloned())
+            .collect();
+
+        Self {
+            graph,
+            instrumented,
+            crossings,
+            dropped,
+        }
+    }
+
+    /// Finds the function whose name is on a line of a file
+    fn function_at(&self, file: &Path, line: usize) -> Option<&'a str> {
+        self.graph
+            .nodes()
+            .filter(|node| !matches!(node.kind, NodeKind::External | NodeKind::Closure))
+            .find(|node| node.line() == Some(line) && node.file().as_deref() == Some(file))
+            .map(|node| node.id.as_str())
+    }
+
+    /// Checks whether a call keeps the caller's span current
+    fn keeps_span(&self, from: &str, to: &str) -> bool {
+        !self.dropped.contains(&(from.to_string(), to.to_string()))
+    }
+
+    /// Finds the instrumented functions first reached from `starts` through
+    /// calls that keep the span
+    fn instrumented_below<'s>(&self, starts: impl IntoIterator<Item = &'s str>) -> Vec<&'a str> {
+        let mut found = Vec::new();
+        let mut visited = HashSet::new();
+        let mut queue: VecDeque<&'a str> = starts
+            .into_iter()
+            .filter_map(|id| self.graph.get_node(id))
+            .map(|node| node.id.as_str())
+            .collect();
+
+        while let Some(id) = queue.pop_front() {
+            if !visited.insert(id) {
+                continue;
+            }
+            if self.instrumented.contains(id) {
+                found.push(id);
+                continue;
+            }
+            let mut callees: Vec<&'a str> = self
+                .graph
+                .get_callees(id)
+                .into_iter()
+                .flatten()
+                .filter(|callee| self.keeps_span(id, callee))
+                .filter_map(|callee| self.graph.get_node(callee))
+                .map(|node| node.id.as_str())
+                .collect();
+            callees.sort();
+            queue.extend(callees);
+        }
+
+        found.sort();
+        found
+    }
+
+    /// Finds the nearest instrumented function whose span is current in `id`
+    fn instrumented_above(&self, id: &'a str) -> Option<&'a str> {
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::from([id]);
+
+        while let Some(id) = queue.pop_front() {
+            if !visited.insert(id) {
+                continue;
+            }
+            if self.instrumented.contains(id) {
+                return Some(id);
+            }
+            let mut callers: Vec<&'a str> = self
+                .graph
+                .get_callers(id)
+                .into_iter()
+                .flatten()
+                .filter(|caller| self.keeps_span(caller, id))
+                .filter_map(|caller| self.graph.get_node(caller))
+                .map(|node| node.id.as_str())
+                .collect();
+            callers.sort();
+            queue.extend(callers);
+        }
+
+        None
+    }
+}
+
+/// Visitor collecting context boundaries and channel receivers
+struct SpawnVisitor {
+    file: PathBuf,
+    /// Stack of enclosing function names (`Type::method` for methods)
+    functions: Vec<String>,
+    /// Lines of the enclosing functions' names
+    function_lines: Vec<usize>,
+    /// Stack of enclosing `impl` self types
+    impl_types: Vec<String>,
+    boundaries: Vec<ContextBoundary>,
+    receivers: Vec<ChannelReceiver>,
+}
+
+impl SpawnVisitor {
+    fn new(file: &Path) -> Self {
+        Self {
+            file: file.to_path_buf(),
+            functions: Vec::new(),
+            function_lines: Vec::new(),
+            impl_types: Vec::new(),
+            boundaries: Vec::new(),
+            receivers: Vec::new(),
+        }
+    }
+
+    /// Records a context boundary in the current function
+    fn add_boundary(
+        &mut self,
+        kind: BoundaryKind,
+        call: String,
+        span: proc_macro2::Span,
+        end_line: usize,
+        propagates_span: bool,
+        channel: Option<String>,
+    ) {
+        let (Some(function), Some(&function_line)) =
+            (self.functions.last(), self.function_lines.last())
+        else {
+            return;
+        };
+
+        let start = span.start();
+        self.boundaries.push(ContextBoundary {
+            kind,
+            location: Location {
+                file: self.file.clone(),
+                line: start.line,
+                column: start.column + 1,
+                function_name: function.clone(),
+            },
+            end_line: end_line.max(start.line),
+            function_line,
+            call,
+            propagates_span,
+            channel,
+        });
+    }
+
+    /// Records a task or closure handed to a spawner
+    fn add_spawn(&mut self, spawner: &str, task: &Expr, span: proc_macro2::Span) {
+        let end_line = task.span().end().line;
+        let (kind, propagates_span) = match task {
+            Expr::Closure(closure) => {
+                let kind = if spawner.ends_with("spawn_blocking") {
+                    BoundaryKind::BlockingTask
+                } else {
+                    BoundaryKind::Thread
+                };
+                (kind, enters_span(&closure.body))
+            }
+            _ => (BoundaryKind::SpawnedTask, carries_span(task)),
+        };
+        self.add_boundary(
+            kind,
+            spawner.to_string(),
+            span,
+            end_line,
+            propagates_span,
+            None,
+        );
+    }
+
+    /// Records a callback stored in a field or struct
+    fn add_stored_callback(&mut self, call: String, value: &Expr, span: proc_macro2::Span) {
+        let Some(closure) = callback(value) else {
+            return;
+        };
+        let end_line = value.span().end().line;
+        let propagates_span = enters_span(&closure.body);
+        self.add_boundary(
+            BoundaryKind::StoredCallback,
+            call,
+            span,
+            end_line,
+            propagates_span,
+            None,
+        );
+    }
+}
+
+impl<'ast> Visit<'ast> for SpawnVisitor {
+    fn visit_item_fn(&mut self, item: &'ast ItemFn) {
+        self.functions.push(item.sig.ident.to_string());
+        self.function_lines.push(item.sig.ident.span().start().line);
+        syn::visit::visit_item_fn(self, item);
+        self.function_lines.pop();
+        self.functions.pop();
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast ItemImpl) {
+        let self_type = type_name(&item.self_ty).unwrap_or_default();
+        self.impl_types.push(self_type);
+        syn::visit::visit_item_impl(self, item);
+        self.impl_types.pop();
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &'ast ImplItemFn) {
+        let name = match self.impl_types.last() {
+            Some(self_type) if !self_type.is_empty() => {
+                format!("{}::{}", self_type, item.sig.ident)
+            }
+            _ => item.sig.ident.to_string(),
+        };
+        self.functions.push(name);
+        self.function_lines.push(item.sig.ident.span().start().line);
+        syn::visit::visit_impl_item_fn(self, item);
+        self.function_lines.pop();
+        self.functions.pop();
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        if let (Expr::Path(func), Some(task)) = (&*call.func, call.args.first()) {
+            let name = func
+                .path
+                .segments
+                .last()
+                .map(|segment| segment.ident.to_string())
+                .unwrap_or_default();
+            let spawner = crate::framework::web::routing::path_to_string(&func.path);
+            if FUTURE_SPAWNERS.contains(&name.as_str()) || name == "spawn_blocking" {
+                self.add_spawn(&spawner, task, call.span());
+            }
+        }
+
+        syn::visit::visit_expr_call(self, call);
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        let method = call.method.to_string();
+        let receiver = &call.receiver;
+        if FUTURE_SPAWNERS.contains(&method.as_str()) || method == "spawn_blocking" {
+            if let Some(task) = call.args.first() {
+                let spawner = format!("{}.{}", receiver_text(receiver), method);
+                self.add_spawn(&spawner, task, call.method.span());
+            }
+        } else if CHANNEL_SENDS.contains(&method.as_str()) && call.args.len() == 1 {
+            // `reqwest::RequestBuilder::send()` takes no message
+            if let Some(channel) = channel_name(receiver) {
+                let message = &call.args[0];
+                self.add_boundary(
+                    BoundaryKind::Channel,
+                    format!("{}.{}", receiver_text(receiver), method),
+                    call.method.span(),
+                    message.span().end().line,
+                    mentions_span(message),
+                    Some(channel),
+                );
+            }
+        } else if CHANNEL_RECEIVES.contains(&method.as_str()) {
+            let channel = channel_name(receiver);
+            if let (Some(channel), Some(&function_line)) = (channel, self.function_lines.last()) {
+                self.receivers.push(ChannelReceiver {
+                    file: self.file.clone(),
+                    function_line,
+                    channel,
+                });
+            }
+        } else if CALLBACK_STORES.contains(&method.as_str())
+            && matches!(&**receiver, Expr::Field(_))
+        {
+            // `self.handlers.push(Box::new(move |event| ...))`
+            let call_text = format!("{}.{}", receiver_text(receiver), method);
+            for arg in &call.args {
+                self.add_stored_callback(call_text.clone(), arg, call.method.span());
+            }
+        }
+
+        syn::visit::visit_expr_method_call(self, call);
+    }
+
+    fn visit_expr_assign(&mut self, assign: &'ast syn::ExprAssign) {
+        // `self.on_complete = Some(Box::new(move || ...))`
+        if let Expr::Field(field) = &*assign.left {
+            let call = format!("{} = ..", receiver_text(&assign.left));
+            self.add_stored_callback(call, &assign.right, field.span());
+        }
+
+        syn::visit::visit_expr_assign(self, assign);
+    }
+
+    fn visit_expr_struct(&mut self, expr: &'ast syn::ExprStruct) {
+        // `Job { on_done: Box::new(move || ...) }`
+        let name = crate::framework::web::routing::path_to_string(&expr.path);
+        for field in &expr.fields {
+            let member = match &field.member {
+                syn::Member::Named(ident) => ident.to_string(),
+                syn::Member::Unnamed(index) => index.index.to_string(),
+            };
+            let call = format!("{} {{ {} }}", name, member);
+            self.add_stored_callback(call, &field.expr, field.span());
+        }
+
+        syn::visit::visit_expr_struct(self, expr);
+    }
+}
+
+/// Renders the receiver of a call as written, without the spaces `quote`
+/// puts between tokens (`self.jobs_tx`, `pool.get(..)`)
+fn receiver_text(expr: &Expr) -> String {
+    match expr {
+        Expr::Path(path) => crate::framework::web::routing::path_to_string(&path.path),
+        Expr::Field(field) => {
+            let member = match &field.member {
+                syn::Member::Named(ident) => ident.to_string(),
+                syn::Member::Unnamed(index) => index.index.to_string(),
+            };
+            format!("{}.{}", receiver_text(&field.base), member)
+        }
+        Expr::MethodCall(call) => format!("{}.{}(..)", receiver_text(&call.receiver), call.method),
+        Expr::Call(call) => format!("{}(..)", receiver_text(&call.func)),
+        Expr::Await(inner) => format!("{}.await", receiver_text(&inner.base)),
+        Expr::Try(inner) => format!("{}?", receiver_text(&inner.expr)),
+        Expr::Reference(reference) => receiver_text(&reference.expr),
+        Expr::Paren(inner) => receiver_text(&inner.expr),
+        _ => "..".to_string(),
+    }
+}
+
+/// Returns the closure of a callback value (`|| ..`, `Box::new(|| ..)`,
+/// `Some(Arc::new(|| ..))`)
+fn callback(value: &Expr) -> Option<&syn::ExprClosure> {
+    match value {
+        Expr::Closure(closure) => Some(closure),
+        Expr::Paren(inner) => callback(&inner.expr),
+        Expr::Call(call) if call.args.len() == 1 => callback(&call.args[0]),
+        _ => None,
+    }
+}
+
+/// Checks whether an expression makes a span current (`span.enter()`,
+/// `span.in_scope(..)`, `fut.instrument(span)`, ...)
+fn enters_span(expr: &Expr) -> bool {
+    struct SpanEnter(bool);
+
+    impl<'ast> Visit<'ast> for SpanEnter {
+        fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+            if SPAN_ENTERS.contains(&call.method.to_string().as_str()) {
+                self.0 = true;
+            }
+            syn::visit::visit_expr_method_call(self, call);
+        }
+    }
+
+    let mut visitor = SpanEnter(false);
+    visitor.visit_expr(expr);
+    visitor.0
+}
+
+/// Checks whether a message mentions a span (`Span::current()`, `span`,
+/// `parent_span`, ...)
+pub(crate) fn mentions_span(message: &Expr) -> bool {
+    quote::quote!(#message)
+        .to_string()
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .any(|token| token == "Span" || token.to_lowercase().ends_with("span"))
+}
+
+/// Names the channel an expression sends or receives on
+///
+/// The sender/receiver affix is removed so both ends share a name: `jobs_tx`
+/// and `jobs_rx` are `jobs`, while `tx`, `sender` and `rx` are all `""`.
+fn channel_name(receiver: &Expr) -> Option<String> {
+    let ident = match receiver {
+        Expr::Path(path) => path.path.get_ident()?.to_string(),
+        Expr::Field(field) => match &field.member {
+            syn::Member::Named(ident) => ident.to_string(),
+            syn::Member::Unnamed(_) => return None,
+        },
+        Expr::Reference(reference) => return channel_name(&reference.expr),
+        Expr::Paren(inner) => return channel_name(&inner.expr),
+        _ => return None,
+    };
+
+    let ident = ident.to_lowercase();
+    let affixes = ["tx", "rx", "sender", "receiver", "send", "recv"];
+    for affix in affixes {
+        if ident == affix {
+            return Some(String::new());
+        }
+        if let Some(stem) = ident.strip_suffix(&format!("_{}", affix)) {
+            return Some(stem.to_string());
+        }
+        if let Some(stem) = ident.strip_prefix(&format!("{}_", affix)) {
+            return Some(stem.to_string());
+        }
+    }
+
+    None
+}
+
+/// Checks whether a future is wrapped with a span-propagating adapter
+fn carries_span(task: &Expr) -> bool {
+    match task {
+        Expr::MethodCall(call) => {
+            SPAN_PROPAGATORS.contains(&call.method.to_string().as_str())
+                || carries_span(&call.receiver)
+        }
+        Expr::Paren(inner) => carries_span(&inner.expr),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(code: &str) -> SourceFile {
+        SourceFile {
+            path: PathBuf::from("src/lib.rs"),
+            syntax_tree: syn::parse_file(code).unwrap(),
+            source: code.to_string(),
+            content_hash: String::new(),
+        }
+    }
+
+    fn analyze(
+        code: &str,
+        detect: fn(&CallGraph, &[SourceFile], &[ExistingInstrumentation]) -> Vec<ContextBreak>,
+    ) -> Vec<ContextBreak> {
+        let files = vec![source(code)];
+        let mut builder = crate::call_graph::GraphBuilder::new();
+        builder.add_parsed_file(&files[0]).unwrap();
+        let graph = builder.build().unwrap();
+        let existing = crate::detector::existing::detect_existing_instrumentation(&files, &graph);
+        detect(&graph, &files, &existing)
+    }
+
+    fn lost_spans(code: &str) -> Vec<ContextBreak> {
+        analyze(code, detect_lost_spans)
+    }
+
+    fn broken_chains(code: &str) -> Vec<ContextBreak> {
+        analyze(code, detect_broken_chains)
+    }
+
+    #[test]
+    fn test_context_boundaries() {
+        let boundaries = find_context_boundaries(&[source(
+            r#"
+            fn handler(&self) {
+                tokio::task::spawn_blocking(move || compress());
+                let span = tracing::Span::current();
+                std::thread::spawn(move || span.in_scope(|| compress()));
+                self.jobs_tx.send(job);
+                self.jobs_tx.send((job, Span::current()));
+                client.post(url).send();
+                self.handlers.push(Box::new(move |event| log(event)));
+                let job = Job { on_done: Some(Box::new(move || notify())) };
+            }
+            "#,
+        )]);
+
+        let summary: Vec<_> = boundaries
+            .iter()
+            .map(|b| (b.kind, b.call.as_str(), b.propagates_span))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    BoundaryKind::BlockingTask,
+                    "tokio::task::spawn_blocking",
+                    false
+                ),
+                (BoundaryKind::Thread, "std::thread::spawn", true),
+                (BoundaryKind::Channel, "self.jobs_tx.send", false),
+                (BoundaryKind::Channel, "self.jobs_tx.send", true),
+                (BoundaryKind::StoredCallback, "self.handlers.push", false),
+                (BoundaryKind::StoredCallback, "Job { on_done }", false),
+            ]
+        );
+        assert_eq!(boundaries[2].channel.as_deref(), Some("jobs"));
+    }
+
+    #[test]
+    fn test_broken_chains_between_instrumented_functions() {
+        let breaks = broken_chains(
+            r#"
+            #[instrument]
+            async fn create_order(jobs_tx: Sender<Job>) {
+                validate();
+                tokio::task::spawn_blocking(move || render_invoice());
+                tokio::spawn(notify().in_current_span());
+                jobs_tx.send(Job::new());
+            }
+
+            fn validate() {
+                audit();
+            }
+
+            #[instrument]
+            fn audit() {}
+
+            #[instrument]
+            fn render_invoice() {}
+
+            #[instrument]
+            async fn notify() {}
+
+            async fn worker(mut jobs_rx: Receiver<Job>) {
+                while let Some(job) = jobs_rx.recv().await {
+                    charge(job);
+                }
+            }
+
+            #[instrument]
+            fn charge(job: Job) {}
+            "#,
+        );
+
+        let summary: Vec<_> = breaks
+            .iter()
+            .map(|b| (b.kind, b.caller.as_str(), b.callees.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    BoundaryKind::BlockingTask,
+                    "create_order",
+                    vec!["render_invoice".to_string()]
+                ),
+                (
+                    BoundaryKind::Channel,
+                    "create_order",
+                    vec!["charge".to_string()]
+                ),
+            ]
+        );
+        assert_eq!(breaks[0].location.line, 5);
+        assert_eq!(breaks[0].call, "tokio::task::spawn_blocking");
+    }
+
+    #[test]
+    fn test_spawned_future_without_span() {
+        let lost = lost_spans(
+            r#"
+            async fn create_order() {
+                tokio::spawn(async move {
+                    send_email().await;
+                });
+            }
+
+            impl Worker {
+                fn start(&self, set: &mut JoinSet<()>) {
+                    set.spawn(run(self.rx.clone()));
+                }
+            }
+            "#,
+        );
+
+        assert_eq!(lost.len(), 2);
+        assert_eq!(lost[0].location.function_name, "create_order");
+        assert_eq!(lost[0].location.line, 3);
+        assert_eq!(lost[0].call, "tokio::spawn");
+        assert_eq!(lost[0].caller, "create_order");
+        assert_eq!(
+            lost[0].suggested_fix,
+            BoundaryKind::SpawnedTask.suggested_fix()
+        );
+        assert_eq!(lost[1].location.function_name, "Worker::start");
+    }
+
+    #[test]
+    fn test_instrumented_and_blocking_tasks_are_ignored() {
+        let lost = lost_spans(
+            r#"
+            async fn handler() {
+                tokio::spawn(async move { work().await }.in_current_span());
+                tokio::spawn(work().instrument(tracing::info_span!("work")));
+                tokio::task::spawn_blocking(move || compress());
+                std::thread::spawn(move || compress());
+                std::process::Command::new("ls").spawn();
+            }
+            "#,
+        );
+
+        assert!(lost.is_empty());
+    }
+}
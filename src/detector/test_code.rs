@@ -0,0 +1,192 @@
+//! Test code detection
+//!
+//! Finds the parts of the analyzed sources that only exist for testing:
+//! functions with a test attribute, `#[cfg(test)]` modules and `impl` blocks,
+//! and the files of a package's `tests/` directory. Unless `include_tests` is
+//! set, findings in test code are left out of the analysis.
+
+use crate::ast::SourceFile;
+use crate::files::is_test_file;
+use crate::patterns::TestMarkers;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{Attribute, ImplItemFn, ItemFn, ItemImpl, ItemMod};
+
+/// Line ranges of test code in each analyzed file
+#[derive(Debug, Clone, Default)]
+pub struct TestCode {
+    /// Files that are test code as a whole
+    files: Vec<PathBuf>,
+    /// Inclusive line ranges of test functions and modules by file
+    ranges: HashMap<PathBuf, Vec<(usize, usize)>>,
+}
+
+impl TestCode {
+    /// Finds the test code in the parsed files
+    ///
+    /// # Arguments
+    ///
+    /// * `files` - The parsed source files
+    /// * `markers` - Attribute patterns that mark test items
+    ///
+    /// # Returns
+    ///
+    /// The test files and the line ranges of test items in the others
+    pub fn find(files: &[SourceFile], markers: &TestMarkers) -> Self {
+        let mut test_code = Self::default();
+
+        for file in files {
+            if is_test_file(file.path()) {
+                test_code.files.push(file.path().to_path_buf());
+                continue;
+            }
+
+            let mut visitor = TestVisitor {
+                markers,
+                ranges: Vec::new(),
+            };
+            visitor.visit_file(&file.syntax_tree);
+            if !visitor.ranges.is_empty() {
+                test_code
+                    .ranges
+                    .insert(file.path().to_path_buf(), visitor.ranges);
+            }
+        }
+
+        test_code
+    }
+
+    /// Checks whether a line is test code
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The source file
+    /// * `line` - The line to check (1-based)
+    pub fn contains(&self, file: &Path, line: usize) -> bool {
+        self.files.iter().any(|f| f == file)
+            || self.ranges.get(file).is_some_and(|ranges| {
+                ranges
+                    .iter()
+                    .any(|&(start, end)| (start..=end).contains(&line))
+            })
+    }
+}
+
+/// Collects the line ranges of test items in a file
+struct TestVisitor<'a> {
+    markers: &'a TestMarkers,
+    ranges: Vec<(usize, usize)>,
+}
+
+impl TestVisitor<'_> {
+    /// Records the item's lines, attributes included, if it is marked as test code
+    ///
+    /// Returns whether the item was recorded, in which case its contents
+    /// needn't be visited.
+    fn record(&mut self, attrs: &[Attribute], item: &impl Spanned) -> bool {
+        let is_test = attrs
+            .iter()
+            .any(|attr| self.markers.is_test_attribute(attr));
+        if is_test {
+            let span = item.span();
+            self.ranges.push((span.start().line, span.end().line));
+        }
+        is_test
+    }
+}
+
+impl<'ast> Visit<'ast> for TestVisitor<'_> {
+    fn visit_item_fn(&mut self, item: &'ast ItemFn) {
+        if !self.record(&item.attrs, item) {
+            visit::visit_item_fn(self, item);
+        }
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &'ast ImplItemFn) {
+        if !self.record(&item.attrs, item) {
+            visit::visit_impl_item_fn(self, item);
+        }
+    }
+
+    fn visit_item_mod(&mut self, item: &'ast ItemMod) {
+        if !self.record(&item.attrs, item) {
+            visit::visit_item_mod(self, item);
+        }
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast ItemImpl) {
+        if !self.record(&item.attrs, item) {
+            visit::visit_item_impl(self, item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finds_test_functions_and_modules() {
+        let source = r#"
+pub fn handler() {}
+
+#[tokio::test]
+async fn handler_works() {
+    handler();
+}
+
+#[cfg(test)]
+mod tests {
+    fn helper() {}
+}
+
+pub fn after() {}
+"#;
+        let file = SourceFile {
+            path: PathBuf::from("src/lib.rs"),
+            syntax_tree: syn::parse_file(source).unwrap(),
+            source: source.to_string(),
+            content_hash: String::new(),
+        };
+        let test_code = TestCode::find(&[file], &TestMarkers::default());
+        let path = Path::new("src/lib.rs");
+
+        assert!(!test_code.contains(path, 2));
+        assert!(test_code.contains(path, 4));
+        assert!(test_code.contains(path, 6));
+        assert!(test_code.contains(path, 11));
+        assert!(!test_code.contains(path, 14));
+    }
+    #[test]
+    fn test_finds_test_impl_blocks() {
+        let source = r#"
+pub struct Orders;
+
+#[cfg(test)]
+impl Orders {
+    fn fixture() -> Self {
+        Orders
+    }
+}
+
+impl Orders {
+    pub fn list(&self) {}
+}
+"#;
+        let file = SourceFile {
+            path: PathBuf::from("src/orders.rs"),
+            syntax_tree: syn::parse_file(source).unwrap(),
+            source: source.to_string(),
+            content_hash: String::new(),
+        };
+        let test_code = TestCode::find(&[file], &TestMarkers::default());
+        let path = Path::new("src/orders.rs");
+
+        assert!(!test_code.contains(path, 2));
+        assert!(test_code.contains(path, 4));
+        assert!(test_code.contains(path, 7));
+        assert!(!test_code.contains(path, 13));
+    }
+}
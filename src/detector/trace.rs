@@ -0,0 +1,186 @@
+//! Endpoint-rooted call path tracing
+//!
+//! Walks the call graph from each endpoint's handler so instrumentation points
+//! can be limited to code that serves requests, and so each point records the
+//! endpoints and calls that reach it.
+
+use super::{CallPath, Endpoint, InstrumentationKind, InstrumentationPoint};
+use crate::call_graph::CallGraph;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Call paths from endpoints to the functions they reach
+#[derive(Debug, Default)]
+pub struct EndpointTrace {
+    /// Call paths reaching each function, keyed by function ID
+    paths: HashMap<String, Vec<CallPath>>,
+    /// Handler function ID of each endpoint, keyed by the endpoint's location
+    handlers: HashMap<(PathBuf, usize), String>,
+}
+
+impl EndpointTrace {
+    /// Traces the call graph from every endpoint
+    ///
+    /// # Arguments
+    ///
+    /// * `graph` - The call graph to walk
+    /// * `endpoints` - Detected endpoints; those whose handler isn't in the
+    ///   graph are skipped
+    /// * `max_depth` - Maximum number of calls to follow from a handler
+    ///
+    /// # Returns
+    ///
+    /// The shortest call path from each endpoint to each function it reaches
+    pub fn new(graph: &CallGraph, endpoints: &[Endpoint], max_depth: usize) -> Self {
+        let mut trace = Self::default();
+
+        for endpoint in endpoints {
+            // Endpoint locations are named after their handler's node
+            let Some(handler) = graph.get_node(&endpoint.location.function_name) else {
+                continue;
+            };
+            trace.handlers.insert(
+                (endpoint.location.file.clone(), endpoint.location.line),
+                handler.id.clone(),
+            );
+
+            let label = format!("{} {}", endpoint.method, endpoint.path);
+            let mut reached: Vec<_> = graph
+                .shortest_paths_from(&handler.id, max_depth)
+                .into_iter()
+                .collect();
+            reached.sort();
+
+            for (function_id, functions) in reached {
+                let paths = trace.paths.entry(function_id).or_default();
+                if !paths.iter().any(|p| p.endpoint == label) {
+                    paths.push(CallPath {
+                        endpoint: label.clone(),
+                        functions,
+                    });
+                }
+            }
+        }
+
+        trace
+    }
+
+    /// Returns the call paths reaching a function
+    ///
+    /// # Arguments
+    ///
+    /// * `function_id` - ID of the function's call graph node
+    pub fn paths_to(&self, function_id: &str) -> &[CallPath] {
+        self.paths.get(function_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Checks whether any endpoint reaches a function
+    ///
+    /// # Arguments
+    ///
+    /// * `function_id` - ID of the function's call graph node
+    pub fn reaches(&self, function_id: &str) -> bool {
+        self.paths.contains_key(function_id)
+    }
+
+    /// Returns the call paths reaching an instrumentation point
+    ///
+    /// Endpoint points are located at their handler, so they are looked up
+    /// through the handler found for the endpoint.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The instrumentation point
+    pub fn paths_for(&self, point: &InstrumentationPoint) -> Vec<CallPath> {
+        let function_id = if point.kind == InstrumentationKind::Endpoint {
+            let key = (point.location.file.clone(), point.location.line);
+            match self.handlers.get(&key) {
+                Some(handler) => handler.as_str(),
+                None => return Vec::new(),
+            }
+        } else {
+            point.location.function_name.as_str()
+        };
+
+        self.paths_to(function_id).to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::SourceFile;
+    use crate::call_graph::GraphBuilder;
+    use crate::detector::Location;
+
+    fn endpoint(handler: &str, path: &str) -> Endpoint {
+        Endpoint {
+            method: "GET".to_string(),
+            path: path.to_string(),
+            handler: handler.to_string(),
+            location: Location {
+                file: PathBuf::from("src/lib.rs"),
+                line: 1,
+                column: 1,
+                function_name: handler.to_string(),
+            },
+            framework: "axum".to_string(),
+            parameters: vec![],
+            documentation: None,
+            streaming: None,
+        }
+    }
+
+    #[test]
+    fn test_paths_from_endpoints() {
+        let code = r#"
+            mod handlers {
+                pub fn list_users() { crate::service::load(); }
+                pub fn health() {}
+            }
+            mod service {
+                pub fn load() { crate::repo::query(); }
+            }
+            mod repo {
+                pub fn query() {}
+            }
+            fn unused() {}
+        "#;
+        let source = SourceFile {
+            path: PathBuf::from("src/lib.rs"),
+            syntax_tree: syn::parse_file(code).unwrap(),
+            source: code.to_string(),
+            content_hash: String::new(),
+        };
+        let mut builder = GraphBuilder::new();
+        builder.add_parsed_file(&source).unwrap();
+        let graph = builder.build().unwrap();
+
+        // Only endpoints attributed to their handler's node are traced
+        let endpoints = vec![
+            endpoint("handlers::list_users", "/users"),
+            endpoint("handlers::health", "/health"),
+            endpoint("query", "/query"),
+        ];
+
+        let trace = EndpointTrace::new(&graph, &endpoints, 10);
+        assert_eq!(
+            trace.paths_to("repo::query"),
+            &[CallPath {
+                endpoint: "GET /users".to_string(),
+                functions: vec![
+                    "handlers::list_users".to_string(),
+                    "service::load".to_string(),
+                    "repo::query".to_string(),
+                ],
+            }]
+        );
+        assert!(trace.reaches("handlers::health"));
+        assert!(!trace.reaches("unused"));
+
+        // The depth limit stops the walk
+        let trace = EndpointTrace::new(&graph, &endpoints, 1);
+        assert!(trace.reaches("service::load"));
+        assert!(!trace.reaches("repo::query"));
+    }
+}
@@ -0,0 +1,323 @@
+//! Source file discovery
+//!
+//! Decides which `.rs` files are analyzed. Directories are walked with
+//! `.gitignore` and `.ignore` rules applied, limited to each package's
+//! `source_dirs`, and filtered by the configured include and exclude globs.
+
+use crate::config::Config;
+use crate::error::{Error, Result};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use std::path::{Path, PathBuf};
+
+/// Collects the source files to analyze
+#[derive(Debug, Clone)]
+pub struct FileWalker {
+    /// Files must match one of these, if any are configured
+    include: Option<GlobSet>,
+    /// Files and directories matching these are skipped
+    exclude: GlobSet,
+    /// Directories of a package that hold its sources
+    source_dirs: Vec<PathBuf>,
+    /// Whether `.gitignore` and `.ignore` files are honoured
+    respect_ignore_files: bool,
+}
+
+impl FileWalker {
+    /// Creates a walker from the configuration
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Provides `include_patterns`, `exclude_patterns`,
+    ///   `source_dirs` and `respect_ignore_files`, with `tests` added to the
+    ///   source directories when `include_tests` is set
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a pattern is not a valid glob
+    pub fn new(config: &Config) -> Result<Self> {
+        let include = if config.include_patterns.is_empty() {
+            None
+        } else {
+            Some(glob_set(&config.include_patterns)?)
+        };
+
+        // Integration tests live next to the sources, in `tests/`
+        let mut source_dirs = config.source_dirs.clone();
+        let tests = PathBuf::from("tests");
+        if config.include_tests && !source_dirs.contains(&tests) {
+            source_dirs.push(tests);
+        }
+
+        Ok(Self {
+            include,
+            exclude: glob_set(&config.exclude_patterns)?,
+            source_dirs,
+            respect_ignore_files: config.respect_ignore_files,
+        })
+    }
+
+    /// Collects the `.rs` files under the given paths
+    ///
+    /// Files passed directly are always included. In a directory, a file is
+    /// included when it:
+    ///
+    /// - isn't ignored by a `.gitignore` or `.ignore` file,
+    /// - is under one of `source_dirs` of its package (the nearest directory
+    ///   with a `Cargo.toml`, or the walked directory), unless that package
+    ///   has none of them,
+    /// - matches an include pattern, if any are configured, and
+    /// - matches no exclude pattern.
+    ///
+    /// Patterns match paths relative to the walked directory. A pattern
+    /// without a `/` matches any file or directory name (`target`,
+    /// `*_generated.rs`); one with a `/` matches from the walked directory
+    /// (`src/legacy/**`).
+    ///
+    /// # Arguments
+    ///
+    /// * `paths` - Files and directories to analyze
+    ///
+    /// # Returns
+    ///
+    /// The files to analyze, sorted within each directory
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a directory cannot be walked
+    pub fn collect<P: AsRef<Path>>(&self, paths: &[P]) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+
+        for path in paths {
+            let path = path.as_ref();
+            if path.is_file() {
+                if is_rust_file(path) {
+                    files.push(path.to_path_buf());
+                }
+            } else if path.is_dir() {
+                files.extend(self.walk(path)?);
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Walks a directory, pruning excluded directories as it goes
+    fn walk(&self, root: &Path) -> Result<Vec<PathBuf>> {
+        let exclude = self.exclude.clone();
+        let prune_root = root.to_path_buf();
+
+        let mut builder = WalkBuilder::new(root);
+        builder
+            .standard_filters(self.respect_ignore_files)
+            .hidden(false)
+            .require_git(false)
+            .follow_links(true)
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .filter_entry(move |entry| {
+                let relative = entry
+                    .path()
+                    .strip_prefix(&prune_root)
+                    .unwrap_or(entry.path());
+                relative.as_os_str().is_empty() || !exclude.is_match(relative)
+            });
+
+        let mut files = Vec::new();
+        for entry in builder.build() {
+            let entry = entry
+                .map_err(|e| Error::Generic(format!("cannot walk {}: {e}", root.display())))?;
+            let path = entry.path();
+            if !entry.file_type().is_some_and(|t| t.is_file()) || !is_rust_file(path) {
+                continue;
+            }
+
+            let relative = path.strip_prefix(root).unwrap_or(path);
+            let included = self
+                .include
+                .as_ref()
+                .is_none_or(|include| include.is_match(relative));
+            if included && self.in_source_dir(root, path) {
+                files.push(path.to_path_buf());
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Checks whether a file is under one of its package's source directories
+    fn in_source_dir(&self, root: &Path, file: &Path) -> bool {
+        let package = file
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(root))
+            .find(|dir| dir.join("Cargo.toml").is_file())
+            .unwrap_or(root);
+
+        let source_dirs: Vec<PathBuf> = self
+            .source_dirs
+            .iter()
+            .map(|dir| package.join(dir))
+            .filter(|dir| dir.is_dir())
+            .collect();
+
+        source_dirs.is_empty() || source_dirs.iter().any(|dir| file.starts_with(dir))
+    }
+}
+
+/// Compiles patterns, matching bare names at any depth
+fn glob_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+
+    for pattern in patterns {
+        let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+        let anchored = if pattern.contains('/') {
+            pattern.to_string()
+        } else {
+            format!("**/{pattern}")
+        };
+
+        for glob in [anchored.clone(), format!("{anchored}/**")] {
+            let glob = GlobBuilder::new(&glob)
+                .literal_separator(true)
+                .build()
+                .map_err(|e| Error::Config(format!("invalid pattern '{pattern}': {e}")))?;
+            builder.add(glob);
+        }
+    }
+
+    builder
+        .build()
+        .map_err(|e| Error::Config(format!("invalid patterns: {e}")))
+}
+
+/// Checks whether a file is in a package's `tests/` directory
+///
+/// The package is the nearest directory with a `Cargo.toml`. Files outside
+/// any package are never considered test files.
+///
+/// # Arguments
+///
+/// * `path` - The source file
+pub fn is_test_file(path: &Path) -> bool {
+    path.ancestors()
+        .skip(1)
+        .find(|dir| dir.join("Cargo.toml").is_file())
+        .and_then(|package| path.strip_prefix(package).ok())
+        .is_some_and(|relative| relative.starts_with("tests"))
+}
+
+/// Checks whether a path has the `.rs` extension
+fn is_rust_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "rs")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn collect(root: &Path, config: &Config) -> Vec<String> {
+        FileWalker::new(config)
+            .unwrap()
+            .collect(&[root])
+            .unwrap()
+            .iter()
+            .map(|file| {
+                file.strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_globs_match_names_not_substrings() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        write(root, "src/attestation.rs", "");
+        write(root, "src/test/helpers.rs", "");
+        write(root, "src/proto/user_generated.rs", "");
+        write(root, "src/legacy/old.rs", "");
+        write(root, "src/lib.rs", "");
+
+        let config = Config {
+            exclude_patterns: vec![
+                "test".to_string(),
+                "*_generated.rs".to_string(),
+                "src/legacy/**".to_string(),
+            ],
+            ..Config::default()
+        };
+
+        assert_eq!(
+            collect(root, &config),
+            vec!["src/attestation.rs", "src/lib.rs"]
+        );
+
+        let config = Config {
+            include_patterns: vec!["src/proto/**".to_string()],
+            ..Config::default()
+        };
+        assert_eq!(collect(root, &config), vec!["src/proto/user_generated.rs"]);
+
+        assert!(
+            FileWalker::new(&Config {
+                exclude_patterns: vec!["src/[".to_string()],
+                ..Config::default()
+            })
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_ignore_files_and_source_dirs() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        write(root, "Cargo.toml", "[workspace]\n");
+        write(root, ".gitignore", "src/generated/\n");
+        write(root, "src/lib.rs", "");
+        write(root, "src/generated/schema.rs", "");
+        write(root, "src/scratch.rs", "");
+        write(root, ".ignore", "scratch.rs\n");
+        write(root, "build.rs", "");
+        write(root, "api/Cargo.toml", "");
+        write(root, "api/src/main.rs", "");
+        write(root, "api/benches/load.rs", "");
+        write(root, "tools/gen.rs", "");
+
+        assert_eq!(
+            collect(root, &Config::default()),
+            vec!["api/src/main.rs", "src/lib.rs"]
+        );
+
+        let config = Config {
+            respect_ignore_files: false,
+            source_dirs: vec![PathBuf::from("src"), PathBuf::from("benches")],
+            ..Config::default()
+        };
+        assert_eq!(
+            collect(root, &config),
+            vec![
+                "api/benches/load.rs",
+                "api/src/main.rs",
+                "src/generated/schema.rs",
+                "src/lib.rs",
+                "src/scratch.rs",
+            ]
+        );
+
+        // A directory without any of the source dirs is analyzed whole
+        assert_eq!(
+            collect(&root.join("tools"), &Config::default()),
+            vec!["gen.rs"]
+        );
+    }
+}
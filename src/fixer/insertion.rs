@@ -41,7 +41,7 @@ pub fn plan_insertions(source: &str, gaps: &[&InstrumentationGap]) -> Vec<Planne
     }
 
     // Sort by line number descending (process bottom-up to maintain line numbers)
-    insertions.sort_by(|a, b| b.target_line.cmp(&a.target_line));
+    insertions.sort_by_key(|b| std::cmp::Reverse(b.target_line));
 
     insertions
 }
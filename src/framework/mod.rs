@@ -1,7 +1,7 @@
 //! Framework detection and integration for web frameworks
 //!
 //! This module provides automatic detection of web frameworks (Axum, Actix-web,
-//! Rocket, Tonic) used in Rust projects. It identifies framework-specific
+//! Rocket, Tonic, Warp, Poem) used in Rust projects. It identifies framework-specific
 //! patterns, endpoints, and handlers.
 //!
 //! # Supported Web Frameworks
@@ -10,6 +10,8 @@
 //! - **Actix-web**: Actor-based web framework
 //! - **Rocket**: Web framework with code generation
 //! - **Tonic**: gRPC framework for Rust
+//! - **Warp**: Web framework built from composable filters
+//! - **Poem**: Web framework with `#[handler]` endpoints
 
 pub mod adapter;
 pub mod detector;
@@ -26,6 +28,10 @@ pub enum DetectedFramework {
     Rocket,
     /// Tonic gRPC framework
     Tonic,
+    /// Warp web framework
+    Warp,
+    /// Poem web framework
+    Poem,
     /// Unknown or no framework detected
     #[default]
     Unknown,
@@ -40,6 +46,8 @@ impl DetectedFramework {
             Self::Actix => "actix-web",
             Self::Rocket => "rocket",
             Self::Tonic => "tonic",
+            Self::Warp => "warp",
+            Self::Poem => "poem",
             Self::Unknown => "unknown",
         }
     }
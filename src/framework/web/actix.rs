@@ -0,0 +1,851 @@
+//! Actix-web framework detector and analyzer
+//!
+//! This module implements the `FrameworkDetector` trait for Actix-web. Routes
+//! are collected from route attribute macros (`#[get]`, `#[route]`, ...) and
+//! from service registration chains on `App`, `web::scope`, `web::resource`
+//! and `ServiceConfig`, then composed into full paths.
+
+use super::routing::{
+    RouterFunction, binding_name, empty_router, file_endpoints, handler_name, merge_router,
+    path_of, path_to_string, project_endpoints, references_crate, span_line, string_literal,
+    strip_parens,
+};
+use super::{
+    Endpoint, FrameworkDetector, HandlerInfo, HandlerParam, HttpMethod, RouteInfo, RouterInfo,
+    RouterMount,
+};
+use crate::Result;
+use crate::ast::SourceFile;
+use crate::call_graph::ModuleTree;
+use std::path::Path;
+use syn::punctuated::Punctuated;
+use syn::{
+    Attribute, Expr, ExprMethodCall, FnArg, Item, ItemFn, Pat, ReturnType, Signature, Token,
+    visit::Visit,
+};
+
+/// Detector for the Actix-web framework
+pub struct ActixDetector {
+    /// Known Actix-web extractor types
+    extractors: Vec<String>,
+}
+
+impl ActixDetector {
+    /// Create a new Actix-web detector
+    pub fn new() -> Self {
+        Self {
+            extractors: vec![
+                "Path".to_string(),
+                "Query".to_string(),
+                "Json".to_string(),
+                "Form".to_string(),
+                "Data".to_string(),
+                "ReqData".to_string(),
+                "Header".to_string(),
+                "HttpRequest".to_string(),
+                "Payload".to_string(),
+                "Bytes".to_string(),
+                "Multipart".to_string(),
+                "Identity".to_string(),
+                "Session".to_string(),
+            ],
+        }
+    }
+
+    /// Check if a type is an Actix-web extractor
+    pub fn is_extractor(&self, ty: &str) -> bool {
+        self.extractors.iter().any(|e| ty.contains(e))
+    }
+
+    /// Parse the route attribute macros of a handler
+    ///
+    /// Supports `#[get("/path")]` style attributes as well as
+    /// `#[route("/path", method = "GET", method = "HEAD")]`. Each method
+    /// produces its own route.
+    fn attribute_routes(&self, attrs: &[Attribute], handler: &str) -> Vec<RouteInfo> {
+        let mut routes = Vec::new();
+
+        for attr in attrs {
+            let Some(name) = attr.path().segments.last().map(|s| s.ident.to_string()) else {
+                continue;
+            };
+            let is_route = name == "route";
+            let attr_method = HttpMethod::standard(&name);
+            if !is_route && attr_method.is_none() {
+                continue;
+            }
+
+            let Ok(args) = attr.parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated)
+            else {
+                continue;
+            };
+            let Some(path) = args.first().and_then(string_literal) else {
+                continue;
+            };
+
+            let mut methods: Vec<HttpMethod> = attr_method.into_iter().collect();
+            let mut middleware = Vec::new();
+            for arg in args.iter().skip(1) {
+                let Expr::Assign(assign) = arg else {
+                    continue;
+                };
+                let key = path_of(&assign.left).map(path_to_string);
+                let Some(value) = string_literal(&assign.right) else {
+                    continue;
+                };
+                match key.as_deref() {
+                    Some("method") if is_route => {
+                        methods.extend(HttpMethod::standard(&value));
+                    }
+                    Some("wrap") => middleware.push(value),
+                    _ => {}
+                }
+            }
+
+            let line = span_line(attr.pound_token.spans[0]);
+            for method in methods {
+                routes.push(RouteInfo {
+                    method,
+                    path: path.clone(),
+                    handler: handler.to_string(),
+                    middleware: middleware.clone(),
+                    line,
+                });
+            }
+        }
+
+        routes
+    }
+
+    /// Check whether a handler has a route attribute macro
+    fn has_route_attribute(&self, attrs: &[Attribute]) -> bool {
+        attrs.iter().any(|attr| {
+            attr.path().segments.last().is_some_and(|s| {
+                let name = s.ident.to_string();
+                name == "route" || HttpMethod::standard(&name).is_some()
+            })
+        })
+    }
+
+    /// Parse handler parameters from function signature
+    fn parse_handler_params(&self, sig: &Signature) -> Vec<HandlerParam> {
+        sig.inputs
+            .iter()
+            .filter_map(|input| match input {
+                FnArg::Typed(pat_type) => {
+                    let ty = &pat_type.ty;
+                    let ty = quote::quote!(#ty).to_string();
+                    Some(HandlerParam {
+                        name: binding_name(&pat_type.pat),
+                        is_extractor: self.is_extractor(&ty),
+                        ty,
+                    })
+                }
+                FnArg::Receiver(_) => None,
+            })
+            .collect()
+    }
+
+    /// Collect every router-building function and attribute handler in a file
+    ///
+    /// Attribute handlers become single-route routers named after the
+    /// function, so `.service(handler)` registrations resolve to them like any
+    /// other mounted router. Attribute handlers are only considered in files
+    /// that reference `actix_web`, which keeps Rocket handlers (which share
+    /// the same attribute names) out.
+    ///
+    /// # Arguments
+    ///
+    /// * `syntax_tree` - The parsed syntax tree of a source file
+    /// * `module_path` - Module path of the file within its crate
+    pub fn router_functions(
+        &self,
+        syntax_tree: &syn::File,
+        module_path: &[String],
+    ) -> Vec<RouterFunction> {
+        let uses_actix = references_crate(&syntax_tree.items, "actix_web");
+        let mut routers = Vec::new();
+        self.collect_router_functions(&syntax_tree.items, module_path, uses_actix, &mut routers);
+        routers
+    }
+
+    fn collect_router_functions(
+        &self,
+        items: &[Item],
+        module_path: &[String],
+        uses_actix: bool,
+        routers: &mut Vec<RouterFunction>,
+    ) {
+        for item in items {
+            match item {
+                Item::Fn(item_fn) => {
+                    let name = item_fn.sig.ident.to_string();
+
+                    if uses_actix {
+                        let routes = self.attribute_routes(&item_fn.attrs, &name);
+                        if !routes.is_empty() {
+                            let mut info = empty_router();
+                            info.name = name.clone();
+                            info.routes = routes;
+                            routers.push(RouterFunction {
+                                module_path: module_path.to_vec(),
+                                info,
+                            });
+                        }
+                    }
+
+                    let mut visitor = ServiceVisitor::new(self, config_params(&item_fn.sig));
+                    visitor.visit_block(&item_fn.block);
+                    if let Some(info) = visitor.finish(name) {
+                        routers.push(RouterFunction {
+                            module_path: module_path.to_vec(),
+                            info,
+                        });
+                    }
+                }
+                Item::Impl(item_impl) => {
+                    let self_ty = &item_impl.self_ty;
+                    let mut impl_path = module_path.to_vec();
+                    impl_path.push(quote::quote!(#self_ty).to_string().replace(' ', ""));
+
+                    for impl_item in &item_impl.items {
+                        if let syn::ImplItem::Fn(method) = impl_item {
+                            let mut visitor = ServiceVisitor::new(self, config_params(&method.sig));
+                            visitor.visit_block(&method.block);
+                            if let Some(info) = visitor.finish(method.sig.ident.to_string()) {
+                                routers.push(RouterFunction {
+                                    module_path: impl_path.clone(),
+                                    info,
+                                });
+                            }
+                        }
+                    }
+                }
+                Item::Mod(item_mod) => {
+                    if let Some((_, items)) = &item_mod.content {
+                        let mut nested = module_path.to_vec();
+                        nested.push(item_mod.ident.to_string());
+                        self.collect_router_functions(items, &nested, uses_actix, routers);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Extract `(method, handler)` pairs from a route expression
+    ///
+    /// Handles `web::get().to(h)`, `web::route().method(Method::GET).to(h)`,
+    /// `web::route().guard(guard::Post()).to(h)` and `web::method(..).to(h)`.
+    /// A route without a method constraint matches any method.
+    fn route_methods(&self, expr: &Expr) -> Vec<(HttpMethod, String)> {
+        let mut calls = Vec::new();
+        let mut root = strip_parens(expr);
+        while let Expr::MethodCall(call) = root {
+            calls.push(call);
+            root = strip_parens(&call.receiver);
+        }
+
+        let Expr::Call(root_call) = root else {
+            return Vec::new();
+        };
+        let Some(root_name) = path_of(&root_call.func)
+            .and_then(|p| p.segments.last())
+            .map(|s| s.ident.to_string())
+        else {
+            return Vec::new();
+        };
+
+        let mut methods: Vec<HttpMethod> = match root_name.as_str() {
+            "route" => Vec::new(),
+            "method" => root_call
+                .args
+                .first()
+                .and_then(|arg| self.method_from_expr(arg))
+                .into_iter()
+                .collect(),
+            name => match HttpMethod::standard(name) {
+                Some(method) => vec![method],
+                None => return Vec::new(),
+            },
+        };
+
+        let mut handler = None;
+        for call in calls.iter().rev() {
+            let Some(arg) = call.args.first() else {
+                continue;
+            };
+            match call.method.to_string().as_str() {
+                "method" | "guard" => methods.extend(self.method_from_expr(arg)),
+                "to" => handler = handler_name(arg),
+                _ => {}
+            }
+        }
+
+        let Some(handler) = handler else {
+            return Vec::new();
+        };
+        if methods.is_empty() {
+            methods.push(HttpMethod::Custom("ANY"));
+        }
+
+        methods
+            .into_iter()
+            .map(|method| (method, handler.clone()))
+            .collect()
+    }
+
+    /// Method named by `Method::GET` or a `guard::Get()` guard
+    fn method_from_expr(&self, expr: &Expr) -> Option<HttpMethod> {
+        let path = match strip_parens(expr) {
+            Expr::Call(call) => path_of(&call.func)?,
+            other => path_of(other)?,
+        };
+        HttpMethod::standard(&path.segments.last()?.ident.to_string())
+    }
+}
+
+impl Default for ActixDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameworkDetector for ActixDetector {
+    fn name(&self) -> &'static str {
+        "Actix-web"
+    }
+
+    fn detect(&self, project_root: &Path) -> Result<bool> {
+        super::manifest_has_dependency(project_root, "actix-web")
+    }
+
+    fn extract_endpoints(&self, syntax_tree: &syn::File) -> Vec<Endpoint> {
+        file_endpoints(syntax_tree, |tree, module_path| {
+            self.router_functions(tree, module_path)
+        })
+    }
+
+    fn extract_project_endpoints(
+        &self,
+        files: &[SourceFile],
+        modules: &ModuleTree,
+    ) -> Vec<Endpoint> {
+        // Scopes and `configure` callbacks routinely live in other modules, so
+        // routers are composed across all files
+        project_endpoints(files, modules, |tree, module_path| {
+            self.router_functions(tree, module_path)
+        })
+    }
+
+    fn analyze_router(&self, item: &Item) -> Option<RouterInfo> {
+        match item {
+            Item::Fn(item_fn) => {
+                let mut visitor = ServiceVisitor::new(self, config_params(&item_fn.sig));
+                visitor.visit_block(&item_fn.block);
+                visitor.finish(item_fn.sig.ident.to_string())
+            }
+            _ => None,
+        }
+    }
+
+    fn detect_handler(&self, function: &ItemFn) -> Option<HandlerInfo> {
+        let sig = &function.sig;
+        let return_type = match &sig.output {
+            ReturnType::Default => "()".to_string(),
+            ReturnType::Type(_, ty) => quote::quote!(#ty).to_string(),
+        };
+
+        let is_handler = self.has_route_attribute(&function.attrs)
+            || sig.asyncness.is_some()
+            || return_type.contains("Responder")
+            || return_type.contains("HttpResponse")
+            || return_type.contains("Result");
+        if !is_handler {
+            return None;
+        }
+
+        let parameters = self.parse_handler_params(sig);
+        let extractors = parameters
+            .iter()
+            .filter(|p| p.is_extractor)
+            .map(|p| p.ty.clone())
+            .collect();
+
+        Some(HandlerInfo {
+            name: sig.ident.to_string(),
+            is_async: sig.asyncness.is_some(),
+            parameters,
+            return_type,
+            extractors,
+            documentation: super::doc_comment(&function.attrs),
+        })
+    }
+}
+
+/// Names of `ServiceConfig` parameters, whose method calls register services
+fn config_params(sig: &Signature) -> Vec<String> {
+    sig.inputs
+        .iter()
+        .filter_map(|input| match input {
+            FnArg::Typed(pat_type) => {
+                let ty = &pat_type.ty;
+                let ty = quote::quote!(#ty).to_string();
+                ty.contains("ServiceConfig")
+                    .then(|| binding_name(&pat_type.pat))
+            }
+            FnArg::Receiver(_) => None,
+        })
+        .collect()
+}
+
+/// Kind of service a registration chain starts from
+enum ServiceRoot {
+    /// `App::new()`, a `ServiceConfig` parameter or a local variable
+    Plain(RouterInfo),
+    /// `web::scope("/prefix")`
+    Scope(String),
+    /// `web::resource("/path")`
+    Resource(String),
+}
+
+/// Visitor that reconstructs the services registered by a single function
+///
+/// Registration chains are evaluated symbolically: `App::new()`,
+/// `web::scope(..)` and `web::resource(..)` start a service, `route`,
+/// `service`, `configure` and `wrap` calls add to it, and services bound to
+/// local variables are inlined where they are used.
+struct ServiceVisitor<'a> {
+    detector: &'a ActixDetector,
+    router_info: Option<RouterInfo>,
+    /// Services bound to local variables and whether another service consumed them
+    locals: Vec<(String, RouterInfo, bool)>,
+    /// Parameters of type `ServiceConfig`
+    config_params: Vec<String>,
+}
+
+impl<'a> ServiceVisitor<'a> {
+    fn new(detector: &'a ActixDetector, config_params: Vec<String>) -> Self {
+        Self {
+            detector,
+            router_info: None,
+            locals: Vec::new(),
+            config_params,
+        }
+    }
+
+    /// Combine every service found in the body into the function's router
+    fn finish(mut self, name: String) -> Option<RouterInfo> {
+        let locals = std::mem::take(&mut self.locals);
+        for (_, info, used) in locals {
+            if !used {
+                self.add_router(info);
+            }
+        }
+
+        self.router_info.map(|mut info| {
+            info.name = name;
+            info
+        })
+    }
+
+    fn add_router(&mut self, info: RouterInfo) {
+        match &mut self.router_info {
+            Some(existing) => merge_router(existing, info, ""),
+            None => self.router_info = Some(info),
+        }
+    }
+
+    fn set_local(&mut self, name: String, info: RouterInfo) {
+        if let Some(local) = self.locals.iter_mut().find(|(n, _, _)| *n == name) {
+            local.1 = info;
+            local.2 = false;
+        } else {
+            self.locals.push((name, info, false));
+        }
+    }
+
+    fn use_local(&mut self, name: &str) -> Option<RouterInfo> {
+        self.locals
+            .iter_mut()
+            .find(|(n, _, _)| n == name)
+            .map(|local| {
+                local.2 = true;
+                local.1.clone()
+            })
+    }
+
+    /// Identify the service a registration chain starts from
+    fn parse_root(&mut self, root: &Expr, has_service_ops: bool) -> Option<ServiceRoot> {
+        match root {
+            Expr::Call(call) => {
+                let func = path_of(&call.func)?;
+                let segments: Vec<String> =
+                    func.segments.iter().map(|s| s.ident.to_string()).collect();
+                let first_arg = call.args.first().and_then(string_literal);
+
+                match segments.as_slice() {
+                    [.., app, ctor] if app == "App" && ctor == "new" => {
+                        Some(ServiceRoot::Plain(empty_router()))
+                    }
+                    [.., scope, ctor] if scope == "Scope" && ctor == "new" => {
+                        first_arg.map(ServiceRoot::Scope)
+                    }
+                    [.., resource, ctor] if resource == "Resource" && ctor == "new" => {
+                        first_arg.map(ServiceRoot::Resource)
+                    }
+                    [.., name] if name == "scope" => first_arg.map(ServiceRoot::Scope),
+                    [.., name] if name == "resource" => first_arg.map(ServiceRoot::Resource),
+                    _ if has_service_ops => {
+                        // A service returned by another function, e.g. `api::scope().service(..)`
+                        let mut info = empty_router();
+                        info.mounts.push(RouterMount {
+                            prefix: String::new(),
+                            target: path_to_string(func),
+                            line: span_line(call.paren_token.span.open()),
+                        });
+                        Some(ServiceRoot::Plain(info))
+                    }
+                    _ => None,
+                }
+            }
+            Expr::Path(path) => {
+                let ident = path.path.get_ident()?.to_string();
+                if self.config_params.contains(&ident) {
+                    return Some(ServiceRoot::Plain(empty_router()));
+                }
+                self.use_local(&ident).map(ServiceRoot::Plain)
+            }
+            _ => None,
+        }
+    }
+
+    /// Evaluate an expression that may build a service
+    fn parse_service(&mut self, expr: &Expr) -> Option<RouterInfo> {
+        let mut calls = Vec::new();
+        let mut root = strip_parens(expr);
+        while let Expr::MethodCall(call) = root {
+            calls.push(call);
+            root = strip_parens(&call.receiver);
+        }
+        calls.reverse();
+
+        let has_service_ops = calls.iter().any(|call| {
+            matches!(
+                call.method.to_string().as_str(),
+                "route" | "service" | "configure"
+            )
+        });
+
+        let (mut info, prefix, resource) = match self.parse_root(root, has_service_ops)? {
+            ServiceRoot::Plain(info) => (info, None, None),
+            ServiceRoot::Scope(prefix) => (empty_router(), Some(prefix), None),
+            ServiceRoot::Resource(path) => (empty_router(), None, Some(path)),
+        };
+
+        for call in calls {
+            self.apply_service_call(&mut info, call, resource.as_deref());
+        }
+
+        match prefix {
+            Some(prefix) => {
+                let mut scoped = empty_router();
+                scoped.base_path = Some(prefix.clone());
+                merge_router(&mut scoped, info, &prefix);
+                Some(scoped)
+            }
+            None => Some(info),
+        }
+    }
+
+    /// Apply a single chained call (`.route(..)`, `.service(..)`, ...) to a service
+    fn apply_service_call(
+        &mut self,
+        info: &mut RouterInfo,
+        call: &ExprMethodCall,
+        resource: Option<&str>,
+    ) {
+        let line = span_line(call.method.span());
+        let args: Vec<&Expr> = call.args.iter().collect();
+        let method = call.method.to_string();
+
+        match (method.as_str(), resource) {
+            ("route", _) if args.len() >= 2 => {
+                if let Some(path) = string_literal(args[0]) {
+                    self.push_routes(info, &path, self.detector.route_methods(args[1]), line);
+                }
+            }
+            ("route", Some(path)) if args.len() == 1 => {
+                let routes = self.detector.route_methods(args[0]);
+                self.push_routes(info, path, routes, line);
+            }
+            ("to", Some(path)) if args.len() == 1 => {
+                if let Some(handler) = handler_name(args[0]) {
+                    self.push_routes(info, path, vec![(HttpMethod::Custom("ANY"), handler)], line);
+                }
+            }
+            (name, Some(path)) if args.len() == 1 && HttpMethod::standard(name).is_some() => {
+                let method = HttpMethod::standard(name);
+                if let (Some(method), Some(handler)) = (method, handler_name(args[0])) {
+                    self.push_routes(info, path, vec![(method, handler)], line);
+                }
+            }
+            ("service", _) => {
+                for arg in args {
+                    self.register_service(info, arg, line);
+                }
+            }
+            ("configure", _) if !args.is_empty() => {
+                self.configure(info, args[0], line);
+            }
+            ("wrap" | "wrap_fn", _) if !args.is_empty() => {
+                let layer = args[0];
+                info.middleware
+                    .push(quote::quote!(#layer).to_string().replace(' ', ""));
+            }
+            _ => {}
+        }
+    }
+
+    fn push_routes(
+        &self,
+        info: &mut RouterInfo,
+        path: &str,
+        routes: Vec<(HttpMethod, String)>,
+        line: usize,
+    ) {
+        for (method, handler) in routes {
+            info.routes.push(RouteInfo {
+                method,
+                path: path.to_string(),
+                handler,
+                middleware: Vec::new(),
+                line,
+            });
+        }
+    }
+
+    /// Register the argument of a `.service(..)` call
+    fn register_service(&mut self, info: &mut RouterInfo, service: &Expr, line: usize) {
+        let service = strip_parens(service);
+
+        if let Expr::Tuple(tuple) = service {
+            for elem in &tuple.elems {
+                self.register_service(info, elem, line);
+            }
+            return;
+        }
+
+        if let Some(sub) = self.parse_service(service) {
+            merge_router(info, sub, "");
+            return;
+        }
+
+        // Attribute handlers (`.service(get_user)`) and services built by
+        // other functions (`.service(api::scope())`) are resolved later
+        let target = match service {
+            Expr::Path(path) => Some(path_to_string(&path.path)),
+            Expr::Call(call) => path_of(&call.func).map(path_to_string),
+            _ => None,
+        };
+        if let Some(target) = target {
+            info.mounts.push(RouterMount {
+                prefix: String::new(),
+                target,
+                line,
+            });
+        }
+    }
+
+    /// Register the argument of a `.configure(..)` call
+    fn configure(&mut self, info: &mut RouterInfo, callback: &Expr, line: usize) {
+        match strip_parens(callback) {
+            Expr::Path(path) => {
+                let target = path_to_string(&path.path);
+                info.nested_routers.push(target.clone());
+                info.mounts.push(RouterMount {
+                    prefix: String::new(),
+                    target,
+                    line,
+                });
+            }
+            Expr::Closure(closure) => {
+                let params = closure
+                    .inputs
+                    .iter()
+                    .map(|pat| binding_name(strip_pat_type(pat)))
+                    .collect();
+                let mut visitor = ServiceVisitor::new(self.detector, params);
+                visitor.visit_expr(&closure.body);
+                if let Some(sub) = visitor.finish(String::new()) {
+                    merge_router(info, sub, "");
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn strip_pat_type(pat: &Pat) -> &Pat {
+    match pat {
+        Pat::Type(pat_type) => &pat_type.pat,
+        other => other,
+    }
+}
+
+impl<'a> Visit<'_> for ServiceVisitor<'a> {
+    fn visit_item(&mut self, _item: &Item) {
+        // Nested items are analyzed on their own
+    }
+
+    fn visit_local(&mut self, local: &syn::Local) {
+        let name = match strip_pat_type(&local.pat) {
+            Pat::Ident(pat_ident) => Some(pat_ident.ident.to_string()),
+            _ => None,
+        };
+
+        if let (Some(name), Some(init)) = (name, &local.init) {
+            if let Some(info) = self.parse_service(&init.expr) {
+                self.set_local(name, info);
+                return;
+            }
+        }
+
+        syn::visit::visit_local(self, local);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Assign(assign) => {
+                if let Some(ident) = path_of(&assign.left).and_then(|p| p.get_ident()) {
+                    if let Some(info) = self.parse_service(&assign.right) {
+                        self.set_local(ident.to_string(), info);
+                        return;
+                    }
+                }
+            }
+            Expr::Call(_) | Expr::MethodCall(_) | Expr::Path(_) => {
+                if let Some(info) = self.parse_service(expr) {
+                    // A bare `ServiceConfig` parameter or `App::new()` registers nothing
+                    if !info.routes.is_empty() || !info.mounts.is_empty() {
+                        self.add_router(info);
+                    }
+                    return;
+                }
+            }
+            _ => {}
+        }
+
+        syn::visit::visit_expr(self, expr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    fn routes(endpoints: &[Endpoint]) -> Vec<(&str, &str, &str)> {
+        endpoints
+            .iter()
+            .map(|e| (e.method.as_str(), e.path.as_str(), e.handler.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_scope_and_resource_routes() {
+        let detector = ActixDetector::new();
+
+        let syntax_tree: syn::File = parse_quote! {
+            use actix_web::{web, App, HttpServer};
+
+            #[actix_web::main]
+            async fn main() -> std::io::Result<()> {
+                HttpServer::new(|| {
+                    App::new()
+                        .wrap(Logger::default())
+                        .service(
+                            web::scope("/v1")
+                                .route("/users", web::get().to(list_users))
+                                .service(
+                                    web::resource("/users/{id}")
+                                        .route(web::get().to(get_user))
+                                        .route(web::delete().to(delete_user)),
+                                ),
+                        )
+                        .route("/health", web::route().guard(guard::Head()).to(health))
+                })
+                .bind(("127.0.0.1", 8080))?
+                .run()
+                .await
+            }
+        };
+
+        let endpoints = detector.extract_endpoints(&syntax_tree);
+        assert_eq!(
+            routes(&endpoints),
+            vec![
+                ("GET", "/v1/users", "list_users"),
+                ("GET", "/v1/users/{id}", "get_user"),
+                ("DELETE", "/v1/users/{id}", "delete_user"),
+                ("HEAD", "/health", "health"),
+            ]
+        );
+        assert!(endpoints.iter().all(|e| !e.middleware.is_empty()));
+    }
+
+    #[test]
+    fn test_attribute_handlers_and_configure() {
+        let detector = ActixDetector::new();
+
+        let syntax_tree: syn::File = parse_quote! {
+            use actix_web::{get, route, web, App};
+
+            #[get("/{id}")]
+            async fn get_order(path: web::Path<u32>) -> impl Responder {
+                HttpResponse::Ok()
+            }
+
+            #[route("/status", method = "GET", method = "HEAD")]
+            async fn status() -> impl Responder {
+                HttpResponse::Ok()
+            }
+
+            fn orders(cfg: &mut web::ServiceConfig) {
+                cfg.service(web::scope("/orders").service(get_order));
+            }
+
+            fn app() -> App {
+                App::new().configure(orders).service(status)
+            }
+        };
+
+        let endpoints = detector.extract_endpoints(&syntax_tree);
+        assert_eq!(
+            routes(&endpoints),
+            vec![
+                ("GET", "/orders/{id}", "get_order"),
+                ("GET", "/status", "status"),
+                ("HEAD", "/status", "status"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_handler_detection() {
+        let detector = ActixDetector::new();
+
+        let handler: ItemFn = parse_quote! {
+            /// Fetch an order
+            #[get("/orders/{id}")]
+            async fn get_order(path: web::Path<u32>, data: web::Data<AppState>) -> impl Responder {
+                HttpResponse::Ok()
+            }
+        };
+
+        let info = detector.detect_handler(&handler).unwrap();
+        assert_eq!(info.name, "get_order");
+        assert_eq!(info.extractors.len(), 2);
+        assert_eq!(info.documentation.as_deref(), Some("Fetch an order"));
+    }
+}
@@ -3,16 +3,19 @@
 //! This module implements the `FrameworkDetector` trait for the Axum web framework,
 //! providing functionality to detect Axum usage and extract HTTP endpoint information.
 
+use super::routing::{
+    RouterFunction, binding_name, empty_router, file_endpoints, handler_name, merge_router,
+    path_of, path_to_string, project_endpoints, span_line, string_literal, strip_parens,
+};
 use super::{
     Endpoint, FrameworkDetector, HandlerInfo, HandlerParam, HttpMethod, RouteInfo, RouterInfo,
+    RouterMount,
 };
-use crate::{Error, Result};
-use std::fs;
+use crate::Result;
+use crate::ast::SourceFile;
+use crate::call_graph::ModuleTree;
 use std::path::Path;
-use syn::{
-    Attribute, Expr, ExprMethodCall, Item, ItemFn, Lit, Meta, Pat, ReturnType, Signature,
-    visit::Visit,
-};
+use syn::{Expr, ExprMethodCall, Item, ItemFn, Pat, ReturnType, Signature, visit::Visit};
 
 /// Detector for the Axum web framework
 pub struct AxumDetector {
@@ -66,22 +69,24 @@ impl AxumDetector {
     }
 
     /// Parse handler parameters from function signature
+    ///
+    /// Destructuring patterns such as `Path(id): Path<u32>` are named after
+    /// the bindings they introduce.
     fn parse_handler_params(&self, sig: &Signature) -> Vec<HandlerParam> {
         let mut params = Vec::new();
 
         for input in &sig.inputs {
             if let syn::FnArg::Typed(pat_type) = input {
-                if let Pat::Ident(pat_ident) = &*pat_type.pat {
-                    let name = pat_ident.ident.to_string();
-                    let ty = quote::quote!(#pat_type.ty).to_string();
-                    let is_extractor = self.is_extractor(&ty);
-
-                    params.push(HandlerParam {
-                        name,
-                        ty,
-                        is_extractor,
-                    });
-                }
+                let name = binding_name(&pat_type.pat);
+                let ty = &pat_type.ty;
+                let ty = quote::quote!(#ty).to_string();
+                let is_extractor = self.is_extractor(&ty);
+
+                params.push(HandlerParam {
+                    name,
+                    ty,
+                    is_extractor,
+                });
             }
         }
 
@@ -95,29 +100,6 @@ impl AxumDetector {
             ReturnType::Type(_, ty) => quote::quote!(#ty).to_string(),
         }
     }
-
-    /// Extract documentation from attributes
-    fn extract_docs(&self, attrs: &[Attribute]) -> Option<String> {
-        let mut docs = Vec::new();
-
-        for attr in attrs {
-            if attr.path().is_ident("doc") {
-                if let Meta::NameValue(meta) = &attr.meta {
-                    if let Expr::Lit(expr_lit) = &meta.value {
-                        if let Lit::Str(lit_str) = &expr_lit.lit {
-                            docs.push(lit_str.value().trim().to_string());
-                        }
-                    }
-                }
-            }
-        }
-
-        if docs.is_empty() {
-            None
-        } else {
-            Some(docs.join("\n"))
-        }
-    }
 }
 
 impl FrameworkDetector for AxumDetector {
@@ -126,49 +108,43 @@ impl FrameworkDetector for AxumDetector {
     }
 
     fn detect(&self, project_root: &Path) -> Result<bool> {
-        // Check Cargo.toml for axum dependency
-        let cargo_toml_path = project_root.join("Cargo.toml");
-        if !cargo_toml_path.exists() {
-            return Ok(false);
-        }
-
-        let cargo_content = fs::read_to_string(&cargo_toml_path).map_err(|e| Error::Io(e))?;
-
-        // Check for axum in dependencies
-        let cargo_toml: toml::Value = toml::from_str(&cargo_content).map_err(|e| Error::Toml(e))?;
-
-        if let Some(deps) = cargo_toml.get("dependencies") {
-            if deps.get("axum").is_some() {
-                return Ok(true);
-            }
-        }
-
-        if let Some(deps) = cargo_toml.get("dev-dependencies") {
-            if deps.get("axum").is_some() {
-                return Ok(true);
-            }
-        }
-
-        Ok(false)
+        super::manifest_has_dependency(project_root, "axum")
     }
 
     fn extract_endpoints(&self, syntax_tree: &syn::File) -> Vec<Endpoint> {
-        let mut visitor = EndpointVisitor::new(self);
-        visitor.visit_file(syntax_tree);
-        visitor.endpoints
+        file_endpoints(syntax_tree, |tree, module_path| {
+            self.router_functions(tree, module_path)
+        })
+    }
+
+    fn extract_project_endpoints(
+        &self,
+        files: &[SourceFile],
+        modules: &ModuleTree,
+    ) -> Vec<Endpoint> {
+        // Routers are composed across files so that `nest`/`merge` of a router
+        // built in another module resolves to fully prefixed paths
+        project_endpoints(files, modules, |tree, module_path| {
+            self.router_functions(tree, module_path)
+        })
     }
 
     fn analyze_router(&self, item: &Item) -> Option<RouterInfo> {
         match item {
             Item::Fn(item_fn) => {
                 let mut visitor = RouterVisitor::new(self);
-                visitor.visit_item_fn(item_fn);
-                visitor.router_info
+                visitor.visit_block(&item_fn.block);
+                visitor.finish(item_fn.sig.ident.to_string())
             }
             Item::Impl(item_impl) => {
                 let mut visitor = RouterVisitor::new(self);
-                visitor.visit_item_impl(item_impl);
-                visitor.router_info
+                for impl_item in &item_impl.items {
+                    if let syn::ImplItem::Fn(method) = impl_item {
+                        visitor.visit_block(&method.block);
+                    }
+                }
+                let self_ty = &item_impl.self_ty;
+                visitor.finish(quote::quote!(#self_ty).to_string())
             }
             _ => None,
         }
@@ -204,96 +180,89 @@ impl FrameworkDetector for AxumDetector {
             parameters: params,
             return_type,
             extractors,
-            documentation: self.extract_docs(&function.attrs),
+            documentation: super::doc_comment(&function.attrs),
         })
     }
 }
 
-/// Visitor for finding HTTP endpoints in the AST
-struct EndpointVisitor<'a> {
-    detector: &'a AxumDetector,
-    endpoints: Vec<Endpoint>,
-    current_module: Vec<String>,
-}
-
-impl<'a> EndpointVisitor<'a> {
-    fn new(detector: &'a AxumDetector) -> Self {
-        Self {
-            detector,
-            endpoints: Vec::new(),
-            current_module: Vec::new(),
-        }
+impl AxumDetector {
+    /// Collect every function in a file that builds an Axum router
+    ///
+    /// Inline modules extend the module path and methods in `impl` blocks are
+    /// recorded under their self type, so the result can be passed to
+    /// [`project_endpoints`] together with the routers of other files.
+    ///
+    /// # Arguments
+    ///
+    /// * `syntax_tree` - The parsed syntax tree of a source file
+    /// * `module_path` - Module path of the file within its crate
+    pub fn router_functions(
+        &self,
+        syntax_tree: &syn::File,
+        module_path: &[String],
+    ) -> Vec<RouterFunction> {
+        let mut routers = Vec::new();
+        self.collect_router_functions(&syntax_tree.items, module_path, &mut routers);
+        routers
     }
 
-    /// Process a router method call to extract endpoint information
-    fn process_router_call(&mut self, expr: &ExprMethodCall) {
-        if let Some(method) = self
-            .detector
-            .extract_method_from_call(&expr.method.to_string())
-        {
-            // Extract path from first argument
-            let path = if let Some(arg) = expr.args.first() {
-                if let Expr::Lit(expr_lit) = arg {
-                    if let Lit::Str(lit_str) = &expr_lit.lit {
-                        Some(lit_str.value())
-                    } else {
-                        None
+    fn collect_router_functions(
+        &self,
+        items: &[Item],
+        module_path: &[String],
+        routers: &mut Vec<RouterFunction>,
+    ) {
+        for item in items {
+            match item {
+                Item::Fn(_) => {
+                    if let Some(info) = self.analyze_router(item) {
+                        routers.push(RouterFunction {
+                            module_path: module_path.to_vec(),
+                            info,
+                        });
                     }
-                } else {
-                    None
                 }
-            } else {
-                None
-            };
-
-            // Extract handler from second argument
-            let handler = if expr.args.len() > 1 {
-                if let Some(Expr::Path(expr_path)) = expr.args.iter().nth(1) {
-                    Some(quote::quote!(#expr_path).to_string())
-                } else {
-                    None
+                Item::Impl(item_impl) => {
+                    let self_ty = &item_impl.self_ty;
+                    let mut impl_path = module_path.to_vec();
+                    impl_path.push(quote::quote!(#self_ty).to_string().replace(' ', ""));
+
+                    for impl_item in &item_impl.items {
+                        if let syn::ImplItem::Fn(method) = impl_item {
+                            let mut visitor = RouterVisitor::new(self);
+                            visitor.visit_block(&method.block);
+                            if let Some(info) = visitor.finish(method.sig.ident.to_string()) {
+                                routers.push(RouterFunction {
+                                    module_path: impl_path.clone(),
+                                    info,
+                                });
+                            }
+                        }
+                    }
                 }
-            } else {
-                None
-            };
-
-            if let (Some(path), Some(handler)) = (path, handler) {
-                self.endpoints.push(Endpoint {
-                    method,
-                    path,
-                    handler,
-                    module_path: self.current_module.clone(),
-                    line: 0, // Would need span information for accurate line numbers
-                    middleware: Vec::new(),
-                    documentation: None,
-                });
+                Item::Mod(item_mod) => {
+                    if let Some((_, items)) = &item_mod.content {
+                        let mut nested = module_path.to_vec();
+                        nested.push(item_mod.ident.to_string());
+                        self.collect_router_functions(items, &nested, routers);
+                    }
+                }
+                _ => {}
             }
         }
     }
 }
 
-impl<'a> Visit<'_> for EndpointVisitor<'a> {
-    fn visit_expr_method_call(&mut self, expr: &ExprMethodCall) {
-        // Look for router method calls like router.get("/path", handler)
-        self.process_router_call(expr);
-
-        // Continue visiting nested expressions
-        syn::visit::visit_expr_method_call(self, expr);
-    }
-
-    fn visit_item_mod(&mut self, module: &syn::ItemMod) {
-        // Track module path
-        self.current_module.push(module.ident.to_string());
-        syn::visit::visit_item_mod(self, module);
-        self.current_module.pop();
-    }
-}
-
-/// Visitor for analyzing router configurations
+/// Visitor that reconstructs the router built by a single function body
+///
+/// Router method-call chains are evaluated symbolically: `Router::new()`
+/// starts an empty router, `route`/`nest`/`merge`/`layer` calls add to it,
+/// and routers bound to local variables are inlined where they are used.
 struct RouterVisitor<'a> {
     detector: &'a AxumDetector,
     router_info: Option<RouterInfo>,
-    current_router: Option<String>,
+    /// Routers bound to local variables and whether another router consumed them
+    locals: Vec<(String, RouterInfo, bool)>,
 }
 
 impl<'a> RouterVisitor<'a> {
@@ -301,92 +270,284 @@ impl<'a> RouterVisitor<'a> {
         Self {
             detector,
             router_info: None,
-            current_router: None,
+            locals: Vec::new(),
+        }
+    }
+
+    /// Combine every router found in the body into the function's router
+    fn finish(mut self, name: String) -> Option<RouterInfo> {
+        let locals = std::mem::take(&mut self.locals);
+        for (_, info, used) in locals {
+            if !used {
+                self.add_router(info);
+            }
         }
+
+        self.router_info.map(|mut info| {
+            info.name = name;
+            info
+        })
     }
 
-    /// Process Router::new() calls
-    fn process_router_new(&mut self, expr: &ExprMethodCall) {
-        if let Expr::Path(path) = &*expr.receiver {
-            let path_str = quote::quote!(#path).to_string();
-            if path_str.contains("Router") && expr.method == "new" {
-                // Found Router::new()
-                self.router_info = Some(RouterInfo {
-                    name: "router".to_string(),
-                    base_path: None,
-                    routes: Vec::new(),
-                    middleware: Vec::new(),
-                    nested_routers: Vec::new(),
+    fn add_router(&mut self, info: RouterInfo) {
+        match &mut self.router_info {
+            Some(existing) => merge_router(existing, info, ""),
+            None => self.router_info = Some(info),
+        }
+    }
+
+    fn set_local(&mut self, name: String, info: RouterInfo) {
+        if let Some(local) = self.locals.iter_mut().find(|(n, _, _)| *n == name) {
+            local.1 = info;
+            local.2 = false;
+        } else {
+            self.locals.push((name, info, false));
+        }
+    }
+
+    /// Take the router bound to a local variable, marking it as consumed
+    fn use_local(&mut self, name: &str) -> Option<RouterInfo> {
+        self.locals
+            .iter_mut()
+            .find(|(n, _, _)| n == name)
+            .map(|local| {
+                local.2 = true;
+                local.1.clone()
+            })
+    }
+
+    /// Evaluate an expression that may build a router
+    fn parse_router(&mut self, expr: &Expr) -> Option<RouterInfo> {
+        // Unroll the method-call chain so that operations apply innermost first
+        let mut calls = Vec::new();
+        let mut root = strip_parens(expr);
+        while let Expr::MethodCall(call) = root {
+            calls.push(call);
+            root = strip_parens(&call.receiver);
+        }
+        calls.reverse();
+
+        let has_router_ops = calls
+            .iter()
+            .any(|call| matches!(call.method.to_string().as_str(), "route" | "nest" | "merge"));
+
+        let mut info = match root {
+            Expr::Call(call) if is_router_new(call) => empty_router(),
+            Expr::Call(call) if has_router_ops => {
+                // A router returned by another function, e.g. `api_routes().route(..)`
+                let Expr::Path(func) = &*call.func else {
+                    return None;
+                };
+                let mut info = empty_router();
+                info.mounts.push(RouterMount {
+                    prefix: String::new(),
+                    target: path_to_string(&func.path),
+                    line: span_line(call.paren_token.span.open()),
                 });
+                info
+            }
+            Expr::Path(path) => {
+                let ident = path.path.get_ident()?.to_string();
+                self.use_local(&ident)?
             }
+            _ => return None,
+        };
+
+        for call in calls {
+            self.apply_router_call(&mut info, call);
         }
+
+        Some(info)
     }
 
-    /// Process route registration calls
-    fn process_route_registration(&mut self, expr: &ExprMethodCall) {
-        if let Some(router_info) = &mut self.router_info {
-            if let Some(method) = self
-                .detector
-                .extract_method_from_call(&expr.method.to_string())
-            {
-                // Extract path and handler
-                if expr.args.len() >= 2 {
-                    let path = if let Expr::Lit(expr_lit) = &expr.args[0] {
-                        if let Lit::Str(lit_str) = &expr_lit.lit {
-                            Some(lit_str.value())
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    };
-
-                    let handler = if let Expr::Path(expr_path) = &expr.args[1] {
-                        Some(quote::quote!(#expr_path).to_string())
-                    } else {
-                        None
-                    };
-
-                    if let (Some(path), Some(handler)) = (path, handler) {
-                        router_info.routes.push(RouteInfo {
-                            method,
-                            path,
-                            handler,
-                            middleware: Vec::new(),
-                        });
-                    }
+    /// Apply a single chained call (`.route(..)`, `.nest(..)`, ...) to a router
+    fn apply_router_call(&mut self, info: &mut RouterInfo, call: &ExprMethodCall) {
+        let line = span_line(call.method.span());
+        let args: Vec<&Expr> = call.args.iter().collect();
+
+        match call.method.to_string().as_str() {
+            "route" if args.len() >= 2 => {
+                let Some(path) = string_literal(args[0]) else {
+                    return;
+                };
+                for (method, handler) in self.detector.method_routes(args[1]) {
+                    info.routes.push(RouteInfo {
+                        method,
+                        path: path.clone(),
+                        handler,
+                        middleware: Vec::new(),
+                        line,
+                    });
                 }
             }
+            "nest" if args.len() >= 2 => {
+                if let Some(prefix) = string_literal(args[0]) {
+                    self.mount(info, &prefix, args[1], line);
+                }
+            }
+            "merge" if !args.is_empty() => {
+                self.mount(info, "", args[0], line);
+            }
+            "layer" | "route_layer" if !args.is_empty() => {
+                let layer = args[0];
+                info.middleware
+                    .push(quote::quote!(#layer).to_string().replace(' ', ""));
+            }
+            _ => {}
+        }
+    }
+
+    /// Nest or merge another router at the given prefix
+    fn mount(&mut self, info: &mut RouterInfo, prefix: &str, target: &Expr, line: usize) {
+        let target = strip_parens(target);
 
-            // Check for nest() calls
-            if expr.method == "nest" && expr.args.len() >= 2 {
-                if let Expr::Lit(expr_lit) = &expr.args[0] {
-                    if let Lit::Str(lit_str) = &expr_lit.lit {
-                        router_info.base_path = Some(lit_str.value());
+        if let Expr::Call(call) = target {
+            if let Expr::Path(func) = &*call.func {
+                if !is_router_new(call) {
+                    let target = path_to_string(&func.path);
+                    if !prefix.is_empty() {
+                        info.nested_routers.push(target.clone());
                     }
+                    info.mounts.push(RouterMount {
+                        prefix: prefix.to_string(),
+                        target,
+                        line,
+                    });
+                    return;
                 }
             }
+        }
 
-            // Check for layer() calls (middleware)
-            if expr.method == "layer" {
-                router_info.middleware.push("layer".to_string());
-            }
+        // Routers built inline or bound to a local variable are merged directly
+        if let Some(sub) = self.parse_router(target) {
+            merge_router(info, sub, prefix);
         }
     }
 }
 
 impl<'a> Visit<'_> for RouterVisitor<'a> {
-    fn visit_expr_method_call(&mut self, expr: &ExprMethodCall) {
-        self.process_router_new(expr);
-        self.process_route_registration(expr);
+    fn visit_item(&mut self, _item: &Item) {
+        // Nested items are analyzed on their own
+    }
+
+    fn visit_local(&mut self, local: &syn::Local) {
+        let name = match &local.pat {
+            Pat::Ident(pat_ident) => Some(pat_ident.ident.to_string()),
+            Pat::Type(pat_type) => match &*pat_type.pat {
+                Pat::Ident(pat_ident) => Some(pat_ident.ident.to_string()),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        if let (Some(name), Some(init)) = (name, &local.init) {
+            if let Some(info) = self.parse_router(&init.expr) {
+                self.set_local(name, info);
+                return;
+            }
+        }
 
-        syn::visit::visit_expr_method_call(self, expr);
+        syn::visit::visit_local(self, local);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Assign(assign) => {
+                if let Expr::Path(path) = &*assign.left {
+                    if let Some(ident) = path.path.get_ident() {
+                        if let Some(info) = self.parse_router(&assign.right) {
+                            self.set_local(ident.to_string(), info);
+                            return;
+                        }
+                    }
+                }
+            }
+            Expr::Call(_) | Expr::MethodCall(_) | Expr::Path(_) => {
+                if let Some(info) = self.parse_router(expr) {
+                    self.add_router(info);
+                    return;
+                }
+            }
+            _ => {}
+        }
+
+        syn::visit::visit_expr(self, expr);
+    }
+}
+
+impl AxumDetector {
+    /// Extract `(method, handler)` pairs from a method router expression
+    ///
+    /// Handles `get(h)`, `routing::get(h)`, chained method routers such as
+    /// `get(a).post(b)`, `on(MethodFilter::GET, h)` and `any(h)`. Handlers that
+    /// are not paths (e.g. closures) are skipped.
+    fn method_routes(&self, expr: &Expr) -> Vec<(HttpMethod, String)> {
+        match strip_parens(expr) {
+            Expr::Call(call) => {
+                let Expr::Path(func) = &*call.func else {
+                    return Vec::new();
+                };
+                let Some(name) = func.path.segments.last().map(|s| s.ident.to_string()) else {
+                    return Vec::new();
+                };
+                let args: Vec<&Expr> = call.args.iter().collect();
+
+                let route = match name.as_str() {
+                    "on" if args.len() >= 2 => {
+                        let filter = path_of(args[0])
+                            .and_then(|p| p.segments.last())
+                            .map(|s| s.ident.to_string().to_lowercase());
+                        filter
+                            .and_then(|f| self.extract_method_from_call(&f))
+                            .zip(handler_name(args[1]))
+                    }
+                    "any" if !args.is_empty() => {
+                        handler_name(args[0]).map(|h| (HttpMethod::Custom("ANY"), h))
+                    }
+                    _ if !args.is_empty() => self
+                        .extract_method_from_call(&name)
+                        .zip(handler_name(args[0])),
+                    _ => None,
+                };
+                route.into_iter().collect()
+            }
+            Expr::MethodCall(call) => {
+                let mut routes = self.method_routes(&call.receiver);
+                if call.args.len() == 1 {
+                    if let Some(method) = self.extract_method_from_call(&call.method.to_string()) {
+                        if let Some(handler) = handler_name(&call.args[0]) {
+                            routes.push((method, handler));
+                        }
+                    }
+                }
+                routes
+            }
+            _ => Vec::new(),
+        }
     }
 }
 
+/// Check whether a call is `Router::new()` (optionally qualified or with turbofish)
+fn is_router_new(call: &syn::ExprCall) -> bool {
+    let Expr::Path(func) = &*call.func else {
+        return false;
+    };
+    let segments: Vec<String> = func
+        .path
+        .segments
+        .iter()
+        .map(|s| s.ident.to_string())
+        .collect();
+    matches!(
+        segments.as_slice(),
+        [.., router, ctor] if router == "Router" && (ctor == "new" || ctor == "default")
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::framework::web::routing::compose_routes;
     use syn::parse_quote;
 
     #[test]
@@ -414,7 +575,6 @@ mod tests {
     }
 
     #[test]
-    #[ignore = "Framework detection needs implementation"]
     fn test_handler_detection() {
         let detector = AxumDetector::new();
 
@@ -432,7 +592,6 @@ mod tests {
     }
 
     #[test]
-    #[ignore = "Framework detection needs implementation"]
     fn test_endpoint_extraction() {
         let detector = AxumDetector::new();
 
@@ -456,4 +615,70 @@ mod tests {
         assert_eq!(endpoints[0].method, HttpMethod::Get);
         assert_eq!(endpoints[0].handler, "hello");
     }
+
+    #[test]
+    fn test_chained_method_router() {
+        let detector = AxumDetector::new();
+
+        let syntax_tree: syn::File = parse_quote! {
+            fn app() -> Router {
+                let users = Router::new()
+                    .route("/", get(list_users).post(create_user))
+                    .route("/{id}", routing::delete(handlers::delete_user));
+
+                Router::new()
+                    .nest("/users", users)
+                    .route("/health", on(MethodFilter::GET, health))
+            }
+        };
+
+        let endpoints = detector.extract_endpoints(&syntax_tree);
+        let routes: Vec<_> = endpoints
+            .iter()
+            .map(|e| (e.method.as_str(), e.path.as_str(), e.handler.as_str()))
+            .collect();
+
+        assert_eq!(
+            routes,
+            vec![
+                ("GET", "/users", "list_users"),
+                ("POST", "/users", "create_user"),
+                ("DELETE", "/users/{id}", "handlers::delete_user"),
+                ("GET", "/health", "health"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compose_routes_across_modules() {
+        let detector = AxumDetector::new();
+
+        let main_file: syn::File = parse_quote! {
+            fn app() -> Router {
+                Router::new()
+                    .nest("/api", api::routes())
+                    .merge(admin_routes())
+                    .layer(TraceLayer::new_for_http())
+            }
+
+            fn admin_routes() -> Router {
+                Router::new().route("/admin", get(admin))
+            }
+        };
+        let api_file: syn::File = parse_quote! {
+            pub fn routes() -> Router {
+                Router::new().route("/orders", get(list_orders))
+            }
+        };
+
+        let mut routers = detector.router_functions(&main_file, &[]);
+        routers.extend(detector.router_functions(&api_file, &["api".to_string()]));
+
+        let composed = compose_routes(&routers);
+        let paths: Vec<_> = composed.iter().map(|c| c.route.path.as_str()).collect();
+        assert_eq!(paths, vec!["/api/orders", "/admin"]);
+
+        // Middleware of the outer router applies to mounted routes
+        assert!(composed.iter().all(|c| !c.route.middleware.is_empty()));
+    }
 }
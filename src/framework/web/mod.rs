@@ -5,12 +5,27 @@
 //! routes, and handler signatures.
 
 use crate::Result;
-use std::path::Path;
+use crate::ast::SourceFile;
+use crate::call_graph::ModuleTree;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use syn::{Item, ItemFn};
 
+pub mod actix;
 pub mod axum;
+pub mod poem;
+pub mod rocket;
+pub mod routing;
+pub mod tonic;
+pub mod warp;
 
+pub use actix::ActixDetector;
 pub use axum::AxumDetector;
+pub use poem::PoemDetector;
+pub use rocket::RocketDetector;
+pub use routing::{ComposedRoute, RouterFunction, compose_routes};
+pub use tonic::TonicDetector;
+pub use warp::WarpDetector;
 
 #[cfg(test)]
 mod test_standalone;
@@ -61,6 +76,46 @@ pub trait FrameworkDetector: Send + Sync {
     /// A vector of detected endpoints
     fn extract_endpoints(&self, syntax_tree: &syn::File) -> Vec<Endpoint>;
 
+    /// Extract HTTP endpoints from every source file of a project
+    ///
+    /// The default implementation calls [`extract_endpoints`] on each file,
+    /// prefixes module paths with the module of the file in `modules` and
+    /// records the file path. Detectors whose routes span several files (e.g.
+    /// routers nested from other modules) should override it.
+    ///
+    /// [`extract_endpoints`]: FrameworkDetector::extract_endpoints
+    ///
+    /// # Arguments
+    ///
+    /// * `files` - The parsed source files of the project
+    /// * `modules` - Module tree placing each file in its crate
+    ///
+    /// # Returns
+    ///
+    /// A vector of detected endpoints
+    fn extract_project_endpoints(
+        &self,
+        files: &[SourceFile],
+        modules: &ModuleTree,
+    ) -> Vec<Endpoint> {
+        let mut endpoints = Vec::new();
+
+        for file in files {
+            let module_path = modules.module_location(file.path()).modules;
+            for mut endpoint in self.extract_endpoints(&file.syntax_tree) {
+                let mut full_path = module_path.clone();
+                full_path.append(&mut endpoint.module_path);
+                endpoint.module_path = full_path;
+                endpoint
+                    .file
+                    .get_or_insert_with(|| file.path().to_path_buf());
+                endpoints.push(endpoint);
+            }
+        }
+
+        endpoints
+    }
+
     /// Analyze router configuration
     ///
     /// Extracts routing information from router setup code.
@@ -103,6 +158,9 @@ pub struct Endpoint {
     /// Module path to the handler
     pub module_path: Vec<String>,
 
+    /// File where the endpoint is defined, when known
+    pub file: Option<PathBuf>,
+
     /// Line number where the endpoint is defined
     pub line: usize,
 
@@ -111,6 +169,35 @@ pub struct Endpoint {
 
     /// Documentation extracted from the handler
     pub documentation: Option<String>,
+
+    /// Streaming mode, for gRPC methods
+    pub streaming: Option<StreamingKind>,
+}
+
+/// Streaming mode of a gRPC method
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamingKind {
+    /// Single request, single response
+    Unary,
+    /// Stream of requests, single response
+    ClientStreaming,
+    /// Single request, stream of responses
+    ServerStreaming,
+    /// Streams in both directions
+    Bidirectional,
+}
+
+impl StreamingKind {
+    /// Determine the kind from whether each side streams
+    pub fn from_streams(client: bool, server: bool) -> Self {
+        match (client, server) {
+            (false, false) => Self::Unary,
+            (true, false) => Self::ClientStreaming,
+            (false, true) => Self::ServerStreaming,
+            (true, true) => Self::Bidirectional,
+        }
+    }
 }
 
 /// HTTP methods
@@ -141,17 +228,26 @@ pub enum HttpMethod {
 impl HttpMethod {
     /// Convert from a string representation
     pub fn from_str(s: &str) -> Self {
+        Self::standard(s).unwrap_or_else(|| Self::Custom(Box::leak(s.to_string().into_boxed_str())))
+    }
+
+    /// Convert from the name of a standard method, ignoring case
+    ///
+    /// Unlike [`from_str`](Self::from_str), other names give `None` instead of
+    /// a custom method, so detectors can test any identifier (`get`, `route`)
+    /// for being a method.
+    pub fn standard(s: &str) -> Option<Self> {
         match s.to_uppercase().as_str() {
-            "GET" => Self::Get,
-            "POST" => Self::Post,
-            "PUT" => Self::Put,
-            "DELETE" => Self::Delete,
-            "PATCH" => Self::Patch,
-            "HEAD" => Self::Head,
-            "OPTIONS" => Self::Options,
-            "CONNECT" => Self::Connect,
-            "TRACE" => Self::Trace,
-            _ => Self::Custom(Box::leak(s.to_string().into_boxed_str())),
+            "GET" => Some(Self::Get),
+            "POST" => Some(Self::Post),
+            "PUT" => Some(Self::Put),
+            "DELETE" => Some(Self::Delete),
+            "PATCH" => Some(Self::Patch),
+            "HEAD" => Some(Self::Head),
+            "OPTIONS" => Some(Self::Options),
+            "CONNECT" => Some(Self::Connect),
+            "TRACE" => Some(Self::Trace),
+            _ => None,
         }
     }
 
@@ -189,6 +285,27 @@ pub struct RouterInfo {
 
     /// Nested routers
     pub nested_routers: Vec<String>,
+
+    /// Routers built elsewhere that are nested or merged into this one
+    pub mounts: Vec<RouterMount>,
+}
+
+/// A reference from one router to another router built by a different function
+///
+/// In Axum, `.nest("/api", api_routes())` produces a mount with prefix `/api`,
+/// while `.merge(admin_routes())` produces a mount with an empty prefix.
+/// Actix `.service(handler)` and `.configure(config)` calls mount the target
+/// at the enclosing scope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouterMount {
+    /// Path prefix applied to every route of the mounted router
+    pub prefix: String,
+
+    /// Path of the function building the mounted router (e.g. `api::routes`)
+    pub target: String,
+
+    /// Line number of the call registering the mount
+    pub line: usize,
 }
 
 /// Information about a single route
@@ -205,6 +322,9 @@ pub struct RouteInfo {
 
     /// Route-specific middleware
     pub middleware: Vec<String>,
+
+    /// Line number where the route is registered
+    pub line: usize,
 }
 
 /// Information about a handler function
@@ -253,6 +373,11 @@ impl DetectorRegistry {
         Self {
             detectors: vec![
                 Box::new(axum::AxumDetector::new()),
+                Box::new(actix::ActixDetector::new()),
+                Box::new(rocket::RocketDetector::new()),
+                Box::new(tonic::TonicDetector::new()),
+                Box::new(warp::WarpDetector::new()),
+                Box::new(poem::PoemDetector::new()),
                 // Future: Add more framework detectors here
             ],
         }
@@ -292,6 +417,11 @@ impl DetectorRegistry {
         Ok(detected)
     }
 
+    /// Iterate over the registered detectors
+    pub fn detectors(&self) -> impl Iterator<Item = &dyn FrameworkDetector> {
+        self.detectors.iter().map(|d| d.as_ref())
+    }
+
     /// Get a detector by name
     ///
     /// # Arguments
@@ -315,6 +445,53 @@ impl Default for DetectorRegistry {
     }
 }
 
+/// Check whether the manifest in `project_root` depends on a crate
+///
+/// Both `[dependencies]` and `[dev-dependencies]` are checked.
+///
+/// # Errors
+///
+/// Returns an error if the manifest exists but cannot be read or parsed
+pub(crate) fn manifest_has_dependency(project_root: &Path, crate_name: &str) -> Result<bool> {
+    let cargo_toml_path = project_root.join("Cargo.toml");
+    if !cargo_toml_path.exists() {
+        return Ok(false);
+    }
+
+    let cargo_content = std::fs::read_to_string(&cargo_toml_path)?;
+    let cargo_toml: toml::Value = toml::from_str(&cargo_content)?;
+
+    Ok(["dependencies", "dev-dependencies"].iter().any(|section| {
+        cargo_toml
+            .get(section)
+            .is_some_and(|deps| deps.get(crate_name).is_some())
+    }))
+}
+
+/// Collect the `///` documentation of an item, one line per doc attribute
+pub(crate) fn doc_comment(attrs: &[syn::Attribute]) -> Option<String> {
+    let docs: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(meta) => match &meta.value {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(lit_str),
+                    ..
+                }) => Some(lit_str.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+
+    if docs.is_empty() {
+        None
+    } else {
+        Some(docs.join("\n"))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -323,6 +500,8 @@ mod tests {
     fn test_http_method_conversion() {
         assert_eq!(HttpMethod::from_str("GET"), HttpMethod::Get);
         assert_eq!(HttpMethod::from_str("post"), HttpMethod::Post);
+        assert_eq!(HttpMethod::standard("delete"), Some(HttpMethod::Delete));
+        assert_eq!(HttpMethod::standard("route"), None);
         match HttpMethod::from_str("CUSTOM") {
             HttpMethod::Custom(s) => assert_eq!(s, "CUSTOM"),
             _ => panic!("Expected Custom variant"),
@@ -0,0 +1,502 @@
+//! Poem web framework detector and analyzer
+//!
+//! This module implements the `FrameworkDetector` trait for Poem. Routes are
+//! reconstructed from `Route::new().at("/path", get(handler))` chains, joined
+//! with the prefixes of `nest("/api", api())` calls, and handlers are the
+//! functions marked with `#[handler]`.
+
+use super::routing::{
+    RouterFunction, binding_name, empty_router, file_endpoints, handler_name, merge_router,
+    path_of, path_to_string, project_endpoints, span_line, string_literal, strip_parens,
+};
+use super::{
+    Endpoint, FrameworkDetector, HandlerInfo, HandlerParam, HttpMethod, RouteInfo, RouterInfo,
+    RouterMount,
+};
+use crate::Result;
+use crate::ast::SourceFile;
+use crate::call_graph::ModuleTree;
+use std::path::Path;
+use syn::{Expr, ExprMethodCall, FnArg, Item, ItemFn, Pat, ReturnType, visit::Visit};
+
+/// Detector for the Poem web framework
+pub struct PoemDetector;
+
+impl PoemDetector {
+    /// Create a new Poem detector
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Collect every function in a file that builds a Poem route tree
+    ///
+    /// # Arguments
+    ///
+    /// * `syntax_tree` - The parsed syntax tree of a source file
+    /// * `module_path` - Module path of the file within its crate
+    pub fn router_functions(
+        &self,
+        syntax_tree: &syn::File,
+        module_path: &[String],
+    ) -> Vec<RouterFunction> {
+        let mut routers = Vec::new();
+        self.collect_router_functions(&syntax_tree.items, module_path, &mut routers);
+        routers
+    }
+
+    fn collect_router_functions(
+        &self,
+        items: &[Item],
+        module_path: &[String],
+        routers: &mut Vec<RouterFunction>,
+    ) {
+        for item in items {
+            match item {
+                Item::Fn(_) => {
+                    if let Some(info) = self.analyze_router(item) {
+                        routers.push(RouterFunction {
+                            module_path: module_path.to_vec(),
+                            info,
+                        });
+                    }
+                }
+                Item::Mod(item_mod) => {
+                    if let Some((_, items)) = &item_mod.content {
+                        let mut nested = module_path.to_vec();
+                        nested.push(item_mod.ident.to_string());
+                        self.collect_router_functions(items, &nested, routers);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Extract `(method, handler, middleware)` routes from an endpoint expression
+    ///
+    /// Handles `get(h)`, `poem::get(h)`, chained method routers such as
+    /// `get(a).post(b)`, bare handlers (which accept any method) and endpoints
+    /// wrapped with `.with(middleware)`.
+    fn method_routes(&self, expr: &Expr) -> Vec<(HttpMethod, String, Vec<String>)> {
+        match strip_parens(expr) {
+            Expr::Call(call) => {
+                let Some(func) = path_of(&call.func) else {
+                    return Vec::new();
+                };
+                let Some(name) = func.segments.last().map(|s| s.ident.to_string()) else {
+                    return Vec::new();
+                };
+                let Some(handler) = call.args.first().and_then(handler_name) else {
+                    return Vec::new();
+                };
+
+                HttpMethod::standard(&name)
+                    .map(|method| (method, handler, Vec::new()))
+                    .into_iter()
+                    .collect()
+            }
+            Expr::MethodCall(call) => {
+                let mut routes = self.method_routes(&call.receiver);
+                let method = call.method.to_string();
+
+                if method == "with" || method == "around" {
+                    if let Some(middleware) = call.args.first() {
+                        let middleware = quote::quote!(#middleware).to_string().replace(' ', "");
+                        for route in &mut routes {
+                            route.2.push(middleware.clone());
+                        }
+                    }
+                } else if let Some(method) = HttpMethod::standard(&method) {
+                    if let Some(handler) = call.args.first().and_then(handler_name) {
+                        routes.push((method, handler, Vec::new()));
+                    }
+                }
+                routes
+            }
+            Expr::Path(path) => vec![(
+                HttpMethod::Custom("ANY"),
+                path_to_string(&path.path),
+                Vec::new(),
+            )],
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl Default for PoemDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameworkDetector for PoemDetector {
+    fn name(&self) -> &'static str {
+        "Poem"
+    }
+
+    fn detect(&self, project_root: &Path) -> Result<bool> {
+        super::manifest_has_dependency(project_root, "poem")
+    }
+
+    fn extract_endpoints(&self, syntax_tree: &syn::File) -> Vec<Endpoint> {
+        file_endpoints(syntax_tree, |tree, module_path| {
+            self.router_functions(tree, module_path)
+        })
+    }
+
+    fn extract_project_endpoints(
+        &self,
+        files: &[SourceFile],
+        modules: &ModuleTree,
+    ) -> Vec<Endpoint> {
+        // Route trees are composed across files so that `nest` of a route
+        // built in another module resolves to fully prefixed paths
+        project_endpoints(files, modules, |tree, module_path| {
+            self.router_functions(tree, module_path)
+        })
+    }
+
+    fn analyze_router(&self, item: &Item) -> Option<RouterInfo> {
+        match item {
+            Item::Fn(item_fn) => {
+                let mut visitor = RouteVisitor::new(self);
+                visitor.visit_block(&item_fn.block);
+                visitor.finish(item_fn.sig.ident.to_string())
+            }
+            _ => None,
+        }
+    }
+
+    fn detect_handler(&self, function: &ItemFn) -> Option<HandlerInfo> {
+        let is_handler = function.attrs.iter().any(|attr| {
+            attr.path()
+                .segments
+                .last()
+                .is_some_and(|s| s.ident == "handler")
+        });
+        if !is_handler {
+            return None;
+        }
+
+        // Every handler argument is produced by a `FromRequest` extractor
+        let parameters: Vec<HandlerParam> = function
+            .sig
+            .inputs
+            .iter()
+            .filter_map(|input| match input {
+                FnArg::Typed(pat_type) => {
+                    let ty = &pat_type.ty;
+                    Some(HandlerParam {
+                        name: binding_name(&pat_type.pat),
+                        ty: quote::quote!(#ty).to_string(),
+                        is_extractor: true,
+                    })
+                }
+                FnArg::Receiver(_) => None,
+            })
+            .collect();
+
+        Some(HandlerInfo {
+            name: function.sig.ident.to_string(),
+            is_async: function.sig.asyncness.is_some(),
+            extractors: parameters.iter().map(|p| p.ty.clone()).collect(),
+            parameters,
+            return_type: match &function.sig.output {
+                ReturnType::Default => "()".to_string(),
+                ReturnType::Type(_, ty) => quote::quote!(#ty).to_string(),
+            },
+            documentation: super::doc_comment(&function.attrs),
+        })
+    }
+}
+
+/// Visitor that reconstructs the route tree built by a single function body
+///
+/// `Route::new()` starts an empty route tree, `at`/`nest`/`with` calls add
+/// to it, and route trees bound to local variables are inlined where they
+/// are used.
+struct RouteVisitor<'a> {
+    detector: &'a PoemDetector,
+    router_info: Option<RouterInfo>,
+    /// Route trees bound to local variables and whether another tree consumed them
+    locals: Vec<(String, RouterInfo, bool)>,
+}
+
+impl<'a> RouteVisitor<'a> {
+    fn new(detector: &'a PoemDetector) -> Self {
+        Self {
+            detector,
+            router_info: None,
+            locals: Vec::new(),
+        }
+    }
+
+    fn finish(mut self, name: String) -> Option<RouterInfo> {
+        let locals = std::mem::take(&mut self.locals);
+        for (_, info, used) in locals {
+            if !used {
+                self.add_router(info);
+            }
+        }
+
+        self.router_info.map(|mut info| {
+            info.name = name;
+            info
+        })
+    }
+
+    fn add_router(&mut self, info: RouterInfo) {
+        match &mut self.router_info {
+            Some(existing) => merge_router(existing, info, ""),
+            None => self.router_info = Some(info),
+        }
+    }
+
+    fn set_local(&mut self, name: String, info: RouterInfo) {
+        if let Some(local) = self.locals.iter_mut().find(|(n, _, _)| *n == name) {
+            local.1 = info;
+            local.2 = false;
+        } else {
+            self.locals.push((name, info, false));
+        }
+    }
+
+    fn use_local(&mut self, name: &str) -> Option<RouterInfo> {
+        self.locals
+            .iter_mut()
+            .find(|(n, _, _)| n == name)
+            .map(|local| {
+                local.2 = true;
+                local.1.clone()
+            })
+    }
+
+    /// Evaluate an expression that may build a route tree
+    fn parse_route(&mut self, expr: &Expr) -> Option<RouterInfo> {
+        let mut calls = Vec::new();
+        let mut root = strip_parens(expr);
+        while let Expr::MethodCall(call) = root {
+            calls.push(call);
+            root = strip_parens(&call.receiver);
+        }
+        calls.reverse();
+
+        let has_route_ops = calls
+            .iter()
+            .any(|call| matches!(call.method.to_string().as_str(), "at" | "nest"));
+
+        let mut info = match root {
+            Expr::Call(call) if is_route_new(call) => empty_router(),
+            Expr::Call(call) if has_route_ops => {
+                // A route tree returned by another function, e.g. `api().at(..)`
+                let func = path_of(&call.func)?;
+                let mut info = empty_router();
+                info.mounts.push(RouterMount {
+                    prefix: String::new(),
+                    target: path_to_string(func),
+                    line: span_line(call.paren_token.span.open()),
+                });
+                info
+            }
+            Expr::Path(path) => {
+                let ident = path.path.get_ident()?.to_string();
+                self.use_local(&ident)?
+            }
+            _ => return None,
+        };
+
+        for call in calls {
+            self.apply_route_call(&mut info, call);
+        }
+
+        Some(info)
+    }
+
+    /// Apply a single chained call (`.at(..)`, `.nest(..)`, ...) to a route tree
+    fn apply_route_call(&mut self, info: &mut RouterInfo, call: &ExprMethodCall) {
+        let line = span_line(call.method.span());
+        let args: Vec<&Expr> = call.args.iter().collect();
+
+        match call.method.to_string().as_str() {
+            "at" if args.len() >= 2 => {
+                let Some(path) = string_literal(args[0]) else {
+                    return;
+                };
+                for (method, handler, middleware) in self.detector.method_routes(args[1]) {
+                    info.routes.push(RouteInfo {
+                        method,
+                        path: path.clone(),
+                        handler,
+                        middleware,
+                        line,
+                    });
+                }
+            }
+            "nest" | "nest_no_strip" if args.len() >= 2 => {
+                if let Some(prefix) = string_literal(args[0]) {
+                    self.mount(info, &prefix, args[1], line);
+                }
+            }
+            "with" | "around" if !args.is_empty() => {
+                let middleware = args[0];
+                info.middleware
+                    .push(quote::quote!(#middleware).to_string().replace(' ', ""));
+            }
+            _ => {}
+        }
+    }
+
+    /// Nest another route tree at the given prefix
+    fn mount(&mut self, info: &mut RouterInfo, prefix: &str, target: &Expr, line: usize) {
+        let target = strip_parens(target);
+
+        if let Expr::Call(call) = target {
+            if let Some(func) = path_of(&call.func) {
+                if !is_route_new(call) {
+                    let target = path_to_string(func);
+                    info.nested_routers.push(target.clone());
+                    info.mounts.push(RouterMount {
+                        prefix: prefix.to_string(),
+                        target,
+                        line,
+                    });
+                    return;
+                }
+            }
+        }
+
+        if let Some(sub) = self.parse_route(target) {
+            merge_router(info, sub, prefix);
+        }
+    }
+}
+
+impl<'a> Visit<'_> for RouteVisitor<'a> {
+    fn visit_item(&mut self, _item: &Item) {
+        // Nested items are analyzed on their own
+    }
+
+    fn visit_local(&mut self, local: &syn::Local) {
+        let name = match &local.pat {
+            Pat::Ident(pat_ident) => Some(pat_ident.ident.to_string()),
+            Pat::Type(pat_type) => match &*pat_type.pat {
+                Pat::Ident(pat_ident) => Some(pat_ident.ident.to_string()),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        if let (Some(name), Some(init)) = (name, &local.init) {
+            if let Some(info) = self.parse_route(&init.expr) {
+                self.set_local(name, info);
+                return;
+            }
+        }
+
+        syn::visit::visit_local(self, local);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Assign(assign) => {
+                if let Some(ident) = path_of(&assign.left).and_then(|p| p.get_ident()) {
+                    if let Some(info) = self.parse_route(&assign.right) {
+                        self.set_local(ident.to_string(), info);
+                        return;
+                    }
+                }
+            }
+            Expr::Call(_) | Expr::MethodCall(_) | Expr::Path(_) => {
+                if let Some(info) = self.parse_route(expr) {
+                    self.add_router(info);
+                    return;
+                }
+            }
+            _ => {}
+        }
+
+        syn::visit::visit_expr(self, expr);
+    }
+}
+
+/// Check whether a call is `Route::new()` (optionally qualified)
+fn is_route_new(call: &syn::ExprCall) -> bool {
+    let Some(func) = path_of(&call.func) else {
+        return false;
+    };
+    let segments: Vec<String> = func.segments.iter().map(|s| s.ident.to_string()).collect();
+    matches!(
+        segments.as_slice(),
+        [.., route, ctor] if route == "Route" && (ctor == "new" || ctor == "default")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_route_tree_endpoints() {
+        let detector = PoemDetector::new();
+
+        let syntax_tree: syn::File = parse_quote! {
+            use poem::{get, handler, Route};
+
+            fn app() -> Route {
+                let users = Route::new()
+                    .at("/", get(list_users).post(create_user))
+                    .at("/:id", get(get_user).with(Auth));
+
+                Route::new()
+                    .at("/health", health)
+                    .nest("/users", users)
+                    .nest("/admin", admin::routes())
+                    .with(Tracing)
+            }
+        };
+
+        let endpoints = detector.extract_endpoints(&syntax_tree);
+        let routes: Vec<_> = endpoints
+            .iter()
+            .map(|e| (e.method.as_str(), e.path.as_str(), e.handler.as_str()))
+            .collect();
+
+        assert_eq!(
+            routes,
+            vec![
+                ("ANY", "/health", "health"),
+                ("GET", "/users", "list_users"),
+                ("POST", "/users", "create_user"),
+                ("GET", "/users/:id", "get_user"),
+            ]
+        );
+
+        let get_user = &endpoints[3];
+        assert_eq!(get_user.middleware, vec!["Auth", "Tracing"]);
+    }
+
+    #[test]
+    fn test_handler_attribute_required() {
+        let detector = PoemDetector::new();
+
+        let handler: ItemFn = parse_quote! {
+            /// Look up a user
+            #[handler]
+            async fn get_user(Path(id): Path<u64>, db: Data<&Db>) -> Json<User> {
+                todo!()
+            }
+        };
+        let info = detector.detect_handler(&handler).unwrap();
+        assert_eq!(info.parameters.len(), 2);
+        assert!(info.parameters.iter().all(|p| p.is_extractor));
+        assert_eq!(info.documentation.as_deref(), Some("Look up a user"));
+
+        let plain: ItemFn = parse_quote! {
+            async fn get_user(id: u64) -> User {
+                todo!()
+            }
+        };
+        assert!(detector.detect_handler(&plain).is_none());
+    }
+}
@@ -0,0 +1,606 @@
+//! Rocket framework detector and analyzer
+//!
+//! This module implements the `FrameworkDetector` trait for Rocket. Handlers
+//! are found through their route attributes (`#[get("/<id>")]`,
+//! `#[route(GET, uri = "/")]`) and joined with the base paths given to
+//! `mount("/api", routes![...])` calls.
+
+use super::routing::{
+    RouterFunction, binding_name, empty_router, file_endpoints, merge_router, path_of,
+    path_to_string, project_endpoints, references_crate, span_line, string_literal, strip_parens,
+};
+use super::{
+    Endpoint, FrameworkDetector, HandlerInfo, HandlerParam, HttpMethod, RouteInfo, RouterInfo,
+    RouterMount,
+};
+use crate::Result;
+use crate::ast::SourceFile;
+use crate::call_graph::ModuleTree;
+use std::path::Path;
+use syn::punctuated::Punctuated;
+use syn::{
+    Attribute, Expr, ExprMacro, ExprMethodCall, FnArg, Item, ItemFn, Pat, ReturnType, Token,
+    visit::Visit,
+};
+
+/// Detector for the Rocket web framework
+pub struct RocketDetector;
+
+/// A route declared by a Rocket route attribute
+struct AttributeRoute {
+    method: HttpMethod,
+    /// Path part of the URI, without the query string
+    path: String,
+    /// Names bound by dynamic path and query segments (`<id>`, `<rest..>`)
+    segments: Vec<String>,
+    /// Name bound by the `data = "<name>"` argument
+    data: Option<String>,
+    line: usize,
+}
+
+impl RocketDetector {
+    /// Create a new Rocket detector
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Parse the route attributes of a handler
+    fn attribute_routes(&self, attrs: &[Attribute]) -> Vec<AttributeRoute> {
+        let mut routes = Vec::new();
+
+        for attr in attrs {
+            let Some(name) = attr.path().segments.last().map(|s| s.ident.to_string()) else {
+                continue;
+            };
+            let is_route = name == "route";
+            let mut method = HttpMethod::standard(&name);
+            if !is_route && method.is_none() {
+                continue;
+            }
+
+            let Ok(args) = attr.parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated)
+            else {
+                continue;
+            };
+
+            let mut uri = None;
+            let mut data = None;
+            for arg in &args {
+                match arg {
+                    Expr::Lit(_) => uri = uri.or_else(|| string_literal(arg)),
+                    Expr::Path(path) if is_route => {
+                        method =
+                            method.or_else(|| HttpMethod::standard(&path_to_string(&path.path)));
+                    }
+                    Expr::Assign(assign) => {
+                        let value = string_literal(&assign.right);
+                        match path_of(&assign.left).map(path_to_string).as_deref() {
+                            Some("uri") => uri = value,
+                            Some("data") => data = value.map(|v| segment_names(&v).join("")),
+                            _ => {}
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            let (Some(method), Some(uri)) = (method, uri) else {
+                continue;
+            };
+            let path = uri.split('?').next().unwrap_or_default().to_string();
+
+            routes.push(AttributeRoute {
+                method,
+                path: if path.is_empty() {
+                    "/".to_string()
+                } else {
+                    path
+                },
+                segments: segment_names(&uri),
+                data,
+                line: span_line(attr.pound_token.spans[0]),
+            });
+        }
+
+        routes
+    }
+
+    /// Collect every handler and every function mounting routes in a file
+    ///
+    /// Handlers become single-route routers named after the function, so the
+    /// entries of a `routes![]` macro resolve to them like mounted routers.
+    /// Handlers are only considered in files that reference `rocket`, which
+    /// keeps Actix-web handlers (which share the same attribute names) out.
+    ///
+    /// # Arguments
+    ///
+    /// * `syntax_tree` - The parsed syntax tree of a source file
+    /// * `module_path` - Module path of the file within its crate
+    pub fn router_functions(
+        &self,
+        syntax_tree: &syn::File,
+        module_path: &[String],
+    ) -> Vec<RouterFunction> {
+        let uses_rocket = references_crate(&syntax_tree.items, "rocket");
+        let mut routers = Vec::new();
+        self.collect_router_functions(&syntax_tree.items, module_path, uses_rocket, &mut routers);
+        routers
+    }
+
+    fn collect_router_functions(
+        &self,
+        items: &[Item],
+        module_path: &[String],
+        uses_rocket: bool,
+        routers: &mut Vec<RouterFunction>,
+    ) {
+        for item in items {
+            match item {
+                Item::Fn(item_fn) => {
+                    let name = item_fn.sig.ident.to_string();
+
+                    if uses_rocket {
+                        let routes: Vec<RouteInfo> = self
+                            .attribute_routes(&item_fn.attrs)
+                            .into_iter()
+                            .map(|route| RouteInfo {
+                                method: route.method,
+                                path: route.path,
+                                handler: name.clone(),
+                                middleware: Vec::new(),
+                                line: route.line,
+                            })
+                            .collect();
+                        if !routes.is_empty() {
+                            let mut info = empty_router();
+                            info.name = name.clone();
+                            info.routes = routes;
+                            routers.push(RouterFunction {
+                                module_path: module_path.to_vec(),
+                                info,
+                            });
+                        }
+                    }
+
+                    if let Some(info) = self.analyze_router(item) {
+                        routers.push(RouterFunction {
+                            module_path: module_path.to_vec(),
+                            info,
+                        });
+                    }
+                }
+                Item::Mod(item_mod) => {
+                    if let Some((_, items)) = &item_mod.content {
+                        let mut nested = module_path.to_vec();
+                        nested.push(item_mod.ident.to_string());
+                        self.collect_router_functions(items, &nested, uses_rocket, routers);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Default for RocketDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameworkDetector for RocketDetector {
+    fn name(&self) -> &'static str {
+        "Rocket"
+    }
+
+    fn detect(&self, project_root: &Path) -> Result<bool> {
+        super::manifest_has_dependency(project_root, "rocket")
+    }
+
+    fn extract_endpoints(&self, syntax_tree: &syn::File) -> Vec<Endpoint> {
+        file_endpoints(syntax_tree, |tree, module_path| {
+            self.router_functions(tree, module_path)
+        })
+    }
+
+    fn extract_project_endpoints(
+        &self,
+        files: &[SourceFile],
+        modules: &ModuleTree,
+    ) -> Vec<Endpoint> {
+        // `routes![]` commonly lists handlers from other modules, so routers
+        // are composed across all files
+        project_endpoints(files, modules, |tree, module_path| {
+            self.router_functions(tree, module_path)
+        })
+    }
+
+    fn analyze_router(&self, item: &Item) -> Option<RouterInfo> {
+        match item {
+            Item::Fn(item_fn) => {
+                let mut visitor = MountVisitor::new();
+                visitor.visit_block(&item_fn.block);
+                visitor.finish(item_fn.sig.ident.to_string())
+            }
+            _ => None,
+        }
+    }
+
+    fn detect_handler(&self, function: &ItemFn) -> Option<HandlerInfo> {
+        let routes = self.attribute_routes(&function.attrs);
+        if routes.is_empty() {
+            return None;
+        }
+
+        // Parameters not bound by a URI segment or the data argument are
+        // request guards
+        let parameters: Vec<HandlerParam> = function
+            .sig
+            .inputs
+            .iter()
+            .filter_map(|input| match input {
+                FnArg::Typed(pat_type) => {
+                    let name = binding_name(&pat_type.pat);
+                    let ty = &pat_type.ty;
+                    let is_segment = routes.iter().any(|route| {
+                        route.segments.contains(&name) && route.data.as_ref() != Some(&name)
+                    });
+                    Some(HandlerParam {
+                        is_extractor: !is_segment,
+                        name,
+                        ty: quote::quote!(#ty).to_string(),
+                    })
+                }
+                FnArg::Receiver(_) => None,
+            })
+            .collect();
+
+        let extractors = parameters
+            .iter()
+            .filter(|p| p.is_extractor)
+            .map(|p| p.ty.clone())
+            .collect();
+
+        Some(HandlerInfo {
+            name: function.sig.ident.to_string(),
+            is_async: function.sig.asyncness.is_some(),
+            parameters,
+            return_type: match &function.sig.output {
+                ReturnType::Default => "()".to_string(),
+                ReturnType::Type(_, ty) => quote::quote!(#ty).to_string(),
+            },
+            extractors,
+            documentation: super::doc_comment(&function.attrs),
+        })
+    }
+}
+
+/// Names of the dynamic segments in a Rocket URI (`/<id>/<path..>?<q>`)
+fn segment_names(uri: &str) -> Vec<String> {
+    uri.split('<')
+        .skip(1)
+        .filter_map(|rest| rest.split('>').next())
+        .map(|name| name.trim_end_matches("..").to_string())
+        .collect()
+}
+
+/// Mounts for the handlers listed in a `routes![a, b::c]` macro
+fn routes_macro(mac: &ExprMacro, prefix: &str) -> Option<Vec<RouterMount>> {
+    if mac.mac.path.segments.last()?.ident != "routes" {
+        return None;
+    }
+
+    let handlers = mac
+        .mac
+        .parse_body_with(Punctuated::<syn::Path, Token![,]>::parse_terminated)
+        .ok()?;
+    let line = span_line(mac.mac.bang_token.span);
+
+    Some(
+        handlers
+            .iter()
+            .map(|handler| RouterMount {
+                prefix: prefix.to_string(),
+                target: path_to_string(handler),
+                line,
+            })
+            .collect(),
+    )
+}
+
+/// Visitor that collects the routes mounted by a single function body
+///
+/// `rocket::build()` (or `ignite`/`custom`) starts an instance and every
+/// `mount(base, routes)` call mounts the listed handlers at `base`. A bare
+/// `routes![]` macro (e.g. in a function returning `Vec<Route>`) mounts its
+/// handlers without a base path, to be prefixed wherever the function is
+/// mounted.
+struct MountVisitor {
+    router_info: Option<RouterInfo>,
+    /// Instances bound to local variables and whether a later chain consumed them
+    locals: Vec<(String, RouterInfo, bool)>,
+}
+
+impl MountVisitor {
+    fn new() -> Self {
+        Self {
+            router_info: None,
+            locals: Vec::new(),
+        }
+    }
+
+    fn finish(mut self, name: String) -> Option<RouterInfo> {
+        let locals = std::mem::take(&mut self.locals);
+        for (_, info, used) in locals {
+            if !used {
+                self.add_router(info);
+            }
+        }
+
+        self.router_info.map(|mut info| {
+            info.name = name;
+            info
+        })
+    }
+
+    fn add_router(&mut self, info: RouterInfo) {
+        match &mut self.router_info {
+            Some(existing) => merge_router(existing, info, ""),
+            None => self.router_info = Some(info),
+        }
+    }
+
+    fn set_local(&mut self, name: String, info: RouterInfo) {
+        if let Some(local) = self.locals.iter_mut().find(|(n, _, _)| *n == name) {
+            local.1 = info;
+            local.2 = false;
+        } else {
+            self.locals.push((name, info, false));
+        }
+    }
+
+    fn use_local(&mut self, name: &str) -> Option<RouterInfo> {
+        self.locals
+            .iter_mut()
+            .find(|(n, _, _)| n == name)
+            .map(|local| {
+                local.2 = true;
+                local.1.clone()
+            })
+    }
+
+    /// Evaluate an expression that may build a Rocket instance
+    fn parse_instance(&mut self, expr: &Expr) -> Option<RouterInfo> {
+        let mut calls = Vec::new();
+        let mut root = strip_parens(expr);
+        while let Expr::MethodCall(call) = root {
+            calls.push(call);
+            root = strip_parens(&call.receiver);
+        }
+        calls.reverse();
+
+        let mut info = match root {
+            Expr::Call(call) if is_rocket_constructor(call) => empty_router(),
+            Expr::Path(path) => {
+                let ident = path.path.get_ident()?.to_string();
+                self.use_local(&ident)?
+            }
+            _ => return None,
+        };
+
+        for call in calls {
+            self.apply_call(&mut info, call);
+        }
+
+        Some(info)
+    }
+
+    fn apply_call(&mut self, info: &mut RouterInfo, call: &ExprMethodCall) {
+        let args: Vec<&Expr> = call.args.iter().collect();
+
+        match call.method.to_string().as_str() {
+            "mount" if args.len() >= 2 => {
+                let Some(base) = string_literal(args[0]) else {
+                    return;
+                };
+                match strip_parens(args[1]) {
+                    Expr::Macro(mac) => {
+                        info.mounts
+                            .extend(routes_macro(mac, &base).unwrap_or_default());
+                    }
+                    // Routes returned by another function, e.g. `api::routes()`
+                    Expr::Call(routes_call) => {
+                        if let Some(func) = path_of(&routes_call.func) {
+                            let target = path_to_string(func);
+                            info.nested_routers.push(target.clone());
+                            info.mounts.push(RouterMount {
+                                prefix: base,
+                                target,
+                                line: span_line(call.method.span()),
+                            });
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            "attach" if !args.is_empty() => {
+                let fairing = args[0];
+                info.middleware
+                    .push(quote::quote!(#fairing).to_string().replace(' ', ""));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Check whether a call is `rocket::build()`, `rocket::ignite()` or `rocket::custom(..)`
+fn is_rocket_constructor(call: &syn::ExprCall) -> bool {
+    let Some(func) = path_of(&call.func) else {
+        return false;
+    };
+    let segments: Vec<String> = func.segments.iter().map(|s| s.ident.to_string()).collect();
+    matches!(
+        segments.as_slice(),
+        [.., krate, ctor]
+            if (krate == "rocket" || krate == "Rocket")
+                && matches!(ctor.as_str(), "build" | "ignite" | "custom")
+    )
+}
+
+impl Visit<'_> for MountVisitor {
+    fn visit_item(&mut self, _item: &Item) {
+        // Nested items are analyzed on their own
+    }
+
+    fn visit_local(&mut self, local: &syn::Local) {
+        let name = match &local.pat {
+            Pat::Ident(pat_ident) => Some(pat_ident.ident.to_string()),
+            Pat::Type(pat_type) => match &*pat_type.pat {
+                Pat::Ident(pat_ident) => Some(pat_ident.ident.to_string()),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        if let (Some(name), Some(init)) = (name, &local.init) {
+            if let Some(info) = self.parse_instance(&init.expr) {
+                self.set_local(name, info);
+                return;
+            }
+        }
+
+        syn::visit::visit_local(self, local);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Assign(assign) => {
+                if let Some(ident) = path_of(&assign.left).and_then(|p| p.get_ident()) {
+                    if let Some(info) = self.parse_instance(&assign.right) {
+                        self.set_local(ident.to_string(), info);
+                        return;
+                    }
+                }
+            }
+            Expr::Macro(mac) => {
+                if let Some(mounts) = routes_macro(mac, "") {
+                    let mut info = empty_router();
+                    info.mounts = mounts;
+                    self.add_router(info);
+                    return;
+                }
+            }
+            Expr::Call(_) | Expr::MethodCall(_) => {
+                if let Some(info) = self.parse_instance(expr) {
+                    if !info.mounts.is_empty() {
+                        self.add_router(info);
+                    }
+                    return;
+                }
+            }
+            _ => {}
+        }
+
+        syn::visit::visit_expr(self, expr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_mount_base_paths() {
+        let detector = RocketDetector::new();
+
+        let syntax_tree: syn::File = parse_quote! {
+            #[macro_use]
+            extern crate rocket;
+
+            #[get("/<id>")]
+            fn get_user(id: u64, db: &State<Db>) -> Json<User> {
+                todo!()
+            }
+
+            #[post("/", data = "<user>", format = "json")]
+            fn create_user(user: Json<NewUser>) -> Status {
+                todo!()
+            }
+
+            #[get("/health?<verbose>")]
+            fn health(verbose: Option<bool>) -> &'static str {
+                "ok"
+            }
+
+            #[launch]
+            fn rocket() -> _ {
+                rocket::build()
+                    .mount("/users", routes![get_user, create_user])
+                    .mount("/", routes![health])
+            }
+        };
+
+        let endpoints = detector.extract_endpoints(&syntax_tree);
+        let routes: Vec<_> = endpoints
+            .iter()
+            .map(|e| (e.method.as_str(), e.path.as_str(), e.handler.as_str()))
+            .collect();
+
+        assert_eq!(
+            routes,
+            vec![
+                ("GET", "/users/<id>", "get_user"),
+                ("POST", "/users", "create_user"),
+                ("GET", "/health", "health"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_request_guards() {
+        let detector = RocketDetector::new();
+
+        let handler: ItemFn = parse_quote! {
+            /// Update a user
+            #[put("/<id>", data = "<user>")]
+            async fn update_user(id: u64, user: Json<User>, auth: ApiKey, db: &State<Db>) -> Status {
+                Status::Ok
+            }
+        };
+
+        let info = detector.detect_handler(&handler).unwrap();
+        let guards: Vec<(&str, bool)> = info
+            .parameters
+            .iter()
+            .map(|p| (p.name.as_str(), p.is_extractor))
+            .collect();
+
+        assert_eq!(
+            guards,
+            vec![("id", false), ("user", true), ("auth", true), ("db", true)]
+        );
+        assert_eq!(info.documentation.as_deref(), Some("Update a user"));
+    }
+
+    #[test]
+    fn test_route_attribute_with_method() {
+        let detector = RocketDetector::new();
+
+        let syntax_tree: syn::File = parse_quote! {
+            use rocket::{route, routes};
+
+            #[route(DELETE, uri = "/sessions/<id>")]
+            fn logout(id: String) {}
+
+            fn routes() -> Vec<rocket::Route> {
+                routes![logout]
+            }
+        };
+
+        let endpoints = detector.extract_endpoints(&syntax_tree);
+        assert_eq!(endpoints.len(), 1);
+        assert_eq!(endpoints[0].method, HttpMethod::Delete);
+        assert_eq!(endpoints[0].path, "/sessions/<id>");
+    }
+}
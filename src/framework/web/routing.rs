@@ -0,0 +1,428 @@
+//! Router composition shared by the web framework detectors
+//!
+//! Detectors reconstruct the routers built by individual functions as
+//! [`RouterInfo`]s and reference routers built elsewhere through
+//! [`RouterMount`]s. This module resolves those references across files and
+//! joins route prefixes, along with small syntax helpers the detectors share.
+
+use super::{Endpoint, RouteInfo, RouterInfo};
+use crate::ast::SourceFile;
+use crate::call_graph::ModuleTree;
+use std::path::Path;
+use syn::{Expr, Item, Lit, Pat};
+
+/// A router-building function together with the module it is defined in
+#[derive(Debug, Clone)]
+pub struct RouterFunction {
+    /// Module path of the function (e.g. `["api", "users"]`)
+    pub module_path: Vec<String>,
+
+    /// Router built by the function
+    pub info: RouterInfo,
+}
+
+impl RouterFunction {
+    /// Full path segments of the function (module path followed by its name)
+    pub fn full_path(&self) -> Vec<String> {
+        let mut path = self.module_path.clone();
+        path.push(self.info.name.clone());
+        path
+    }
+}
+
+/// A route whose path includes the prefixes of every enclosing mount
+#[derive(Debug, Clone)]
+pub struct ComposedRoute {
+    /// The route with its fully composed path and inherited middleware
+    pub route: RouteInfo,
+
+    /// Index of the router function that registered the route
+    pub router: usize,
+}
+
+/// Compose routers built by separate functions into fully prefixed routes
+///
+/// Mounts (e.g. Axum's `nest`/`merge` or Actix's `service`/`configure`) are
+/// resolved against the given router functions.
+/// Routers that are never mounted by another router are treated as roots, so
+/// a sub-router only contributes routes under the prefixes it is mounted at.
+pub fn compose_routes(routers: &[RouterFunction]) -> Vec<ComposedRoute> {
+    let paths: Vec<Vec<String>> = routers.iter().map(RouterFunction::full_path).collect();
+
+    let resolved: Vec<Vec<(String, Option<usize>)>> = routers
+        .iter()
+        .map(|router| {
+            router
+                .info
+                .mounts
+                .iter()
+                .map(|mount| {
+                    let target = resolve_item_path(
+                        paths.iter().map(Vec::as_slice),
+                        &router.module_path,
+                        &mount.target,
+                    );
+                    (mount.prefix.clone(), target)
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut mounted = vec![false; routers.len()];
+    for (index, mounts) in resolved.iter().enumerate() {
+        for target in mounts.iter().filter_map(|(_, target)| *target) {
+            if target != index {
+                mounted[target] = true;
+            }
+        }
+    }
+
+    let mut composed = Vec::new();
+    let mut stack = Vec::new();
+    for root in (0..routers.len()).filter(|&index| !mounted[index]) {
+        compose_into(routers, &resolved, root, "", &[], &mut stack, &mut composed);
+    }
+
+    composed
+}
+
+/// Compose the routers built in a single file into endpoints
+///
+/// # Arguments
+///
+/// * `syntax_tree` - The parsed file
+/// * `router_functions` - The detector's extractor of router-building
+///   functions from a file in a module
+pub(crate) fn file_endpoints(
+    syntax_tree: &syn::File,
+    router_functions: impl Fn(&syn::File, &[String]) -> Vec<RouterFunction>,
+) -> Vec<Endpoint> {
+    let routers = router_functions(syntax_tree, &[]);
+
+    compose_routes(&routers)
+        .into_iter()
+        .map(|composed| endpoint(composed, &routers, None))
+        .collect()
+}
+
+/// Compose the routers built across every file of a project into endpoints
+///
+/// Routers are composed across files so that a router mounted from another
+/// module resolves to fully prefixed paths. Routes reachable through several
+/// identical mounts are reported once.
+///
+/// # Arguments
+///
+/// * `files` - The parsed source files of the project
+/// * `modules` - Module tree placing each file in its crate
+/// * `router_functions` - The detector's extractor of router-building
+///   functions from a file in a module
+pub(crate) fn project_endpoints(
+    files: &[SourceFile],
+    modules: &ModuleTree,
+    router_functions: impl Fn(&syn::File, &[String]) -> Vec<RouterFunction>,
+) -> Vec<Endpoint> {
+    let mut routers = Vec::new();
+    let mut router_files = Vec::new();
+
+    for file in files {
+        let module_path = modules.module_location(file.path()).modules;
+        for router in router_functions(&file.syntax_tree, &module_path) {
+            routers.push(router);
+            router_files.push(file.path());
+        }
+    }
+
+    let mut endpoints: Vec<Endpoint> = Vec::new();
+    for composed in compose_routes(&routers) {
+        let file = router_files[composed.router];
+        let endpoint = endpoint(composed, &routers, Some(file));
+
+        if !endpoints.iter().any(|e| {
+            e.method == endpoint.method && e.path == endpoint.path && e.handler == endpoint.handler
+        }) {
+            endpoints.push(endpoint);
+        }
+    }
+
+    endpoints
+}
+
+/// Converts a composed route into an endpoint of the router's module
+fn endpoint(composed: ComposedRoute, routers: &[RouterFunction], file: Option<&Path>) -> Endpoint {
+    Endpoint {
+        method: composed.route.method,
+        path: composed.route.path,
+        handler: composed.route.handler,
+        module_path: routers[composed.router].module_path.clone(),
+        file: file.map(Path::to_path_buf),
+        line: composed.route.line,
+        middleware: composed.route.middleware,
+        documentation: None,
+        streaming: None,
+    }
+}
+
+/// Recursively emit the routes of a router and everything mounted into it
+fn compose_into(
+    routers: &[RouterFunction],
+    resolved: &[Vec<(String, Option<usize>)>],
+    index: usize,
+    prefix: &str,
+    middleware: &[String],
+    stack: &mut Vec<usize>,
+    composed: &mut Vec<ComposedRoute>,
+) {
+    // Guard against routers that (indirectly) mount themselves
+    if stack.contains(&index) {
+        return;
+    }
+    stack.push(index);
+
+    let info = &routers[index].info;
+    let mut inherited = info.middleware.clone();
+    inherited.extend_from_slice(middleware);
+
+    for route in &info.routes {
+        let mut route = route.clone();
+        route.path = join_paths(prefix, &route.path);
+        route.middleware.extend(inherited.iter().cloned());
+        composed.push(ComposedRoute {
+            route,
+            router: index,
+        });
+    }
+
+    for (mount_prefix, target) in &resolved[index] {
+        if let Some(target) = target {
+            let prefix = join_paths(prefix, mount_prefix);
+            compose_into(
+                routers, resolved, *target, &prefix, &inherited, stack, composed,
+            );
+        }
+    }
+
+    stack.pop();
+}
+
+/// Join a route prefix and a route path into a single path
+///
+/// `("/api", "/users")` becomes `/api/users`, and a root route nested under a
+/// prefix (`("/api", "/")`) becomes `/api`.
+pub(crate) fn join_paths(prefix: &str, path: &str) -> String {
+    let prefix = prefix.trim_end_matches('/');
+    if prefix.is_empty() {
+        return path.to_string();
+    }
+
+    let path = path.trim_start_matches('/');
+    if path.is_empty() {
+        prefix.to_string()
+    } else {
+        format!("{prefix}/{path}")
+    }
+}
+
+/// Find the definition a path written in `from_module` most likely refers to
+///
+/// `definitions` yields the full path segments of every candidate (module path
+/// followed by the item name). `crate::`, `self::` and `super::` prefixes are
+/// resolved exactly; other paths are matched by suffix, preferring candidates
+/// that are closest to `from_module`.
+///
+/// # Returns
+///
+/// The index of the chosen definition, or `None` if nothing matches
+pub(crate) fn resolve_item_path<'a>(
+    definitions: impl IntoIterator<Item = &'a [String]>,
+    from_module: &[String],
+    path: &str,
+) -> Option<usize> {
+    let mut segments: Vec<&str> = path.split("::").filter(|s| !s.is_empty()).collect();
+    let mut base: Option<Vec<String>> = None;
+
+    match segments.first().copied() {
+        Some("crate") => {
+            segments.remove(0);
+            base = Some(Vec::new());
+        }
+        Some("self") => {
+            segments.remove(0);
+            base = Some(from_module.to_vec());
+        }
+        Some("super") => {
+            let mut module = from_module.to_vec();
+            while segments.first() == Some(&"super") {
+                segments.remove(0);
+                module.pop();
+            }
+            base = Some(module);
+        }
+        _ => {}
+    }
+
+    if segments.is_empty() {
+        return None;
+    }
+
+    let mut best: Option<(usize, usize)> = None;
+    for (index, definition) in definitions.into_iter().enumerate() {
+        if definition.len() < segments.len() {
+            continue;
+        }
+
+        let split = definition.len() - segments.len();
+        let (module, tail) = definition.split_at(split);
+        if tail.iter().zip(&segments).any(|(a, b)| a != b) {
+            continue;
+        }
+
+        let score = match &base {
+            Some(base) if module == base.as_slice() => usize::MAX,
+            Some(_) => continue,
+            // A path relative to the current module is an exact match
+            None if module == from_module => usize::MAX,
+            None => module
+                .iter()
+                .zip(from_module)
+                .take_while(|(a, b)| a == b)
+                .count(),
+        };
+
+        if best.is_none_or(|(_, best_score)| score > best_score) {
+            best = Some((index, score));
+        }
+    }
+
+    best.map(|(index, _)| index)
+}
+
+/// Name the bindings introduced by a parameter pattern
+pub(crate) fn binding_name(pat: &Pat) -> String {
+    match pat {
+        Pat::Ident(pat_ident) => pat_ident.ident.to_string(),
+        Pat::TupleStruct(tuple_struct) => tuple_struct
+            .elems
+            .iter()
+            .map(binding_name)
+            .collect::<Vec<_>>()
+            .join(", "),
+        Pat::Tuple(tuple) => tuple
+            .elems
+            .iter()
+            .map(binding_name)
+            .collect::<Vec<_>>()
+            .join(", "),
+        Pat::Struct(pat_struct) => pat_struct
+            .fields
+            .iter()
+            .map(|field| binding_name(&field.pat))
+            .collect::<Vec<_>>()
+            .join(", "),
+        Pat::Type(pat_type) => binding_name(&pat_type.pat),
+        Pat::Reference(reference) => binding_name(&reference.pat),
+        Pat::Paren(paren) => binding_name(&paren.pat),
+        Pat::Wild(_) => "_".to_string(),
+        other => quote::quote!(#other).to_string(),
+    }
+}
+
+/// Create a router with no routes
+pub(crate) fn empty_router() -> RouterInfo {
+    RouterInfo {
+        name: "router".to_string(),
+        base_path: None,
+        routes: Vec::new(),
+        middleware: Vec::new(),
+        nested_routers: Vec::new(),
+        mounts: Vec::new(),
+    }
+}
+
+/// Merge `sub` into `info`, prefixing every route and mount of `sub`
+pub(crate) fn merge_router(info: &mut RouterInfo, sub: RouterInfo, prefix: &str) {
+    for mut route in sub.routes {
+        route.path = join_paths(prefix, &route.path);
+        route.middleware.extend(sub.middleware.iter().cloned());
+        info.routes.push(route);
+    }
+    for mut mount in sub.mounts {
+        mount.prefix = join_paths(prefix, &mount.prefix);
+        info.mounts.push(mount);
+    }
+    info.nested_routers.extend(sub.nested_routers);
+}
+
+/// Strip parentheses, groups and references around an expression
+pub(crate) fn strip_parens(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Paren(paren) => strip_parens(&paren.expr),
+        Expr::Group(group) => strip_parens(&group.expr),
+        Expr::Reference(reference) => strip_parens(&reference.expr),
+        _ => expr,
+    }
+}
+
+/// Value of a string literal expression
+pub(crate) fn string_literal(expr: &Expr) -> Option<String> {
+    match strip_parens(expr) {
+        Expr::Lit(expr_lit) => match &expr_lit.lit {
+            Lit::Str(lit_str) => Some(lit_str.value()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Path of a path expression
+pub(crate) fn path_of(expr: &Expr) -> Option<&syn::Path> {
+    match strip_parens(expr) {
+        Expr::Path(expr_path) => Some(&expr_path.path),
+        _ => None,
+    }
+}
+
+/// Render a path as `a::b::c`, dropping generic arguments
+pub(crate) fn path_to_string(path: &syn::Path) -> String {
+    path.segments
+        .iter()
+        .map(|s| s.ident.to_string())
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// Name of a handler given as a path expression (closures have none)
+pub(crate) fn handler_name(expr: &Expr) -> Option<String> {
+    path_of(expr).map(path_to_string)
+}
+
+/// Line on which a span starts
+pub(crate) fn span_line(span: proc_macro2::Span) -> usize {
+    span.start().line
+}
+
+/// Check whether any item imports from or names the given crate
+///
+/// Looks at `use` declarations, `extern crate` items and attribute paths
+/// (e.g. `#[actix_web::main]`), including inside inline modules.
+pub(crate) fn references_crate(items: &[Item], crate_name: &str) -> bool {
+    items.iter().any(|item| match item {
+        Item::Use(item_use) => match &item_use.tree {
+            syn::UseTree::Path(use_path) => use_path.ident == crate_name,
+            syn::UseTree::Name(use_name) => use_name.ident == crate_name,
+            _ => false,
+        },
+        Item::ExternCrate(extern_crate) => extern_crate.ident == crate_name,
+        Item::Fn(item_fn) => item_fn.attrs.iter().any(|attr| {
+            attr.path()
+                .segments
+                .first()
+                .is_some_and(|s| s.ident == crate_name)
+        }),
+        Item::Mod(item_mod) => item_mod
+            .content
+            .as_ref()
+            .is_some_and(|(_, items)| references_crate(items, crate_name)),
+        _ => false,
+    })
+}
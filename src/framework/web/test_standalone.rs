@@ -35,7 +35,6 @@ mod tests {
     }
 
     #[test]
-    #[ignore = "Framework detection needs implementation"]
     fn test_simple_endpoint_extraction() {
         let detector = AxumDetector::new();
 
@@ -62,7 +61,6 @@ mod tests {
     }
 
     #[test]
-    #[ignore = "Framework detection needs implementation"]
     fn test_multiple_methods_extraction() {
         let detector = AxumDetector::new();
 
@@ -90,7 +88,6 @@ mod tests {
     }
 
     #[test]
-    #[ignore = "Framework detection needs implementation"]
     fn test_handler_detection_async() {
         let detector = AxumDetector::new();
 
@@ -125,7 +122,6 @@ mod tests {
     }
 
     #[test]
-    #[ignore = "Framework detection needs implementation"]
     fn test_handler_with_multiple_extractors() {
         let detector = AxumDetector::new();
 
@@ -148,7 +144,6 @@ mod tests {
     }
 
     #[test]
-    #[ignore = "Framework detection needs implementation"]
     fn test_router_analysis() {
         let detector = AxumDetector::new();
 
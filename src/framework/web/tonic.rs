@@ -0,0 +1,776 @@
+//! Tonic gRPC framework detector and analyzer
+//!
+//! This module implements the `FrameworkDetector` trait for Tonic. gRPC
+//! methods are found through implementations of the service traits generated
+//! into `*_server` modules (`impl greeter_server::Greeter for MyGreeter`).
+//! The protobuf package comes from the `include_proto!` invocation of the
+//! generated module, and `.proto` files under the analyzed project root are
+//! parsed (without `protoc`) to confirm packages, method names and streaming
+//! modes.
+
+use super::routing::{path_to_string, span_line};
+use super::{
+    Endpoint, FrameworkDetector, HandlerInfo, HandlerParam, HttpMethod, RouterInfo, StreamingKind,
+};
+use crate::Result;
+use crate::ast::SourceFile;
+use crate::call_graph::ModuleTree;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use syn::{FnArg, ImplItem, Item, ItemFn, ItemImpl, ReturnType, UseTree};
+use walkdir::WalkDir;
+
+/// Detector for the Tonic gRPC framework
+pub struct TonicDetector;
+
+impl TonicDetector {
+    /// Create a new Tonic detector
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Collect the gRPC service implementations of a file
+    ///
+    /// # Arguments
+    ///
+    /// * `syntax_tree` - The parsed syntax tree of a source file
+    /// * `module_path` - Module path of the file within its crate
+    pub fn service_impls(
+        &self,
+        syntax_tree: &syn::File,
+        module_path: &[String],
+    ) -> Vec<ServiceImpl> {
+        let mut impls = Vec::new();
+        collect_service_impls(&syntax_tree.items, module_path, &HashMap::new(), &mut impls);
+        impls
+    }
+
+    /// Build endpoints for service implementations
+    fn endpoints_for(
+        &self,
+        impls: &[ServiceImpl],
+        packages: &[ProtoPackage],
+        protos: &[ProtoFile],
+        files: &[Option<PathBuf>],
+    ) -> Vec<Endpoint> {
+        let mut endpoints = Vec::new();
+
+        for (service, file) in impls.iter().zip(files) {
+            let proto_service =
+                find_proto_service(protos, &service.service, service.package_module());
+            let package = proto_service
+                .map(|(package, _)| package.to_string())
+                .or_else(|| resolve_package(packages, service));
+
+            for method in &service.methods {
+                let proto_method = proto_service.and_then(|(_, proto)| {
+                    proto
+                        .methods
+                        .iter()
+                        .find(|m| to_snake_case(&m.name) == method.name)
+                });
+
+                let method_name = proto_method
+                    .map(|m| m.name.clone())
+                    .unwrap_or_else(|| to_upper_camel_case(&method.name));
+                // The proto definition is authoritative for streaming modes
+                let streaming = proto_method
+                    .map(|m| StreamingKind::from_streams(m.client_streaming, m.server_streaming))
+                    .unwrap_or(method.streaming);
+
+                let path = match &package {
+                    Some(package) if !package.is_empty() => {
+                        format!("/{package}.{}/{method_name}", service.service)
+                    }
+                    _ => format!("/{}/{method_name}", service.service),
+                };
+
+                endpoints.push(Endpoint {
+                    method: HttpMethod::Custom("gRPC"),
+                    path,
+                    handler: format!("{}::{}", service.self_ty, method.name),
+                    module_path: service.module_path.clone(),
+                    file: file.clone(),
+                    line: method.line,
+                    middleware: Vec::new(),
+                    documentation: None,
+                    streaming: Some(streaming),
+                });
+            }
+        }
+
+        endpoints
+    }
+}
+
+impl Default for TonicDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameworkDetector for TonicDetector {
+    fn name(&self) -> &'static str {
+        "Tonic"
+    }
+
+    fn detect(&self, project_root: &Path) -> Result<bool> {
+        super::manifest_has_dependency(project_root, "tonic")
+    }
+
+    fn extract_endpoints(&self, syntax_tree: &syn::File) -> Vec<Endpoint> {
+        let impls = self.service_impls(syntax_tree, &[]);
+        let packages = proto_packages(&syntax_tree.items, &[]);
+        let files = vec![None; impls.len()];
+        self.endpoints_for(&impls, &packages, &[], &files)
+    }
+
+    fn extract_project_endpoints(
+        &self,
+        files: &[SourceFile],
+        modules: &ModuleTree,
+    ) -> Vec<Endpoint> {
+        let mut impls = Vec::new();
+        let mut impl_files = Vec::new();
+        let mut packages = Vec::new();
+
+        for file in files {
+            let module_path = modules.module_location(file.path()).modules;
+            for service in self.service_impls(&file.syntax_tree, &module_path) {
+                impls.push(service);
+                impl_files.push(Some(file.path().to_path_buf()));
+            }
+            packages.extend(proto_packages(&file.syntax_tree.items, &module_path));
+        }
+
+        if impls.is_empty() {
+            return Vec::new();
+        }
+
+        let protos = find_proto_files(modules);
+        self.endpoints_for(&impls, &packages, &protos, &impl_files)
+    }
+
+    fn analyze_router(&self, _item: &Item) -> Option<RouterInfo> {
+        // Tonic services are registered with `Server::builder().add_service(..)`
+        // and carry their routes in the generated code
+        None
+    }
+
+    fn detect_handler(&self, function: &ItemFn) -> Option<HandlerInfo> {
+        let sig = &function.sig;
+        let request = sig.inputs.iter().find_map(|input| match input {
+            FnArg::Typed(pat_type) => {
+                let ty = &pat_type.ty;
+                Some((pat_type, quote::quote!(#ty).to_string()))
+            }
+            FnArg::Receiver(_) => None,
+        });
+        let (pat_type, ty) = request?;
+        if !ty.contains("Request") {
+            return None;
+        }
+
+        let return_type = match &sig.output {
+            ReturnType::Default => "()".to_string(),
+            ReturnType::Type(_, ty) => quote::quote!(#ty).to_string(),
+        };
+
+        Some(HandlerInfo {
+            name: sig.ident.to_string(),
+            is_async: sig.asyncness.is_some(),
+            parameters: vec![HandlerParam {
+                name: super::routing::binding_name(&pat_type.pat),
+                ty: ty.clone(),
+                is_extractor: true,
+            }],
+            return_type,
+            extractors: vec![ty],
+            documentation: super::doc_comment(&function.attrs),
+        })
+    }
+}
+
+/// An implementation of a generated Tonic service trait
+#[derive(Debug, Clone)]
+pub struct ServiceImpl {
+    /// Name of the gRPC service (the trait name, e.g. `Greeter`)
+    pub service: String,
+
+    /// Full path of the implemented trait (e.g. `hello_world::greeter_server::Greeter`)
+    pub trait_path: Vec<String>,
+
+    /// Implementing type (e.g. `MyGreeter`)
+    pub self_ty: String,
+
+    /// Module path of the implementation
+    pub module_path: Vec<String>,
+
+    /// Implemented RPC methods
+    pub methods: Vec<ServiceMethod>,
+}
+
+impl ServiceImpl {
+    /// Path of the module the service code was generated into
+    fn package_module(&self) -> &[String] {
+        let end = self.trait_path.len().saturating_sub(2);
+        let start = self.trait_path[..end]
+            .iter()
+            .position(|s| !matches!(s.as_str(), "crate" | "self" | "super"))
+            .unwrap_or(end);
+        &self.trait_path[start..end]
+    }
+}
+
+/// An RPC method of a service implementation
+#[derive(Debug, Clone)]
+pub struct ServiceMethod {
+    /// Rust method name (e.g. `say_hello`)
+    pub name: String,
+
+    /// Streaming mode derived from the method signature
+    pub streaming: StreamingKind,
+
+    /// Line number of the method
+    pub line: usize,
+}
+
+/// Protobuf package included into a module by `include_proto!`
+#[derive(Debug, Clone)]
+struct ProtoPackage {
+    module_path: Vec<String>,
+    package: String,
+}
+
+/// Services declared by a `.proto` file
+#[derive(Debug, Clone, Default)]
+struct ProtoFile {
+    package: String,
+    services: Vec<ProtoService>,
+}
+
+#[derive(Debug, Clone)]
+struct ProtoService {
+    name: String,
+    methods: Vec<ProtoMethod>,
+}
+
+#[derive(Debug, Clone)]
+struct ProtoMethod {
+    name: String,
+    client_streaming: bool,
+    server_streaming: bool,
+}
+
+fn collect_service_impls(
+    items: &[Item],
+    module_path: &[String],
+    inherited_imports: &HashMap<String, Vec<String>>,
+    impls: &mut Vec<ServiceImpl>,
+) {
+    let mut imports = inherited_imports.clone();
+    for item in items {
+        if let Item::Use(item_use) = item {
+            collect_imports(&item_use.tree, &mut Vec::new(), &mut imports);
+        }
+    }
+
+    for item in items {
+        match item {
+            Item::Impl(item_impl) => {
+                if let Some(service) = service_impl(item_impl, module_path, &imports) {
+                    impls.push(service);
+                }
+            }
+            Item::Mod(item_mod) => {
+                if let Some((_, items)) = &item_mod.content {
+                    let mut nested = module_path.to_vec();
+                    nested.push(item_mod.ident.to_string());
+                    collect_service_impls(items, &nested, &imports, impls);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Record the local names introduced by a `use` tree
+fn collect_imports(
+    tree: &UseTree,
+    prefix: &mut Vec<String>,
+    imports: &mut HashMap<String, Vec<String>>,
+) {
+    match tree {
+        UseTree::Path(use_path) => {
+            prefix.push(use_path.ident.to_string());
+            collect_imports(&use_path.tree, prefix, imports);
+            prefix.pop();
+        }
+        UseTree::Name(use_name) => {
+            let mut full = prefix.clone();
+            full.push(use_name.ident.to_string());
+            imports.insert(use_name.ident.to_string(), full);
+        }
+        UseTree::Rename(use_rename) => {
+            let mut full = prefix.clone();
+            full.push(use_rename.ident.to_string());
+            imports.insert(use_rename.rename.to_string(), full);
+        }
+        UseTree::Group(group) => {
+            for tree in &group.items {
+                collect_imports(tree, prefix, imports);
+            }
+        }
+        UseTree::Glob(_) => {}
+    }
+}
+
+/// Recognize `impl <pkg>::<service>_server::<Service> for <Type>`
+fn service_impl(
+    item_impl: &ItemImpl,
+    module_path: &[String],
+    imports: &HashMap<String, Vec<String>>,
+) -> Option<ServiceImpl> {
+    let (_, trait_path, _) = item_impl.trait_.as_ref()?;
+    let mut segments: Vec<String> = path_to_string(trait_path)
+        .split("::")
+        .map(str::to_string)
+        .collect();
+
+    // Expand the first segment through the imports of the module
+    if let Some(full) = imports.get(&segments[0]) {
+        let mut expanded = full.clone();
+        expanded.extend(segments.drain(1..));
+        segments = expanded;
+    }
+
+    let [.., server_module, service] = segments.as_slice() else {
+        return None;
+    };
+    if !server_module.ends_with("_server") {
+        return None;
+    }
+    let service = service.clone();
+
+    let self_ty = &item_impl.self_ty;
+    let server_streams: Vec<String> = item_impl
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ImplItem::Type(assoc) => Some(assoc.ident.to_string()),
+            _ => None,
+        })
+        .collect();
+
+    let methods = item_impl
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ImplItem::Fn(method) => Some(ServiceMethod {
+                name: method.sig.ident.to_string(),
+                streaming: signature_streaming(&method.sig, &server_streams),
+                line: span_line(method.sig.ident.span()),
+            }),
+            _ => None,
+        })
+        .collect();
+
+    Some(ServiceImpl {
+        service,
+        trait_path: segments,
+        self_ty: quote::quote!(#self_ty).to_string().replace(' ', ""),
+        module_path: module_path.to_vec(),
+        methods,
+    })
+}
+
+/// Derive the streaming mode of an RPC from its Rust signature
+///
+/// Client streaming methods take `Request<Streaming<T>>`; server streaming
+/// methods respond with one of the associated stream types of the impl
+/// (`Response<Self::ListFeaturesStream>`).
+fn signature_streaming(sig: &syn::Signature, server_streams: &[String]) -> StreamingKind {
+    let client = sig.inputs.iter().any(|input| match input {
+        FnArg::Typed(pat_type) => {
+            let ty = &pat_type.ty;
+            quote::quote!(#ty).to_string().contains("Streaming <")
+        }
+        FnArg::Receiver(_) => false,
+    });
+
+    let server = match &sig.output {
+        ReturnType::Default => false,
+        ReturnType::Type(_, ty) => {
+            let ty = quote::quote!(#ty).to_string().replace(' ', "");
+            server_streams
+                .iter()
+                .any(|stream| ty.contains(&format!("Self::{stream}")))
+                || ty.contains("Response<Pin<Box<dynStream")
+                || ty.contains("Response<ReceiverStream")
+        }
+    };
+
+    StreamingKind::from_streams(client, server)
+}
+
+/// Collect the `include_proto!("package")` invocations of a file
+///
+/// `include!(concat!(env!("OUT_DIR"), "/package.rs"))` is recognized as well.
+fn proto_packages(items: &[Item], module_path: &[String]) -> Vec<ProtoPackage> {
+    let mut packages = Vec::new();
+
+    for item in items {
+        match item {
+            Item::Macro(item_macro) => {
+                let Some(name) = item_macro
+                    .mac
+                    .path
+                    .segments
+                    .last()
+                    .map(|s| s.ident.to_string())
+                else {
+                    continue;
+                };
+                let tokens = item_macro.mac.tokens.to_string();
+                let package = match name.as_str() {
+                    "include_proto" => first_string_literal(&tokens),
+                    "include" => first_string_literal_ending(&tokens, ".rs").map(|file| {
+                        file.trim_start_matches('/')
+                            .trim_end_matches(".rs")
+                            .to_string()
+                    }),
+                    _ => None,
+                };
+                if let Some(package) = package {
+                    packages.push(ProtoPackage {
+                        module_path: module_path.to_vec(),
+                        package,
+                    });
+                }
+            }
+            Item::Mod(item_mod) => {
+                if let Some((_, items)) = &item_mod.content {
+                    let mut nested = module_path.to_vec();
+                    nested.push(item_mod.ident.to_string());
+                    packages.extend(proto_packages(items, &nested));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    packages
+}
+
+fn first_string_literal(tokens: &str) -> Option<String> {
+    let start = tokens.find('"')? + 1;
+    let end = tokens[start..].find('"')? + start;
+    Some(tokens[start..end].to_string())
+}
+
+fn first_string_literal_ending(tokens: &str, suffix: &str) -> Option<String> {
+    tokens
+        .split('"')
+        .skip(1)
+        .step_by(2)
+        .find(|literal| literal.ends_with(suffix))
+        .map(str::to_string)
+}
+
+/// Find the package included into the module the service was generated into
+fn resolve_package(packages: &[ProtoPackage], service: &ServiceImpl) -> Option<String> {
+    let module = service.package_module();
+    let paths: Vec<&[String]> = packages.iter().map(|p| p.module_path.as_slice()).collect();
+
+    packages
+        .iter()
+        .zip(&paths)
+        .find(|(_, path)| !module.is_empty() && path.ends_with(module))
+        .or_else(|| {
+            // Generated code included directly into the implementing module
+            packages
+                .iter()
+                .zip(&paths)
+                .find(|(_, path)| module.is_empty() && **path == service.module_path.as_slice())
+        })
+        .map(|(package, _)| package.package.clone())
+}
+
+/// Find a service in the parsed `.proto` files
+///
+/// When several packages declare a service with the same name, the one whose
+/// package matches the generated module name is preferred.
+fn find_proto_service<'a>(
+    protos: &'a [ProtoFile],
+    service: &str,
+    module: &[String],
+) -> Option<(&'a str, &'a ProtoService)> {
+    let candidates: Vec<(&str, &ProtoService)> = protos
+        .iter()
+        .flat_map(|proto| {
+            proto
+                .services
+                .iter()
+                .filter(|s| s.name == service)
+                .map(move |s| (proto.package.as_str(), s))
+        })
+        .collect();
+
+    let module_name = module.last().map(|m| m.replace('_', ""));
+    candidates
+        .iter()
+        .find(|(package, _)| {
+            module_name
+                .as_deref()
+                .is_some_and(|name| package.replace(['.', '_'], "").ends_with(name))
+        })
+        .or_else(|| candidates.first())
+        .copied()
+}
+
+/// Find and parse the `.proto` files of the analyzed project
+///
+/// Only the project root of the module tree is searched, skipping `target`
+/// and hidden directories, so manifests above the project are never used.
+fn find_proto_files(modules: &ModuleTree) -> Vec<ProtoFile> {
+    let mut protos = Vec::new();
+    if let Some(root) = modules.root() {
+        let entries = WalkDir::new(root).into_iter().filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            entry.depth() == 0 || !(name.starts_with('.') || name == "target")
+        });
+        for entry in entries.filter_map(|e| e.ok()) {
+            if entry.path().extension().is_some_and(|ext| ext == "proto") {
+                if let Ok(source) = std::fs::read_to_string(entry.path()) {
+                    protos.push(parse_proto(&source));
+                }
+            }
+        }
+    }
+
+    protos
+}
+
+/// Parse the package and services of a `.proto` file
+///
+/// Only the subset needed to name RPCs is understood: the `package`
+/// statement, `service` blocks and their `rpc` declarations.
+fn parse_proto(source: &str) -> ProtoFile {
+    let comments = Regex::new(r"(?s)//[^\n]*|/\*.*?\*/").expect("valid regex");
+    let package_re = Regex::new(r"\bpackage\s+([\w.]+)\s*;").expect("valid regex");
+    let service_re = Regex::new(r"\bservice\s+(\w+)\s*\{").expect("valid regex");
+    let rpc_re = Regex::new(
+        r"\brpc\s+(\w+)\s*\(\s*(stream\s+)?[\w.]+\s*\)\s*returns\s*\(\s*(stream\s+)?[\w.]+\s*\)",
+    )
+    .expect("valid regex");
+
+    let source = comments.replace_all(source, "");
+    let mut proto = ProtoFile {
+        package: package_re
+            .captures(&source)
+            .map(|c| c[1].to_string())
+            .unwrap_or_default(),
+        services: Vec::new(),
+    };
+
+    for captures in service_re.captures_iter(&source) {
+        let body_start = captures.get(0).map_or(0, |m| m.end());
+        let mut depth = 1;
+        let mut body_end = source.len();
+        for (offset, c) in source[body_start..].char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        body_end = body_start + offset;
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let methods = rpc_re
+            .captures_iter(&source[body_start..body_end])
+            .map(|rpc| ProtoMethod {
+                name: rpc[1].to_string(),
+                client_streaming: rpc.get(2).is_some(),
+                server_streaming: rpc.get(3).is_some(),
+            })
+            .collect();
+
+        proto.services.push(ProtoService {
+            name: captures[1].to_string(),
+            methods,
+        });
+    }
+
+    proto
+}
+
+/// Convert `say_hello` to `SayHello`
+fn to_upper_camel_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Convert `SayHello` to `say_hello`
+fn to_snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_service_impl_endpoints() {
+        let detector = TonicDetector::new();
+
+        let syntax_tree: syn::File = parse_quote! {
+            pub mod hello_world {
+                tonic::include_proto!("helloworld");
+            }
+
+            use hello_world::greeter_server::{Greeter, GreeterServer};
+
+            #[tonic::async_trait]
+            impl Greeter for MyGreeter {
+                type SayManyStream = ReceiverStream<Result<HelloReply, Status>>;
+
+                async fn say_hello(
+                    &self,
+                    request: Request<HelloRequest>,
+                ) -> Result<Response<HelloReply>, Status> {
+                    todo!()
+                }
+
+                async fn say_many(
+                    &self,
+                    request: Request<HelloRequest>,
+                ) -> Result<Response<Self::SayManyStream>, Status> {
+                    todo!()
+                }
+
+                async fn collect(
+                    &self,
+                    request: Request<Streaming<HelloRequest>>,
+                ) -> Result<Response<HelloReply>, Status> {
+                    todo!()
+                }
+            }
+        };
+
+        let endpoints = detector.extract_endpoints(&syntax_tree);
+        let summary: Vec<_> = endpoints
+            .iter()
+            .map(|e| (e.path.as_str(), e.handler.as_str(), e.streaming.unwrap()))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "/helloworld.Greeter/SayHello",
+                    "MyGreeter::say_hello",
+                    StreamingKind::Unary
+                ),
+                (
+                    "/helloworld.Greeter/SayMany",
+                    "MyGreeter::say_many",
+                    StreamingKind::ServerStreaming
+                ),
+                (
+                    "/helloworld.Greeter/Collect",
+                    "MyGreeter::collect",
+                    StreamingKind::ClientStreaming
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_non_service_impls_are_ignored() {
+        let detector = TonicDetector::new();
+
+        let syntax_tree: syn::File = parse_quote! {
+            impl Display for MyGreeter {
+                fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                    Ok(())
+                }
+            }
+        };
+
+        assert!(detector.extract_endpoints(&syntax_tree).is_empty());
+    }
+
+    #[test]
+    fn test_parse_proto() {
+        let proto = parse_proto(
+            r#"
+            syntax = "proto3";
+            // The route guide service
+            package routeguide.v1;
+
+            service RouteGuide {
+                rpc GetFeature(Point) returns (Feature) {}
+                /* rpc Disabled(Point) returns (Feature); */
+                rpc ListFeatures(Rectangle) returns (stream Feature) {}
+                rpc RecordRoute(stream Point) returns (RouteSummary) {}
+                rpc RouteChat(stream RouteNote) returns (stream RouteNote) {}
+            }
+            "#,
+        );
+
+        assert_eq!(proto.package, "routeguide.v1");
+        assert_eq!(proto.services.len(), 1);
+
+        let kinds: Vec<_> = proto.services[0]
+            .methods
+            .iter()
+            .map(|m| {
+                (
+                    m.name.as_str(),
+                    StreamingKind::from_streams(m.client_streaming, m.server_streaming),
+                )
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("GetFeature", StreamingKind::Unary),
+                ("ListFeatures", StreamingKind::ServerStreaming),
+                ("RecordRoute", StreamingKind::ClientStreaming),
+                ("RouteChat", StreamingKind::Bidirectional),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_case_conversion() {
+        assert_eq!(to_upper_camel_case("list_features"), "ListFeatures");
+        assert_eq!(to_snake_case("ListFeatures"), "list_features");
+    }
+}
@@ -0,0 +1,664 @@
+//! Warp framework detector and analyzer
+//!
+//! This module implements the `FrameworkDetector` trait for Warp. Warp has no
+//! router: endpoints are filter chains such as
+//! `warp::path!("users" / u32).and(warp::get()).and_then(get_user)`, combined
+//! with `.or()`. Filters are evaluated symbolically as sets of alternatives,
+//! each carrying the path segments, method and handler it matches.
+
+use super::routing::{
+    RouterFunction, empty_router, file_endpoints, path_of, path_to_string, project_endpoints,
+    references_crate, span_line, string_literal, strip_parens,
+};
+use super::{
+    Endpoint, FrameworkDetector, HandlerInfo, HandlerParam, HttpMethod, RouteInfo, RouterInfo,
+    RouterMount,
+};
+use crate::Result;
+use crate::ast::SourceFile;
+use crate::call_graph::ModuleTree;
+use proc_macro2::TokenTree;
+use std::path::Path;
+use syn::{Expr, ExprMacro, FnArg, Item, ItemFn, Pat, PathArguments, ReturnType, visit::Visit};
+
+/// Detector for the Warp web framework
+pub struct WarpDetector;
+
+/// One alternative of a filter: a request matching all of its parts
+#[derive(Debug, Clone, Default)]
+struct Branch {
+    /// Path matched so far (e.g. `/users/{u32}`)
+    path: String,
+    method: Option<HttpMethod>,
+    handler: Option<String>,
+    /// Filter function this branch continues in (e.g. `users::routes`)
+    target: Option<String>,
+    middleware: Vec<String>,
+    line: usize,
+}
+
+impl Branch {
+    /// Combine two filters chained with `.and()`
+    fn and(&self, other: &Branch) -> Branch {
+        let mut middleware = self.middleware.clone();
+        middleware.extend(other.middleware.iter().cloned());
+        Branch {
+            path: format!("{}{}", self.path, other.path),
+            method: self.method.or(other.method),
+            handler: self.handler.clone().or_else(|| other.handler.clone()),
+            target: other.target.clone().or_else(|| self.target.clone()),
+            middleware,
+            line: self.line.max(other.line),
+        }
+    }
+}
+
+/// A filter with no effect on routing (e.g. `warp::any()`, `warp::body::json()`)
+fn neutral() -> Vec<Branch> {
+    vec![Branch::default()]
+}
+
+impl WarpDetector {
+    /// Create a new Warp detector
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Collect every function in a file that builds Warp filters
+    ///
+    /// Only files that reference `warp` are analyzed, since filter
+    /// combinators share their names with `Option` and iterator methods.
+    ///
+    /// # Arguments
+    ///
+    /// * `syntax_tree` - The parsed syntax tree of a source file
+    /// * `module_path` - Module path of the file within its crate
+    pub fn router_functions(
+        &self,
+        syntax_tree: &syn::File,
+        module_path: &[String],
+    ) -> Vec<RouterFunction> {
+        let mut routers = Vec::new();
+        if references_crate(&syntax_tree.items, "warp") {
+            self.collect_router_functions(&syntax_tree.items, module_path, &mut routers);
+        }
+        routers
+    }
+
+    fn collect_router_functions(
+        &self,
+        items: &[Item],
+        module_path: &[String],
+        routers: &mut Vec<RouterFunction>,
+    ) {
+        for item in items {
+            match item {
+                Item::Fn(_) => {
+                    if let Some(info) = self.analyze_router(item) {
+                        routers.push(RouterFunction {
+                            module_path: module_path.to_vec(),
+                            info,
+                        });
+                    }
+                }
+                Item::Mod(item_mod) => {
+                    if let Some((_, items)) = &item_mod.content {
+                        let mut nested = module_path.to_vec();
+                        nested.push(item_mod.ident.to_string());
+                        self.collect_router_functions(items, &nested, routers);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Default for WarpDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameworkDetector for WarpDetector {
+    fn name(&self) -> &'static str {
+        "Warp"
+    }
+
+    fn detect(&self, project_root: &Path) -> Result<bool> {
+        super::manifest_has_dependency(project_root, "warp")
+    }
+
+    fn extract_endpoints(&self, syntax_tree: &syn::File) -> Vec<Endpoint> {
+        file_endpoints(syntax_tree, |tree, module_path| {
+            self.router_functions(tree, module_path)
+        })
+    }
+
+    fn extract_project_endpoints(
+        &self,
+        files: &[SourceFile],
+        modules: &ModuleTree,
+    ) -> Vec<Endpoint> {
+        // Filters are commonly split into one function per endpoint and
+        // combined with `.or()` elsewhere, so they are composed across files
+        project_endpoints(files, modules, |tree, module_path| {
+            self.router_functions(tree, module_path)
+        })
+    }
+
+    fn analyze_router(&self, item: &Item) -> Option<RouterInfo> {
+        match item {
+            Item::Fn(item_fn) => {
+                let returns_filter = match &item_fn.sig.output {
+                    ReturnType::Type(_, ty) => quote::quote!(#ty).to_string().contains("Filter"),
+                    ReturnType::Default => false,
+                };
+                let mut visitor = FilterVisitor::new(returns_filter);
+                visitor.visit_block(&item_fn.block);
+                visitor.finish(item_fn.sig.ident.to_string())
+            }
+            _ => None,
+        }
+    }
+
+    fn detect_handler(&self, function: &ItemFn) -> Option<HandlerInfo> {
+        let return_type = match &function.sig.output {
+            ReturnType::Default => "()".to_string(),
+            ReturnType::Type(_, ty) => quote::quote!(#ty).to_string(),
+        };
+        if !(return_type.contains("Reply") || return_type.contains("Rejection")) {
+            return None;
+        }
+
+        // Handler arguments are the values extracted by the filter chain
+        let parameters: Vec<HandlerParam> = function
+            .sig
+            .inputs
+            .iter()
+            .filter_map(|input| match input {
+                FnArg::Typed(pat_type) => {
+                    let ty = &pat_type.ty;
+                    Some(HandlerParam {
+                        name: super::routing::binding_name(&pat_type.pat),
+                        ty: quote::quote!(#ty).to_string(),
+                        is_extractor: true,
+                    })
+                }
+                FnArg::Receiver(_) => None,
+            })
+            .collect();
+
+        Some(HandlerInfo {
+            name: function.sig.ident.to_string(),
+            is_async: function.sig.asyncness.is_some(),
+            extractors: parameters.iter().map(|p| p.ty.clone()).collect(),
+            parameters,
+            return_type,
+            documentation: super::doc_comment(&function.attrs),
+        })
+    }
+}
+
+/// Path matched by a `warp::path!("users" / u32 / "posts")` macro
+fn path_macro(mac: &ExprMacro) -> Option<String> {
+    if mac.mac.path.segments.last()?.ident != "path" {
+        return None;
+    }
+
+    let mut path = String::new();
+    let mut segment: Vec<TokenTree> = Vec::new();
+    let mut tokens = mac.mac.tokens.clone().into_iter().peekable();
+
+    loop {
+        let token = tokens.next();
+        let at_separator = match &token {
+            Some(TokenTree::Punct(punct)) => punct.as_char() == '/',
+            Some(_) => false,
+            None => true,
+        };
+        if !at_separator {
+            segment.extend(token);
+            continue;
+        }
+
+        let rendered: String = segment.iter().map(ToString::to_string).collect::<String>();
+        match segment.as_slice() {
+            [] => {}
+            // `..` leaves the rest of the path to following filters
+            _ if rendered == ".." => {}
+            [TokenTree::Literal(literal)] => {
+                let lit: syn::LitStr = syn::parse_str(&literal.to_string()).ok()?;
+                path.push('/');
+                path.push_str(&lit.value());
+            }
+            _ => path.push_str(&format!("/{{{rendered}}}")),
+        }
+        segment.clear();
+
+        if token.is_none() {
+            break;
+        }
+    }
+
+    Some(path)
+}
+
+/// Name of the function handling requests in `.map(..)`/`.and_then(..)`
+///
+/// Closures that only forward to a function (`|id| handlers::get(id)`) are
+/// named after that function.
+fn handler_of(expr: &Expr) -> String {
+    fn forwarded(expr: &Expr) -> Option<String> {
+        match strip_parens(expr) {
+            Expr::Call(call) => path_of(&call.func).map(path_to_string),
+            Expr::Await(await_expr) => forwarded(&await_expr.base),
+            Expr::Try(try_expr) => forwarded(&try_expr.expr),
+            Expr::Async(async_block) => tail_expr(&async_block.block).and_then(forwarded),
+            Expr::Block(block) => tail_expr(&block.block).and_then(forwarded),
+            _ => None,
+        }
+    }
+
+    fn tail_expr(block: &syn::Block) -> Option<&Expr> {
+        match block.stmts.as_slice() {
+            [syn::Stmt::Expr(expr, None)] => Some(expr),
+            _ => None,
+        }
+    }
+
+    match strip_parens(expr) {
+        Expr::Path(path) => path_to_string(&path.path),
+        Expr::Closure(closure) => {
+            forwarded(&closure.body).unwrap_or_else(|| "{closure}".to_string())
+        }
+        _ => "{closure}".to_string(),
+    }
+}
+
+/// Visitor that collects the filters built by a single function body
+///
+/// Filters bound to local variables are inlined where they are used; the
+/// filters a function returns or serves (`warp::serve(routes)`), and locals
+/// never combined into another filter, make up its routes.
+struct FilterVisitor {
+    /// Whether the function returns a filter, so that calls to other filter
+    /// functions in its body are filters too
+    returns_filter: bool,
+    branches: Vec<Branch>,
+    /// Filters bound to local variables and whether another filter consumed them
+    locals: Vec<(String, Vec<Branch>, bool)>,
+}
+
+impl FilterVisitor {
+    fn new(returns_filter: bool) -> Self {
+        Self {
+            returns_filter,
+            branches: Vec::new(),
+            locals: Vec::new(),
+        }
+    }
+
+    fn finish(mut self, name: String) -> Option<RouterInfo> {
+        let locals = std::mem::take(&mut self.locals);
+        for (_, branches, used) in locals {
+            if !used {
+                self.branches.extend(branches);
+            }
+        }
+
+        let mut info = empty_router();
+        info.name = name;
+        for branch in self.branches {
+            if let Some(handler) = branch.handler {
+                info.routes.push(RouteInfo {
+                    method: branch.method.unwrap_or(HttpMethod::Custom("ANY")),
+                    path: if branch.path.is_empty() {
+                        "/".to_string()
+                    } else {
+                        branch.path
+                    },
+                    handler,
+                    middleware: branch.middleware,
+                    line: branch.line,
+                });
+            } else if let Some(target) = branch.target {
+                info.mounts.push(RouterMount {
+                    prefix: branch.path,
+                    target,
+                    line: branch.line,
+                });
+            }
+        }
+
+        if info.routes.is_empty() && info.mounts.is_empty() {
+            None
+        } else {
+            Some(info)
+        }
+    }
+
+    fn set_local(&mut self, name: String, branches: Vec<Branch>) {
+        if let Some(local) = self.locals.iter_mut().find(|(n, _, _)| *n == name) {
+            local.1 = branches;
+            local.2 = false;
+        } else {
+            self.locals.push((name, branches, false));
+        }
+    }
+
+    fn use_local(&mut self, name: &str) -> Option<Vec<Branch>> {
+        self.locals
+            .iter_mut()
+            .find(|(n, _, _)| n == name)
+            .map(|local| {
+                local.2 = true;
+                local.1.clone()
+            })
+    }
+
+    /// Evaluate an expression that may build a filter
+    ///
+    /// `in_filter` is set where the expression is known to be a filter (the
+    /// argument of `.and()`/`.or()`), so calls to other functions are taken
+    /// as filters defined elsewhere.
+    fn parse_filter(&mut self, expr: &Expr, in_filter: bool) -> Option<Vec<Branch>> {
+        let mut calls = Vec::new();
+        let mut root = strip_parens(expr);
+        while let Expr::MethodCall(call) = root {
+            calls.push(call);
+            root = strip_parens(&call.receiver);
+        }
+        calls.reverse();
+
+        let combined = calls.iter().any(|call| {
+            matches!(
+                call.method.to_string().as_str(),
+                "and" | "or" | "map" | "and_then" | "then" | "with" | "recover"
+            )
+        });
+        let is_filter_root = in_filter || (self.returns_filter && combined);
+
+        let mut branches = match root {
+            Expr::Macro(mac) => vec![Branch {
+                path: path_macro(mac)?,
+                line: span_line(mac.mac.bang_token.span),
+                ..Branch::default()
+            }],
+            Expr::Call(call) => {
+                let func = path_of(&call.func)?;
+                let segments: Vec<String> =
+                    func.segments.iter().map(|s| s.ident.to_string()).collect();
+                if matches!(
+                    segments.first().map(String::as_str),
+                    Some("warp" | "path" | "filters")
+                ) {
+                    self.builtin_filter(call, func)?
+                } else if is_filter_root {
+                    vec![Branch {
+                        target: Some(segments.join("::")),
+                        line: span_line(call.paren_token.span.open()),
+                        ..Branch::default()
+                    }]
+                } else {
+                    return None;
+                }
+            }
+            Expr::Path(path) => {
+                let ident = path.path.get_ident()?.to_string();
+                self.use_local(&ident)?
+            }
+            _ => return None,
+        };
+
+        for call in calls {
+            let line = span_line(call.method.span());
+            let args: Vec<&Expr> = call.args.iter().collect();
+
+            match call.method.to_string().as_str() {
+                "and" if !args.is_empty() => {
+                    let other = self.parse_filter(args[0], true).unwrap_or_else(neutral);
+                    branches = branches
+                        .iter()
+                        .flat_map(|branch| other.iter().map(|o| branch.and(o)))
+                        .collect();
+                }
+                "or" if !args.is_empty() => {
+                    branches.extend(self.parse_filter(args[0], true).unwrap_or_default());
+                }
+                "map" | "and_then" | "then" if !args.is_empty() => {
+                    // Later mappings usually only convert the handler's reply
+                    let handler = handler_of(args[0]);
+                    for branch in &mut branches {
+                        if branch.handler.is_none() {
+                            branch.handler = Some(handler.clone());
+                            branch.line = line;
+                        }
+                    }
+                }
+                "with" | "recover" if !args.is_empty() => {
+                    let middleware = args[0];
+                    let middleware = quote::quote!(#middleware).to_string().replace(' ', "");
+                    for branch in &mut branches {
+                        branch.middleware.push(middleware.clone());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Some(branches)
+    }
+
+    /// Evaluate a call to one of Warp's own filters
+    fn builtin_filter(&mut self, call: &syn::ExprCall, func: &syn::Path) -> Option<Vec<Branch>> {
+        let last = func.segments.last()?;
+        let name = last.ident.to_string();
+        let line = span_line(call.paren_token.span.open());
+        let args: Vec<&Expr> = call.args.iter().collect();
+
+        let branch = match name.as_str() {
+            // `warp::serve(routes)` runs the filter it is given
+            "serve" => return args.first().and_then(|arg| self.parse_filter(arg, true)),
+            "path" => Branch {
+                path: format!("/{}", args.first().and_then(|arg| string_literal(arg))?),
+                line,
+                ..Branch::default()
+            },
+            "param" => {
+                let ty = match &last.arguments {
+                    PathArguments::AngleBracketed(generics) => {
+                        let args = &generics.args;
+                        quote::quote!(#args).to_string().replace(' ', "")
+                    }
+                    _ => "param".to_string(),
+                };
+                Branch {
+                    path: format!("/{{{ty}}}"),
+                    line,
+                    ..Branch::default()
+                }
+            }
+            "tail" => Branch {
+                path: "/{tail}".to_string(),
+                line,
+                ..Branch::default()
+            },
+            _ => match HttpMethod::standard(&name) {
+                Some(method) => Branch {
+                    method: Some(method),
+                    line,
+                    ..Branch::default()
+                },
+                None => Branch::default(),
+            },
+        };
+
+        Some(vec![branch])
+    }
+}
+
+impl Visit<'_> for FilterVisitor {
+    fn visit_item(&mut self, _item: &Item) {
+        // Nested items are analyzed on their own
+    }
+
+    fn visit_local(&mut self, local: &syn::Local) {
+        let name = match &local.pat {
+            Pat::Ident(pat_ident) => Some(pat_ident.ident.to_string()),
+            Pat::Type(pat_type) => match &*pat_type.pat {
+                Pat::Ident(pat_ident) => Some(pat_ident.ident.to_string()),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        if let (Some(name), Some(init)) = (name, &local.init) {
+            if let Some(branches) = self.parse_filter(&init.expr, false) {
+                self.set_local(name, branches);
+                return;
+            }
+        }
+
+        syn::visit::visit_local(self, local);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Call(_) | Expr::MethodCall(_) | Expr::Path(_) | Expr::Macro(_) => {
+                if let Some(branches) = self.parse_filter(expr, false) {
+                    self.branches.extend(branches);
+                    return;
+                }
+            }
+            _ => {}
+        }
+
+        syn::visit::visit_expr(self, expr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_filter_chain_endpoints() {
+        let detector = WarpDetector::new();
+
+        let syntax_tree: syn::File = parse_quote! {
+            use warp::Filter;
+
+            #[tokio::main]
+            async fn main() {
+                let hello = warp::path!("hello" / String)
+                    .and(warp::get())
+                    .map(|name| format!("Hello, {}!", name));
+
+                let users = warp::path("users");
+                let get_user = users
+                    .and(warp::path::param::<u64>())
+                    .and(warp::path::end())
+                    .and(warp::get())
+                    .and(with_db(db.clone()))
+                    .and_then(handlers::get_user);
+                let create_user = users
+                    .and(warp::post())
+                    .and(warp::body::json())
+                    .and_then(|user| async move { handlers::create_user(user).await })
+                    .map(|reply| warp::reply::with_status(reply, StatusCode::CREATED));
+
+                let routes = hello
+                    .or(get_user.or(create_user))
+                    .with(warp::log("api"));
+                warp::serve(routes).run(([127, 0, 0, 1], 3030)).await;
+            }
+        };
+
+        let endpoints = detector.extract_endpoints(&syntax_tree);
+        let routes: Vec<_> = endpoints
+            .iter()
+            .map(|e| (e.method.as_str(), e.path.as_str(), e.handler.as_str()))
+            .collect();
+
+        assert_eq!(
+            routes,
+            vec![
+                ("GET", "/hello/{String}", "{closure}"),
+                ("GET", "/users/{u64}", "handlers::get_user"),
+                ("POST", "/users", "handlers::create_user"),
+            ]
+        );
+        assert!(
+            endpoints
+                .iter()
+                .all(|e| e.middleware == vec!["warp::log(\"api\")"])
+        );
+    }
+
+    #[test]
+    fn test_filter_functions_compose() {
+        let detector = WarpDetector::new();
+
+        let syntax_tree: syn::File = parse_quote! {
+            use warp::Filter;
+
+            pub fn api() -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+                warp::path("api").and(todos::routes().or(health()))
+            }
+
+            fn health() -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+                warp::path!("health").map(|| StatusCode::OK)
+            }
+
+            mod todos {
+                pub fn routes() -> BoxedFilter<(impl Reply,)> {
+                    list().or(create()).boxed()
+                }
+
+                fn list() -> BoxedFilter<(impl Reply,)> {
+                    warp::path!("todos").and(warp::get()).and_then(list_todos).boxed()
+                }
+
+                fn create() -> BoxedFilter<(impl Reply,)> {
+                    warp::path!("todos")
+                        .and(warp::post())
+                        .and(json_body())
+                        .and_then(create_todo)
+                        .boxed()
+                }
+            }
+        };
+
+        let endpoints = detector.extract_endpoints(&syntax_tree);
+        let routes: Vec<_> = endpoints
+            .iter()
+            .map(|e| (e.method.as_str(), e.path.as_str(), e.handler.as_str()))
+            .collect();
+
+        assert_eq!(
+            routes,
+            vec![
+                ("GET", "/api/todos", "list_todos"),
+                ("POST", "/api/todos", "create_todo"),
+                ("ANY", "/api/health", "{closure}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_non_filter_chains_are_ignored() {
+        let detector = WarpDetector::new();
+
+        let syntax_tree: syn::File = parse_quote! {
+            use warp::Filter;
+
+            fn port() -> u16 {
+                std::env::var("PORT").ok().and_then(|p| p.parse().ok()).unwrap_or(3030)
+            }
+        };
+
+        assert!(detector.extract_endpoints(&syntax_tree).is_empty());
+    }
+}
@@ -10,7 +10,7 @@
 //! - **AST-based Analysis**: Deep code analysis using Rust's syntax tree
 //! - **Call Graph Construction**: Build and analyze function call relationships
 //! - **Pattern Recognition**: Identify business logic, DB calls, external APIs
-//! - **Framework Detection**: Auto-detect web frameworks (axum, actix, rocket, tonic)
+//! - **Framework Detection**: Auto-detect web frameworks (axum, actix, rocket, tonic, warp, poem)
 //! - **Instrumentation Detection**: Find where `#[instrument]` should be added
 //! - **Multiple Output Formats**: Human-readable, JSON, Mermaid diagrams
 //!
@@ -64,24 +64,30 @@ pub mod dependencies;
 mod dependencies_test;
 pub mod detector;
 pub mod error;
+pub mod files;
 pub mod fixer;
 pub mod framework;
 pub mod output;
 pub mod patterns;
 
 pub use config::Config;
-pub use dependencies::{DetectionContext, ProjectDependencies};
+pub use dependencies::{DetectionContext, ProjectDependencies, WorkspaceMember};
 pub use error::{Error, Result};
 
 // Re-export call graph types for convenience
 pub use call_graph::{CallGraph, GraphBuilder};
 
+use framework::web::{DetectorRegistry, FrameworkDetector};
 use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
+
+/// Confidence of a category shown by a call into a known crate
+const CALL_SITE_CONFIDENCE: f64 = 0.95;
 
 /// Analysis result containing all detected information
 #[derive(Debug)]
 pub struct AnalysisResult {
+    /// Web frameworks whose endpoints were analyzed
+    pub frameworks: Vec<framework::DetectedFramework>,
     /// Detected HTTP/gRPC endpoints
     pub endpoints: Vec<detector::Endpoint>,
     /// Function call graph
@@ -94,12 +100,31 @@ pub struct AnalysisResult {
     pub existing_instrumentation: Vec<detector::ExistingInstrumentation>,
     /// Gaps in instrumentation coverage
     pub gaps: Vec<detector::InstrumentationGap>,
+    /// Most critical paths from each endpoint to an external boundary
+    pub critical_paths: Vec<detector::critical_path::CriticalPath>,
+    /// Instrumented call chains broken by spawns, channels or stored callbacks
+    pub context_breaks: Vec<detector::spawn::ContextBreak>,
     /// Rule violations found in existing instrumentation
     pub rule_violations: Vec<detector::rules::RuleViolation>,
     /// Project dependencies (for context-aware detection)
     pub dependencies: ProjectDependencies,
     /// Analysis statistics
     pub stats: AnalysisStats,
+    /// Per-package results, one for each cargo workspace member
+    pub crates: Vec<CrateAnalysis>,
+}
+
+/// Analysis results for one package of a cargo workspace
+#[derive(Debug)]
+pub struct CrateAnalysis {
+    /// Package name
+    pub name: String,
+    /// Directory containing the package's Cargo.toml
+    pub root: PathBuf,
+    /// The package's own dependencies
+    pub dependencies: ProjectDependencies,
+    /// Statistics for the package's files
+    pub stats: AnalysisStats,
 }
 
 /// Statistics about the analyzed codebase
@@ -121,17 +146,45 @@ pub struct AnalysisStats {
     pub gaps_count: usize,
     /// Number of rule violations found
     pub rule_violations_count: usize,
+    /// Percentage of instrumentation points that are already instrumented
+    pub coverage: f64,
 }
 
 /// The main analyzer for detecting instrumentation points
 pub struct Analyzer {
     config: Config,
+    detectors: DetectorRegistry,
+    /// Attribute patterns that mark test code, shared by every step
+    test_markers: patterns::TestMarkers,
 }
 
 impl Analyzer {
     /// Creates a new analyzer with the given configuration
     pub fn new(config: Config) -> Self {
-        Self { config }
+        Self::with_registry(config, DetectorRegistry::new())
+    }
+
+    /// Creates a new analyzer that detects endpoints with the given registry
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The analysis configuration
+    /// * `detectors` - Registry of web framework detectors
+    pub fn with_registry(config: Config, detectors: DetectorRegistry) -> Self {
+        Self {
+            config,
+            detectors,
+            test_markers: patterns::TestMarkers::default(),
+        }
+    }
+
+    /// Register an additional web framework detector
+    ///
+    /// # Arguments
+    ///
+    /// * `detector` - The detector to add
+    pub fn add_detector(&mut self, detector: Box<dyn FrameworkDetector>) {
+        self.detectors.add_detector(detector);
     }
 
     /// Analyze the given paths and return detection results
@@ -142,12 +195,15 @@ impl Analyzer {
     ///
     /// # Errors
     ///
-    /// Returns an error if file reading or parsing fails
+    /// Returns an error if file reading or parsing fails, or the patterns
+    /// file cannot be loaded
     pub fn analyze<P: AsRef<Path>>(&self, paths: &[P]) -> Result<AnalysisResult> {
-        // 0. Analyze project dependencies for context-aware detection
+        // 0. Enumerate workspace members, each with its own dependency context
         let project_root = paths.first().map(|p| p.as_ref()).unwrap_or(Path::new("."));
-        let dependencies = ProjectDependencies::from_manifest(project_root).unwrap_or_default();
-        let context = DetectionContext::from_deps(dependencies);
+        let metadata = ProjectDependencies::workspace_metadata(project_root)
+            .ok()
+            .flatten();
+        let workspace = Workspace::new(metadata.as_ref());
 
         // 1. Collect all Rust files
         let files = self.collect_files(paths)?;
@@ -158,182 +214,280 @@ impl Analyzer {
             .filter_map(|f| self.parse_file(f).ok())
             .collect();
 
-        // 3. Build call graph
-        let mut graph_builder = GraphBuilder::new();
+        // 3. Build call graph, taking module paths from each crate's module tree
+        //    so calls between workspace members link up
+        let module_tree =
+            call_graph::ModuleTree::discover_with_metadata(project_root, metadata.as_ref());
+        let mut graph_builder = GraphBuilder::new()
+            .with_module_tree(module_tree.clone())
+            .with_test_markers(self.test_markers.clone());
         for source in &parsed {
             graph_builder.add_parsed_file(source)?;
         }
         let call_graph = graph_builder.build()?;
 
-        // 4. Detect framework and endpoints (use deps for framework hint)
-        let framework = self.detect_framework_with_context(&parsed, &context);
-        let endpoints = self.detect_endpoints(&parsed, &framework);
-
-        // 5. Match patterns with dependency context
-        let patterns = self.match_patterns_with_context(&call_graph, &context);
+        // 4. Detect frameworks and endpoints (use deps for framework hints)
+        let frameworks = self.detect_frameworks_with_context(&parsed, &workspace.aggregate);
+        let mut endpoints = self.detect_endpoints(project_root, &parsed, &module_tree, &frameworks);
+        detector::endpoint::attribute_to_graph(&mut endpoints, &call_graph);
+
+        // 5. Match patterns with the dependency context of each function's crate,
+        //    after the rules of the patterns file
+        let rules = self.detection_rules()?;
+        let patterns = self.match_patterns_with_context(&call_graph, &workspace, &rules);
+
+        // 6. Detect instrumentation points, leaving out test code unless
+        //    `include_tests` is set
+        let test_code = if self.config.include_tests {
+            detector::test_code::TestCode::default()
+        } else {
+            detector::test_code::TestCode::find(&parsed, &self.test_markers)
+        };
+        let in_test_code =
+            |location: &detector::Location| test_code.contains(&location.file, location.line);
 
-        // 6. Detect instrumentation points
-        let points = self.detect_instrumentation_points(&call_graph, &endpoints, &patterns);
+        let mut points = self.detect_instrumentation_points(&call_graph, &endpoints, &patterns);
+        points.retain(|point| {
+            let test_node = call_graph
+                .get_node(&point.location.function_name)
+                .is_some_and(|node| node.kind == call_graph::NodeKind::Test);
+            self.config.include_tests || !(test_node || in_test_code(&point.location))
+        });
 
         // 7. Detect existing instrumentation
-        let existing_instrumentation = detector::existing::detect_existing_instrumentation(&parsed);
+        let mut existing_instrumentation =
+            detector::existing::detect_existing_instrumentation(&parsed, &call_graph);
+        existing_instrumentation.retain(|existing| !in_test_code(&existing.location));
 
         // 8. Detect gaps (instrumentation points without existing instrumentation)
         let gaps = self.detect_gaps(&points, &existing_instrumentation);
 
-        // 9. Check naming convention rules
+        // 9. Rank the paths from each endpoint to its external boundaries,
+        //    with the instrumentation status of every hop
+        let critical_paths = detector::critical_path::CriticalPathFinder::new(
+            &call_graph,
+            &patterns,
+            &workspace.aggregate.deps,
+            &existing_instrumentation,
+        )
+        .with_max_depth(self.config.max_depth)
+        .find_all(&endpoints, self.config.critical_paths);
+
+        // 10. Find instrumented call chains whose spans don't link up across
+        //     spawns, channels and stored callbacks, and spawned tasks that
+        //     lose the parent span
+        let mut context_breaks =
+            detector::spawn::detect_broken_chains(&call_graph, &parsed, &existing_instrumentation);
+        for lost in
+            detector::spawn::detect_lost_spans(&call_graph, &parsed, &existing_instrumentation)
+        {
+            let (file, line) = (&lost.location.file, lost.location.line);
+            if !context_breaks
+                .iter()
+                .any(|b| &b.location.file == file && b.location.line == line)
+            {
+                context_breaks.push(lost);
+            }
+        }
+        context_breaks.retain(|context_break| !in_test_code(&context_break.location));
+
+        // 11. Check naming convention rules
         let rule_checker = detector::rules::RuleChecker::new(&self.config.naming_rules);
         let mut rule_violations = rule_checker.check_existing(&existing_instrumentation);
         rule_violations.extend(rule_checker.check_points(&points));
 
-        // 10. Compute stats
-        let stats = AnalysisStats {
-            total_files: parsed.len(),
-            total_functions: call_graph.node_count(),
-            total_lines: parsed.iter().map(|p| p.line_count()).sum(),
-            endpoints_count: endpoints.len(),
-            instrumentation_points: points.len(),
-            existing_count: existing_instrumentation.len(),
-            gaps_count: gaps.len(),
-            rule_violations_count: rule_violations.len(),
-        };
-
-        // Extract dependencies from context
-        let dependencies = context.deps;
+        let Workspace {
+            members,
+            roots,
+            contexts,
+            aggregate,
+        } = workspace;
 
-        Ok(AnalysisResult {
+        let mut result = AnalysisResult {
+            frameworks,
             endpoints,
             call_graph,
             patterns,
             points,
             existing_instrumentation,
             gaps,
+            critical_paths,
+            context_breaks,
             rule_violations,
-            dependencies,
-            stats,
-        })
+            dependencies: aggregate.deps,
+            stats: AnalysisStats::default(),
+            crates: Vec::new(),
+        };
+
+        // 12. Compute stats, in aggregate and for each workspace member
+        result.stats = collect_stats(&result, &parsed, |_| true);
+        result.crates = members
+            .into_iter()
+            .zip(contexts)
+            .enumerate()
+            .map(|(index, (member, context))| CrateAnalysis {
+                stats: collect_stats(&result, &parsed, |file| {
+                    member_index(&roots, file) == Some(index)
+                }),
+                name: member.name,
+                root: member.root,
+                dependencies: context.deps,
+            })
+            .collect();
+
+        Ok(result)
     }
 
-    fn collect_files<P: AsRef<Path>>(&self, paths: &[P]) -> Result<Vec<PathBuf>> {
-        let mut files = Vec::new();
+    /// Collects the source files that [`Analyzer::analyze`] would analyze
+    ///
+    /// # Arguments
+    ///
+    /// * `paths` - Paths to analyze (files or directories)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a pattern is invalid or a directory cannot be walked
+    pub fn collect_files<P: AsRef<Path>>(&self, paths: &[P]) -> Result<Vec<PathBuf>> {
+        files::FileWalker::new(&self.config)?.collect(paths)
+    }
 
-        for path in paths {
-            let path = path.as_ref();
-            if path.is_file() {
-                if path.extension().is_some_and(|ext| ext == "rs") {
-                    files.push(path.to_path_buf());
-                }
-            } else if path.is_dir() {
-                for entry in WalkDir::new(path)
-                    .follow_links(true)
-                    .into_iter()
-                    .filter_map(|e| e.ok())
-                {
-                    let p = entry.path();
-                    if p.extension().is_some_and(|ext| ext == "rs") {
-                        // Skip excluded patterns
-                        let should_exclude = self
-                            .config
-                            .exclude_patterns
-                            .iter()
-                            .any(|pattern| p.to_string_lossy().contains(pattern));
-                        if !should_exclude {
-                            files.push(p.to_path_buf());
-                        }
-                    }
-                }
-            }
+    /// Loads the category rules, with those of `patterns_file` first
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the patterns file cannot be loaded
+    fn detection_rules(&self) -> Result<patterns::DetectionRules> {
+        match &self.config.patterns_file {
+            Some(path) => patterns::DetectionRules::load(path),
+            None => Ok(patterns::DetectionRules::builtin()),
         }
-
-        Ok(files)
     }
 
     fn parse_file(&self, path: &Path) -> Result<ast::SourceFile> {
         ast::SourceFile::parse(path)
     }
 
-    fn detect_framework(&self, parsed: &[ast::SourceFile]) -> framework::DetectedFramework {
+    fn detect_frameworks(&self, parsed: &[ast::SourceFile]) -> Vec<framework::DetectedFramework> {
+        use framework::DetectedFramework;
+
         // Check for framework-specific imports
-        for file in parsed {
-            let source = file.source();
-            if source.contains("axum::") || source.contains("use axum") {
-                return framework::DetectedFramework::Axum;
-            }
-            if source.contains("actix_web::") || source.contains("use actix_web") {
-                return framework::DetectedFramework::Actix;
-            }
-            if source.contains("rocket::") || source.contains("#[rocket") {
-                return framework::DetectedFramework::Rocket;
-            }
-            if source.contains("tonic::") || source.contains("use tonic") {
-                return framework::DetectedFramework::Tonic;
-            }
-        }
-        framework::DetectedFramework::Unknown
+        let markers: [(DetectedFramework, &[&str]); 6] = [
+            (DetectedFramework::Axum, &["axum::", "use axum"]),
+            (DetectedFramework::Actix, &["actix_web::", "use actix_web"]),
+            (DetectedFramework::Rocket, &["rocket::", "#[rocket"]),
+            (DetectedFramework::Tonic, &["tonic::", "use tonic"]),
+            (DetectedFramework::Warp, &["warp::", "use warp"]),
+            (DetectedFramework::Poem, &["poem::", "use poem"]),
+        ];
+
+        markers
+            .iter()
+            .filter(|(_, markers)| {
+                parsed.iter().any(|file| {
+                    let source = file.source();
+                    markers.iter().any(|marker| source.contains(marker))
+                })
+            })
+            .map(|(framework, _)| *framework)
+            .collect()
     }
 
-    /// Detect framework using both source analysis and dependency information
-    fn detect_framework_with_context(
+    /// Detect every framework used by the project
+    ///
+    /// Frameworks named in the configuration are always included. With `auto`
+    /// selected, frameworks found in the dependencies are added, falling back
+    /// to source analysis when no framework dependency is declared.
+    fn detect_frameworks_with_context(
         &self,
         parsed: &[ast::SourceFile],
         context: &DetectionContext,
-    ) -> framework::DetectedFramework {
-        // First check dependencies (more reliable)
-        if context
-            .deps
-            .frameworks
-            .contains(&dependencies::FrameworkCrate::Axum)
-        {
-            return framework::DetectedFramework::Axum;
-        }
-        if context
-            .deps
-            .frameworks
-            .contains(&dependencies::FrameworkCrate::ActixWeb)
-        {
-            return framework::DetectedFramework::Actix;
-        }
-        if context
-            .deps
-            .frameworks
-            .contains(&dependencies::FrameworkCrate::Rocket)
-        {
-            return framework::DetectedFramework::Rocket;
-        }
-        if context
-            .deps
-            .frameworks
-            .contains(&dependencies::FrameworkCrate::Tonic)
-        {
-            return framework::DetectedFramework::Tonic;
+    ) -> Vec<framework::DetectedFramework> {
+        use dependencies::FrameworkCrate;
+        use framework::DetectedFramework;
+
+        let mut frameworks = self.config.selected_frameworks();
+        if !self.config.auto_detect_frameworks() {
+            return frameworks;
         }
 
+        // First check dependencies (more reliable)
+        let crates = [
+            (FrameworkCrate::Axum, DetectedFramework::Axum),
+            (FrameworkCrate::ActixWeb, DetectedFramework::Actix),
+            (FrameworkCrate::Rocket, DetectedFramework::Rocket),
+            (FrameworkCrate::Tonic, DetectedFramework::Tonic),
+            (FrameworkCrate::Warp, DetectedFramework::Warp),
+            (FrameworkCrate::Poem, DetectedFramework::Poem),
+        ];
+        let mut detected: Vec<DetectedFramework> = crates
+            .iter()
+            .filter(|(krate, _)| context.deps.frameworks.contains(krate))
+            .map(|(_, framework)| *framework)
+            .collect();
+
         // Fall back to source analysis
-        self.detect_framework(parsed)
+        if detected.is_empty() {
+            detected = self.detect_frameworks(parsed);
+        }
+
+        for framework in detected {
+            if !frameworks.contains(&framework) {
+                frameworks.push(framework);
+            }
+        }
+        frameworks
     }
 
     fn detect_endpoints(
         &self,
+        project_root: &Path,
         parsed: &[ast::SourceFile],
-        framework_type: &framework::DetectedFramework,
+        module_tree: &call_graph::ModuleTree,
+        frameworks: &[framework::DetectedFramework],
     ) -> Vec<detector::Endpoint> {
-        detector::endpoint::detect_endpoints(parsed, framework_type)
+        // Detectors may only claim a project themselves when detection is automatic
+        let project_root = self.config.auto_detect_frameworks().then_some(project_root);
+
+        detector::endpoint::detect_endpoints_with_registry(
+            &self.detectors,
+            parsed,
+            module_tree,
+            frameworks,
+            project_root,
+        )
     }
 
-    fn match_patterns(&self, graph: &CallGraph) -> Vec<patterns::MatchResult> {
+    fn match_patterns(
+        &self,
+        graph: &CallGraph,
+        rules: &patterns::DetectionRules,
+    ) -> Vec<patterns::MatchResult> {
         // Pattern matching based on function names in the call graph
         let mut results = Vec::new();
 
         for node_name in graph.node_names() {
             if let Some(node) = graph.get_node(&node_name) {
+                // Library functions and closures are reached through call edges
+                // but aren't instrumented on their own
+                if matches!(
+                    node.kind,
+                    call_graph::NodeKind::External | call_graph::NodeKind::Closure
+                ) {
+                    continue;
+                }
+
                 let mut result = patterns::MatchResult::with_location(
                     node.file().unwrap_or_default(),
                     node_name.clone(),
                     node.line().unwrap_or(0),
                 );
 
+                // Match within the crate so a crate name like `order_service`
+                // doesn't match every function in it
+                let name = node.crate_relative_id();
+
                 // Database patterns
-                if matches_db_pattern(&node_name) {
+                if rules.matches_name(patterns::Category::Database, name) {
                     result.category = patterns::Category::Database;
                     result.confidence = 0.9;
                     results.push(result);
@@ -341,7 +495,7 @@ impl Analyzer {
                 }
 
                 // HTTP client patterns
-                if matches_http_pattern(&node_name) {
+                if rules.matches_name(patterns::Category::HttpClient, name) {
                     result.category = patterns::Category::HttpClient;
                     result.confidence = 0.85;
                     results.push(result);
@@ -349,7 +503,7 @@ impl Analyzer {
                 }
 
                 // Error handling patterns
-                if matches_error_pattern(&node_name) {
+                if rules.matches_name(patterns::Category::ErrorHandling, name) {
                     result.category = patterns::Category::ErrorHandling;
                     result.confidence = 0.8;
                     results.push(result);
@@ -357,7 +511,7 @@ impl Analyzer {
                 }
 
                 // Business logic patterns
-                if matches_business_pattern(&node_name) {
+                if rules.matches_name(patterns::Category::BusinessLogic, name) {
                     result.category = patterns::Category::BusinessLogic;
                     result.confidence = 0.7;
                     results.push(result);
@@ -370,26 +524,72 @@ impl Analyzer {
 
     /// Match patterns with dependency context for smarter detection
     ///
-    /// This method uses project dependency information to:
+    /// Rules from the patterns file are checked first, then what each function
+    /// calls: a call to a database, HTTP client or cache crate classifies it,
+    /// with the call site recorded as the match. Name heuristics are the
+    /// fallback, using project dependency information to:
     /// - Avoid false positives (e.g., "get_user" won't be HTTP if no reqwest)
     /// - Boost confidence for known patterns (e.g., DB patterns if sqlx is used)
     fn match_patterns_with_context(
         &self,
         graph: &CallGraph,
-        context: &DetectionContext,
+        workspace: &Workspace,
+        rules: &patterns::DetectionRules,
     ) -> Vec<patterns::MatchResult> {
         let mut results = Vec::new();
+        let call_sites = patterns::CallSites::from_graph(graph);
 
         for node_name in graph.node_names() {
             if let Some(node) = graph.get_node(&node_name) {
+                if matches!(
+                    node.kind,
+                    call_graph::NodeKind::External | call_graph::NodeKind::Closure
+                ) || (node.kind == call_graph::NodeKind::Test && !self.config.include_tests)
+                {
+                    continue;
+                }
+
                 let mut result = patterns::MatchResult::with_location(
                     node.file().unwrap_or_default(),
                     node_name.clone(),
                     node.line().unwrap_or(0),
                 );
 
+                // Match within the crate so a crate name like `order_service`
+                // doesn't match every function in it
+                let name = node.crate_relative_id();
+                let context = workspace.context_for(&result.file);
+
+                // Rules declared by the team win over the built-in heuristics
+                if let Some(rule_match) = rules.custom_match(node) {
+                    result.category = rule_match.rule.category;
+                    result.confidence = rule_match.rule.confidence;
+                    result.add_match(
+                        &rule_match.rule.name,
+                        rule_match.rule.confidence,
+                        &rule_match.evidence,
+                    );
+                    results.push(result);
+                    continue;
+                }
+
+                // Calls into database, HTTP client and cache crates
+                if let Some(evidence) = call_sites.classify(&node_name, context) {
+                    result.category = evidence.category;
+                    result.confidence = CALL_SITE_CONFIDENCE;
+                    result.add_match_with_location(
+                        evidence.pattern,
+                        CALL_SITE_CONFIDENCE,
+                        &evidence.call_site.path,
+                        evidence.call_site.line,
+                        evidence.call_site.column,
+                    );
+                    results.push(result);
+                    continue;
+                }
+
                 // Database patterns - only if project uses a DB crate
-                if context.is_likely_db_operation(&node_name) {
+                if context.is_likely_db_operation(name) {
                     result.category = patterns::Category::Database;
                     result.confidence = context.db_priority;
                     results.push(result);
@@ -397,7 +597,7 @@ impl Analyzer {
                 }
 
                 // HTTP client patterns - only if project uses an HTTP client
-                if context.is_likely_http_call(&node_name) {
+                if context.is_likely_http_call(name) {
                     result.category = patterns::Category::HttpClient;
                     result.confidence = context.http_priority;
                     results.push(result);
@@ -405,7 +605,7 @@ impl Analyzer {
                 }
 
                 // Cache patterns - only if project uses a cache crate
-                if context.is_likely_cache_operation(&node_name) {
+                if context.is_likely_cache_operation(name) {
                     result.category = patterns::Category::Cache;
                     result.confidence = context.cache_priority;
                     results.push(result);
@@ -413,7 +613,7 @@ impl Analyzer {
                 }
 
                 // Error handling patterns (always relevant)
-                if matches_error_pattern(&node_name) {
+                if rules.matches_name(patterns::Category::ErrorHandling, name) {
                     result.category = patterns::Category::ErrorHandling;
                     result.confidence = 0.8;
                     results.push(result);
@@ -421,7 +621,7 @@ impl Analyzer {
                 }
 
                 // Business logic patterns (always relevant)
-                if matches_business_pattern(&node_name) {
+                if rules.matches_name(patterns::Category::BusinessLogic, name) {
                     result.category = patterns::Category::BusinessLogic;
                     result.confidence = 0.7;
                     results.push(result);
@@ -430,11 +630,15 @@ impl Analyzer {
 
                 // Fallback: use old naive patterns if context check passed
                 // This handles cases where deps weren't detected but source shows usage
-                if matches_db_pattern(&node_name) && context.deps.has_database() {
+                if rules.matches_name(patterns::Category::Database, name)
+                    && context.deps.has_database()
+                {
                     result.category = patterns::Category::Database;
                     result.confidence = 0.7; // Lower confidence for fallback
                     results.push(result);
-                } else if matches_http_pattern(&node_name) && context.deps.has_http_client() {
+                } else if rules.matches_name(patterns::Category::HttpClient, name)
+                    && context.deps.has_http_client()
+                {
                     result.category = patterns::Category::HttpClient;
                     result.confidence = 0.6; // Lower confidence for fallback
                     results.push(result);
@@ -445,13 +649,36 @@ impl Analyzer {
         results
     }
 
+    /// Detect instrumentation points and the endpoint call paths reaching them
+    ///
+    /// With `trace_from_endpoints`, only functions an endpoint reaches within
+    /// `max_depth` calls are kept.
     fn detect_instrumentation_points(
         &self,
         graph: &CallGraph,
         endpoints: &[detector::Endpoint],
         patterns: &[patterns::MatchResult],
     ) -> Vec<detector::InstrumentationPoint> {
-        detector::priority::prioritize_points(graph, endpoints, patterns, self.config.threshold)
+        let mut points = detector::priority::prioritize_points(
+            graph,
+            endpoints,
+            patterns,
+            self.config.threshold,
+        );
+
+        let trace = detector::trace::EndpointTrace::new(graph, endpoints, self.config.max_depth);
+        for point in &mut points {
+            point.call_paths = trace.paths_for(point);
+        }
+
+        if self.config.trace_from_endpoints {
+            points.retain(|point| {
+                point.kind == detector::InstrumentationKind::Endpoint
+                    || !point.call_paths.is_empty()
+            });
+        }
+
+        points
     }
 
     /// Detect gaps between suggested instrumentation points and existing instrumentation
@@ -463,15 +690,7 @@ impl Analyzer {
         let mut gaps = Vec::new();
 
         for point in points {
-            // Check if this point has existing instrumentation
-            let has_existing = existing.iter().any(|e| {
-                e.location.file == point.location.file
-                    && (e.location.line == point.location.line
-                        || e.location.line == point.location.line.saturating_sub(1)
-                        || e.location.line == point.location.line + 1)
-            });
-
-            if !has_existing {
+            if !has_existing_instrumentation(point, existing) {
                 let severity = match point.priority {
                     detector::Priority::Critical => detector::GapSeverity::Critical,
                     detector::Priority::High => detector::GapSeverity::Major,
@@ -498,94 +717,139 @@ impl Analyzer {
     }
 }
 
-// Pattern matching helpers
-fn matches_db_pattern(name: &str) -> bool {
-    let db_patterns = [
-        "query",
-        "execute",
-        "fetch",
-        "insert",
-        "update",
-        "delete",
-        "select",
-        "transaction",
-        "commit",
-        "rollback",
-        "connect",
-        "pool",
-        "database",
-        "db_",
-        "_db",
-        "sql",
-        "postgres",
-        "mysql",
-        "sqlite",
-        "redis",
-        "mongo",
-        "dynamo",
-    ];
-    let lower = name.to_lowercase();
-    db_patterns.iter().any(|p| lower.contains(p))
+/// Workspace members and the dependency context each is analyzed with
+struct Workspace {
+    /// Packages of the cargo workspace, empty outside a cargo project
+    members: Vec<WorkspaceMember>,
+    /// Canonicalized root directory of each member, in the same order
+    roots: Vec<PathBuf>,
+    /// Detection context of each member, in the same order
+    contexts: Vec<DetectionContext>,
+    /// Context from the dependencies of all members together
+    aggregate: DetectionContext,
+}
+
+impl Workspace {
+    /// Enumerates the members of a workspace, if the project has one
+    fn new(metadata: Option<&cargo_metadata::Metadata>) -> Self {
+        let members = metadata
+            .map(ProjectDependencies::workspace_members)
+            .unwrap_or_default();
+        let roots = members
+            .iter()
+            .map(|member| member.root.canonicalize().unwrap_or(member.root.clone()))
+            .collect();
+
+        let mut dependencies = ProjectDependencies::default();
+        for member in &members {
+            dependencies.merge(&member.dependencies);
+        }
+        let contexts = members
+            .iter()
+            .map(|member| DetectionContext::from_deps(member.dependencies.clone()))
+            .collect();
+
+        Self {
+            members,
+            roots,
+            contexts,
+            aggregate: DetectionContext::from_deps(dependencies),
+        }
+    }
+
+    /// Returns the detection context for a file's package
+    fn context_for(&self, file: &Path) -> &DetectionContext {
+        member_index(&self.roots, file)
+            .and_then(|index| self.contexts.get(index))
+            .unwrap_or(&self.aggregate)
+    }
 }
 
-fn matches_http_pattern(name: &str) -> bool {
-    let http_patterns = [
-        "request",
-        "response",
-        "http",
-        "fetch",
-        "call_api",
-        "send_request",
-        "client",
-        "get_",
-        "post_",
-        "put_",
-        "delete_",
-        "patch_",
-        "api_call",
-        "remote",
-        "external",
-    ];
-    let lower = name.to_lowercase();
-    http_patterns.iter().any(|p| lower.contains(p))
+/// Finds the package a file belongs to among canonicalized package roots
+///
+/// The deepest root wins, so a file in a member nested under the workspace's
+/// root package belongs to the member.
+fn member_index(roots: &[PathBuf], file: &Path) -> Option<usize> {
+    let file = file.canonicalize().ok()?;
+    roots
+        .iter()
+        .enumerate()
+        .filter(|(_, root)| file.starts_with(root))
+        .max_by_key(|(_, root)| root.components().count())
+        .map(|(index, _)| index)
 }
 
-fn matches_error_pattern(name: &str) -> bool {
-    let error_patterns = [
-        "error",
-        "handle_error",
-        "map_err",
-        "on_error",
-        "catch",
-        "recover",
-        "fallback",
-        "retry",
-        "validate",
-    ];
-    let lower = name.to_lowercase();
-    error_patterns.iter().any(|p| lower.contains(p))
+/// Checks whether the function of a point already has a span
+fn has_existing_instrumentation(
+    point: &detector::InstrumentationPoint,
+    existing: &[detector::ExistingInstrumentation],
+) -> bool {
+    existing
+        .iter()
+        .any(|e| e.instruments(&point.location.function_name))
 }
 
-fn matches_business_pattern(name: &str) -> bool {
-    let business_patterns = [
-        "process",
-        "handle",
-        "create",
-        "calculate",
-        "validate",
-        "authorize",
-        "authenticate",
-        "payment",
-        "order",
-        "checkout",
-        "register",
-        "login",
-        "logout",
-        "subscribe",
-        "publish",
-    ];
-    let lower = name.to_lowercase();
-    business_patterns.iter().any(|p| lower.contains(p))
+/// Computes statistics over the parts of a result in files accepted by `filter`
+fn collect_stats(
+    result: &AnalysisResult,
+    parsed: &[ast::SourceFile],
+    filter: impl Fn(&Path) -> bool,
+) -> AnalysisStats {
+    let files: Vec<_> = parsed.iter().filter(|p| filter(p.path())).collect();
+    let points: Vec<_> = result
+        .points
+        .iter()
+        .filter(|p| filter(&p.location.file))
+        .collect();
+    let existing: Vec<_> = result
+        .existing_instrumentation
+        .iter()
+        .filter(|e| filter(&e.location.file))
+        .collect();
+
+    let covered = points
+        .iter()
+        .filter(|point| has_existing_instrumentation(point, &result.existing_instrumentation))
+        .count();
+    let coverage = if points.is_empty() {
+        100.0
+    } else {
+        covered as f64 / points.len() as f64 * 100.0
+    };
+
+    AnalysisStats {
+        total_files: files.len(),
+        total_functions: result
+            .call_graph
+            .nodes()
+            .filter(|node| {
+                !matches!(
+                    node.kind,
+                    call_graph::NodeKind::External | call_graph::NodeKind::Closure
+                )
+            })
+            .filter(|node| node.file().is_some_and(|file| filter(&file)))
+            .count(),
+        total_lines: files.iter().map(|p| p.line_count()).sum(),
+        endpoints_count: result
+            .endpoints
+            .iter()
+            .filter(|e| filter(&e.location.file))
+            .count(),
+        instrumentation_points: points.len(),
+        existing_count: existing.len(),
+        gaps_count: result
+            .gaps
+            .iter()
+            .filter(|g| filter(&g.location.file))
+            .count(),
+        rule_violations_count: result
+            .rule_violations
+            .iter()
+            .filter(|v| filter(&v.location.file))
+            .count(),
+        coverage,
+    }
 }
 
 // Keep backward compatibility alias
@@ -2,13 +2,14 @@
 //!
 //! A Rust CLI tool for detecting optimal instrumentation points for observability.
 
-use clap::{Parser, Subcommand, ValueEnum};
-use instrument_rs::config::{FrameworkType, OutputFormat};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use instrument_rs::config::{ConfigOverrides, FrameworkType, LoadedConfig, OutputFormat};
 use instrument_rs::detector::GapSeverity;
 use instrument_rs::fixer::{Fixer, FixerConfig};
 use instrument_rs::output::{FormatterFactory, FormatterOptions, write_output};
 use instrument_rs::{Analyzer, Config};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
 #[command(
@@ -23,12 +24,18 @@ struct Cli {
     paths: Vec<PathBuf>,
 
     /// Trace from HTTP/gRPC endpoints
-    #[arg(long)]
+    #[arg(long, global = true)]
     trace_from_endpoints: bool,
 
-    /// Web framework to use for endpoint detection
-    #[arg(long, value_enum, default_value = "auto")]
-    framework: FrameworkType,
+    /// Web frameworks to use for endpoint detection (comma-separated)
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_value = "auto",
+        global = true
+    )]
+    framework: Vec<FrameworkType>,
 
     /// Output format
     #[arg(short, long, value_enum, default_value = "human")]
@@ -39,21 +46,33 @@ struct Cli {
     filter_path: Option<String>,
 
     /// Maximum call graph depth
-    #[arg(long, default_value = "10")]
+    #[arg(long, default_value = "10", global = true)]
     max_depth: usize,
 
+    /// Maximum number of critical paths to report per endpoint
+    #[arg(long, default_value = "5", global = true)]
+    critical_paths: usize,
+
     /// Detection threshold (0.0-1.0)
     #[arg(long, default_value = "0.8")]
     threshold: f64,
 
-    /// Include test functions in analysis
-    #[arg(long)]
+    /// Include test code (#[test] functions, #[cfg(test)] modules, tests/) in analysis
+    #[arg(long, global = true)]
     include_tests: bool,
 
     /// Custom patterns file
-    #[arg(long)]
+    #[arg(long, global = true)]
     patterns: Option<PathBuf>,
 
+    /// Don't skip files ignored by .gitignore and .ignore
+    #[arg(long, global = true)]
+    no_ignore: bool,
+
+    /// List the files that would be analyzed, then exit
+    #[arg(long)]
+    list_files: bool,
+
     /// Output file (default: stdout)
     #[arg(short, long)]
     output: Option<PathBuf>,
@@ -78,6 +97,11 @@ struct Cli {
     #[arg(long, default_value = "0")]
     max_fixes: usize,
 
+    /// Configuration file (default: instrument-rs.toml in the current
+    /// directory, then in the workspace root)
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -116,6 +140,25 @@ enum Commands {
         #[arg(long)]
         critical_only: bool,
 
+        /// Output format (human or json)
+        #[arg(short, long, value_enum, default_value = "human")]
+        format: OutputFormat,
+    },
+    /// Inspect the configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Show the effective configuration and where each setting came from
+    Show {
+        /// Project whose workspace root is searched for a config file
+        #[arg(default_value = ".")]
+        paths: Vec<PathBuf>,
+
         /// Output format (human or json)
         #[arg(short, long, value_enum, default_value = "human")]
         format: OutputFormat,
@@ -123,7 +166,10 @@ enum Commands {
 }
 
 fn main() -> anyhow::Result<()> {
-    let cli = Cli::parse();
+    // Parse through `ArgMatches` so explicit flags can be told apart from
+    // defaults when merging with the config file
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches)?;
 
     match cli.command {
         Some(Commands::Init { output }) => {
@@ -135,10 +181,15 @@ fn main() -> anyhow::Result<()> {
             critical_only,
             format,
         }) => {
-            check_coverage(&cli, paths, threshold, critical_only, format)?;
+            check_coverage(&cli, &matches, paths, threshold, critical_only, format)?;
+        }
+        Some(Commands::Config {
+            command: ConfigCommand::Show { ref paths, format },
+        }) => {
+            show_config(&cli, &matches, paths, format)?;
         }
         None => {
-            analyze(&cli)?;
+            analyze(&cli, &matches)?;
         }
     }
 
@@ -152,14 +203,58 @@ fn init_config(output: PathBuf) -> anyhow::Result<()> {
     Ok(())
 }
 
+fn show_config(
+    cli: &Cli,
+    matches: &ArgMatches,
+    paths: &[PathBuf],
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    let loaded = load_config(cli, matches, paths)?;
+
+    match format {
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "file": loaded.file.as_ref().map(|f| f.display().to_string()),
+                "settings": loaded.settings().iter().map(|(key, value, source)| {
+                    serde_json::json!({
+                        "key": key,
+                        "value": value,
+                        "source": source.to_string(),
+                    })
+                }).collect::<Vec<_>>(),
+                "ignored_keys": loaded.ignored_keys,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        _ => {
+            match &loaded.file {
+                Some(file) => println!("Config file: {}", file.display()),
+                None => println!("Config file: none (using defaults)"),
+            }
+            println!();
+            for (key, value, source) in loaded.settings() {
+                let value = value.unwrap_or_else(|| "(not set)".to_string());
+                println!("{:<22} = {:<30} # {}", key, value, source);
+            }
+            if !loaded.ignored_keys.is_empty() {
+                println!();
+                println!("Ignored keys: {}", loaded.ignored_keys.join(", "));
+            }
+        }
+    }
+
+    Ok(())
+}
+
 fn check_coverage(
     cli: &Cli,
+    matches: &ArgMatches,
     paths: &[PathBuf],
     threshold: f64,
     critical_only: bool,
     format: OutputFormat,
 ) -> anyhow::Result<()> {
-    let config = build_config(cli);
+    let config = load_config(cli, matches, paths)?.config;
     let analyzer = Analyzer::new(config);
 
     let paths: Vec<&str> = paths.iter().map(|p| p.to_str().unwrap_or(".")).collect();
@@ -194,6 +289,14 @@ fn check_coverage(
                 "covered": covered,
                 "gaps": gaps,
                 "critical_only": critical_only,
+                "crates": result.crates.iter().map(|c| {
+                    serde_json::json!({
+                        "name": c.name,
+                        "coverage": c.stats.coverage,
+                        "total_points": c.stats.instrumentation_points,
+                        "gaps": c.stats.gaps_count,
+                    })
+                }).collect::<Vec<_>>(),
             });
             println!("{}", serde_json::to_string_pretty(&output)?);
         }
@@ -220,6 +323,20 @@ fn check_coverage(
             println!("   Threshold:                    {:.1}%", threshold);
             println!();
 
+            if result.crates.len() > 1 {
+                println!("📦 Coverage by crate:");
+                for krate in &result.crates {
+                    println!(
+                        "   {:<28} {:5.1}% ({} points, {} gaps)",
+                        krate.name,
+                        krate.stats.coverage,
+                        krate.stats.instrumentation_points,
+                        krate.stats.gaps_count
+                    );
+                }
+                println!();
+            }
+
             if coverage >= threshold {
                 println!("✅ PASSED: Coverage meets threshold");
             } else {
@@ -255,8 +372,8 @@ fn check_coverage(
     Ok(())
 }
 
-fn analyze(cli: &Cli) -> anyhow::Result<()> {
-    let config = build_config(cli);
+fn analyze(cli: &Cli, matches: &ArgMatches) -> anyhow::Result<()> {
+    let config = load_config(cli, matches, &cli.paths)?.config;
     let analyzer = Analyzer::new(config);
 
     let paths: Vec<&str> = cli
@@ -264,6 +381,14 @@ fn analyze(cli: &Cli) -> anyhow::Result<()> {
         .iter()
         .map(|p| p.to_str().unwrap_or("."))
         .collect();
+
+    if cli.list_files {
+        for file in analyzer.collect_files(&paths)? {
+            println!("{}", file.display());
+        }
+        return Ok(());
+    }
+
     let result = analyzer.analyze(&paths)?;
 
     // Handle fix mode
@@ -333,19 +458,32 @@ fn apply_fixes(
     Ok(())
 }
 
-fn build_config(cli: &Cli) -> Config {
-    Config {
-        threshold: cli.threshold,
-        max_depth: cli.max_depth,
-        include_tests: cli.include_tests,
-        framework: cli.framework,
+/// Loads the configuration file and applies the flags given explicitly
+fn load_config(cli: &Cli, matches: &ArgMatches, paths: &[PathBuf]) -> anyhow::Result<LoadedConfig> {
+    let explicit = |id: &str| {
+        matches!(
+            matches.value_source(id),
+            Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+        )
+    };
+
+    let overrides = ConfigOverrides {
+        threshold: explicit("threshold").then_some(cli.threshold),
+        max_depth: explicit("max_depth").then_some(cli.max_depth),
+        trace_from_endpoints: explicit("trace_from_endpoints").then_some(cli.trace_from_endpoints),
+        critical_paths: explicit("critical_paths").then_some(cli.critical_paths),
+        include_tests: explicit("include_tests").then_some(cli.include_tests),
+        framework: explicit("framework").then(|| cli.framework.clone()),
         patterns_file: cli.patterns.clone(),
-        exclude_patterns: vec![
-            "target".to_string(),
-            "node_modules".to_string(),
-            ".git".to_string(),
-        ],
-        source_dirs: vec![PathBuf::from("src")],
-        naming_rules: instrument_rs::config::NamingRules::default(),
-    }
+        respect_ignore_files: cli.no_ignore.then_some(false),
+    };
+
+    let cwd = std::env::current_dir()?;
+    let project_root = paths.first().map_or(Path::new("."), PathBuf::as_path);
+    Ok(LoadedConfig::load(
+        cli.config.as_deref(),
+        &cwd,
+        project_root,
+        overrides,
+    )?)
 }
@@ -19,6 +19,7 @@ impl JsonFormatter {
 impl OutputFormatter for JsonFormatter {
     fn format(&self, result: &AnalysisResult) -> Result<String> {
         let output = serde_json::json!({
+            "frameworks": result.frameworks.iter().map(|f| f.name()).collect::<Vec<_>>(),
             "stats": {
                 "total_files": result.stats.total_files,
                 "total_functions": result.stats.total_functions,
@@ -28,7 +29,25 @@ impl OutputFormatter for JsonFormatter {
                 "existing_count": result.stats.existing_count,
                 "gaps_count": result.stats.gaps_count,
                 "rule_violations_count": result.stats.rule_violations_count,
+                "coverage": result.stats.coverage,
             },
+            "crates": result.crates.iter().map(|c| {
+                serde_json::json!({
+                    "name": c.name,
+                    "root": c.root.display().to_string(),
+                    "stats": {
+                        "total_files": c.stats.total_files,
+                        "total_functions": c.stats.total_functions,
+                        "total_lines": c.stats.total_lines,
+                        "endpoints_count": c.stats.endpoints_count,
+                        "instrumentation_points": c.stats.instrumentation_points,
+                        "existing_count": c.stats.existing_count,
+                        "gaps_count": c.stats.gaps_count,
+                        "rule_violations_count": c.stats.rule_violations_count,
+                        "coverage": c.stats.coverage,
+                    },
+                })
+            }).collect::<Vec<_>>(),
             "endpoints": result.endpoints.iter().map(|e| {
                 serde_json::json!({
                     "method": e.method,
@@ -38,7 +57,16 @@ impl OutputFormatter for JsonFormatter {
                     "location": {
                         "file": e.location.file.display().to_string(),
                         "line": e.location.line,
-                    }
+                    },
+                    "parameters": e.parameters.iter().map(|p| {
+                        serde_json::json!({
+                            "name": p.name,
+                            "type": p.ty,
+                            "is_extractor": p.is_extractor,
+                        })
+                    }).collect::<Vec<_>>(),
+                    "documentation": e.documentation,
+                    "streaming": e.streaming,
                 })
             }).collect::<Vec<_>>(),
             "existing_instrumentation": result.existing_instrumentation.iter().map(|e| {
@@ -52,9 +80,13 @@ impl OutputFormatter for JsonFormatter {
                             "message": i.message,
                         })
                     }).collect::<Vec<_>>(),
+                    "args": e.args,
+                    "instrumented_futures": e.instrumented_futures,
+                    "otel": e.otel,
                     "location": {
                         "file": e.location.file.display().to_string(),
                         "line": e.location.line,
+                        "function": e.location.function_name,
                     }
                 })
             }).collect::<Vec<_>>(),
@@ -79,6 +111,40 @@ impl OutputFormatter for JsonFormatter {
                     "priority": format!("{:?}", p.priority),
                     "reason": p.reason,
                     "suggested_span_name": p.suggested_span_name,
+                    "call_paths": p.call_paths,
+                })
+            }).collect::<Vec<_>>(),
+            "critical_paths": result.critical_paths.iter().map(|c| {
+                serde_json::json!({
+                    "endpoint": c.endpoint,
+                    "boundary": c.boundary.id(),
+                    "score": c.score,
+                    "coverage_lost_at": c.coverage_lost_at().map(|hop| &hop.function),
+                    "hops": c.hops.iter().map(|hop| {
+                        serde_json::json!({
+                            "function": hop.function,
+                            "status": format!("{:?}", hop.status),
+                            "location": hop.location.as_ref().map(|l| serde_json::json!({
+                                "file": l.file.display().to_string(),
+                                "line": l.line,
+                            })),
+                        })
+                    }).collect::<Vec<_>>(),
+                })
+            }).collect::<Vec<_>>(),
+            "context_breaks": result.context_breaks.iter().map(|b| {
+                serde_json::json!({
+                    "kind": format!("{:?}", b.kind),
+                    "call": b.call,
+                    "caller": b.caller,
+                    "callees": b.callees,
+                    "description": b.description,
+                    "suggested_fix": b.suggested_fix,
+                    "location": {
+                        "file": b.location.file.display().to_string(),
+                        "line": b.location.line,
+                        "function": b.location.function_name,
+                    }
                 })
             }).collect::<Vec<_>>(),
             "rule_violations": result.rule_violations.iter().map(|v| {
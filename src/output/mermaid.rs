@@ -3,6 +3,7 @@
 use super::traits::{FormatterOptions, OutputFormat, OutputFormatter};
 use crate::AnalysisResult;
 use crate::Result;
+use std::collections::{HashMap, HashSet};
 
 /// Mermaid diagram formatter for analysis results
 pub struct MermaidFormatter {
@@ -21,24 +22,43 @@ impl OutputFormatter for MermaidFormatter {
         let mut output = String::new();
         output.push_str("graph TD\n");
 
+        // Calls on a path from an endpoint to an instrumentation point
+        let mut traced_calls = HashSet::new();
+        let mut handlers = HashMap::new();
+        for call_path in result.points.iter().flat_map(|p| &p.call_paths) {
+            if let Some(handler) = call_path.functions.first() {
+                handlers.insert(call_path.endpoint.as_str(), handler.as_str());
+            }
+            for call in call_path.functions.windows(2) {
+                traced_calls.insert((call[0].as_str(), call[1].as_str()));
+            }
+        }
+
         // Add endpoints as entry points
         for (i, endpoint) in result.endpoints.iter().enumerate() {
             let node_id = format!("EP{i}");
-            output.push_str(&format!(
-                "    {node_id}[\"{} {}\"]\n",
-                endpoint.method, endpoint.path
-            ));
+            let label = format!("{} {}", endpoint.method, endpoint.path);
+            output.push_str(&format!("    {node_id}[\"{label}\"]\n"));
 
             // Connect to handler
-            let handler_id = sanitize_id(&endpoint.handler);
+            let handler = handlers
+                .get(label.as_str())
+                .copied()
+                .unwrap_or(&endpoint.handler);
+            let handler_id = sanitize_id(handler);
             output.push_str(&format!("    {node_id} --> {handler_id}\n"));
         }
 
-        // Add call graph edges
+        // Add call graph edges, drawing traced calls with thick links
         for edge in result.call_graph.edges() {
             let from_id = sanitize_id(&edge.from);
             let to_id = sanitize_id(&edge.to);
-            output.push_str(&format!("    {from_id} --> {to_id}\n"));
+            let link = if traced_calls.contains(&(edge.from.as_str(), edge.to.as_str())) {
+                "==>"
+            } else {
+                "-->"
+            };
+            output.push_str(&format!("    {from_id} {link} {to_id}\n"));
         }
 
         // Add instrumentation points with styles
@@ -23,6 +23,9 @@ mod tests {
                 function_name: "get_users".to_string(),
             },
             framework: "axum".to_string(),
+            parameters: vec![],
+            documentation: None,
+            streaming: None,
         }];
 
         let points = vec![InstrumentationPoint {
@@ -37,15 +40,19 @@ mod tests {
             reason: "GET endpoint handler".to_string(),
             suggested_span_name: "get_api_users".to_string(),
             suggested_fields: vec![],
+            call_paths: Vec::new(),
         }];
 
         AnalysisResult {
+            frameworks: vec![crate::framework::DetectedFramework::Axum],
             endpoints,
             call_graph: CallGraph::new(),
             patterns: vec![],
             points,
             existing_instrumentation: vec![],
             gaps: vec![],
+            critical_paths: vec![],
+            context_breaks: vec![],
             rule_violations: vec![],
             dependencies: ProjectDependencies::default(),
             stats: AnalysisStats {
@@ -57,7 +64,9 @@ mod tests {
                 existing_count: 0,
                 gaps_count: 0,
                 rule_violations_count: 0,
+                coverage: 0.0,
             },
+            crates: vec![],
         }
     }
 
@@ -3,6 +3,7 @@
 use super::traits::{FormatterOptions, OutputFormat, OutputFormatter};
 use crate::AnalysisResult;
 use crate::Result;
+use crate::detector::critical_path::HopStatus;
 use crate::detector::rules::{ViolationKind, ViolationSeverity};
 use crate::detector::{ExistingKind, GapSeverity};
 use colored::*;
@@ -36,6 +37,9 @@ impl TreeFormatter {
         match kind {
             ExistingKind::TracingInstrument => "#[instrument]",
             ExistingKind::ManualSpan => "span!()",
+            ExistingKind::InstrumentedFuture => ".instrument()",
+            ExistingKind::SpanGuard => "span.enter()",
+            ExistingKind::OpenTelemetrySpan => "otel span",
             ExistingKind::LogMacro => "log macro",
             ExistingKind::Metrics => "metrics",
         }
@@ -57,6 +61,23 @@ impl TreeFormatter {
         }
     }
 
+    fn format_hop_status(&self, status: HopStatus) -> ColoredString {
+        let marker = match status {
+            HopStatus::Instrumented => "✓",
+            HopStatus::Uninstrumented => "✗",
+            HopStatus::External => "·",
+        };
+        if self.options.use_colors {
+            match status {
+                HopStatus::Instrumented => marker.green(),
+                HopStatus::Uninstrumented => marker.red().bold(),
+                HopStatus::External => marker.dimmed(),
+            }
+        } else {
+            marker.normal()
+        }
+    }
+
     fn format_quality_status(&self, score: f64) -> ColoredString {
         if self.options.use_colors {
             if score >= 0.8 {
@@ -126,6 +147,10 @@ impl OutputFormatter for TreeFormatter {
             "   Endpoints:          {}\n",
             result.stats.endpoints_count
         ));
+        if !result.frameworks.is_empty() {
+            let names: Vec<&str> = result.frameworks.iter().map(|f| f.name()).collect();
+            output.push_str(&format!("   Frameworks:         {}\n", names.join(", ")));
+        }
         output.push_str(&format!(
             "   Instrumentation:    {} points\n",
             result.stats.instrumentation_points
@@ -139,17 +164,48 @@ impl OutputFormatter for TreeFormatter {
             result.stats.gaps_count
         ));
         output.push_str(&format!(
-            "   Rule violations:    {}\n\n",
+            "   Rule violations:    {}\n",
             result.stats.rule_violations_count
         ));
+        output.push_str(&format!(
+            "   Coverage:           {:.1}%\n\n",
+            result.stats.coverage
+        ));
+
+        // Per-crate breakdown for workspaces
+        if result.crates.len() > 1 {
+            output.push_str("📦 Crates\n");
+            for krate in &result.crates {
+                output.push_str(&format!(
+                    "   {}: {} files, {} functions, {} points, {} gaps, {:.1}% covered\n",
+                    krate.name,
+                    krate.stats.total_files,
+                    krate.stats.total_functions,
+                    krate.stats.instrumentation_points,
+                    krate.stats.gaps_count,
+                    krate.stats.coverage
+                ));
+            }
+            output.push('\n');
+        }
 
         // Endpoints
         if !result.endpoints.is_empty() {
             output.push_str("🔗 Detected Endpoints\n");
+            // Tag endpoints with their framework when several contributed
+            let mixed = result
+                .endpoints
+                .iter()
+                .any(|e| e.framework != result.endpoints[0].framework);
             for endpoint in &result.endpoints {
+                let framework = if mixed {
+                    format!(" [{}]", endpoint.framework)
+                } else {
+                    String::new()
+                };
                 output.push_str(&format!(
-                    "   {} {} → {}\n",
-                    endpoint.method, endpoint.path, endpoint.handler
+                    "   {} {} → {}{}\n",
+                    endpoint.method, endpoint.path, endpoint.handler, framework
                 ));
                 output.push_str(&format!(
                     "      {}:{}\n",
@@ -202,6 +258,60 @@ impl OutputFormatter for TreeFormatter {
             }
         }
 
+        // Critical paths from endpoints to external boundaries
+        if !result.critical_paths.is_empty() {
+            output.push_str("🔥 Critical Paths\n");
+            for path in &result.critical_paths {
+                output.push_str(&format!(
+                    "   {} → {} (score {:.2})\n",
+                    path.endpoint,
+                    path.boundary.name(),
+                    path.score
+                ));
+                for hop in &path.hops {
+                    let status = self.format_hop_status(hop.status);
+                    match &hop.location {
+                        Some(location) => output.push_str(&format!(
+                            "      {} {} ({}:{})\n",
+                            status,
+                            hop.function,
+                            location.file.display(),
+                            location.line
+                        )),
+                        None => output
+                            .push_str(&format!("      {} {} (external)\n", status, hop.function)),
+                    }
+                }
+                if let Some(hop) = path.coverage_lost_at() {
+                    output.push_str(&format!("      Coverage lost at: {}\n", hop.function));
+                }
+                output.push('\n');
+            }
+        }
+
+        // Broken trace chains
+        if !result.context_breaks.is_empty() {
+            output.push_str("🔗 Broken Trace Chains\n");
+            for context_break in &result.context_breaks {
+                output.push_str(&format!(
+                    "   [{}] {}\n",
+                    context_break.kind.name(),
+                    context_break.description
+                ));
+                output.push_str(&format!(
+                    "      Call site: {}:{} in {}\n",
+                    context_break.location.file.display(),
+                    context_break.location.line,
+                    context_break.location.function_name
+                ));
+                output.push_str(&format!(
+                    "      Suggested: {}\n",
+                    context_break.suggested_fix
+                ));
+                output.push('\n');
+            }
+        }
+
         // Rule Violations
         if !result.rule_violations.is_empty() {
             output.push_str("📋 Rule Violations\n");
@@ -242,6 +352,13 @@ impl OutputFormatter for TreeFormatter {
                     point.location.file.display(),
                     point.location.line
                 ));
+                for call_path in &point.call_paths {
+                    output.push_str(&format!(
+                        "      Reached from: {} via {}\n",
+                        call_path.endpoint,
+                        call_path.functions.join(" → ")
+                    ));
+                }
                 output.push('\n');
             }
         }
@@ -0,0 +1,209 @@
+//! Call-site based classification
+//!
+//! What a function calls says more about it than its name: `get_user`
+//! calling `sqlx::query_as!` is a database operation whatever it is called.
+//! This module collects the external calls and macro invocations of each
+//! function and classifies them with the usage patterns of the known
+//! database, HTTP client and cache crates.
+
+use super::Category;
+use crate::call_graph::{CallGraph, NodeKind};
+use crate::dependencies::DetectionContext;
+use std::collections::HashMap;
+
+/// A call to a library function or macro
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallSite {
+    /// Called path without whitespace, macros ending in `!`
+    pub path: String,
+    /// Line of the call
+    pub line: usize,
+    /// Column of the call
+    pub column: usize,
+}
+
+/// A call site that puts its function into a category
+#[derive(Debug, Clone)]
+pub struct CallSiteEvidence<'a> {
+    /// Category of the call
+    pub category: Category,
+    /// The crate usage pattern that matched
+    pub pattern: &'static str,
+    /// The call that matched
+    pub call_site: &'a CallSite,
+}
+
+/// External call sites of every function in a call graph
+#[derive(Debug, Clone, Default)]
+pub struct CallSites {
+    /// Call sites in source order, keyed by function ID
+    ///
+    /// Calls in closures and async blocks belong to the enclosing function.
+    by_function: HashMap<String, Vec<CallSite>>,
+}
+
+impl CallSites {
+    /// Collects the call sites from a call graph
+    ///
+    /// # Arguments
+    ///
+    /// * `graph` - Graph whose edges to external functions and recorded
+    ///   macro invocations are collected
+    pub fn from_graph(graph: &CallGraph) -> Self {
+        let mut by_function: HashMap<String, Vec<CallSite>> = HashMap::new();
+
+        for edge in graph.edges() {
+            let external = graph
+                .get_node(&edge.to)
+                .is_some_and(|node| node.kind == NodeKind::External);
+            if !external {
+                continue;
+            }
+            by_function
+                .entry(enclosing_function(&edge.from).to_string())
+                .or_default()
+                .push(CallSite {
+                    path: edge.to.chars().filter(|c| !c.is_whitespace()).collect(),
+                    line: edge.line_number.unwrap_or(0),
+                    column: edge.column.unwrap_or(0),
+                });
+        }
+
+        for node in graph.nodes() {
+            if node.macro_calls.is_empty() {
+                continue;
+            }
+            by_function
+                .entry(enclosing_function(&node.id).to_string())
+                .or_default()
+                .extend(node.macro_calls.iter().map(|call| CallSite {
+                    path: call.path.clone(),
+                    line: call.line,
+                    column: call.column,
+                }));
+        }
+
+        for call_sites in by_function.values_mut() {
+            call_sites
+                .sort_by(|a, b| (a.line, a.column, &a.path).cmp(&(b.line, b.column, &b.path)));
+        }
+
+        Self { by_function }
+    }
+
+    /// Returns the call sites of a function, in source order
+    ///
+    /// # Arguments
+    ///
+    /// * `function_id` - ID of the function's node
+    pub fn of(&self, function_id: &str) -> &[CallSite] {
+        self.by_function
+            .get(function_id)
+            .map_or(&[], |call_sites| call_sites.as_slice())
+    }
+
+    /// Classifies a function by the first of its call sites matching a
+    /// crate usage pattern
+    ///
+    /// # Arguments
+    ///
+    /// * `function_id` - ID of the function's node
+    /// * `context` - Dependencies of the function's crate
+    ///
+    /// # Returns
+    ///
+    /// The category with the call site and pattern showing it, if any
+    pub fn classify(
+        &self,
+        function_id: &str,
+        context: &DetectionContext,
+    ) -> Option<CallSiteEvidence<'_>> {
+        self.of(function_id).iter().find_map(|call_site| {
+            context
+                .classify_call(&call_site.path)
+                .map(|(category, pattern)| CallSiteEvidence {
+                    category,
+                    pattern,
+                    call_site,
+                })
+        })
+    }
+}
+
+/// Returns the named function a closure or async block belongs to
+///
+/// Nested bodies have IDs such as `api::load::{closure#1}`.
+fn enclosing_function(id: &str) -> &str {
+    id.find("::{").map_or(id, |index| &id[..index])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::SourceFile;
+    use crate::call_graph::GraphBuilder;
+    use crate::dependencies::{DatabaseCrate, ProjectDependencies};
+    use std::path::PathBuf;
+
+    fn call_sites(code: &str) -> CallSites {
+        let file = SourceFile {
+            path: PathBuf::from("src/lib.rs"),
+            syntax_tree: syn::parse_file(code).unwrap(),
+            source: code.to_string(),
+            content_hash: String::new(),
+        };
+        let mut builder = GraphBuilder::new();
+        builder.add_parsed_file(&file).unwrap();
+        CallSites::from_graph(&builder.build().unwrap())
+    }
+
+    #[test]
+    fn test_classifies_by_calls_and_macros() {
+        let sites = call_sites(
+            r#"
+async fn get_user(pool: &PgPool, id: i64) {
+    println!("loading {id}");
+    let user = sqlx::query_as!(User, "SELECT * FROM users WHERE id = $1", id);
+}
+
+async fn process_order() {
+    let items = vec![1, 2];
+    items.iter().for_each(|item| {
+        reqwest::Client::new().post("https://billing.example.com");
+    });
+}
+
+fn connect(url: &str) {
+    PgPool::connect(url);
+}
+
+fn render() {
+    format!("plain");
+}
+"#,
+        );
+        let no_deps = DetectionContext::from_deps(ProjectDependencies::default());
+
+        let evidence = sites.classify("get_user", &no_deps).unwrap();
+        assert_eq!(evidence.category, Category::Database);
+        assert_eq!(evidence.pattern, "sqlx::query");
+        assert_eq!(evidence.call_site.path, "sqlx::query_as!");
+        assert_eq!(evidence.call_site.line, 4);
+
+        let evidence = sites.classify("process_order", &no_deps).unwrap();
+        assert_eq!(evidence.category, Category::HttpClient);
+        assert_eq!(evidence.call_site.path, "reqwest::Client::new");
+
+        // Bare type names need the dependency
+        assert!(sites.classify("connect", &no_deps).is_none());
+        let mut deps = ProjectDependencies::default();
+        deps.databases.insert(DatabaseCrate::Sqlx);
+        let with_sqlx = DetectionContext::from_deps(deps);
+        assert_eq!(
+            sites.classify("connect", &with_sqlx).map(|e| e.pattern),
+            Some("PgPool")
+        );
+
+        assert!(sites.classify("render", &with_sqlx).is_none());
+    }
+}
@@ -45,7 +45,7 @@
 //!
 //! # Custom Patterns
 //!
-//! You can define custom patterns in TOML:
+//! You can define custom patterns in TOML, loaded with `--patterns`:
 //!
 //! ```toml
 //! [[patterns]]
@@ -55,14 +55,22 @@
 //! confidence = 0.9
 //! description = "Custom API call pattern"
 //! ```
+//!
+//! Instead of `regex`, a rule can match a called path prefix (`call`), an
+//! attribute (`attribute`) or a type in the signature (`type`); see
+//! [`DetectionRules`].
 
+mod call_sites;
 mod matcher;
 mod pattern_set;
 mod result;
+mod rules;
 
 #[cfg(test)]
 mod test_standalone;
 
+pub use call_sites::{CallSite, CallSiteEvidence, CallSites};
 pub use matcher::PatternMatcher;
-pub use pattern_set::{Pattern, PatternSet};
+pub use pattern_set::{Pattern, PatternSet, TEST_MARKER_WEIGHT, TestMarkers};
 pub use result::{Category, MatchResult};
+pub use rules::{CategoryRule, DetectionRules, RuleMatch, RuleMatcher};
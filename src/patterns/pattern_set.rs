@@ -1,8 +1,12 @@
 //! Pattern definitions and pattern set management.
 
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Minimum weight of an attribute pattern that marks an item as test code
+pub const TEST_MARKER_WEIGHT: f64 = 0.9;
+
 /// A single pattern with its associated weight.
 ///
 /// Patterns can be simple string matches or more complex regex patterns.
@@ -264,3 +268,78 @@ impl PatternSet {
         }
     }
 }
+
+/// Attribute patterns that mark test code, compiled once
+///
+/// Built from the attribute patterns of a [`PatternSet`] weighted at least
+/// [`TEST_MARKER_WEIGHT`], so `#[test]`, `#[tokio::test]` and `#[cfg(test)]`
+/// qualify while `#[ignore]` doesn't. One set is shared by everything that
+/// tells test code apart during an analysis.
+#[derive(Debug, Clone)]
+pub struct TestMarkers {
+    /// Simple markers without their closing bracket (`#[test`, `#[cfg(test)`)
+    markers: Vec<String>,
+    /// Compiled regex markers; invalid regexes are left out
+    regexes: Vec<Regex>,
+}
+
+impl TestMarkers {
+    /// Compiles the test markers of a pattern set
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern_set` - The pattern set whose attribute patterns to use
+    pub fn new(pattern_set: &PatternSet) -> Self {
+        let mut markers = Vec::new();
+        let mut regexes = Vec::new();
+
+        for pattern in &pattern_set.attributes {
+            if pattern.weight < TEST_MARKER_WEIGHT {
+                continue;
+            }
+            if pattern.is_regex {
+                regexes.extend(Regex::new(&pattern.pattern).ok());
+            } else {
+                let marker = pattern
+                    .pattern
+                    .strip_suffix(']')
+                    .unwrap_or(&pattern.pattern);
+                markers.push(marker.to_string());
+            }
+        }
+
+        Self { markers, regexes }
+    }
+
+    /// Checks whether an attribute marks test code
+    ///
+    /// The attribute is compared without whitespace. A simple marker also
+    /// matches the attribute with arguments, as in
+    /// `#[tokio::test(flavor = "multi_thread")]`.
+    ///
+    /// # Arguments
+    ///
+    /// * `attr` - The attribute to check
+    ///
+    /// # Returns
+    ///
+    /// `true` if the attribute matches a test marker
+    pub fn is_test_attribute(&self, attr: &syn::Attribute) -> bool {
+        let attr: String = quote::quote!(#attr)
+            .to_string()
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+
+        self.markers.iter().any(|marker| {
+            attr.strip_prefix(marker.as_str())
+                .is_some_and(|rest| rest.starts_with([']', '(']))
+        }) || self.regexes.iter().any(|regex| regex.is_match(&attr))
+    }
+}
+
+impl Default for TestMarkers {
+    fn default() -> Self {
+        Self::new(&PatternSet::with_defaults())
+    }
+}
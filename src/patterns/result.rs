@@ -104,6 +104,35 @@ impl Category {
             Self::Unknown => "unknown",
         }
     }
+
+    /// Looks up a category by its short identifier
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - An identifier as returned by [`Category::id`]
+    pub fn from_id(id: &str) -> Option<Self> {
+        [
+            Self::UnitTest,
+            Self::IntegrationTest,
+            Self::PropertyTest,
+            Self::Benchmark,
+            Self::Fuzz,
+            Self::Mock,
+            Self::TestUtility,
+            Self::Example,
+            Self::Database,
+            Self::HttpClient,
+            Self::ExternalService,
+            Self::Cache,
+            Self::MessageQueue,
+            Self::ErrorHandling,
+            Self::Auth,
+            Self::BusinessLogic,
+            Self::Unknown,
+        ]
+        .into_iter()
+        .find(|category| category.id() == id)
+    }
 }
 
 /// Details about a single pattern match.
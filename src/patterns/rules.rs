@@ -0,0 +1,514 @@
+//! Category detection rules, built in or loaded from a patterns file
+//!
+//! A patterns file lets a team teach the analyzer about its own code. Each
+//! rule puts functions into a [`Category`] by one of their function name,
+//! the paths they call, their attributes or the types in their signature:
+//!
+//! ```toml
+//! [[patterns]]
+//! name = "repositories"
+//! category = "database"
+//! type = "PgPool"
+//! confidence = 0.9
+//!
+//! [[patterns]]
+//! name = "billing_api"
+//! category = "external_service"
+//! call = "billing_client::"
+//! description = "Calls through the billing SDK"
+//! ```
+//!
+//! YAML files (`.yaml` or `.yml`) take the same shape under a `patterns` key.
+//! Rules from a file are checked in order, before the built-in rules.
+
+use super::Category;
+use crate::call_graph::FunctionNode;
+use crate::error::{Error, Result};
+use regex::Regex;
+use serde::Deserialize;
+use std::path::Path;
+
+/// Confidence of a file rule that doesn't set one
+const DEFAULT_CONFIDENCE: f64 = 0.8;
+
+/// What a rule looks at to match a function
+#[derive(Debug, Clone)]
+pub enum RuleMatcher {
+    /// A regex searched in the function name and its crate-relative path
+    FunctionName(Regex),
+    /// A prefix of the path of a function it calls, such as `sqlx::query`
+    Call(String),
+    /// An attribute on the function, with or without its arguments
+    Attribute(String),
+    /// A type named in the signature or owning the method
+    Type(String),
+}
+
+/// A rule putting matching functions into a category
+#[derive(Debug, Clone)]
+pub struct CategoryRule {
+    /// Name identifying the rule in match evidence
+    pub name: String,
+    /// Category of the functions it matches
+    pub category: Category,
+    /// What the rule matches
+    pub matcher: RuleMatcher,
+    /// Confidence given to a match (0.0 - 1.0)
+    pub confidence: f64,
+    /// Optional description of what the rule matches
+    pub description: Option<String>,
+    /// Whether this is one of the analyzer's own rules
+    pub builtin: bool,
+}
+
+/// A rule that matched a function, with the text that matched
+#[derive(Debug, Clone)]
+pub struct RuleMatch<'a> {
+    /// The rule that matched
+    pub rule: &'a CategoryRule,
+    /// What the rule matched, such as `calls sqlx::query`
+    pub evidence: String,
+}
+
+/// Ordered category rules: those from a patterns file, then the built-in ones
+#[derive(Debug, Clone)]
+pub struct DetectionRules {
+    rules: Vec<CategoryRule>,
+}
+
+/// Layout of a patterns file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PatternsFile {
+    #[serde(default)]
+    patterns: Vec<RuleDefinition>,
+}
+
+/// A rule as written in a patterns file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleDefinition {
+    name: String,
+    category: String,
+    regex: Option<String>,
+    call: Option<String>,
+    attribute: Option<String>,
+    #[serde(rename = "type")]
+    type_name: Option<String>,
+    confidence: Option<f64>,
+    description: Option<String>,
+}
+
+impl DetectionRules {
+    /// Creates the built-in rules, matching substrings of function names
+    pub fn builtin() -> Self {
+        let categories: [(Category, f64, &[&str]); 4] = [
+            (
+                Category::Database,
+                0.9,
+                &[
+                    "query",
+                    "execute",
+                    "fetch",
+                    "insert",
+                    "update",
+                    "delete",
+                    "select",
+                    "transaction",
+                    "commit",
+                    "rollback",
+                    "connect",
+                    "pool",
+                    "database",
+                    "db_",
+                    "_db",
+                    "sql",
+                    "postgres",
+                    "mysql",
+                    "sqlite",
+                    "redis",
+                    "mongo",
+                    "dynamo",
+                ],
+            ),
+            (
+                Category::HttpClient,
+                0.85,
+                &[
+                    "request",
+                    "response",
+                    "http",
+                    "fetch",
+                    "call_api",
+                    "send_request",
+                    "client",
+                    "get_",
+                    "post_",
+                    "put_",
+                    "delete_",
+                    "patch_",
+                    "api_call",
+                    "remote",
+                    "external",
+                ],
+            ),
+            (
+                Category::ErrorHandling,
+                0.8,
+                &[
+                    "error",
+                    "handle_error",
+                    "map_err",
+                    "on_error",
+                    "catch",
+                    "recover",
+                    "fallback",
+                    "retry",
+                    "validate",
+                ],
+            ),
+            (
+                Category::BusinessLogic,
+                0.7,
+                &[
+                    "process",
+                    "handle",
+                    "create",
+                    "calculate",
+                    "validate",
+                    "authorize",
+                    "authenticate",
+                    "payment",
+                    "order",
+                    "checkout",
+                    "register",
+                    "login",
+                    "logout",
+                    "subscribe",
+                    "publish",
+                ],
+            ),
+        ];
+
+        let rules = categories
+            .iter()
+            .flat_map(|(category, confidence, substrings)| {
+                substrings.iter().map(|substring| CategoryRule {
+                    name: format!("builtin:{}", category.id()),
+                    category: *category,
+                    matcher: RuleMatcher::FunctionName(
+                        Regex::new(&format!("(?i){}", regex::escape(substring)))
+                            .expect("escaped substring is a valid regex"),
+                    ),
+                    confidence: *confidence,
+                    description: None,
+                    builtin: true,
+                })
+            })
+            .collect();
+
+        Self { rules }
+    }
+
+    /// Loads the built-in rules, preceded by those of a patterns file
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Patterns file, parsed as YAML for a `.yaml` or `.yml`
+    ///   extension and as TOML otherwise
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, doesn't parse, or has a
+    /// rule with an unknown category, an invalid regex, or not exactly one of
+    /// `regex`, `call`, `attribute` and `type`
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            Error::Config(format!("cannot read patterns file {}: {e}", path.display()))
+        })?;
+        let invalid =
+            |e: String| Error::Config(format!("invalid patterns file {}: {e}", path.display()));
+
+        let yaml = path
+            .extension()
+            .is_some_and(|ext| ext == "yaml" || ext == "yml");
+        let file: PatternsFile = if yaml {
+            serde_norway::from_str(&content).map_err(|e| invalid(e.to_string()))?
+        } else {
+            toml::from_str(&content).map_err(|e| invalid(e.to_string()))?
+        };
+
+        let mut rules = file
+            .patterns
+            .into_iter()
+            .map(RuleDefinition::into_rule)
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(invalid)?;
+        rules.extend(Self::builtin().rules);
+
+        Ok(Self { rules })
+    }
+
+    /// Returns the rules, in the order they are checked
+    pub fn rules(&self) -> &[CategoryRule] {
+        &self.rules
+    }
+
+    /// Finds the first rule from a patterns file that matches a function
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The function to classify
+    ///
+    /// # Returns
+    ///
+    /// The matching rule and what it matched, if any
+    pub fn custom_match(&self, node: &FunctionNode) -> Option<RuleMatch<'_>> {
+        self.rules
+            .iter()
+            .filter(|rule| !rule.builtin)
+            .find_map(|rule| {
+                rule.matches(node)
+                    .map(|evidence| RuleMatch { rule, evidence })
+            })
+    }
+
+    /// Checks whether any function name rule of a category matches a name
+    ///
+    /// # Arguments
+    ///
+    /// * `category` - The category whose rules are checked
+    /// * `name` - The crate-relative function name
+    pub fn matches_name(&self, category: Category, name: &str) -> bool {
+        self.rules.iter().any(|rule| {
+            rule.category == category
+                && matches!(&rule.matcher, RuleMatcher::FunctionName(regex) if regex.is_match(name))
+        })
+    }
+}
+
+impl Default for DetectionRules {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl CategoryRule {
+    /// Checks the rule against a function
+    ///
+    /// # Returns
+    ///
+    /// A description of what matched, if the rule matches
+    pub fn matches(&self, node: &FunctionNode) -> Option<String> {
+        match &self.matcher {
+            RuleMatcher::FunctionName(regex) => [node.name.as_str(), node.crate_relative_id()]
+                .into_iter()
+                .find(|name| regex.is_match(name))
+                .map(|name| format!("name {name}")),
+            RuleMatcher::Call(prefix) => {
+                let mut calls: Vec<String> = node.calls.iter().map(|c| strip_spaces(c)).collect();
+                calls.sort();
+                calls
+                    .into_iter()
+                    .find(|call| call.starts_with(prefix.as_str()))
+                    .map(|call| format!("calls {call}"))
+            }
+            RuleMatcher::Attribute(attribute) => node
+                .attributes
+                .iter()
+                .map(|attr| strip_spaces(attr))
+                .find(|attr| {
+                    let inner = attr.trim_start_matches("#[").trim_end_matches(']');
+                    inner
+                        .strip_prefix(attribute.as_str())
+                        .is_some_and(|rest| rest.is_empty() || rest.starts_with('('))
+                })
+                .map(|attr| format!("attribute {attr}")),
+            RuleMatcher::Type(type_name) => {
+                let owner = node.id.split("::").any(|segment| segment == type_name);
+                let in_signature = node
+                    .signature
+                    .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .any(|token| token == type_name);
+                (owner || in_signature).then(|| format!("type {type_name}"))
+            }
+        }
+    }
+}
+
+impl RuleDefinition {
+    /// Validates a rule from a file
+    fn into_rule(self) -> std::result::Result<CategoryRule, String> {
+        let category = Category::from_id(&self.category).ok_or_else(|| {
+            format!(
+                "rule '{}' has unknown category '{}'",
+                self.name, self.category
+            )
+        })?;
+
+        let mut matchers = Vec::new();
+        if let Some(regex) = self.regex {
+            let regex = Regex::new(&regex)
+                .map_err(|e| format!("rule '{}' has an invalid regex: {e}", self.name))?;
+            matchers.push(RuleMatcher::FunctionName(regex));
+        }
+        if let Some(call) = self.call {
+            matchers.push(RuleMatcher::Call(strip_spaces(&call)));
+        }
+        if let Some(attribute) = self.attribute {
+            let attribute = strip_spaces(&attribute);
+            let attribute = attribute.trim_start_matches("#[").trim_end_matches(']');
+            matchers.push(RuleMatcher::Attribute(attribute.to_string()));
+        }
+        if let Some(type_name) = self.type_name {
+            let type_name = type_name.rsplit("::").next().unwrap_or(&type_name);
+            matchers.push(RuleMatcher::Type(type_name.trim().to_string()));
+        }
+
+        if matchers.len() != 1 {
+            return Err(format!(
+                "rule '{}' needs exactly one of regex, call, attribute and type",
+                self.name
+            ));
+        }
+        let confidence = self.confidence.unwrap_or(DEFAULT_CONFIDENCE);
+        if !(0.0..=1.0).contains(&confidence) {
+            return Err(format!(
+                "rule '{}' has confidence {confidence} outside 0.0 - 1.0",
+                self.name
+            ));
+        }
+
+        Ok(CategoryRule {
+            name: self.name,
+            category,
+            matcher: matchers.remove(0),
+            confidence,
+            description: self.description,
+            builtin: false,
+        })
+    }
+}
+
+/// Removes whitespace, as in paths and attributes printed from tokens
+fn strip_spaces(text: &str) -> String {
+    text.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::call_graph::GraphBuilder;
+    use std::path::PathBuf;
+
+    fn graph(code: &str) -> crate::call_graph::CallGraph {
+        let file = crate::ast::SourceFile {
+            path: PathBuf::from("src/lib.rs"),
+            syntax_tree: syn::parse_file(code).unwrap(),
+            source: code.to_string(),
+            content_hash: String::new(),
+        };
+        let mut builder = GraphBuilder::new();
+        builder.add_parsed_file(&file).unwrap();
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_file_rules_match_before_builtins() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("patterns.toml");
+        std::fs::write(
+            &path,
+            r#"
+[[patterns]]
+name = "ledger"
+category = "external_service"
+call = "ledger_sdk::post"
+confidence = 0.95
+
+[[patterns]]
+name = "pools"
+category = "database"
+type = "sqlx::PgPool"
+
+[[patterns]]
+name = "jobs"
+category = "message_queue"
+attribute = "job"
+
+[[patterns]]
+name = "reports"
+category = "business_logic"
+regex = "^render_.*_report$"
+"#,
+        )
+        .unwrap();
+        let rules = DetectionRules::load(&path).unwrap();
+
+        let graph = graph(
+            r#"
+fn record_payment() { ledger_sdk::post(1); }
+fn load_user(pool: &PgPool) {}
+#[job(queue = "mail")]
+fn send_digest() {}
+fn render_monthly_report() {}
+fn update_cache() {}
+"#,
+        );
+        let classify = |id: &str| {
+            rules
+                .custom_match(graph.get_node(id).unwrap())
+                .map(|m| (m.rule.category, m.rule.confidence, m.evidence))
+        };
+
+        assert_eq!(
+            classify("record_payment"),
+            Some((
+                Category::ExternalService,
+                0.95,
+                "calls ledger_sdk::post".to_string()
+            ))
+        );
+        assert_eq!(
+            classify("load_user"),
+            Some((Category::Database, 0.8, "type PgPool".to_string()))
+        );
+        assert_eq!(
+            classify("send_digest").map(|m| m.0),
+            Some(Category::MessageQueue)
+        );
+        assert_eq!(
+            classify("render_monthly_report").map(|m| m.0),
+            Some(Category::BusinessLogic)
+        );
+        // Only built-in rules match, and those aren't file rules
+        assert_eq!(classify("update_cache"), None);
+        assert!(rules.matches_name(Category::Database, "update_cache"));
+    }
+
+    #[test]
+    fn test_yaml_file_and_invalid_rules() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let yaml = dir.path().join("patterns.yaml");
+        std::fs::write(
+            &yaml,
+            "patterns:\n  - name: kv\n    category: cache\n    call: \"moka::\"\n",
+        )
+        .unwrap();
+        let rules = DetectionRules::load(&yaml).unwrap();
+        assert_eq!(rules.rules()[0].category, Category::Cache);
+        assert!(rules.rules().len() > 1);
+
+        let invalid = [
+            "[[patterns]]\nname = \"x\"\ncategory = \"nope\"\ncall = \"a\"\n",
+            "[[patterns]]\nname = \"x\"\ncategory = \"cache\"\n",
+            "[[patterns]]\nname = \"x\"\ncategory = \"cache\"\ncall = \"a\"\ntype = \"B\"\n",
+            "[[patterns]]\nname = \"x\"\ncategory = \"cache\"\nregex = \"(\"\n",
+        ];
+        let toml = dir.path().join("patterns.toml");
+        for content in invalid {
+            std::fs::write(&toml, content).unwrap();
+            assert!(DetectionRules::load(&toml).is_err(), "{content}");
+        }
+    }
+}
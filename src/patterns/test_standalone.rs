@@ -2,7 +2,9 @@
 
 #[cfg(test)]
 mod tests {
-    use crate::patterns::{Category, MatchResult, Pattern, PatternMatcher, PatternSet};
+    use crate::patterns::{
+        Category, MatchResult, Pattern, PatternMatcher, PatternSet, TestMarkers,
+    };
 
     #[test]
     fn test_pattern_creation() {
@@ -38,6 +40,22 @@ mod tests {
         assert!(!defaults.framework_patterns.is_empty());
     }
 
+    #[test]
+    fn test_test_attributes() {
+        let markers = TestMarkers::new(&PatternSet::with_defaults());
+        let is_test = |attr: syn::Attribute| markers.is_test_attribute(&attr);
+
+        assert!(is_test(syn::parse_quote!(#[test])));
+        assert!(is_test(
+            syn::parse_quote!(#[tokio::test(flavor = "multi_thread")])
+        ));
+        assert!(is_test(syn::parse_quote!(#[cfg(test)])));
+        assert!(is_test(syn::parse_quote!(#[rstest])));
+        assert!(!is_test(syn::parse_quote!(#[ignore])));
+        assert!(!is_test(syn::parse_quote!(#[instrument])));
+        assert!(!is_test(syn::parse_quote!(#[cfg(feature = "testing")])));
+    }
+
     #[test]
     fn test_match_result() {
         let mut result = MatchResult::new();
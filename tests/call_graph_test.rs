@@ -5,7 +5,6 @@ use std::fs;
 use tempfile::TempDir;
 
 #[test]
-#[ignore = "Module path resolution needs improvement"]
 fn test_simple_call_graph() {
     let temp_dir = TempDir::new().unwrap();
     let src_dir = temp_dir.path().join("src");
@@ -274,3 +273,76 @@ fn test_path_finding() {
     let reverse_path = graph.find_path("d", "main");
     assert!(reverse_path.is_none());
 }
+
+#[test]
+fn test_module_paths_from_crate_module_tree() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let src_dir = root.join("src");
+    fs::create_dir_all(src_dir.join("api")).unwrap();
+    fs::create_dir_all(src_dir.join("bin")).unwrap();
+
+    fs::write(
+        root.join("Cargo.toml"),
+        r#"
+[package]
+name = "shop"
+version = "0.1.0"
+edition = "2021"
+
+[[bin]]
+name = "shop-admin"
+path = "src/bin/admin.rs"
+"#,
+    )
+    .unwrap();
+    fs::write(
+        src_dir.join("lib.rs"),
+        "pub mod api;\n#[path = \"storage.rs\"]\npub mod db;\n",
+    )
+    .unwrap();
+    fs::write(src_dir.join("api/mod.rs"), "pub mod users;\n").unwrap();
+    fs::write(
+        src_dir.join("api/users.rs"),
+        r#"
+use crate::db;
+
+pub fn create() {
+    db::insert();
+    super::super::db::audit();
+}
+"#,
+    )
+    .unwrap();
+    fs::write(
+        src_dir.join("storage.rs"),
+        "pub fn insert() {}\npub fn audit() {}\n",
+    )
+    .unwrap();
+    fs::write(
+        src_dir.join("bin/admin.rs"),
+        "fn main() {\n    shop::api::users::create();\n}\n",
+    )
+    .unwrap();
+
+    let mut builder = GraphBuilder::new();
+    let graph = builder.build_from_directory(root).unwrap();
+
+    let create = graph.get_node("shop::api::users::create").unwrap();
+    assert_eq!(create.crate_name.as_deref(), Some("shop"));
+    assert_eq!(create.crate_relative_id(), "api::users::create");
+    assert!(graph.get_node("shop::db::insert").is_some());
+    assert!(graph.get_node("shop_admin::main").is_some());
+
+    let edges = graph.edges();
+    for (from, to) in [
+        ("shop::api::users::create", "shop::db::insert"),
+        ("shop::api::users::create", "shop::db::audit"),
+        ("shop_admin::main", "shop::api::users::create"),
+    ] {
+        assert!(
+            edges.iter().any(|e| e.from == from && e.to == to),
+            "missing edge {from} -> {to}"
+        );
+    }
+}
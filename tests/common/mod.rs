@@ -48,6 +48,16 @@ impl TestProject {
         file_path
     }
 
+    /// Add a file relative to the project root
+    pub fn add_file(&self, relative_path: &str, content: &str) -> PathBuf {
+        let file_path = self.root_path.join(relative_path);
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(&file_path, content).unwrap();
+        file_path
+    }
+
     /// Add a Cargo.toml file
     pub fn add_cargo_toml(&self, content: &str) -> PathBuf {
         let file_path = self.root_path.join("Cargo.toml");
@@ -7,6 +7,8 @@ mod common;
 use common::TestProject;
 use common::sample_projects;
 use instrument_rs::dependencies::{DetectionContext, ProjectDependencies};
+use instrument_rs::fixer::{Fixer, FixerConfig};
+use instrument_rs::framework::web::{self, FrameworkDetector, HttpMethod, StreamingKind};
 use instrument_rs::{Analyzer, Config};
 
 // ============================================================================
@@ -85,6 +87,32 @@ fn test_e2e_axum_route_paths() {
     );
 }
 
+#[test]
+fn test_e2e_axum_handler_parameters() {
+    let project = create_axum_project();
+    let analyzer = Analyzer::new(Config::default());
+
+    let src_path = project.root_path.join("src");
+    let result = analyzer.analyze(&[src_path]).unwrap();
+
+    let get_user = result
+        .endpoints
+        .iter()
+        .find(|e| e.handler == "get_user")
+        .expect("Should detect get_user endpoint");
+
+    // Location points at the handler definition, not the route registration
+    assert_eq!(get_user.location.function_name, "axum_test::get_user");
+    assert_eq!(get_user.location.line, 38);
+
+    let params: Vec<(&str, bool)> = get_user
+        .parameters
+        .iter()
+        .map(|p| (p.name.as_str(), p.is_extractor))
+        .collect();
+    assert_eq!(params, vec![("user_id", true), ("_state", true)]);
+}
+
 // ============================================================================
 // Dependency Detection Tests
 // ============================================================================
@@ -869,3 +897,1434 @@ pub fn validate_address(address: &str) -> bool {
 
     project
 }
+
+// ============================================================================
+// Analysis Pipeline Tests
+// ============================================================================
+
+/// Creates a project with a single package
+///
+/// # Arguments
+///
+/// * `name` - The package name
+/// * `dependencies` - Name and version requirement of each dependency
+fn package_project(name: &str, dependencies: &[(&str, &str)]) -> TestProject {
+    let mut manifest = format!(
+        "[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\n"
+    );
+    for (dependency, version) in dependencies {
+        manifest.push_str(&format!("{dependency} = \"{version}\"\n"));
+    }
+
+    let project = TestProject::new();
+    project.add_cargo_toml(&manifest);
+    project
+}
+
+/// Detector treating every `handle_*` function as a `POST /<name>` endpoint
+struct PrefixDetector;
+
+impl FrameworkDetector for PrefixDetector {
+    fn name(&self) -> &'static str {
+        "Custom"
+    }
+
+    fn detect(&self, _project_root: &std::path::Path) -> instrument_rs::Result<bool> {
+        Ok(true)
+    }
+
+    fn extract_endpoints(&self, syntax_tree: &syn::File) -> Vec<web::Endpoint> {
+        syntax_tree
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                syn::Item::Fn(item_fn) => {
+                    let name = item_fn.sig.ident.to_string();
+                    let route = name.strip_prefix("handle_")?.to_string();
+                    Some(web::Endpoint {
+                        method: HttpMethod::Post,
+                        path: format!("/{route}"),
+                        handler: name,
+                        module_path: Vec::new(),
+                        file: None,
+                        line: 0,
+                        middleware: Vec::new(),
+                        documentation: None,
+                        streaming: None,
+                    })
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn analyze_router(&self, _item: &syn::Item) -> Option<web::RouterInfo> {
+        None
+    }
+
+    fn detect_handler(&self, function: &syn::ItemFn) -> Option<web::HandlerInfo> {
+        Some(web::HandlerInfo {
+            name: function.sig.ident.to_string(),
+            is_async: function.sig.asyncness.is_some(),
+            parameters: Vec::new(),
+            return_type: "()".to_string(),
+            extractors: Vec::new(),
+            documentation: Some("custom handler".to_string()),
+        })
+    }
+}
+
+#[test]
+fn test_e2e_custom_framework_detector() {
+    let project = TestProject::new();
+    project.add_source_file(
+        "lib.rs",
+        r#"
+pub fn handle_webhook(payload: String) {
+    let _ = payload;
+}
+"#,
+    );
+
+    let mut analyzer = Analyzer::new(Config::default());
+    analyzer.add_detector(Box::new(PrefixDetector));
+
+    let result = analyzer.analyze(&[&project.root_path]).unwrap();
+
+    let webhook = result
+        .endpoints
+        .iter()
+        .find(|e| e.handler == "handle_webhook")
+        .expect("Custom detector should contribute endpoints");
+    assert_eq!(webhook.method, "POST");
+    assert_eq!(webhook.path, "/webhook");
+    assert_eq!(webhook.framework, "custom");
+    assert_eq!(webhook.documentation.as_deref(), Some("custom handler"));
+    assert_eq!(webhook.location.line, 2);
+}
+
+#[test]
+fn test_e2e_actix_scoped_routes() {
+    let project = package_project("actix-test", &[("actix-web", "4")]);
+    project.add_source_file(
+        "main.rs",
+        r#"
+mod orders;
+
+use actix_web::{web, App, HttpServer};
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    HttpServer::new(|| {
+        App::new().service(web::scope("/api").configure(orders::config))
+    })
+    .bind(("127.0.0.1", 8080))?
+    .run()
+    .await
+}
+"#,
+    );
+    project.add_source_file(
+        "orders.rs",
+        r#"
+use actix_web::{get, web, HttpResponse, Responder};
+
+#[get("/orders/{id}")]
+async fn get_order(path: web::Path<u64>) -> impl Responder {
+    HttpResponse::Ok()
+}
+
+async fn create_order(body: web::Json<String>) -> impl Responder {
+    HttpResponse::Created()
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_order)
+        .route("/orders", web::post().to(create_order));
+}
+"#,
+    );
+
+    let analyzer = Analyzer::new(Config::default());
+    let result = analyzer.analyze(&[&project.root_path]).unwrap();
+
+    let routes: Vec<(&str, &str, &str)> = result
+        .endpoints
+        .iter()
+        .map(|e| (e.method.as_str(), e.path.as_str(), e.framework.as_str()))
+        .collect();
+    assert!(routes.contains(&("GET", "/api/orders/{id}", "actix-web")));
+    assert!(routes.contains(&("POST", "/api/orders", "actix-web")));
+    assert_eq!(result.endpoints.len(), 2);
+
+    let create = result
+        .endpoints
+        .iter()
+        .find(|e| e.handler == "create_order")
+        .unwrap();
+    assert!(create.location.file.ends_with("orders.rs"));
+    assert!(create.parameters.iter().any(|p| p.is_extractor));
+}
+
+#[test]
+fn test_e2e_rocket_mounted_routes() {
+    let project = package_project("rocket-test", &[("rocket", "0.5")]);
+    project.add_source_file(
+        "main.rs",
+        r#"
+#[macro_use]
+extern crate rocket;
+
+mod users;
+
+#[launch]
+fn rocket() -> _ {
+    rocket::build().mount("/api/users", users::routes())
+}
+"#,
+    );
+    project.add_source_file(
+        "users.rs",
+        r#"
+use rocket::serde::json::Json;
+use rocket::{get, routes, Route, State};
+
+#[get("/<id>")]
+pub async fn get_user(id: u64, db: &State<Db>) -> Json<User> {
+    todo!()
+}
+
+pub fn routes() -> Vec<Route> {
+    routes![get_user]
+}
+"#,
+    );
+
+    let analyzer = Analyzer::new(Config::default());
+    let result = analyzer.analyze(&[&project.root_path]).unwrap();
+
+    assert_eq!(result.endpoints.len(), 1);
+    let endpoint = &result.endpoints[0];
+    assert_eq!(endpoint.method, "GET");
+    assert_eq!(endpoint.path, "/api/users/<id>");
+    assert_eq!(endpoint.framework, "rocket");
+
+    let guards: Vec<&str> = endpoint
+        .parameters
+        .iter()
+        .filter(|p| p.is_extractor)
+        .map(|p| p.name.as_str())
+        .collect();
+    assert_eq!(guards, vec!["db"]);
+}
+
+#[test]
+fn test_e2e_tonic_service_methods() {
+    let project = package_project("tonic-test", &[("tonic", "0.12")]);
+    project.add_file(
+        "proto/route_guide.proto",
+        r#"
+syntax = "proto3";
+package routeguide.v1;
+
+service RouteGuide {
+    rpc GetFeature(Point) returns (Feature) {}
+    rpc ListFeatures(Rectangle) returns (stream Feature) {}
+    rpc RecordRoute(stream Point) returns (RouteSummary) {}
+}
+"#,
+    );
+    project.add_source_file(
+        "main.rs",
+        r#"
+mod service;
+
+pub mod pb {
+    tonic::include_proto!("routeguide.v1");
+}
+"#,
+    );
+    project.add_source_file(
+        "service.rs",
+        r#"
+use crate::pb::route_guide_server::RouteGuide;
+use crate::pb::{Feature, Point, Rectangle, RouteSummary};
+use tonic::{Request, Response, Status};
+
+pub struct RouteGuideService;
+
+#[tonic::async_trait]
+impl RouteGuide for RouteGuideService {
+    type ListFeaturesStream = ReceiverStream<Result<Feature, Status>>;
+
+    async fn get_feature(&self, request: Request<Point>) -> Result<Response<Feature>, Status> {
+        todo!()
+    }
+
+    async fn list_features(
+        &self,
+        request: Request<Rectangle>,
+    ) -> Result<Response<Self::ListFeaturesStream>, Status> {
+        todo!()
+    }
+
+    async fn record_route(
+        &self,
+        request: Request<PointStream>,
+    ) -> Result<Response<RouteSummary>, Status> {
+        todo!()
+    }
+}
+"#,
+    );
+
+    let analyzer = Analyzer::new(Config::default());
+    let result = analyzer.analyze(&[&project.root_path]).unwrap();
+
+    let endpoints: Vec<_> = result
+        .endpoints
+        .iter()
+        .map(|e| (e.path.as_str(), e.streaming, e.location.line))
+        .collect();
+    assert_eq!(
+        endpoints,
+        vec![
+            (
+                "/routeguide.v1.RouteGuide/GetFeature",
+                Some(StreamingKind::Unary),
+                12
+            ),
+            (
+                "/routeguide.v1.RouteGuide/ListFeatures",
+                Some(StreamingKind::ServerStreaming),
+                16
+            ),
+            // The proto declares the client stream the signature hides
+            (
+                "/routeguide.v1.RouteGuide/RecordRoute",
+                Some(StreamingKind::ClientStreaming),
+                23
+            ),
+        ]
+    );
+    assert!(result.endpoints.iter().all(|e| e.framework == "tonic"));
+    assert_eq!(
+        result.endpoints[0].handler,
+        "RouteGuideService::get_feature"
+    );
+}
+
+#[test]
+fn test_e2e_warp_and_poem_endpoints() {
+    let warp_project = package_project("warp-test", &[("warp", "0.3")]);
+    warp_project.add_source_file(
+        "main.rs",
+        r#"
+use warp::Filter;
+
+async fn get_user(id: u64) -> Result<impl warp::Reply, warp::Rejection> {
+    todo!()
+}
+
+#[tokio::main]
+async fn main() {
+    let routes = warp::path!("users" / u64)
+        .and(warp::get())
+        .and_then(get_user);
+    warp::serve(routes).run(([127, 0, 0, 1], 3030)).await;
+}
+"#,
+    );
+
+    let analyzer = Analyzer::new(Config::default());
+    let result = analyzer.analyze(&[&warp_project.root_path]).unwrap();
+
+    assert_eq!(result.endpoints.len(), 1);
+    let endpoint = &result.endpoints[0];
+    assert_eq!(endpoint.method, "GET");
+    assert_eq!(endpoint.path, "/users/{u64}");
+    assert_eq!(endpoint.framework, "warp");
+    assert_eq!(endpoint.location.line, 4);
+
+    let poem_project = package_project("poem-test", &[("poem", "3")]);
+    poem_project.add_source_file(
+        "main.rs",
+        r#"
+use poem::{get, handler, web::Path, Route};
+
+#[handler]
+fn hello(Path(name): Path<String>) -> String {
+    format!("hello: {name}")
+}
+
+fn app() -> Route {
+    Route::new().nest("/api", Route::new().at("/hello/:name", get(hello)))
+}
+"#,
+    );
+
+    let config = Config {
+        framework: vec![instrument_rs::config::FrameworkType::Poem],
+        ..Config::default()
+    };
+    let result = Analyzer::new(config)
+        .analyze(&[&poem_project.root_path])
+        .unwrap();
+
+    assert_eq!(result.endpoints.len(), 1);
+    let endpoint = &result.endpoints[0];
+    assert_eq!(endpoint.path, "/api/hello/:name");
+    assert_eq!(endpoint.handler, "hello");
+    assert_eq!(endpoint.framework, "poem");
+    assert_eq!(endpoint.parameters.len(), 1);
+}
+
+#[test]
+fn test_e2e_multi_framework_endpoints() {
+    let project = package_project("multi-test", &[("axum", "0.7"), ("tonic", "0.12")]);
+    project.add_source_file(
+        "main.rs",
+        r#"
+use axum::{routing::get, Router};
+use pb::greeter_server::Greeter;
+
+pub mod pb {
+    tonic::include_proto!("helloworld");
+}
+
+async fn health() -> &'static str {
+    "ok"
+}
+
+fn http_app() -> Router {
+    Router::new().route("/health", get(health))
+}
+
+pub struct MyGreeter;
+
+#[tonic::async_trait]
+impl Greeter for MyGreeter {
+    async fn say_hello(
+        &self,
+        request: tonic::Request<pb::HelloRequest>,
+    ) -> Result<tonic::Response<pb::HelloReply>, tonic::Status> {
+        todo!()
+    }
+}
+"#,
+    );
+
+    let result = Analyzer::new(Config::default())
+        .analyze(&[&project.root_path])
+        .unwrap();
+
+    let frameworks: Vec<&str> = result.frameworks.iter().map(|f| f.name()).collect();
+    assert_eq!(frameworks, vec!["axum", "tonic"]);
+
+    let endpoints: Vec<_> = result
+        .endpoints
+        .iter()
+        .map(|e| (e.path.as_str(), e.framework.as_str()))
+        .collect();
+    assert_eq!(
+        endpoints,
+        vec![
+            ("/health", "axum"),
+            ("/helloworld.Greeter/SayHello", "tonic"),
+        ]
+    );
+
+    // Selecting frameworks explicitly restricts detection to them
+    let config_path = project.add_file("instrument-rs.toml", "framework = [\"tonic\"]\n");
+    let config = Config::from_file(&config_path).unwrap();
+    let result = Analyzer::new(config)
+        .analyze(&[&project.root_path])
+        .unwrap();
+
+    assert_eq!(result.endpoints.len(), 1);
+    assert_eq!(result.endpoints[0].framework, "tonic");
+}
+
+#[test]
+fn test_e2e_instrumented_handler_is_covered() {
+    let project = package_project("covered-test", &[("axum", "0.7"), ("tracing", "0.1")]);
+    let main_rs = project.add_source_file(
+        "main.rs",
+        r#"
+use axum::{routing::{get, post}, Router};
+use tracing::instrument;
+
+#[tracing::instrument(skip(body))]
+async fn create_user(body: String) -> String {
+    body
+}
+
+async fn health() -> &'static str {
+    "ok"
+}
+
+fn app() -> Router {
+    Router::new()
+        .route("/users", post(create_user))
+        .route("/health", get(health))
+}
+"#,
+    );
+
+    let analyze = || {
+        Analyzer::new(Config::default())
+            .analyze(&[&project.root_path])
+            .unwrap()
+    };
+    let result = analyze();
+
+    // Endpoint locations name the handler's node, like the other points
+    let create_user = result
+        .endpoints
+        .iter()
+        .find(|e| e.path == "/users")
+        .unwrap();
+    assert!(
+        result
+            .call_graph
+            .get_node(&create_user.location.function_name)
+            .is_some()
+    );
+    let create_user_points = result
+        .points
+        .iter()
+        .filter(|p| p.location.function_name == create_user.location.function_name)
+        .count();
+    assert_eq!(create_user_points, 1);
+
+    let gaps: Vec<_> = result
+        .gaps
+        .iter()
+        .map(|g| g.location.function_name.as_str())
+        .collect();
+    assert_eq!(gaps, vec!["covered_test::health"]);
+
+    // Fixing twice only instruments `health` once
+    let fixer = Fixer::new(FixerConfig {
+        apply: true,
+        ..FixerConfig::default()
+    });
+    fixer.apply_fixes(result.gaps).unwrap();
+    let fixed = std::fs::read_to_string(&main_rs).unwrap();
+    assert_eq!(fixed.matches("#[instrument").count(), 1);
+
+    let result = analyze();
+    assert!(result.gaps.is_empty(), "unexpected gaps: {:?}", result.gaps);
+    fixer.apply_fixes(result.gaps).unwrap();
+    assert_eq!(std::fs::read_to_string(&main_rs).unwrap(), fixed);
+}
+
+#[test]
+fn test_e2e_spawned_tasks() {
+    let project = package_project("spawn-test", &[("axum", "0.7"), ("tokio", "1")]);
+    let main_rs = project.add_source_file(
+        "main.rs",
+        r#"
+use axum::{routing::post, Router};
+use tracing::Instrument;
+
+async fn notify() {}
+
+async fn audit() {}
+
+async fn create_order() -> &'static str {
+    tokio::spawn(async move {
+        notify().await;
+    });
+    tokio::spawn(audit().in_current_span());
+    "created"
+}
+
+fn app() -> Router {
+    Router::new().route("/orders", post(create_order))
+}
+"#,
+    );
+
+    let config = Config {
+        threshold: 0.5,
+        ..Config::default()
+    };
+    let result = Analyzer::new(config)
+        .analyze(&[&project.root_path])
+        .unwrap();
+
+    assert!(
+        result
+            .points
+            .iter()
+            .any(|p| p.kind == instrument_rs::detector::InstrumentationKind::BackgroundJob),
+        "Spawned tasks should be suggested as background jobs"
+    );
+
+    // Lost spans are context breaks, not gaps the fixer would try to fill
+    let lost: Vec<_> = result
+        .context_breaks
+        .iter()
+        .filter(|b| b.description.contains("does not carry the current span"))
+        .collect();
+    assert_eq!(lost.len(), 1);
+    assert_eq!(lost[0].location.function_name, "spawn_test::create_order");
+
+    // Only named functions get `#[instrument]`, never the spawned async block
+    for gap in &result.gaps {
+        let node = result
+            .call_graph
+            .get_node(&gap.location.function_name)
+            .unwrap();
+        assert_ne!(node.kind, instrument_rs::call_graph::NodeKind::Closure);
+    }
+
+    let fixer = Fixer::new(FixerConfig {
+        apply: true,
+        ..FixerConfig::default()
+    });
+    fixer.apply_fixes(result.gaps).unwrap();
+    let fixed = std::fs::read_to_string(&main_rs).unwrap();
+    assert!(fixed.contains("#[instrument"));
+    syn::parse_file(&fixed).expect("fixed source should still parse");
+}
+
+#[test]
+fn test_e2e_workspace_members() {
+    let project = TestProject::new();
+    project.add_cargo_toml(
+        r#"
+[workspace]
+members = ["api", "storage"]
+"#,
+    );
+    project.add_file(
+        "api/Cargo.toml",
+        r#"
+[package]
+name = "shop-api"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+axum = "0.7"
+shop-storage = { path = "../storage" }
+"#,
+    );
+    project.add_file(
+        "api/src/lib.rs",
+        r#"
+pub async fn list_orders() -> String {
+    let orders = shop_storage::fetch_orders();
+    fetch_page(orders)
+}
+
+fn fetch_page(orders: Vec<u64>) -> String {
+    format!("{:?}", orders)
+}
+"#,
+    );
+    project.add_file(
+        "storage/Cargo.toml",
+        r#"
+[package]
+name = "shop-storage"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+sqlx = "0.7"
+"#,
+    );
+    project.add_file(
+        "storage/src/lib.rs",
+        r#"
+pub fn fetch_orders() -> Vec<u64> {
+    vec![]
+}
+"#,
+    );
+
+    let result = Analyzer::new(Config::default())
+        .analyze(&[&project.root_path])
+        .unwrap();
+
+    // Calls between workspace members are linked
+    assert!(
+        result
+            .call_graph
+            .edges()
+            .iter()
+            .any(|e| e.from == "shop_api::list_orders" && e.to == "shop_storage::fetch_orders")
+    );
+
+    // Each member is matched against its own dependencies: only the crate
+    // using sqlx gets database patterns
+    let db_functions: Vec<&str> = result
+        .patterns
+        .iter()
+        .filter(|p| p.category == instrument_rs::patterns::Category::Database)
+        .map(|p| p.function_name.as_str())
+        .collect();
+    assert_eq!(db_functions, vec!["shop_storage::fetch_orders"]);
+
+    // Span names leave out the crate and module path of the node ID
+    let config = Config {
+        threshold: 0.5,
+        ..Config::default()
+    };
+    let points = Analyzer::new(config)
+        .analyze(&[&project.root_path])
+        .unwrap()
+        .points;
+    let fetch_orders = points
+        .iter()
+        .find(|p| p.location.function_name == "shop_storage::fetch_orders")
+        .unwrap();
+    assert_eq!(fetch_orders.suggested_span_name, "db.fetch_orders");
+
+    let crates: Vec<(&str, usize, usize)> = result
+        .crates
+        .iter()
+        .map(|c| {
+            (
+                c.name.as_str(),
+                c.stats.total_files,
+                c.stats.total_functions,
+            )
+        })
+        .collect();
+    assert_eq!(crates, vec![("shop-api", 1, 2), ("shop-storage", 1, 1)]);
+    assert_eq!(result.stats.total_files, 2);
+    assert_eq!(result.stats.total_functions, 3);
+    assert!(result.dependencies.has_database());
+    assert!(!result.crates[0].dependencies.has_database());
+}
+
+#[test]
+fn test_e2e_trace_from_endpoints() {
+    let project = package_project("trace-test", &[("axum", "0.7"), ("sqlx", "0.7")]);
+    project.add_source_file(
+        "main.rs",
+        r#"
+use axum::{routing::get, Router};
+
+async fn list_users() -> &'static str {
+    load_users().await;
+    "users"
+}
+
+async fn load_users() {
+    fetch_users_from_database().await;
+}
+
+async fn fetch_users_from_database() {}
+
+async fn delete_orders_from_database() {}
+
+fn app() -> Router {
+    Router::new().route("/users", get(list_users))
+}
+"#,
+    );
+
+    let analyze = |max_depth| {
+        let config = Config {
+            threshold: 0.5,
+            trace_from_endpoints: true,
+            max_depth,
+            ..Config::default()
+        };
+        Analyzer::new(config)
+            .analyze(&[&project.root_path])
+            .unwrap()
+    };
+
+    let result = analyze(10);
+    let names: Vec<_> = result
+        .points
+        .iter()
+        .map(|p| p.location.function_name.as_str())
+        .collect();
+    assert!(
+        !names
+            .iter()
+            .any(|n| n.contains("delete_orders_from_database")),
+        "Unreachable functions should be dropped, got {names:?}"
+    );
+
+    let fetch = result
+        .points
+        .iter()
+        .find(|p| {
+            p.location
+                .function_name
+                .ends_with("fetch_users_from_database")
+        })
+        .expect("database call reached from the endpoint should be suggested");
+    assert_eq!(fetch.call_paths.len(), 1);
+    assert_eq!(fetch.call_paths[0].endpoint, "GET /users");
+    let chain: Vec<_> = fetch.call_paths[0]
+        .functions
+        .iter()
+        .map(|f| f.rsplit("::").next().unwrap())
+        .collect();
+    assert_eq!(
+        chain,
+        vec!["list_users", "load_users", "fetch_users_from_database"]
+    );
+
+    // A shallow walk stops before the database call
+    let result = analyze(1);
+    assert!(!result.points.iter().any(|p| {
+        p.location
+            .function_name
+            .ends_with("fetch_users_from_database")
+    }));
+}
+
+#[test]
+fn test_e2e_critical_paths() {
+    use instrument_rs::detector::critical_path::HopStatus;
+    use instrument_rs::patterns::Category;
+
+    let project = package_project(
+        "critical-test",
+        &[("axum", "0.7"), ("sqlx", "0.7"), ("tracing", "0.1")],
+    );
+    project.add_source_file(
+        "main.rs",
+        r#"
+use axum::{routing::get, Router};
+use tracing::instrument;
+
+#[instrument]
+async fn list_orders(ids: Vec<u64>) -> &'static str {
+    for id in ids {
+        load_order(id).await;
+    }
+    "orders"
+}
+
+async fn load_order(id: u64) {
+    sqlx::query("SELECT * FROM orders");
+}
+
+fn app() -> Router {
+    Router::new().route("/orders", get(list_orders))
+}
+"#,
+    );
+
+    let config = Config {
+        threshold: 0.5,
+        ..Config::default()
+    };
+    let result = Analyzer::new(config)
+        .analyze(&[&project.root_path])
+        .unwrap();
+
+    let path = result
+        .critical_paths
+        .iter()
+        .find(|p| p.endpoint == "GET /orders")
+        .expect("endpoint should have a critical path to the database");
+    assert_eq!(path.boundary, Category::Database);
+
+    let statuses: Vec<_> = path.hops.iter().map(|h| h.status).collect();
+    assert_eq!(
+        statuses,
+        vec![
+            HopStatus::Instrumented,
+            HopStatus::Uninstrumented,
+            HopStatus::External
+        ]
+    );
+    assert!(
+        path.coverage_lost_at()
+            .unwrap()
+            .function
+            .ends_with("load_order")
+    );
+}
+
+#[test]
+fn test_e2e_broken_trace_chains() {
+    use instrument_rs::detector::spawn::BoundaryKind;
+
+    let project = package_project(
+        "chain-test",
+        &[("axum", "0.7"), ("tokio", "1"), ("tracing", "0.1")],
+    );
+    project.add_source_file(
+        "main.rs",
+        r#"
+use axum::{routing::post, Router};
+use tracing::instrument;
+
+#[instrument]
+async fn upload() -> &'static str {
+    tokio::task::spawn_blocking(move || resize_image());
+    "ok"
+}
+
+#[instrument]
+fn resize_image() {}
+
+fn app() -> Router {
+    Router::new().route("/upload", post(upload))
+}
+"#,
+    );
+
+    let result = Analyzer::new(Config::default())
+        .analyze(&[&project.root_path])
+        .unwrap();
+
+    assert_eq!(result.context_breaks.len(), 1);
+    let context_break = &result.context_breaks[0];
+    assert_eq!(context_break.kind, BoundaryKind::BlockingTask);
+    assert!(context_break.caller.ends_with("upload"));
+    assert!(context_break.callees[0].ends_with("resize_image"));
+    assert_eq!(context_break.location.line, 7);
+}
+
+#[test]
+fn test_e2e_config_file_discovery_and_overrides() {
+    use instrument_rs::config::{ConfigOverrides, ConfigSource, LoadedConfig};
+
+    let project = TestProject::new();
+    project.add_cargo_toml(
+        r#"
+[workspace]
+members = ["api"]
+"#,
+    );
+    project.add_file(
+        "api/Cargo.toml",
+        r#"
+[package]
+name = "api"
+version = "0.1.0"
+edition = "2021"
+"#,
+    );
+    project.add_file("api/src/lib.rs", "");
+    project.add_file("api/examples/demo.rs", "fn main() {}\n");
+    let config_path = project.add_file(
+        "instrument-rs.toml",
+        r#"
+threshold = 0.6
+max_depth = 4
+patterns_file = "patterns.toml"
+source_dirs = ["api/src"]
+
+[naming_rules]
+database_prefix = "db."
+
+[legacy]
+mode = "coverage"
+"#,
+    );
+    let config_path = config_path.canonicalize().unwrap();
+    let elsewhere = tempfile::TempDir::new().unwrap();
+    let member = project.root_path.join("api");
+
+    // Found in the workspace root of the analyzed member
+    let overrides = ConfigOverrides {
+        max_depth: Some(2),
+        ..ConfigOverrides::default()
+    };
+    let loaded = LoadedConfig::load(None, elsewhere.path(), &member, overrides).unwrap();
+
+    assert_eq!(loaded.file.as_deref(), Some(config_path.as_path()));
+    assert!((loaded.config.threshold - 0.6).abs() < f64::EPSILON);
+    assert_eq!(loaded.config.max_depth, 2);
+    assert_eq!(
+        loaded.config.naming_rules.database_prefix.as_deref(),
+        Some("db.")
+    );
+    // Relative paths are resolved against the file, not the current directory
+    let config_dir = config_path.parent().unwrap();
+    assert_eq!(
+        loaded.config.patterns_file,
+        Some(config_dir.join("patterns.toml"))
+    );
+    assert_eq!(loaded.config.source_dirs, vec![config_dir.join("api/src")]);
+    let files = instrument_rs::files::FileWalker::new(&loaded.config)
+        .unwrap()
+        .collect(&[&member])
+        .unwrap();
+    let names: Vec<_> = files.iter().filter_map(|f| f.file_name()).collect();
+    assert_eq!(names, vec!["lib.rs"]);
+    assert_eq!(
+        loaded.source("threshold"),
+        Some(&ConfigSource::File(config_path.clone()))
+    );
+    assert_eq!(loaded.source("max_depth"), Some(&ConfigSource::CommandLine));
+    assert_eq!(loaded.source("include_tests"), Some(&ConfigSource::Default));
+    assert_eq!(loaded.ignored_keys, vec!["legacy".to_string()]);
+
+    // The current directory wins over the workspace root
+    let local = elsewhere.path().join("instrument-rs.toml");
+    std::fs::write(&local, "threshold = 0.3\n").unwrap();
+    let loaded =
+        LoadedConfig::load(None, elsewhere.path(), &member, ConfigOverrides::default()).unwrap();
+    assert_eq!(loaded.file.as_deref(), Some(local.as_path()));
+    assert!((loaded.config.threshold - 0.3).abs() < f64::EPSILON);
+
+    // An explicit path wins over both, and must exist
+    let loaded = LoadedConfig::load(
+        Some(&config_path),
+        elsewhere.path(),
+        &member,
+        ConfigOverrides::default(),
+    )
+    .unwrap();
+    assert_eq!(loaded.config.max_depth, 4);
+    assert!(
+        LoadedConfig::load(
+            Some(&member.join("missing.toml")),
+            elsewhere.path(),
+            &member,
+            ConfigOverrides::default(),
+        )
+        .is_err()
+    );
+}
+
+#[test]
+fn test_e2e_test_code_excluded_unless_included() {
+    let project = package_project("tested", &[("sqlx", "0.7"), ("tokio", "1")]);
+    project.add_source_file(
+        "lib.rs",
+        r#"
+pub async fn query_users() {
+    sqlx::query("SELECT * FROM users");
+}
+
+#[cfg(test)]
+mod tests {
+    async fn insert_fixture_users() {
+        sqlx::query("INSERT INTO users VALUES (1)");
+    }
+
+    #[tokio::test]
+    async fn query_users_returns_fixtures() {
+        insert_fixture_users().await;
+        super::query_users().await;
+    }
+}
+"#,
+    );
+    project.add_file(
+        "tests/api.rs",
+        r#"
+async fn delete_fixture_users() {
+    sqlx::query("DELETE FROM users");
+}
+"#,
+    );
+
+    let functions = |include_tests: bool| {
+        let config = Config {
+            include_tests,
+            threshold: 0.5,
+            ..Config::default()
+        };
+        let result = Analyzer::new(config)
+            .analyze(&[&project.root_path])
+            .unwrap();
+        let mut names: Vec<String> = result
+            .points
+            .iter()
+            .map(|p| p.location.function_name.clone())
+            .chain(result.gaps.iter().map(|g| g.location.function_name.clone()))
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    };
+
+    let excluded = functions(false);
+    assert_eq!(excluded, vec!["tested::query_users"]);
+
+    let included = functions(true);
+    assert!(
+        included.contains(&"tested::tests::insert_fixture_users".to_string()),
+        "{included:?}"
+    );
+    assert!(
+        included.iter().any(|f| f.ends_with("delete_fixture_users")),
+        "{included:?}"
+    );
+}
+
+#[test]
+fn test_e2e_patterns_file_categories() {
+    use instrument_rs::patterns::Category;
+
+    let project = package_project("billing", &[]);
+    project.add_source_file(
+        "lib.rs",
+        r#"
+pub fn charge_card(amount: u64) {
+    billing_client::charge(amount);
+}
+
+pub fn render_invoice() {}
+"#,
+    );
+    let patterns = project.add_file(
+        "patterns.yaml",
+        r#"
+patterns:
+  - name: billing_sdk
+    category: external_service
+    call: "billing_client::"
+    confidence: 0.95
+  - name: invoices
+    category: business_logic
+    regex: "^render_invoice$"
+"#,
+    );
+
+    let config = Config {
+        patterns_file: Some(patterns),
+        threshold: 0.5,
+        ..Config::default()
+    };
+    let result = Analyzer::new(config)
+        .analyze(&[&project.root_path])
+        .unwrap();
+
+    let charge = result
+        .patterns
+        .iter()
+        .find(|p| p.function_name == "billing::charge_card")
+        .expect("charge_card should match the billing_sdk rule");
+    assert_eq!(charge.category, Category::ExternalService);
+    assert_eq!(charge.confidence, 0.95);
+    assert_eq!(charge.matches[0].pattern, "billing_sdk");
+    assert_eq!(
+        charge.matches[0].matched_text,
+        "calls billing_client::charge"
+    );
+
+    let invoice = result
+        .patterns
+        .iter()
+        .find(|p| p.function_name == "billing::render_invoice")
+        .expect("render_invoice should match the invoices rule");
+    assert_eq!(invoice.category, Category::BusinessLogic);
+
+    // A broken patterns file fails the analysis
+    let broken = project.add_file("broken.toml", "[[patterns]]\nname = \"x\"\n");
+    let config = Config {
+        patterns_file: Some(broken),
+        ..Config::default()
+    };
+    assert!(
+        Analyzer::new(config)
+            .analyze(&[&project.root_path])
+            .is_err()
+    );
+}
+
+#[test]
+fn test_e2e_call_site_classification() {
+    use instrument_rs::patterns::Category;
+
+    let project = package_project("shop", &[("sqlx", "0.7"), ("reqwest", "0.11")]);
+    project.add_source_file(
+        "lib.rs",
+        r#"
+pub async fn get_user(id: i64) {
+    let user = sqlx::query_as!(User, "SELECT * FROM users WHERE id = $1", id);
+}
+
+pub async fn process_order(order_id: u64) {
+    reqwest::Client::new().post("https://payments.example.com/charge");
+}
+
+pub async fn fetch_summary() {}
+"#,
+    );
+
+    let config = Config {
+        threshold: 0.5,
+        ..Config::default()
+    };
+    let result = Analyzer::new(config)
+        .analyze(&[&project.root_path])
+        .unwrap();
+    let pattern = |id: &str| result.patterns.iter().find(|p| p.function_name == id);
+
+    let get_user = pattern("shop::get_user").expect("get_user calls sqlx");
+    assert_eq!(get_user.category, Category::Database);
+    assert_eq!(get_user.matches[0].matched_text, "sqlx::query_as!");
+    assert_eq!(get_user.matches[0].location, Some((3, 15)));
+
+    let process_order = pattern("shop::process_order").expect("process_order calls reqwest");
+    assert_eq!(process_order.category, Category::HttpClient);
+    assert_eq!(
+        process_order.matches[0].matched_text,
+        "reqwest::Client::new"
+    );
+
+    // Without a telling call, the name heuristics still apply
+    let fetch_summary = pattern("shop::fetch_summary").expect("fetch is a DB name pattern");
+    assert!(fetch_summary.matches.is_empty());
+
+    let point = result
+        .points
+        .iter()
+        .find(|p| p.location.function_name == "shop::get_user")
+        .unwrap();
+    assert!(
+        point.reason.starts_with("Calls sqlx::query_as! at line 3"),
+        "{}",
+        point.reason
+    );
+}
+
+#[test]
+fn test_e2e_existing_instrumentation_by_function() {
+    let project = package_project("shop", &[("sqlx", "0.7"), ("tracing", "0.1")]);
+    project.add_source_file(
+        "lib.rs",
+        r#"
+use tracing::{info, instrument};
+
+#[instrument(
+    name = "db.get_user",
+    skip(pool),
+    err,
+)]
+/// Loads a user
+///
+/// The attribute is several lines away from the function's name.
+pub async fn get_user(pool: &PgPool, id: i64) -> Result<User, Error> {
+    sqlx::query_as!(User, "SELECT * FROM users WHERE id = $1", id)
+}
+
+// #[instrument] in a comment doesn't instrument anything
+pub async fn get_order(pool: &PgPool, id: i64) {
+    info!("loading order {id}");
+    sqlx::query_as!(Order, "SELECT * FROM orders WHERE id = $1", id);
+}
+"#,
+    );
+
+    let config = Config {
+        threshold: 0.5,
+        ..Config::default()
+    };
+    let result = Analyzer::new(config)
+        .analyze(&[&project.root_path])
+        .unwrap();
+
+    let instrument = result
+        .existing_instrumentation
+        .iter()
+        .find(|e| e.span_name.as_deref() == Some("db.get_user"))
+        .expect("the multi-line attribute should be detected");
+    assert_eq!(instrument.location.function_name, "shop::get_user");
+    let args = instrument.args.as_ref().unwrap();
+    assert_eq!(args.skip, vec!["pool"]);
+    assert!(args.err);
+
+    let log = result
+        .existing_instrumentation
+        .iter()
+        .find(|e| e.kind == instrument_rs::detector::ExistingKind::LogMacro)
+        .unwrap();
+    assert_eq!(log.location.function_name, "shop::get_order");
+    assert_eq!(result.existing_instrumentation.len(), 2);
+
+    // A log event doesn't give its function a span
+    let gaps: Vec<_> = result
+        .gaps
+        .iter()
+        .map(|g| g.location.function_name.as_str())
+        .collect();
+    assert!(gaps.contains(&"shop::get_order"), "{gaps:?}");
+    assert!(!gaps.contains(&"shop::get_user"), "{gaps:?}");
+}
+
+#[test]
+fn test_e2e_instrumented_futures_and_guards() {
+    use instrument_rs::detector::{ExistingKind, QualityIssueKind};
+
+    let project = package_project("shop", &[("sqlx", "0.7"), ("tracing", "0.1")]);
+    project.add_source_file(
+        "lib.rs",
+        r#"
+use tracing::{info_span, Instrument};
+
+pub async fn show_user(pool: &PgPool, id: i64) {
+    get_user(pool, id)
+        .instrument(info_span!("db.get_user", id))
+        .await;
+}
+
+pub async fn get_user(pool: &PgPool, id: i64) {
+    sqlx::query_as!(User, "SELECT * FROM users WHERE id = $1", id);
+}
+
+pub async fn get_order(pool: &PgPool, id: i64) {
+    let span = info_span!("db.get_order");
+    let _enter = span.enter();
+    sqlx::query_as!(Order, "SELECT * FROM orders WHERE id = $1", id)
+        .fetch_one(pool)
+        .await;
+}
+"#,
+    );
+
+    let config = Config {
+        threshold: 0.5,
+        ..Config::default()
+    };
+    let result = Analyzer::new(config)
+        .analyze(&[&project.root_path])
+        .unwrap();
+
+    let future = result
+        .existing_instrumentation
+        .iter()
+        .find(|e| e.kind == ExistingKind::InstrumentedFuture)
+        .unwrap();
+    assert_eq!(future.instrumented_futures, vec!["shop::get_user"]);
+    assert_eq!(future.span_name.as_deref(), Some("db.get_user"));
+
+    let guard = result
+        .existing_instrumentation
+        .iter()
+        .find(|e| e.kind == ExistingKind::SpanGuard)
+        .unwrap();
+    assert_eq!(guard.location.function_name, "shop::get_order");
+    assert_eq!(
+        guard.quality.issues[0].kind,
+        QualityIssueKind::GuardAcrossAwait
+    );
+
+    let gaps: Vec<_> = result
+        .gaps
+        .iter()
+        .map(|g| g.location.function_name.as_str())
+        .collect();
+    assert!(!gaps.contains(&"shop::get_user"), "{gaps:?}");
+    assert!(!gaps.contains(&"shop::get_order"), "{gaps:?}");
+}
+
+#[test]
+fn test_e2e_opentelemetry_spans() {
+    use instrument_rs::detector::ExistingKind;
+
+    let project = package_project("shop", &[("sqlx", "0.7"), ("opentelemetry", "0.21")]);
+    project.add_source_file(
+        "lib.rs",
+        r#"
+use opentelemetry::{global, trace::{Span, Tracer}, KeyValue};
+
+pub async fn get_user(pool: &PgPool, id: i64) -> Result<User, Error> {
+    let tracer = global::tracer("shop");
+    let mut span = tracer.start("db.get_user");
+    span.set_attribute(KeyValue::new("user.id", id));
+    sqlx::query_as!(User, "SELECT * FROM users WHERE id = $1", id)
+}
+
+pub async fn get_order(pool: &PgPool, id: i64) {
+    sqlx::query_as!(Order, "SELECT * FROM orders WHERE id = $1", id);
+}
+"#,
+    );
+
+    let config = Config {
+        threshold: 0.5,
+        ..Config::default()
+    };
+    let result = Analyzer::new(config)
+        .analyze(&[&project.root_path])
+        .unwrap();
+
+    let span = result
+        .existing_instrumentation
+        .iter()
+        .find(|e| e.kind == ExistingKind::OpenTelemetrySpan)
+        .expect("the tracer span should be detected");
+    assert_eq!(span.location.function_name, "shop::get_user");
+    assert_eq!(span.span_name.as_deref(), Some("db.get_user"));
+    assert_eq!(span.otel.as_ref().unwrap().attributes, vec!["user.id"]);
+    // The error returned by the query is never recorded on the span
+    assert!(span.quality.score < 1.0);
+
+    let gaps: Vec<_> = result
+        .gaps
+        .iter()
+        .map(|g| g.location.function_name.as_str())
+        .collect();
+    assert!(!gaps.contains(&"shop::get_user"), "{gaps:?}");
+    assert!(gaps.contains(&"shop::get_order"), "{gaps:?}");
+}
+
+#[test]
+fn test_e2e_log_event_quality() {
+    use instrument_rs::detector::{ExistingKind, QualityIssueKind};
+
+    let project = package_project("shop", &[("tracing", "0.1")]);
+    project.add_source_file(
+        "lib.rs",
+        r#"
+use tracing::{error, info};
+
+fn charge(order: &Order) -> Result<(), Error> {
+    gateway::charge(order).map_err(|e| {
+        error!(error = %e, "charge failed");
+        e
+    })
+}
+
+pub fn checkout(orders: &[Order]) -> Result<(), Error> {
+    for order in orders {
+        charge(order).map_err(|e| {
+            error!("checkout failed for order {}: {:?}", order.id, e);
+            e
+        })?;
+    }
+    match notify() {
+        Ok(()) => info!(count = orders.len(), "checked out"),
+        Err(e) => info!("notify failed"),
+    }
+    Ok(())
+}
+"#,
+    );
+
+    let result = Analyzer::new(Config::default())
+        .analyze(&[&project.root_path])
+        .unwrap();
+
+    let issues = |function: &str| -> Vec<QualityIssueKind> {
+        result
+            .existing_instrumentation
+            .iter()
+            .filter(|e| e.kind == ExistingKind::LogMacro && e.location.function_name == function)
+            .flat_map(|e| e.quality.issues.iter().map(|issue| issue.kind))
+            .collect()
+    };
+
+    assert!(issues("shop::charge").is_empty());
+    assert_eq!(
+        issues("shop::checkout"),
+        vec![
+            QualityIssueKind::UnstructuredLog,
+            QualityIssueKind::LogInLoop,
+            QualityIssueKind::DuplicateErrorLog,
+            QualityIssueKind::MissingErrorValue,
+            QualityIssueKind::LevelMismatch,
+        ]
+    );
+}
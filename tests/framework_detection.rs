@@ -4,7 +4,6 @@ use instrument_rs::framework::web::{AxumDetector, FrameworkDetector, HttpMethod}
 use syn::parse_quote;
 
 #[test]
-#[ignore = "Framework detection needs implementation"]
 fn test_axum_endpoint_detection() {
     let detector = AxumDetector::new();
 
@@ -62,7 +61,6 @@ fn test_axum_endpoint_detection() {
 }
 
 #[test]
-#[ignore = "Framework detection needs implementation"]
 fn test_axum_handler_detection() {
     let detector = AxumDetector::new();
 
@@ -97,7 +95,6 @@ fn test_axum_handler_detection() {
 }
 
 #[test]
-#[ignore = "Framework detection needs implementation"]
 fn test_nested_router_detection() {
     let detector = AxumDetector::new();
 
@@ -129,7 +126,6 @@ fn test_nested_router_detection() {
 }
 
 #[test]
-#[ignore = "Framework detection needs implementation"]
 fn test_middleware_detection() {
     let detector = AxumDetector::new();
 
@@ -152,7 +148,6 @@ fn test_middleware_detection() {
 }
 
 #[test]
-#[ignore = "Framework detection needs implementation"]
 fn test_http_method_parsing() {
     use instrument_rs::framework::web::HttpMethod;
 